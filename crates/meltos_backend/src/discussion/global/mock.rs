@@ -137,8 +137,7 @@ impl DiscussionIo for MockGlobalDiscussionIo {
         let discussions = self.discussions.lock().await;
         let mut bundles = Vec::new();
         let ids = discussions
-            .iter()
-            .map(|(id, _)| id)
+            .keys()
             .cloned()
             .collect::<Vec<DiscussionId>>();
         drop(discussions);
@@ -20,7 +20,7 @@ pub struct SqliteDiscussionIo {
 }
 
 impl SqliteDiscussionIo {
-    fn lock(&self) -> MutexGuard<Connection> {
+    fn lock(&self) -> MutexGuard<'_, Connection> {
         self.db.lock().unwrap()
     }
 
@@ -8,7 +8,7 @@ use tokio::sync::{Mutex, MutexGuard};
 pub struct ArcMutex<S: Send + Sync>(Arc<Mutex<S>>);
 
 impl<S: Send + Sync> ArcMutex<S> {
-    pub async fn lock(&self) -> MutexGuard<S> {
+    pub async fn lock(&self) -> MutexGuard<'_, S> {
         self.0.lock().await
     }
 }
@@ -4,7 +4,11 @@ use meltos_tvc::branch::BranchName;
 use meltos_tvc::file_system::FileSystem;
 use meltos_tvc::io::atomic::head::HeadIo;
 use meltos_tvc::io::atomic::object::ObjIo;
-use meltos_tvc::io::bundle::{Bundle, BundleIo};
+use meltos_tvc::io::bundle::{Bundle, BundleHashes, BundleIo, BundleRecord, BundleStream};
+use meltos_tvc::object::commit::CommitHash;
+use meltos_tvc::operation::export_git::ExportGit;
+use meltos_tvc::operation::fsck::{Fsck, FsckReport};
+use meltos_tvc::operation::gc::{Gc, GcReport};
 use meltos_tvc::operation::save::Save;
 
 use crate::tvc::file_system::BackendFileSystem;
@@ -17,16 +21,22 @@ pub struct TvcBackendIo<Fs: FileSystem + Clone> {
     save: Save<BackendFileSystem<Fs>>,
     obj: ObjIo<BackendFileSystem<Fs>>,
     head: HeadIo<BackendFileSystem<Fs>>,
+    gc: Gc<BackendFileSystem<Fs>>,
+    fsck: Fsck<BackendFileSystem<Fs>>,
+    export_git: ExportGit<BackendFileSystem<Fs>>,
 }
 
-impl<Fs: FileSystem + Clone> TvcBackendIo<Fs> {
+impl<Fs: FileSystem + Clone + 'static> TvcBackendIo<Fs> {
     pub fn new(room_id: RoomId, fs: Fs) -> TvcBackendIo<Fs> {
         let fs = BackendFileSystem::new(room_id, fs);
         Self {
             bundle: BundleIo::new(fs.clone()),
             save: Save::new(fs.clone()),
             obj: ObjIo::new(fs.clone()),
-            head: HeadIo::new(fs),
+            head: HeadIo::new(fs.clone()),
+            gc: Gc::new(fs.clone()),
+            fsck: Fsck::new(fs.clone()),
+            export_git: ExportGit::new(fs),
         }
     }
 
@@ -35,9 +45,21 @@ impl<Fs: FileSystem + Clone> TvcBackendIo<Fs> {
         self.obj.total_objs_size().await
     }
 
-    #[inline(always)]
-    pub async fn save(&self, bundle: Bundle) -> meltos_tvc::error::Result {
-        self.save.execute(bundle).await
+    /// The heads are moved only after all objects referred to by the pushed commits are verified to exist.
+    pub async fn save(&self, mut bundle: Bundle) -> meltos_tvc::error::Result {
+        let branches = std::mem::take(&mut bundle.branches);
+        self.save.execute(bundle).await?;
+        for branch in branches {
+            self.save_record(BundleRecord::Branch(branch)).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn save_record(&self, record: BundleRecord) -> meltos_tvc::error::Result {
+        if let BundleRecord::Branch(branch) = &record {
+            self.bundle.verify_branch(branch).await?;
+        }
+        self.save.write_record(record).await
     }
 
     #[inline(always)]
@@ -58,4 +80,39 @@ impl<Fs: FileSystem + Clone> TvcBackendIo<Fs> {
     pub async fn bundle(&self) -> meltos_tvc::error::Result<Bundle> {
         self.bundle.create().await
     }
+
+    #[inline(always)]
+    pub async fn bundle_since(&self, known: &[CommitHash]) -> meltos_tvc::error::Result<Bundle> {
+        self.bundle.create_since(known).await
+    }
+
+    #[inline(always)]
+    pub async fn bundle_stream(&self) -> meltos_tvc::error::Result<BundleStream> {
+        self.bundle.create_stream().await
+    }
+
+    #[inline(always)]
+    pub async fn bundle_stream_since(&self, known: &[CommitHash]) -> meltos_tvc::error::Result<BundleStream> {
+        self.bundle.create_stream_since(known).await
+    }
+
+    #[inline(always)]
+    pub async fn missing(&self, hashes: &BundleHashes) -> meltos_tvc::error::Result<BundleHashes> {
+        self.bundle.missing(hashes).await
+    }
+
+    #[inline(always)]
+    pub async fn gc(&self, dry_run: bool) -> meltos_tvc::error::Result<GcReport> {
+        self.gc.execute(dry_run).await
+    }
+
+    #[inline(always)]
+    pub async fn fsck(&self) -> meltos_tvc::error::Result<FsckReport> {
+        self.fsck.execute().await
+    }
+
+    #[inline(always)]
+    pub async fn export_git(&self, branch_name: &BranchName) -> meltos_tvc::error::Result<Vec<u8>> {
+        self.export_git.execute(branch_name).await
+    }
 }
@@ -4,7 +4,7 @@ use async_trait::async_trait;
 
 use meltos_core::room::RoomId;
 use meltos_tvc::file_system::std_fs::StdFileSystem;
-use meltos_tvc::file_system::{FileSystem, Stat};
+use meltos_tvc::file_system::{FileMode, FileSystem, Stat};
 use meltos_util::path::AsUri;
 
 use crate::path::room_resource_dir;
@@ -68,6 +68,23 @@ impl<Fs: FileSystem> FileSystem for BackendFileSystem<Fs> {
         self.fs.write_file(&self.as_path(path), buf).await
     }
 
+    #[inline(always)]
+    async fn write_file_with_mode(
+        &self,
+        path: &str,
+        buf: &[u8],
+        mode: FileMode,
+    ) -> std::io::Result<()> {
+        self.fs
+            .write_file_with_mode(&self.as_path(path), buf, mode)
+            .await
+    }
+
+    #[inline(always)]
+    async fn append_file(&self, path: &str, buf: &[u8]) -> std::io::Result<()> {
+        self.fs.append_file(&self.as_path(path), buf).await
+    }
+
     #[inline(always)]
     async fn create_dir(&self, path: &str) -> std::io::Result<()> {
         self.fs.create_dir(&self.as_path(path)).await
@@ -97,7 +114,7 @@ impl<Fs: FileSystem> FileSystem for BackendFileSystem<Fs> {
 mod tests {
     use meltos_core::room::RoomId;
     use meltos_tvc::file_system::memory::MemoryFileSystem;
-    use meltos_tvc::file_system::FileSystem;
+    use meltos_tvc::file_system::{FileMode, FileSystem};
 
     use crate::tvc::file_system::BackendFileSystem;
 
@@ -128,4 +145,15 @@ mod tests {
             vec!["dir/hello.txt".to_string(), "hello2.txt".to_string()]
         )
     }
+
+    #[tokio::test]
+    async fn write_file_with_mode() {
+        let fs = MemoryFileSystem::default();
+        let fs = BackendFileSystem::new(RoomId::new(), fs.clone());
+        fs.write_file_with_mode("run.sh", b"run", FileMode::Executable)
+            .await
+            .unwrap();
+        let stat = fs.stat("run.sh").await.unwrap().unwrap();
+        assert_eq!(stat.mode, FileMode::Executable);
+    }
 }
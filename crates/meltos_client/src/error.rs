@@ -18,6 +18,12 @@ pub enum Error {
     #[error("please open or join room")]
     NotInitialized,
 
+    #[error("failed to encode or decode binary body; {0}")]
+    Wire(#[from] meltos_core::schema::wire::Error),
+
+    #[error("invalid stream response; {0}")]
+    InvalidStream(String),
+
     #[error(transparent)]
     Tvc(#[from] meltos_tvc::error::Error),
 
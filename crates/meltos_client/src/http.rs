@@ -1,17 +1,23 @@
 use async_trait::async_trait;
 #[cfg(not(feature = "wasm"))]
-use reqwest::{Client, header, Response};
+use futures::StreamExt;
+#[cfg(not(feature = "wasm"))]
+use reqwest::{Body, Client, header, RequestBuilder, Response};
 #[cfg(feature = "wasm")]
-use reqwest_wasm::{Client, header, Response};
+use reqwest_wasm::{Client, header, RequestBuilder, Response};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
 use meltos_core::room::RoomId;
 use meltos_core::schema::discussion::global::{Create, Created, Replied, Reply, Speak, Spoke};
-use meltos_core::schema::room::{Join, Joined, Open};
+use meltos_core::schema::room::{Fetch, Join, Joined, Open};
 use meltos_core::schema::room::Opened;
+use meltos_core::schema::wire::{self, FrameDecoder};
 use meltos_core::user::UserId;
-use meltos_tvc::io::bundle::Bundle;
+use meltos_tvc::branch::BranchName;
+use meltos_tvc::io::bundle::{Bundle, BundleHashes};
+#[cfg(not(feature = "wasm"))]
+use meltos_tvc::io::bundle::BundleStream;
 use meltos_tvc::operation::push::Pushable;
 
 use crate::config::SessionConfigs;
@@ -53,13 +59,14 @@ impl HttpClient {
         let client = Client::new();
         let response = client
             .post(format!("{base_uri}/room/{room_id}/join"))
+            .header(header::ACCEPT, wire::BINARY_CONTENT_TYPE)
             .json(&Join {
                 user_id,
             })
             .send()
             .await?;
 
-        let joined: Joined = response_to_json(response).await?;
+        let joined: Joined = deserialize_response(response).await?;
         Ok((
             Self {
                 configs: SessionConfigs {
@@ -74,6 +81,43 @@ impl HttpClient {
         ))
     }
 
+    /// Joins the room and receives the bundle as the stream.
+    ///
+    /// The records of the bundle should be read from the returned [`FrameReader`].
+    pub async fn join_stream(
+        base_uri: &str,
+        room_id: RoomId,
+        user_id: Option<UserId>,
+    ) -> error::Result<(Self, FrameReader)> {
+        let client = Client::new();
+        let response = client
+            .post(format!("{base_uri}/room/{room_id}/join"))
+            .header(header::ACCEPT, wire::STREAM_CONTENT_TYPE)
+            .json(&Join {
+                user_id,
+            })
+            .send()
+            .await?;
+
+        let mut reader = FrameReader::new(response)?;
+        let joined: Joined = reader
+            .next()
+            .await?
+            .ok_or_else(|| error::Error::InvalidStream("joined is missing".to_string()))?;
+        Ok((
+            Self {
+                configs: SessionConfigs {
+                    session_id: joined.session_id,
+                    user_id: joined.user_id,
+                    room_id,
+                },
+                client,
+                base_uri: base_uri.to_string(),
+            },
+            reader,
+        ))
+    }
+
     pub async fn open(
         base_uri: &str,
         bundle: Option<Bundle>,
@@ -81,17 +125,19 @@ impl HttpClient {
         user_limits: Option<u64>,
     ) -> error::Result<Self> {
         let client = Client::new();
-        let response = client
-            .post(format!("{base_uri}/room/open"))
-            .json(&Open {
+        let request = client.post(format!("{base_uri}/room/open"));
+        let response = binary_body(
+            request,
+            &Open {
                 lifetime_secs,
                 user_limits,
                 bundle,
-            })
+            },
+        )?
             .send()
-            .await;
+            .await?;
 
-        let opened: Opened = response?.error_for_status()?.json().await?;
+        let opened: Opened = deserialize_response(response).await?;
         Ok(Self {
             configs: SessionConfigs::from(opened),
             client,
@@ -117,6 +163,50 @@ impl HttpClient {
         self.get().await
     }
 
+    /// Fetches only the commits and objects which are not reachable from the known commits.
+    #[inline]
+    pub async fn fetch_since(&self, fetch: &Fetch) -> error::Result<Bundle> {
+        self.post("tvc/fetch", Some(fetch)).await
+    }
+
+    /// The stream version of [`HttpClient::fetch_since`].
+    pub async fn fetch_stream_since(&self, fetch: &Fetch) -> error::Result<FrameReader> {
+        let response = self
+            .client
+            .post(format!(
+                "{}/room/{}/tvc/fetch",
+                self.base_uri, self.configs.room_id
+            ))
+            .header(header::ACCEPT, wire::STREAM_CONTENT_TYPE)
+            .header(
+                header::SET_COOKIE,
+                format!("session_id={}", self.configs.session_id),
+            )
+            .json(fetch)
+            .send()
+            .await?;
+        FrameReader::new(response)
+    }
+
+    /// Exports the history of the branch in the room as a `git fast-import` stream.
+    pub async fn export_git(&self, branch_name: &BranchName) -> error::Result<Vec<u8>> {
+        let response = self
+            .client
+            .get(format!(
+                "{}/room/{}/tvc/export",
+                self.base_uri, self.configs.room_id
+            ))
+            .query(&[("branch", branch_name.0.as_str())])
+            .header(
+                header::SET_COOKIE,
+                format!("session_id={}", self.configs.session_id),
+            )
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
     #[inline(always)]
     pub async fn create_discussion(&self, create: &Create) -> error::Result<Created> {
         self.post("discussion/global/create", Some(create)).await
@@ -144,6 +234,7 @@ impl HttpClient {
                 self.configs.room_id
             ))
             .header(header::CONTENT_TYPE, "application/json")
+            .header(header::ACCEPT, wire::BINARY_CONTENT_TYPE)
             .header(
                 header::SET_COOKIE,
                 format!("session_id={}", self.configs.session_id),
@@ -151,7 +242,7 @@ impl HttpClient {
             .send()
             .await?;
 
-        response_to_json(response).await
+        deserialize_response(response).await
     }
 
     async fn post<S, D>(&self, path: &str, body: Option<&S>) -> error::Result<D>
@@ -165,6 +256,7 @@ impl HttpClient {
                 "{}/room/{}/{path}",
                 &self.base_uri, self.configs.room_id
             ))
+            .header(header::ACCEPT, wire::BINARY_CONTENT_TYPE)
             .header(
                 header::SET_COOKIE,
                 format!("session_id={}", self.configs.session_id),
@@ -174,7 +266,7 @@ impl HttpClient {
             request = request.json(body);
         }
         let response = request.send().await?;
-        response_to_json(response).await
+        deserialize_response(response).await
     }
 }
 
@@ -182,17 +274,24 @@ impl HttpClient {
 impl Pushable<()> for HttpClient {
     type Error = String;
 
+    async fn missing(&mut self, hashes: BundleHashes) -> Result<BundleHashes, Self::Error> {
+        self.post("tvc/push/check", Some(&hashes))
+            .await
+            .map_err(|e| e.to_string())
+    }
+
     async fn push(&mut self, bundle: Bundle) -> Result<(), Self::Error> {
         let base = &self.base_uri;
 
-        let response = self
+        let request = self
             .client
             .post(format!("{base}/room/{}/tvc/push", self.configs.room_id))
             .header(
                 header::SET_COOKIE,
                 format!("session_id={}", self.configs.session_id),
-            )
-            .json(&bundle)
+            );
+        let response = binary_body(request, &bundle)
+            .map_err(|e| e.to_string())?
             .send()
             .await
             .map_err(|e| e.to_string())?;
@@ -206,11 +305,142 @@ impl Pushable<()> for HttpClient {
 
         Ok(())
     }
+
+    /// Sends the records as the request body in the stream format while they are read.
+    ///
+    /// The request body of wasm cannot be a stream, so it falls back to [`Pushable::push`] there.
+    #[cfg(not(feature = "wasm"))]
+    async fn push_stream(&mut self, stream: BundleStream) -> meltos_tvc::error::Result {
+        let frames = futures::stream::iter([Ok(wire::MAGIC.to_vec())]).chain(stream.map(
+            |record| -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+                Ok(wire::encode_frame(&record?)?)
+            },
+        ));
+        let base = &self.base_uri;
+        let response = self
+            .client
+            .post(format!("{base}/room/{}/tvc/push", self.configs.room_id))
+            .header(
+                header::SET_COOKIE,
+                format!("session_id={}", self.configs.session_id),
+            )
+            .header(header::CONTENT_TYPE, wire::STREAM_CONTENT_TYPE)
+            .body(Body::wrap_stream(SyncStream(std::sync::Mutex::new(frames))))
+            .send()
+            .await
+            .map_err(|e| meltos_tvc::error::Error::FailedConnectServer(e.to_string()))?;
+        if response.error_for_status_ref().is_err() {
+            return Err(meltos_tvc::error::Error::FailedConnectServer(format!(
+                "http error\n status: {}\nmessage:{}",
+                response.status(),
+                response.text().await.unwrap_or_default()
+            )));
+        }
+
+        Ok(())
+    }
 }
 
-async fn response_to_json<D>(response: Response) -> error::Result<D>
+/// Makes the stream `Sync` as required by the request body.
+///
+/// The stream is only polled through `&mut self`, so the mutex is never locked.
+#[cfg(not(feature = "wasm"))]
+struct SyncStream<S>(std::sync::Mutex<S>);
+
+#[cfg(not(feature = "wasm"))]
+impl<S> futures::Stream for SyncStream<S>
+where
+    S: futures::Stream + Unpin,
+{
+    type Item = S::Item;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        match self.get_mut().0.get_mut() {
+            Ok(stream) => stream.poll_next_unpin(cx),
+            Err(poisoned) => poisoned.into_inner().poll_next_unpin(cx),
+        }
+    }
+}
+
+/// Reads the frames of the stream response as the chunks arrive.
+pub struct FrameReader {
+    response: Option<Response>,
+    decoder: FrameDecoder,
+}
+
+impl FrameReader {
+    fn new(response: Response) -> error::Result<Self> {
+        Ok(Self {
+            response: Some(response.error_for_status()?),
+            decoder: FrameDecoder::default(),
+        })
+    }
+
+    /// Returns the next frame, or `None` at the end of the stream.
+    pub async fn next<D: DeserializeOwned>(&mut self) -> error::Result<Option<D>> {
+        loop {
+            if let Some(value) = self.decoder.next_frame()? {
+                return Ok(Some(value));
+            }
+            if !self.read_chunk().await? {
+                self.decoder.finish()?;
+                return Ok(None);
+            }
+        }
+    }
+
+    #[cfg(not(feature = "wasm"))]
+    async fn read_chunk(&mut self) -> error::Result<bool> {
+        let Some(response) = self.response.as_mut() else {
+            return Ok(false);
+        };
+        match response.chunk().await? {
+            Some(chunk) => {
+                self.decoder.push(&chunk);
+                Ok(true)
+            }
+            None => {
+                self.response = None;
+                Ok(false)
+            }
+        }
+    }
+
+    /// The response body of wasm cannot be read as chunks, so it is read at once.
+    #[cfg(feature = "wasm")]
+    async fn read_chunk(&mut self) -> error::Result<bool> {
+        let Some(response) = self.response.take() else {
+            return Ok(false);
+        };
+        self.decoder.push(&response.bytes().await?);
+        Ok(true)
+    }
+}
+
+/// Sets the body encoded in the binary format, which is much smaller than JSON for bundles.
+fn binary_body<S: Serialize>(request: RequestBuilder, body: &S) -> error::Result<RequestBuilder> {
+    Ok(request
+        .header(header::CONTENT_TYPE, wire::BINARY_CONTENT_TYPE)
+        .body(wire::encode(body)?))
+}
+
+/// Deserializes the response body as JSON or the binary format according to its `Content-Type`.
+async fn deserialize_response<D>(response: Response) -> error::Result<D>
     where
         D: DeserializeOwned,
 {
-    Ok(response.error_for_status()?.json().await?)
+    let response = response.error_for_status()?;
+    let is_binary = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(wire::is_binary);
+    if is_binary {
+        Ok(wire::decode(&response.bytes().await?)?)
+    } else {
+        Ok(response.json().await?)
+    }
 }
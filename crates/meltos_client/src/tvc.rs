@@ -3,28 +3,41 @@ use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::wasm_bindgen;
 
 use meltos_core::room::RoomId;
+use meltos_core::schema::room::Fetch;
 use meltos_core::user::UserId;
 use meltos_tvc::branch::BranchName;
 use meltos_tvc::file_system::{FilePath, FileSystem};
+use meltos_tvc::io::atomic::config::ConfigIo;
 use meltos_tvc::io::atomic::head::HeadIo;
 use meltos_tvc::io::atomic::local_commits::LocalCommitsIo;
 use meltos_tvc::io::atomic::object::ObjIo;
+use meltos_tvc::io::atomic::reflog::ReflogEntry;
 use meltos_tvc::io::atomic::staging::StagingIo;
-use meltos_tvc::io::bundle::Bundle;
-use meltos_tvc::io::commit_hashes::CommitHashIo;
+use meltos_tvc::io::bundle::{Bundle, BundleBranch, BundleHashes, BundleRecord, BundleStream};
 use meltos_tvc::io::commit_obj::CommitObjIo;
 use meltos_tvc::io::trace_tree::TraceTreeIo;
 use meltos_tvc::io::workspace::WorkspaceIo;
-use meltos_tvc::object::commit::CommitHash;
+use meltos_tvc::object::commit::{CommitHash, CommitObj};
 use meltos_tvc::object::ObjHash;
 use meltos_tvc::object::tree::TreeObj;
+use meltos_tvc::operation::blame::BlameLine;
+use meltos_tvc::operation::diff::{DiffTarget, FileChange};
+use meltos_tvc::operation::fsck::FsckReport;
+use meltos_tvc::operation::gc::GcReport;
+use meltos_tvc::operation::log::{LogEntry, LogOptions};
 use meltos_tvc::operation::merge::MergedStatus;
 use meltos_tvc::operation::Operations;
 use meltos_tvc::operation::push::Pushable;
+use meltos_tvc::operation::repack::RepackReport;
+use meltos_tvc::operation::reset::ResetMode;
+use meltos_tvc::operation::stash::StashEntry;
+use meltos_tvc::operation::status::WorkspaceStatus;
+use meltos_tvc::operation::tag::TagEntry;
+use meltos_tvc::tag::TagName;
 
 use crate::config::SessionConfigs;
 use crate::error;
-use crate::http::HttpClient;
+use crate::http::{FrameReader, HttpClient};
 
 #[wasm_bindgen(getter_with_clone)]
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -38,6 +51,10 @@ pub struct ObjMeta {
 pub struct CommitMeta {
     pub hash: String,
     pub message: String,
+    /// The branch name that created the commit. `None` if the commit does not record it.
+    pub author: Option<String>,
+    /// Seconds elapsed since the UNIX epoch when committed.
+    pub committed_at: Option<u64>,
     pub objs: Vec<ObjMeta>,
 }
 
@@ -58,13 +75,12 @@ pub struct TvcClient<Fs: FileSystem + Clone> {
     trace: TraceTreeIo<Fs>,
     local_commits: LocalCommitsIo<Fs>,
     commit_obj: CommitObjIo<Fs>,
-    commit_hashes: CommitHashIo<Fs>,
     workspace: WorkspaceIo<Fs>,
     obj: ObjIo<Fs>,
     fs: Fs,
 }
 
-impl<Fs: FileSystem + Clone> TvcClient<Fs> {
+impl<Fs: FileSystem + Clone + 'static> TvcClient<Fs> {
     pub fn new(fs: Fs) -> Self {
         Self {
             operations: Operations::new(fs.clone()),
@@ -73,7 +89,6 @@ impl<Fs: FileSystem + Clone> TvcClient<Fs> {
             trace: TraceTreeIo::new(fs.clone()),
             local_commits: LocalCommitsIo::new(fs.clone()),
             commit_obj: CommitObjIo::new(fs.clone()),
-            commit_hashes: CommitHashIo::new(fs.clone()),
             workspace: WorkspaceIo::new(fs.clone()),
             obj: ObjIo::new(fs.clone()),
             fs,
@@ -97,6 +112,17 @@ impl<Fs: FileSystem + Clone> TvcClient<Fs> {
     }
 
 
+    /// Deletes the local branch; `force` allows deleting it even if its local commits are not merged.
+    #[inline(always)]
+    pub async fn delete_branch(&self, branch_name: &BranchName, force: bool) -> error::Result {
+        Ok(self.operations.delete_branch.execute(branch_name, force).await?)
+    }
+
+    #[inline(always)]
+    pub async fn rename_branch(&self, old: &BranchName, new: &BranchName) -> error::Result {
+        Ok(self.operations.rename_branch.execute(old, new).await?)
+    }
+
     #[inline(always)]
     pub async fn unzip(&self, branch_name: &BranchName) -> error::Result {
         self.operations.unzip.execute(branch_name).await?;
@@ -125,10 +151,10 @@ impl<Fs: FileSystem + Clone> TvcClient<Fs> {
         room_id: String,
         user_id: Option<UserId>,
     ) -> error::Result<SessionConfigs> {
-        let (http, bundle) = HttpClient::join(BASE, RoomId(room_id), user_id).await?;
+        let (http, mut records) = HttpClient::join_stream(BASE, RoomId(room_id), user_id).await?;
         let branch = BranchName(http.configs().user_id.0.clone());
 
-        self.operations.save.execute(bundle).await?;
+        self.save_records(&mut records).await?;
         self.operations.checkout.execute(&branch).await?;
         self.operations.unzip.execute(&branch).await?;
 
@@ -143,8 +169,19 @@ impl<Fs: FileSystem + Clone> TvcClient<Fs> {
     #[inline]
     pub async fn fetch(&self, session_config: SessionConfigs) -> error::Result {
         let http = HttpClient::new(BASE, session_config);
-        let bundle = http.fetch().await?;
-        self.operations.save.execute(bundle).await?;
+        let mut records = http
+            .fetch_stream_since(&Fetch {
+                known: self.known_branches().await?,
+            })
+            .await?;
+        self.save_records(&mut records).await
+    }
+
+    /// Writes the records of the bundle one by one as they are received.
+    async fn save_records(&self, records: &mut FrameReader) -> error::Result {
+        while let Some(record) = records.next::<BundleRecord>().await? {
+            self.operations.save.write_record(record).await?;
+        }
         Ok(())
     }
 
@@ -175,6 +212,24 @@ impl<Fs: FileSystem + Clone> TvcClient<Fs> {
             .await?)
     }
 
+    /// Moves the head of the working branch to `commit_hash`; the dropped local commits are not pushed.
+    #[inline(always)]
+    pub async fn reset(&self, commit_hash: &CommitHash, mode: ResetMode) -> error::Result {
+        Ok(self.operations.reset.execute(commit_hash, mode).await?)
+    }
+
+    /// Returns the movements of the head of `branch_name`; the newest one comes first.
+    #[inline(always)]
+    pub async fn reflog(&self, branch_name: &BranchName) -> error::Result<Vec<ReflogEntry>> {
+        Ok(self.operations.reflog.list(branch_name).await?)
+    }
+
+    /// Moves the head of `branch_name` to the `index`-th entry of [`TvcClient::reflog`].
+    #[inline(always)]
+    pub async fn restore_reflog(&self, branch_name: &BranchName, index: usize) -> error::Result {
+        Ok(self.operations.reflog.restore(branch_name, index).await?)
+    }
+
     pub async fn push(&self, session_configs: SessionConfigs) -> error::Result {
         let branch_name = session_configs.user_id.clone().into();
         let mut sender = PushSender {
@@ -192,6 +247,182 @@ impl<Fs: FileSystem + Clone> TvcClient<Fs> {
         Ok(status)
     }
 
+    /// Applies the changes of `commit_hash` onto `dist`; conflicts are resolved with [`TvcClient::merge_continue`].
+    pub async fn cherry_pick(&self, dist: BranchName, commit_hash: CommitHash) -> error::Result<MergedStatus> {
+        let status = self.operations.cherry_pick.execute(commit_hash, dist).await?;
+        Ok(status)
+    }
+
+    /// Undoes the changes of `commit_hash` on `dist`; conflicts are resolved with [`TvcClient::merge_continue`].
+    pub async fn revert(&self, dist: BranchName, commit_hash: CommitHash) -> error::Result<MergedStatus> {
+        let status = self.operations.revert.execute(commit_hash, dist).await?;
+        Ok(status)
+    }
+
+    pub async fn merge_continue(&self) -> error::Result<CommitHash> {
+        let commit_hash = self.operations.merge.continue_merge().await?;
+        Ok(commit_hash)
+    }
+
+    pub async fn merge_abort(&self) -> error::Result {
+        self.operations.merge.abort().await?;
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub async fn diff(
+        &self,
+        branch_name: &BranchName,
+        old: &DiffTarget,
+        new: &DiffTarget,
+    ) -> error::Result<Vec<FileChange>> {
+        Ok(self.operations.diff.execute(branch_name, old, new).await?)
+    }
+
+    /// Returns the commit which last changed each line of the file committed on `branch_name`.
+    #[inline(always)]
+    pub async fn blame(&self, branch_name: &BranchName, file_path: &FilePath) -> error::Result<Vec<BlameLine>> {
+        Ok(self.operations.blame.execute(branch_name, file_path).await?)
+    }
+
+    #[inline(always)]
+    pub async fn status(&self, branch_name: &BranchName) -> error::Result<WorkspaceStatus> {
+        Ok(self.operations.status.execute(branch_name).await?)
+    }
+
+    #[inline(always)]
+    pub async fn gc(&self, dry_run: bool) -> error::Result<GcReport> {
+        Ok(self.operations.gc.execute(dry_run).await?)
+    }
+
+    #[inline(always)]
+    pub async fn fsck(&self) -> error::Result<FsckReport> {
+        Ok(self.operations.fsck.execute().await?)
+    }
+
+    #[inline(always)]
+    pub async fn repack(&self) -> error::Result<RepackReport> {
+        Ok(self.operations.repack.execute().await?)
+    }
+
+    /// Stashes the uncommitted changes, and resets the workspace to the head of `branch_name`.
+    #[inline(always)]
+    pub async fn stash(&self, branch_name: &BranchName, message: Option<&str>) -> error::Result<CommitHash> {
+        Ok(self.operations.stash.save(branch_name, message).await?)
+    }
+
+    /// Restores the stashed changes and returns the conflicted files.
+    #[inline(always)]
+    pub async fn stash_apply(&self, branch_name: &BranchName, index: usize) -> error::Result<Vec<FilePath>> {
+        Ok(self.operations.stash.apply(branch_name, index).await?)
+    }
+
+    /// Restores the stashed changes, and drops the stash if there are no conflicts.
+    #[inline(always)]
+    pub async fn stash_pop(&self, branch_name: &BranchName, index: usize) -> error::Result<Vec<FilePath>> {
+        Ok(self.operations.stash.pop(branch_name, index).await?)
+    }
+
+    #[inline(always)]
+    pub async fn stash_list(&self) -> error::Result<Vec<StashEntry>> {
+        Ok(self.operations.stash.list().await?)
+    }
+
+    #[inline(always)]
+    pub async fn stash_drop(&self, index: usize) -> error::Result {
+        Ok(self.operations.stash.drop(index).await?)
+    }
+
+    /// Creates the lightweight tag; it is sent to the room with the next push.
+    #[inline(always)]
+    pub async fn create_tag(&self, tag_name: &TagName, commit_hash: &CommitHash) -> error::Result {
+        Ok(self.operations.tag.create(tag_name, commit_hash).await?)
+    }
+
+    /// Creates the annotated tag tagged by `branch_name`; it is sent to the room with the next push.
+    #[inline(always)]
+    pub async fn create_annotated_tag(
+        &self,
+        branch_name: &BranchName,
+        tag_name: &TagName,
+        commit_hash: &CommitHash,
+        message: &str,
+    ) -> error::Result<ObjHash> {
+        Ok(self
+            .operations
+            .tag
+            .create_annotated(tag_name, commit_hash, branch_name, message)
+            .await?)
+    }
+
+    #[inline(always)]
+    pub async fn tags(&self) -> error::Result<Vec<TagEntry>> {
+        Ok(self.operations.tag.list().await?)
+    }
+
+    #[inline(always)]
+    pub async fn delete_tag(&self, tag_name: &TagName) -> error::Result {
+        Ok(self.operations.tag.delete(tag_name).await?)
+    }
+
+    /// Converts the history of `git_branch` in the git repository at `git_dir` into a bundle for opening a room.
+    #[inline(always)]
+    pub async fn import_git(&self, git_dir: &str, git_branch: &str) -> error::Result<Bundle> {
+        Ok(self
+            .operations
+            .import_git
+            .execute(git_dir, git_branch, &BranchName::owner())
+            .await?)
+    }
+
+    /// Exports the history of the branch in the local repository as a `git fast-import` stream.
+    #[inline(always)]
+    pub async fn export_git(&self, branch_name: &BranchName) -> error::Result<Vec<u8>> {
+        Ok(self.operations.export_git.execute(branch_name).await?)
+    }
+
+    /// Exports the history of the branch in the room's repository as a `git fast-import` stream.
+    #[inline(always)]
+    pub async fn export_room_git(&self, session_configs: SessionConfigs, branch_name: &BranchName) -> error::Result<Vec<u8>> {
+        HttpClient::new(BASE, session_configs).export_git(branch_name).await
+    }
+
+    /// Sets whether `.gitignore` files are applied in addition to `.meltos_ignore`.
+    pub async fn set_honor_gitignore(&self, honor_gitignore: bool) -> error::Result {
+        let config = ConfigIo::new(self.fs.clone());
+        let mut repository_config = config.read().await?;
+        repository_config.honor_gitignore = honor_gitignore;
+        config.write(&repository_config).await?;
+        Ok(())
+    }
+
+    /// Sets whether symlinks whose targets are absolute or contain `..` are written into the workspace.
+    pub async fn set_allow_unsafe_symlinks(&self, allow_unsafe_symlinks: bool) -> error::Result {
+        let config = ConfigIo::new(self.fs.clone());
+        let mut repository_config = config.read().await?;
+        repository_config.allow_unsafe_symlinks = allow_unsafe_symlinks;
+        config.write(&repository_config).await?;
+        Ok(())
+    }
+
+    /// Limits the files written into the workspace to the ones matching the gitignore-style patterns.
+    ///
+    /// The patterns can be set before joining a room, so that only the matching files are written when joined.
+    #[inline(always)]
+    pub async fn set_sparse_checkout(&self, patterns: &[String]) -> error::Result {
+        Ok(self.operations.sparse_checkout.set(patterns).await?)
+    }
+
+    #[inline(always)]
+    pub async fn sparse_checkout_patterns(&self) -> error::Result<Vec<String>> {
+        Ok(self.operations.sparse_checkout.patterns().await?)
+    }
+
+    #[inline(always)]
+    pub async fn disable_sparse_checkout(&self) -> error::Result {
+        Ok(self.operations.sparse_checkout.disable().await?)
+    }
+
     pub async fn read_file_from_hash(&self, obj_hash: &ObjHash) -> error::Result<Option<String>> {
         let Some(file_obj) = self.obj.try_read_to_file(obj_hash).await? else {
             return Ok(None);
@@ -213,26 +444,68 @@ impl<Fs: FileSystem + Clone> TvcClient<Fs> {
     }
 
 
+    /// The heads and the remote heads, which the server does not need to send again.
+    ///
+    /// The server does not have the local commits yet,
+    /// so the parents of the oldest local commit are also sent.
+    async fn known_branches(&self) -> error::Result<Vec<BundleBranch>> {
+        let mut heads = self.head.read_all().await?;
+        heads.extend(self.head.read_all_remotes().await?);
+        let mut branches = Vec::with_capacity(heads.len());
+        for (branch_name, head) in heads {
+            let mut commits = vec![head];
+            if let Some(oldest) = self
+                .local_commits
+                .read(&branch_name)
+                .await?
+                .and_then(|local_commits| local_commits.0.first().cloned())
+            {
+                commits.extend(self.obj.read_to_commit(&oldest).await?.parents);
+            }
+            branches.push(BundleBranch {
+                branch_name,
+                commits,
+            });
+        }
+        Ok(branches)
+    }
+
     async fn all_commit_metas(&self, branch_name: &BranchName) -> error::Result<Vec<CommitMeta>> {
+        self.commit_metas(branch_name, LogOptions::default()).await
+    }
+
+    /// Returns the commits of the branch in the order of the log.
+    ///
+    /// The history panel can page through them with `skip` and `limit` of [`LogOptions`].
+    pub async fn commit_metas(
+        &self,
+        branch_name: &BranchName,
+        options: LogOptions,
+    ) -> error::Result<Vec<CommitMeta>> {
         let Some(head) = self.head.read(branch_name).await? else {
             return Ok(Vec::with_capacity(0));
         };
-        let hashes = self.commit_hashes.read_all(head, &None).await?;
-        let mut metas = Vec::with_capacity(hashes.len());
-        for commit_hash in hashes {
-            metas.push(self.read_commit_meta(&commit_hash).await?);
+        let mut log = self.operations.log.iter(head, options).await?;
+        let mut metas = Vec::new();
+        while let Some(LogEntry { hash, commit }) = log.next().await? {
+            metas.push(self.to_commit_meta(&hash, commit).await?);
         }
         Ok(metas)
     }
 
     pub async fn read_commit_meta(&self, commit_hash: &CommitHash) -> error::Result<CommitMeta> {
         let obj = self.commit_obj.read(commit_hash).await?;
-        let tree = self.commit_obj.read_commit_tree(commit_hash).await?;
+        self.to_commit_meta(commit_hash, obj).await
+    }
+
+    async fn to_commit_meta(&self, commit_hash: &CommitHash, obj: CommitObj) -> error::Result<CommitMeta> {
+        let tree = self.obj.read_to_tree(&obj.committed_objs_tree).await?;
         Ok(CommitMeta {
             hash: commit_hash.to_string(),
             message: obj.text.0,
+            author: obj.author.map(|author| author.0),
+            committed_at: obj.committed_at,
             objs: tree
-                .0
                 .into_iter()
                 .map(|(file_path, hash)| {
                     ObjMeta {
@@ -271,7 +544,7 @@ impl<Fs: FileSystem + Clone> TvcClient<Fs> {
             return Ok(None);
         };
         let trace_tree = self.trace.read(&head).await?;
-        Ok(trace_tree.get(&FilePath::from(format!("{file_path}"))).cloned())
+        Ok(trace_tree.get(&FilePath::from(file_path.to_string())).cloned())
     }
 
 
@@ -321,10 +594,20 @@ struct PushSender {
 impl Pushable<()> for PushSender {
     type Error = String;
 
+    async fn missing(&mut self, hashes: BundleHashes) -> Result<BundleHashes, Self::Error> {
+        let mut http = HttpClient::new(BASE, self.session_configs.clone());
+        http.missing(hashes).await
+    }
+
     async fn push(&mut self, bundle: Bundle) -> Result<(), Self::Error> {
         let mut http = HttpClient::new(BASE, self.session_configs.clone());
         http.push(bundle).await?;
 
         Ok(())
     }
+
+    async fn push_stream(&mut self, stream: BundleStream) -> meltos_tvc::error::Result {
+        let mut http = HttpClient::new(BASE, self.session_configs.clone());
+        http.push_stream(stream).await
+    }
 }
@@ -1,9 +1,8 @@
 use async_trait::async_trait;
-use meltos_tvc::io::bundle::Bundle;
 use serde::{Deserialize, Serialize};
 
 use crate::schema::discussion::global::{Closed, Created, Replied, Spoke};
-use crate::schema::room::Left;
+use crate::schema::room::{Left, Pushed};
 use crate::user::UserId;
 
 #[async_trait]
@@ -35,7 +34,7 @@ pub enum MessageData {
     Joined { user_id: String },
     Left(Left),
     ClosedRoom,
-    Pushed(Bundle),
+    Pushed(Pushed),
     DiscussionCreated(Created),
     DiscussionSpoke(Spoke),
     DiscussionReplied(Replied),
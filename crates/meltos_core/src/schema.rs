@@ -1,3 +1,4 @@
 pub mod discussion;
 pub mod error;
 pub mod room;
+pub mod wire;
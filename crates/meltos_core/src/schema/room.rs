@@ -1,11 +1,17 @@
+mod fetch;
+mod gc;
 mod join;
 mod kick;
 mod leave;
 mod open;
+mod push;
 mod sync;
 
+pub use fetch::*;
+pub use gc::*;
 pub use join::*;
 pub use kick::*;
 pub use leave::*;
 pub use open::*;
+pub use push::*;
 pub use sync::*;
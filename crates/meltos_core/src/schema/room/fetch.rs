@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use meltos_tvc::io::bundle::BundleBranch;
+
+/// クライアントが既に保持しているコミットを送信し、不足しているオブジェクトのみのバンドルを取得するリクエストを表します。
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, Eq, PartialEq)]
+pub struct Fetch {
+    /// ブランチごとにクライアントが保持しているコミットハッシュ
+    ///
+    /// これらのコミットとその祖先はバンドルに含まれません。
+    pub known: Vec<BundleBranch>,
+}
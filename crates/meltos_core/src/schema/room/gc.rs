@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::wasm_bindgen;
+
+/// ルーム内のTvcリポジトリから到達不能なオブジェクトを削除するリクエストを表します。
+///
+/// このリクエストはルームオーナーのみ受理されます。
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Gc {
+    /// trueの場合、削除対象の報告のみ行い実際には削除しません。
+    #[serde(default)]
+    pub dry_run: bool,
+}
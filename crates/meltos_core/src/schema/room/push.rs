@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+use meltos_tvc::io::bundle::{Bundle, BundleBranch, BundleHashes, BundleRecord, BundleTag};
+
+/// ルーム内のTvcリポジトリにプッシュされたことを表します。
+///
+/// オブジェクトの内容は含まれないため、受信したクライアントは[`Fetch`](crate::schema::room::Fetch)で不足しているオブジェクトを取得します。
+#[derive(Debug, Serialize, Deserialize, Clone, Default, Eq, PartialEq)]
+pub struct Pushed {
+    /// プッシュされたブランチとそのコミットハッシュ
+    pub branches: Vec<BundleBranch>,
+
+    /// プッシュされたタグ
+    pub tags: Vec<BundleTag>,
+
+    /// プッシュされたオブジェクトとトレースのハッシュ
+    pub hashes: BundleHashes,
+}
+
+impl Pushed {
+    /// ストリーム形式で受信したレコードを追加します。
+    pub fn push_record(&mut self, record: &BundleRecord) {
+        match record {
+            BundleRecord::Trace(trace) => self.hashes.traces.push(trace.commit_hash.clone()),
+            BundleRecord::Obj(obj) => self.hashes.objs.push(obj.hash.clone()),
+            BundleRecord::Branch(branch) => self.branches.push(branch.clone()),
+            BundleRecord::Tag(tag) => self.tags.push(tag.clone()),
+        }
+    }
+}
+
+impl From<&Bundle> for Pushed {
+    fn from(bundle: &Bundle) -> Self {
+        Self {
+            branches: bundle.branches.clone(),
+            tags: bundle.tags.clone(),
+            hashes: bundle.hashes(),
+        }
+    }
+}
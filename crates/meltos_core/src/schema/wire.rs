@@ -0,0 +1,258 @@
+//! バンドルを送受信する際のバイナリ形式
+//!
+//! JSON形式では`CompressedBuf`が数値の配列になりデータサイズが数倍に膨らむため、
+//! `Content-Type`または`Accept`に[`BINARY_CONTENT_TYPE`]が指定された場合はこの形式で送受信します。
+//!
+//! データは先頭に[`MAGIC`]が付与され、その後にbincodeでエンコードされた値が続きます。
+//!
+//! [`STREAM_CONTENT_TYPE`]が指定された場合は、[`MAGIC`]の後に複数のフレームが続くストリーム形式になります。
+//! 各フレームは4バイト(リトルエンディアン)の長さとbincodeでエンコードされた値で構成され、
+//! リポジトリ全体をメモリに載せることなく1件ずつ送受信できます。
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+pub const JSON_CONTENT_TYPE: &str = "application/json";
+
+pub const BINARY_CONTENT_TYPE: &str = "application/x-meltos-bundle";
+
+pub const STREAM_CONTENT_TYPE: &str = "application/x-meltos-bundle-stream";
+
+/// バイナリ形式の識別子とバージョン
+pub const MAGIC: &[u8] = b"MLTB\x01";
+
+pub type Error = bincode::Error;
+
+/// 値をバイナリ形式にエンコードします。
+pub fn encode<S: Serialize>(value: &S) -> Result<Vec<u8>, Error> {
+    let mut buf = MAGIC.to_vec();
+    bincode::serialize_into(&mut buf, value)?;
+    Ok(buf)
+}
+
+/// [`encode`]でエンコードされたデータをデコードします。
+pub fn decode<D: DeserializeOwned>(buf: &[u8]) -> Result<D, Error> {
+    let body = buf
+        .strip_prefix(MAGIC)
+        .ok_or_else(|| invalid("unknown binary format"))?;
+    bincode::deserialize(body)
+}
+
+/// ストリーム形式の1フレームをエンコードします。
+pub fn encode_frame<S: Serialize>(value: &S) -> Result<Vec<u8>, Error> {
+    let len = bincode::serialized_size(value)?;
+    let len = u32::try_from(len).map_err(|_| invalid("frame is too large"))?;
+    let mut buf = len.to_le_bytes().to_vec();
+    bincode::serialize_into(&mut buf, value)?;
+    Ok(buf)
+}
+
+/// 受信したデータを順に渡し、ストリーム形式のフレームを1件ずつ取り出します。
+#[derive(Debug, Default)]
+pub struct FrameDecoder {
+    buf: Vec<u8>,
+    read_magic: bool,
+}
+
+impl FrameDecoder {
+    #[inline]
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.buf.extend_from_slice(chunk);
+    }
+
+    /// まだデコードされていないデータのバイト数
+    #[inline]
+    pub fn buffered_len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// 次のフレームをデコードします。
+    ///
+    /// フレームのデータが全て届いていない場合は`None`を返します。
+    pub fn next_frame<D: DeserializeOwned>(&mut self) -> Result<Option<D>, Error> {
+        if !self.read_magic {
+            if self.buf.len() < MAGIC.len() {
+                return Ok(None);
+            }
+            if !self.buf.starts_with(MAGIC) {
+                return Err(invalid("unknown binary format"));
+            }
+            self.buf.drain(..MAGIC.len());
+            self.read_magic = true;
+        }
+        let Some(len) = self.buf.get(..4) else {
+            return Ok(None);
+        };
+        let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+        if self.buf.len() < 4 + len {
+            return Ok(None);
+        }
+        let value = bincode::deserialize(&self.buf[4..4 + len])?;
+        self.buf.drain(..4 + len);
+        Ok(Some(value))
+    }
+
+    /// ストリームの終端で呼び出し、途中で途切れたフレームが無いかを確認します。
+    pub fn finish(&self) -> Result<(), Error> {
+        if self.read_magic && self.buf.is_empty() {
+            Ok(())
+        } else {
+            Err(invalid("stream ended in the middle of a frame"))
+        }
+    }
+}
+
+/// `Content-Type`または`Accept`ヘッダーの値がバイナリ形式を表すかを判定します。
+#[inline]
+pub fn is_binary(header_value: &str) -> bool {
+    contains_media_type(header_value, BINARY_CONTENT_TYPE)
+}
+
+/// `Content-Type`または`Accept`ヘッダーの値がストリーム形式を表すかを判定します。
+#[inline]
+pub fn is_stream(header_value: &str) -> bool {
+    contains_media_type(header_value, STREAM_CONTENT_TYPE)
+}
+
+fn contains_media_type(header_value: &str, content_type: &str) -> bool {
+    header_value
+        .split(',')
+        .any(|media_type| media_type.split(';').next().unwrap_or_default().trim() == content_type)
+}
+
+#[inline]
+fn invalid(reason: &str) -> Error {
+    Box::new(bincode::ErrorKind::Custom(reason.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use meltos_tvc::branch::BranchName;
+    use meltos_tvc::io::bundle::{Bundle, BundleBranch, BundleObject, BundleRecord, BundleTrace};
+    use meltos_tvc::object::commit::CommitHash;
+    use meltos_tvc::object::{CompressedBuf, ObjHash};
+    use meltos_util::serde::SerializeJson;
+
+    use crate::discussion::id::DiscussionId;
+    use crate::discussion::message::Message;
+    use crate::discussion::{DiscussionBundle, DiscussionMeta, MessageBundle};
+    use crate::schema::room::{Joined, Open, RoomBundle};
+    use crate::schema::wire::{
+        decode, encode, encode_frame, is_binary, is_stream, FrameDecoder, MAGIC,
+    };
+    use crate::user::{SessionId, UserId};
+
+    #[test]
+    fn decode_encoded_bundle() {
+        let bundle = bundle();
+        let buf = encode(&bundle).unwrap();
+        assert!(buf.len() < bundle.as_json().len());
+        assert_eq!(decode::<Bundle>(&buf).unwrap(), bundle);
+
+        let open = Open::new(Some(60), None, Some(bundle.clone()));
+        assert_eq!(decode::<Open>(&encode(&open).unwrap()).unwrap(), open);
+
+        let joined = Joined {
+            user_id: UserId::from("user"),
+            session_id: SessionId("session".to_string()),
+            bundle: bundle.clone(),
+            discussions: vec![discussion()],
+        };
+        assert_eq!(decode::<Joined>(&encode(&joined).unwrap()).unwrap(), joined);
+
+        let room_bundle = RoomBundle {
+            tvc: bundle.clone(),
+            discussion: vec![discussion()],
+        };
+        let decoded: RoomBundle = decode(&encode(&room_bundle).unwrap()).unwrap();
+        assert_eq!(decoded.tvc, bundle);
+        assert_eq!(decoded.discussion, room_bundle.discussion);
+    }
+
+    #[test]
+    fn failed_if_magic_is_invalid() {
+        assert!(decode::<Bundle>(b"{}").is_err());
+        let mut buf = encode(&bundle()).unwrap();
+        buf.truncate(buf.len() - 1);
+        assert!(decode::<Bundle>(&buf).is_err());
+    }
+
+    #[test]
+    fn detect_binary_media_type() {
+        assert!(is_binary("application/x-meltos-bundle"));
+        assert!(is_binary(
+            "application/json, application/x-meltos-bundle;q=0.9"
+        ));
+        assert!(!is_binary("application/json"));
+    }
+
+    #[test]
+    fn decode_frames_split_into_chunks() {
+        let records = bundle().into_records().collect::<Vec<BundleRecord>>();
+        let mut buf = MAGIC.to_vec();
+        for record in records.iter() {
+            buf.extend(encode_frame(record).unwrap());
+        }
+
+        let mut decoder = FrameDecoder::default();
+        let mut decoded = Vec::new();
+        for chunk in buf.chunks(7) {
+            decoder.push(chunk);
+            while let Some(record) = decoder.next_frame::<BundleRecord>().unwrap() {
+                decoded.push(record);
+            }
+        }
+        decoder.finish().unwrap();
+        assert_eq!(decoded, records);
+        assert!(is_stream("application/x-meltos-bundle-stream"));
+        assert!(!is_binary("application/x-meltos-bundle-stream"));
+    }
+
+    #[test]
+    fn failed_if_stream_is_truncated() {
+        let mut decoder = FrameDecoder::default();
+        decoder.push(MAGIC);
+        let frame = encode_frame(&bundle()).unwrap();
+        decoder.push(&frame[..frame.len() - 1]);
+        assert!(decoder.next_frame::<Bundle>().unwrap().is_none());
+        assert!(decoder.finish().is_err());
+
+        let mut decoder = FrameDecoder::default();
+        decoder.push(b"{\"traces\": []}");
+        assert!(decoder.next_frame::<Bundle>().is_err());
+    }
+
+    fn discussion() -> DiscussionBundle {
+        let message = Message::new("owner".to_string(), "hello".to_string());
+        DiscussionBundle {
+            meta: DiscussionMeta::new(
+                DiscussionId::new(),
+                "title".to_string(),
+                UserId::from("owner"),
+            ),
+            messages: vec![MessageBundle {
+                replies: vec![message.clone()],
+                message,
+            }],
+        }
+    }
+
+    fn bundle() -> Bundle {
+        let hash = ObjHash::new(b"hello");
+        Bundle {
+            branches: vec![BundleBranch {
+                branch_name: BranchName::owner(),
+                commits: vec![CommitHash(hash.clone())],
+            }],
+            traces: vec![BundleTrace {
+                commit_hash: CommitHash(hash.clone()),
+                obj_hash: hash.clone(),
+            }],
+            objs: vec![BundleObject {
+                hash,
+                compressed_buf: CompressedBuf(vec![255; 128]),
+            }],
+            tags: Vec::new(),
+        }
+    }
+}
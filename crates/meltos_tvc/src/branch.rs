@@ -19,6 +19,18 @@ impl BranchName {
         Self::from("owner")
     }
 
+    /// Fails if the branch name cannot be stored under `.meltos/refs/heads`.
+    ///
+    /// The same rules as [`TagName::validate`](crate::tag::TagName::validate) apply,
+    /// so that neither the head nor the reflog escapes its directory.
+    pub fn validate(&self) -> error::Result {
+        if is_valid_ref_name(&self.0) {
+            Ok(())
+        } else {
+            Err(error::Error::InvalidBranchName(self.clone()))
+        }
+    }
+
     pub async fn working<Fs>(fs: Fs) -> error::Result<Self>
     where
         Fs: FileSystem,
@@ -26,3 +38,32 @@ impl BranchName {
         WorkingIo::new(fs).try_read().await
     }
 }
+
+/// Returns true if the name is not empty, does not start or end with `/`,
+/// and contains neither backslashes nor empty, `.` or `..` segments.
+pub(crate) fn is_valid_ref_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.contains('\\')
+        && name
+            .split('/')
+            .all(|segment| !segment.is_empty() && segment != "." && segment != "..")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::branch::BranchName;
+
+    #[test]
+    fn valid_branch_names() {
+        for name in ["owner", "feature/login", "a..b"] {
+            assert!(BranchName::from(name).validate().is_ok(), "{name}");
+        }
+    }
+
+    #[test]
+    fn invalid_branch_names() {
+        for name in ["", "/owner", "owner/", "feature//login", "..", "../tags/v1", "../../objects/..", "./owner", "a\\b"] {
+            assert!(BranchName::from(name).validate().is_err(), "{name}");
+        }
+    }
+}
@@ -9,6 +9,7 @@ use crate::branch::BranchName;
 use crate::file_system::FilePath;
 use crate::object::commit::CommitHash;
 use crate::object::ObjHash;
+use crate::tag::TagName;
 
 pub type Result<T = ()> = std::result::Result<T, Error>;
 
@@ -35,27 +36,99 @@ pub enum Error {
     #[error("commit obj buffer is invalid")]
     CommitObjBufferIsInValid,
 
+    #[error("tag obj buffer is invalid")]
+    TagObjBufferIsInvalid,
+
+    #[error("tree obj buffer is invalid")]
+    TreeObjBufferIsInvalid,
+
+    #[error("local commits obj buffer is invalid")]
+    LocalCommitsObjBufferIsInvalid,
+
+    #[error("file mode is invalid; mode: {0}")]
+    InvalidFileMode(String),
+
     #[error("not found object; hash: {0}")]
     NotfoundObj(ObjHash),
 
+    #[error("branch name is invalid; branch: {0}")]
+    InvalidBranchName(BranchName),
+
+    #[error("branch {0} already exists")]
+    BranchAlreadyExists(BranchName),
+
+    #[error("cannot delete the working branch; branch: {0}")]
+    DeleteWorkingBranch(BranchName),
+
+    #[error("branch {0} has local commits which are not merged into other branches")]
+    UnmergedLocalCommits(BranchName),
+
     #[error("not found stages")]
     NotfoundStages,
 
     #[error("not found workspace file; path: {0}")]
     NotfoundWorkspaceFile(FilePath),
 
+    #[error("not found committed file; path: {0}, commit hash: {1}")]
+    NotfoundCommittedFile(FilePath, CommitHash),
+
     #[error("changed file does not exists")]
     ChangedFileNotExits,
 
     #[error("not found trace; commit hash:{0}")]
     NotfoundTrace(CommitHash),
 
+    #[error("not found merge base of {0} and {1}")]
+    NotfoundMergeBase(CommitHash, CommitHash),
+
     #[error("not found local commits")]
     NotfoundLocalCommits,
 
     #[error("not found head; branch: {0}")]
     NotfoundHead(BranchName),
 
+    #[error("not found tag; tag: {0}")]
+    NotfoundTag(TagName),
+
+    #[error("tag name is invalid; tag: {0}")]
+    InvalidTagName(TagName),
+
+    #[error("tag {0} already exists")]
+    TagAlreadyExists(TagName),
+
+    #[error("no local changes to stash")]
+    NothingToStash,
+
+    #[error("not found stash; index: {0}")]
+    NotfoundStash(usize),
+
+    #[error("local changes would be overwritten; files: {0:?}")]
+    LocalChangesWouldBeOverwritten(Vec<FilePath>),
+
+    #[error("not found reflog entry; branch: {0}, index: {1}")]
+    NotfoundReflogEntry(BranchName, usize),
+
+    #[error("not found merge head")]
+    NotfoundMergeHead,
+
+    #[error("pack is invalid; {0}")]
+    InvalidPack(String),
+
+    #[error("git object is invalid; {0}")]
+    InvalidGitObj(String),
+
+    #[error("not found git object; id: {0}")]
+    NotfoundGitObj(String),
+
+    #[error("not found git ref; {0}")]
+    NotfoundGitRef(String),
+
+    #[error("merge is in progress; branch: {0}")]
+    MergeInProgress(BranchName),
+
+    #[error("conflicts have not been resolved; files: {0:?}")]
+    UnresolvedConflicts(Vec<FilePath>),
+
     #[error(transparent)]
     Io(#[from] std::io::Error),
 
@@ -74,6 +147,12 @@ pub enum Error {
     #[error("expect obj type is file or delete bad was {0}")]
     InvalidWorkspaceObj(String),
 
+    #[error("the parent directory of {0} is a symlink")]
+    SymlinkInPath(FilePath),
+
+    #[error("symlink {0} points outside the workspace; target: {1}")]
+    UnsafeSymlink(FilePath, String),
+
     #[error("failed connect server; detail: {0}")]
     FailedConnectServer(String),
 }
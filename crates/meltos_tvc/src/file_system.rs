@@ -26,6 +26,10 @@ pub struct Stat {
 
     /// ファイルが更新された時点におけるUTCの基準時刻からの経過時間（秒）
     pub update_time: u64,
+
+    /// ファイルのモード
+    /// ディレクトリの場合、常に[`FileMode::Regular`]
+    pub mode: FileMode,
 }
 
 impl Stat {
@@ -39,6 +43,7 @@ impl Stat {
             size,
             create_time: time,
             update_time: time,
+            mode: FileMode::Regular,
         }
     }
 
@@ -60,6 +65,43 @@ pub enum StatType {
     Dir,
 }
 
+#[wasm_bindgen]
+#[derive(Debug, Eq, Copy, Clone, PartialEq, Hash, Default, Ord, PartialOrd)]
+pub enum FileMode {
+    /// 通常のファイル
+    #[default]
+    Regular,
+    /// 実行可能なファイル
+    Executable,
+    /// シンボリックリンク
+    ///
+    /// ファイルバッファはリンク先のパスになります。
+    Symlink,
+}
+
+impl FileMode {
+    /// gitと同じ8進数表記の文字列を返します。
+    pub const fn as_octal(&self) -> &'static str {
+        match self {
+            FileMode::Regular => "100644",
+            FileMode::Executable => "100755",
+            FileMode::Symlink => "120000",
+        }
+    }
+
+    /// gitと同じ8進数表記の文字列から変換します。
+    ///
+    /// ファイル以外を表す場合、`None`が返されます。
+    pub fn from_octal(octal: &str) -> Option<FileMode> {
+        match octal {
+            "100644" | "100664" => Some(FileMode::Regular),
+            "100755" => Some(FileMode::Executable),
+            "120000" => Some(FileMode::Symlink),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[async_trait]
 pub trait FileSystem: Send + Sync {
@@ -74,6 +116,29 @@ pub trait FileSystem: Send + Sync {
     /// 親ディレクトリが存在しない場合、親となるディレクトリを全て作成します。
     async fn write_file(&self, path: &str, buf: &[u8]) -> std::io::Result<()>;
 
+    /// 対象のパスにモードを指定してファイルを書き込みます。
+    ///
+    /// シンボリックリンクの場合、ファイルバッファはリンク先のパスになります。
+    /// モードを表現できないファイルシステムでは、通常のファイルとして書き込まれます。
+    async fn write_file_with_mode(
+        &self,
+        path: &str,
+        buf: &[u8],
+        _mode: FileMode,
+    ) -> std::io::Result<()> {
+        self.write_file(path, buf).await
+    }
+
+    /// 対象のパスのファイルの末尾にバッファを追記します。
+    ///
+    /// ファイルが存在しない場合は新規作成されます。
+    /// 既定の実装はファイル全体を読み込んで書き直すため、追記をサポートするファイルシステムは上書きしてください。
+    async fn append_file(&self, path: &str, buf: &[u8]) -> std::io::Result<()> {
+        let mut file = self.read_file(path).await?.unwrap_or_default();
+        file.extend_from_slice(buf);
+        self.write_file(path, &file).await
+    }
+
     /// ディレクトリを作成します。
     ///
     /// 親ディレクトリが存在しない場合、再帰的に作成します。
@@ -82,6 +147,7 @@ pub trait FileSystem: Send + Sync {
     /// ファイルバッファを読み込みます。
     ///
     /// 対象のパスにファイルが存在しない場合、`None`が返されます。
+    /// シンボリックリンクの場合、リンク先のパスが返されます。
     async fn read_file(&self, path: &str) -> std::io::Result<Option<Vec<u8>>>;
 
     /// ディレクトリ内のエントリパスをすべて取得します。
@@ -166,6 +232,29 @@ pub trait FileSystem {
     /// 親ディレクトリが存在しない場合、親となるディレクトリを全て作成します。
     async fn write_file(&self, path: &str, buf: &[u8]) -> std::io::Result<()>;
 
+    /// 対象のパスにモードを指定してファイルを書き込みます。
+    ///
+    /// シンボリックリンクの場合、ファイルバッファはリンク先のパスになります。
+    /// モードを表現できないファイルシステムでは、通常のファイルとして書き込まれます。
+    async fn write_file_with_mode(
+        &self,
+        path: &str,
+        buf: &[u8],
+        _mode: FileMode,
+    ) -> std::io::Result<()> {
+        self.write_file(path, buf).await
+    }
+
+    /// 対象のパスのファイルの末尾にバッファを追記します。
+    ///
+    /// ファイルが存在しない場合は新規作成されます。
+    /// 既定の実装はファイル全体を読み込んで書き直すため、追記をサポートするファイルシステムは上書きしてください。
+    async fn append_file(&self, path: &str, buf: &[u8]) -> std::io::Result<()> {
+        let mut file = self.read_file(path).await?.unwrap_or_default();
+        file.extend_from_slice(buf);
+        self.write_file(path, &file).await
+    }
+
     /// ディレクトリを作成します。
     ///
     /// 親ディレクトリが存在しない場合、再帰的に作成します。
@@ -174,6 +263,7 @@ pub trait FileSystem {
     /// ファイルバッファを読み込みます。
     ///
     /// 対象のパスにファイルが存在しない場合、`None`が返されます。
+    /// シンボリックリンクの場合、リンク先のパスが返されます。
     async fn read_file(&self, path: &str) -> std::io::Result<Option<Vec<u8>>>;
 
     /// ディレクトリ内のエントリパスをすべて取得します。
@@ -250,6 +340,13 @@ pub trait FileSystem {
 pub struct FilePath(pub String);
 impl_string_new_type!(FilePath);
 
+/// Returns the parent directories of the path from the shallowest, spelled as in the path.
+pub(crate) fn parent_dirs(path: &str) -> impl Iterator<Item = &str> {
+    path.match_indices('/')
+        .map(|(i, _)| &path[..i])
+        .filter(|parent| !parent.is_empty() && *parent != ".")
+}
+
 impl FilePath {
     pub fn from_path(path: impl AsRef<Path>) -> Self {
         Self(path.as_ref().to_str().unwrap().to_string())
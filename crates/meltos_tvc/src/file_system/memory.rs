@@ -6,7 +6,7 @@ use async_trait::async_trait;
 use meltos_util::path::AsUri;
 
 use crate::file_system::memory::entry::dir::MemoryDir;
-use crate::file_system::{FileSystem, Stat};
+use crate::file_system::{FileMode, FileSystem, Stat};
 
 mod entry;
 
@@ -43,6 +43,17 @@ impl FileSystem for MemoryFileSystem {
         Ok(())
     }
 
+    async fn write_file_with_mode(
+        &self,
+        path: &str,
+        buf: &[u8],
+        mode: FileMode,
+    ) -> std::io::Result<()> {
+        self.0.write_file(path, buf);
+        self.0.try_read(path)?.file()?.set_mode(mode);
+        Ok(())
+    }
+
     async fn create_dir(&self, path: &str) -> std::io::Result<()> {
         self.0.create_dir(path);
         Ok(())
@@ -96,7 +107,7 @@ mod tests {
     use std::time::Duration;
 
     use crate::file_system::memory::MemoryFileSystem;
-    use crate::file_system::FileSystem;
+    use crate::file_system::{FileMode, FileSystem};
 
     #[tokio::test]
     async fn read_root_dir() {
@@ -337,4 +348,27 @@ mod tests {
         assert_eq!(fs.read_file("buf1").await.unwrap().unwrap(), buf1.to_vec());
         assert_eq!(fs.read_file("buf2").await.unwrap().unwrap(), buf2.to_vec());
     }
+
+    #[tokio::test]
+    async fn keep_file_mode() {
+        let fs = MemoryFileSystem::default();
+        fs.write_file_with_mode("run.sh", b"echo hi", FileMode::Executable)
+            .await
+            .unwrap();
+        assert_eq!(
+            fs.stat("run.sh").await.unwrap().unwrap().mode,
+            FileMode::Executable
+        );
+
+        fs.write_file("run.sh", b"echo hello").await.unwrap();
+        assert_eq!(
+            fs.stat("run.sh").await.unwrap().unwrap().mode,
+            FileMode::Executable
+        );
+        fs.write_file("hello.txt", b"hello").await.unwrap();
+        assert_eq!(
+            fs.stat("hello.txt").await.unwrap().unwrap().mode,
+            FileMode::Regular
+        );
+    }
 }
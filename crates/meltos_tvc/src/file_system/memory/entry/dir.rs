@@ -9,7 +9,7 @@ use meltos_util::path::AsUri;
 use crate::file_system::memory::entry::file::MemoryFile;
 use crate::file_system::memory::entry::MemoryEntry;
 use crate::file_system::memory::{as_schemes, entry_name, parent_path};
-use crate::file_system::{Stat, StatType};
+use crate::file_system::{FileMode, Stat, StatType};
 use crate::time::since_epoch_secs;
 
 #[repr(transparent)]
@@ -268,6 +268,7 @@ impl MemoryDirInner {
             update_time: self.update_time,
             ty: StatType::Dir,
             size: self.entries.len() as u64 - 2, // `.`と`..`を除く
+            mode: FileMode::Regular,
         }
     }
 }
@@ -2,7 +2,7 @@ use std::sync::{Arc, RwLock};
 
 use wasm_bindgen::prelude::wasm_bindgen;
 
-use crate::file_system::{Stat, StatType};
+use crate::file_system::{FileMode, Stat, StatType};
 use crate::time::since_epoch_secs;
 
 #[repr(transparent)]
@@ -30,6 +30,11 @@ impl MemoryFile {
         self.0.write().unwrap().buf = buf;
     }
 
+    #[inline(always)]
+    pub fn set_mode(&self, mode: FileMode) {
+        self.0.write().unwrap().mode = mode;
+    }
+
     #[inline(always)]
     pub fn set_update_time(&self, update_time: u64) {
         self.0.write().unwrap().update_time = update_time;
@@ -42,6 +47,7 @@ struct MemoryFileInner {
     pub create_time: u64,
     pub update_time: u64,
     pub buf: Vec<u8>,
+    pub mode: FileMode,
 }
 
 impl MemoryFileInner {
@@ -52,6 +58,7 @@ impl MemoryFileInner {
             create_time,
             update_time: create_time,
             buf,
+            mode: FileMode::Regular,
         }
     }
 
@@ -62,6 +69,7 @@ impl MemoryFileInner {
             update_time: self.update_time,
             ty: StatType::File,
             size: self.buf.len() as u64,
+            mode: self.mode,
         }
     }
 }
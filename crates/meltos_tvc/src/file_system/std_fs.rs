@@ -1,12 +1,14 @@
-use std::fs::{DirEntry, File};
+use std::fs::{DirEntry, File, Metadata, OpenOptions};
 use std::io::{ErrorKind, Read, Write};
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
 use std::path::Path;
 use std::time::UNIX_EPOCH;
 
 use async_trait::async_trait;
 use meltos_util::path::AsUri;
 
-use crate::file_system::{FileSystem, Stat, StatType};
+use crate::file_system::{FileMode, FileSystem, Stat, StatType};
 
 #[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct StdFileSystem;
@@ -14,17 +16,17 @@ pub struct StdFileSystem;
 #[cfg_attr(target_arch = "wasm32", async_trait(? Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 impl FileSystem for StdFileSystem {
+    /// Symlinks are not followed, and are reported as files.
     async fn stat(&self, path: &str) -> std::io::Result<Option<Stat>> {
         let path = Path::new(path);
-        if !path.exists() {
+        let Some(meta_data) = symlink_metadata(path)? else {
             return Ok(None);
-        }
-        let meta_data = path.metadata()?;
+        };
         Ok(Some(Stat {
-            ty: if meta_data.is_file() {
-                StatType::File
-            } else {
+            ty: if meta_data.is_dir() {
                 StatType::Dir
+            } else {
+                StatType::File
             },
             create_time: meta_data
                 .created()?
@@ -36,13 +38,14 @@ impl FileSystem for StdFileSystem {
                 .duration_since(UNIX_EPOCH)
                 .unwrap()
                 .as_secs(),
-            size: if meta_data.is_file() {
-                meta_data.len()
-            } else {
+            size: if meta_data.is_dir() {
                 std::fs::read_dir(path)?
                     .collect::<Vec<std::io::Result<DirEntry>>>()
                     .len() as u64
+            } else {
+                meta_data.len()
             },
+            mode: file_mode(&meta_data),
         }))
     }
 
@@ -58,11 +61,44 @@ impl FileSystem for StdFileSystem {
         File::create(path)?.write_all(buf)
     }
 
+    async fn write_file_with_mode(
+        &self,
+        path: &str,
+        buf: &[u8],
+        mode: FileMode,
+    ) -> std::io::Result<()> {
+        // Remove the existing symlink so as not to write through it.
+        if symlink_metadata(Path::new(path))?.is_some_and(|meta| meta.is_symlink()) {
+            std::fs::remove_file(path)?;
+        }
+        if mode == FileMode::Symlink {
+            return write_symlink(path, buf);
+        }
+        self.write_file(path, buf).await?;
+        set_executable(path, mode == FileMode::Executable)
+    }
+
+    async fn append_file(&self, path: &str, buf: &[u8]) -> std::io::Result<()> {
+        let path: &Path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?
+            .write_all(buf)
+    }
+
     async fn create_dir(&self, path: &str) -> std::io::Result<()> {
         std::fs::create_dir_all(path)
     }
 
     async fn read_file(&self, path: &str) -> std::io::Result<Option<Vec<u8>>> {
+        if symlink_metadata(Path::new(path))?.is_some_and(|meta| meta.is_symlink()) {
+            let target = std::fs::read_link(path)?;
+            return Ok(Some(target.to_string_lossy().replace('\\', "/").into_bytes()));
+        }
         match File::open(path) {
             Ok(mut file) => {
                 let mut buf = Vec::new();
@@ -93,31 +129,91 @@ impl FileSystem for StdFileSystem {
 
     async fn delete(&self, path: &str) -> std::io::Result<()> {
         let path: &Path = path.as_ref();
-        if !path.exists() {
+        let Some(meta_data) = symlink_metadata(path)? else {
             return Ok(());
-        }
-        if path.is_dir() {
+        };
+        if meta_data.is_dir() {
             std::fs::remove_dir_all(path)
         } else {
             std::fs::remove_file(path)
         }
     }
 
+    /// Symlinks are not followed even if they point to directories, and are returned as files.
     async fn all_files_in(&self, path: &str) -> std::io::Result<Vec<String>> {
-        if Path::new(path).is_dir() {
-            let mut p = Vec::new();
-            for entry in std::fs::read_dir(path)? {
-                p.extend(self.all_files_in(entry?.path().to_str().unwrap()).await?);
+        match symlink_metadata(Path::new(path))? {
+            Some(meta_data) if meta_data.is_dir() => {
+                let mut p = Vec::new();
+                for entry in std::fs::read_dir(path)? {
+                    p.extend(self.all_files_in(entry?.path().to_str().unwrap()).await?);
+                }
+                Ok(p)
             }
-            Ok(p)
-        } else if std::fs::File::open(path).is_ok() {
-            Ok(vec![path.to_string().replace('\\', "/")])
-        } else {
-            Ok(Vec::with_capacity(0))
+            Some(_) => Ok(vec![path.to_string().replace('\\', "/")]),
+            None => Ok(Vec::with_capacity(0)),
         }
     }
 }
 
+/// Reads the metadata without following the symlink; `None` if the path does not exist.
+fn symlink_metadata(path: &Path) -> std::io::Result<Option<Metadata>> {
+    match std::fs::symlink_metadata(path) {
+        Ok(meta_data) => Ok(Some(meta_data)),
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+fn file_mode(meta_data: &Metadata) -> FileMode {
+    if meta_data.is_symlink() {
+        return FileMode::Symlink;
+    }
+    #[cfg(unix)]
+    if meta_data.is_file() && meta_data.permissions().mode() & 0o111 != 0 {
+        return FileMode::Executable;
+    }
+    FileMode::Regular
+}
+
+#[cfg(unix)]
+fn write_symlink(path: &str, target: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = Path::new(path).parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::os::unix::fs::symlink(String::from_utf8_lossy(target).as_ref(), path)
+}
+
+/// Symlinks require extra privileges on other platforms, so the link path is written as a regular file.
+#[cfg(not(unix))]
+fn write_symlink(path: &str, target: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = Path::new(path).parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    File::create(path)?.write_all(target)
+}
+
+/// Sets the execute bits where the read bits are set, and clears them otherwise.
+#[cfg(unix)]
+fn set_executable(path: &str, executable: bool) -> std::io::Result<()> {
+    let mut permissions = std::fs::metadata(path)?.permissions();
+    let mode = permissions.mode();
+    let new_mode = if executable {
+        mode | ((mode & 0o444) >> 2)
+    } else {
+        mode & !0o111
+    };
+    if new_mode != mode {
+        permissions.set_mode(new_mode);
+        std::fs::set_permissions(path, permissions)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_executable(_path: &str, _executable: bool) -> std::io::Result<()> {
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::Path;
@@ -126,7 +222,7 @@ mod tests {
 
     use crate::error;
     use crate::file_system::std_fs::StdFileSystem;
-    use crate::file_system::{FileSystem, StatType};
+    use crate::file_system::{FileMode, FileSystem, StatType};
 
     fn tmp_dir() -> String {
         let path = directories::BaseDirs::new()
@@ -168,6 +264,16 @@ mod tests {
         assert_eq!(fs.read_file(&path).await.unwrap().unwrap(), b"hello");
     }
 
+    #[tokio::test]
+    async fn append_file() {
+        let fs = StdFileSystem;
+        let path = as_path("dir9/hello.txt");
+        fs.delete(&path).await.unwrap();
+        fs.append_file(&path, b"hello").await.unwrap();
+        fs.append_file(&path, b" world").await.unwrap();
+        assert_eq!(fs.read_file(&path).await.unwrap().unwrap(), b"hello world");
+    }
+
     #[tokio::test]
     async fn delete_file() {
         let fs = StdFileSystem;
@@ -222,4 +328,27 @@ mod tests {
         );
         Ok(())
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn keep_file_mode() -> error::Result {
+        let fs = StdFileSystem;
+        fs.delete(&as_path("dir8")).await?;
+        let script = as_path("dir8/run.sh");
+        fs.write_file_with_mode(&script, b"echo hi", FileMode::Executable)
+            .await?;
+        assert_eq!(fs.stat(&script).await?.unwrap().mode, FileMode::Executable);
+
+        let link = as_path("dir8/link");
+        fs.write_file_with_mode(&link, b"run.sh", FileMode::Symlink)
+            .await?;
+        assert_eq!(fs.stat(&link).await?.unwrap().mode, FileMode::Symlink);
+        assert_eq!(fs.try_read_file(&link).await?, b"run.sh");
+
+        fs.write_file_with_mode(&link, b"hello", FileMode::Regular)
+            .await?;
+        assert_eq!(fs.stat(&link).await?.unwrap().mode, FileMode::Regular);
+        assert_eq!(fs.try_read_file(&script).await?, b"echo hi");
+        Ok(())
+    }
 }
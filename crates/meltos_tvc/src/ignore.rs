@@ -0,0 +1,382 @@
+//! Matching of the ignore files that follow the gitignore format.
+
+pub const MELTOS_IGNORE: &str = ".meltos_ignore";
+pub const GIT_IGNORE: &str = ".gitignore";
+
+/// The compiled rules of all ignore files in the workspace.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct IgnoreMatcher {
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreMatcher {
+    /// Adds the rules of the ignore file located in `base_dir`.
+    ///
+    /// `base_dir` is the directory relative to the workspace root, an empty string means the root itself.
+    /// Rules added later take precedence over the earlier ones,
+    /// so ignore files must be added from the shallowest directory.
+    pub fn add_rules(&mut self, base_dir: &str, content: &str) {
+        let base_dir = normalize(base_dir);
+        self.rules.extend(
+            content
+                .lines()
+                .filter_map(|line| IgnoreRule::parse(base_dir, line)),
+        );
+    }
+
+    /// Returns true if the file is ignored.
+    ///
+    /// The parent directories are checked first from the shallowest, and the file is ignored as soon as one of them is excluded,
+    /// because a file cannot be re-included if its directory is excluded, as in git.
+    /// Only then is the path itself checked.
+    pub fn is_ignored(&self, path: &str) -> bool {
+        let path = normalize(path);
+        let excluded_dir = path
+            .match_indices('/')
+            .map(|(i, _)| &path[..i])
+            .any(|dir| self.matched(dir, true) == Some(true));
+        excluded_dir || self.matched(path, false).unwrap_or(false)
+    }
+
+    /// Returns true if the path, or otherwise its nearest parent directory decided by any rule, is matched by a rule not negated.
+    ///
+    /// Unlike [`IgnoreMatcher::is_ignored`], a negated pattern for a file takes precedence over its directory,
+    /// which is how git evaluates the sparse-checkout patterns.
+    pub fn matches_nearest(&self, path: &str) -> bool {
+        let path = normalize(path);
+        if let Some(matched) = self.matched(path, false) {
+            return matched;
+        }
+        let mut dir = path;
+        while let Some((parent, _)) = dir.rsplit_once('/') {
+            if let Some(matched) = self.matched(parent, true) {
+                return matched;
+            }
+            dir = parent;
+        }
+        false
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    fn matched(&self, path: &str, is_dir: bool) -> Option<bool> {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(path, is_dir))
+            .map(|rule| !rule.negated)
+    }
+}
+
+/// Returns the directory of the ignore file if the path is one of them.
+pub fn ignore_file_dir(path: &str, honor_gitignore: bool) -> Option<&str> {
+    let path = normalize(path);
+    let (dir, name) = path.rsplit_once('/').unwrap_or(("", path));
+    if name == MELTOS_IGNORE || (honor_gitignore && name == GIT_IGNORE) {
+        Some(dir)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct IgnoreRule {
+    base_dir: String,
+    pattern: Vec<char>,
+    negated: bool,
+    dir_only: bool,
+}
+
+impl IgnoreRule {
+    fn parse(base_dir: &str, line: &str) -> Option<Self> {
+        let line = trim_trailing_spaces(line.trim_end_matches('\r'));
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(line) => (true, line),
+            None => (false, line.strip_prefix('\\').filter(|l| l.starts_with(['#', '!'])).unwrap_or(line)),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(line) => (true, line),
+            None => (false, line),
+        };
+        if line.is_empty() {
+            return None;
+        }
+
+        // A pattern that contains a slash except at the end is relative to the directory of the ignore file,
+        // otherwise it matches at any level below it.
+        let pattern = if line.contains('/') {
+            line.trim_start_matches('/').chars().collect()
+        } else {
+            format!("**/{line}").chars().collect()
+        };
+        Some(Self {
+            base_dir: base_dir.to_string(),
+            pattern,
+            negated,
+            dir_only,
+        })
+    }
+
+    fn matches(&self, path: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        let relative = if self.base_dir.is_empty() {
+            path
+        } else {
+            match path
+                .strip_prefix(self.base_dir.as_str())
+                .and_then(|p| p.strip_prefix('/'))
+            {
+                Some(relative) => relative,
+                None => return false,
+            }
+        };
+        let text = relative.chars().collect::<Vec<char>>();
+        wildmatch(&self.pattern, &text)
+    }
+}
+
+fn wildmatch(pattern: &[char], text: &[char]) -> bool {
+    let mut p = 0;
+    let mut t = 0;
+    while p < pattern.len() {
+        match pattern[p] {
+            '*' => {
+                let mut next = p;
+                while next < pattern.len() && pattern[next] == '*' {
+                    next += 1;
+                }
+                let at_segment_start = p == 0 || pattern[p - 1] == '/';
+                if next - p >= 2 && at_segment_start {
+                    return match pattern.get(next) {
+                        // Trailing "**" matches everything inside.
+                        None => true,
+                        // "**/" matches zero or more directories.
+                        Some('/') => {
+                            let rest = &pattern[next + 1..];
+                            wildmatch(rest, &text[t..])
+                                || (t..text.len())
+                                    .any(|i| text[i] == '/' && wildmatch(rest, &text[i + 1..]))
+                        }
+                        Some(_) => match_star(&pattern[next..], &text[t..]),
+                    };
+                }
+                return match_star(&pattern[next..], &text[t..]);
+            }
+            '?' => {
+                if t >= text.len() || text[t] == '/' {
+                    return false;
+                }
+                p += 1;
+                t += 1;
+            }
+            '[' if t < text.len() => {
+                match match_class(&pattern[p..], text[t]) {
+                    Some((true, len)) => {
+                        p += len;
+                        t += 1;
+                    }
+                    Some((false, _)) => return false,
+                    None => {
+                        if text[t] != '[' {
+                            return false;
+                        }
+                        p += 1;
+                        t += 1;
+                    }
+                }
+            }
+            '\\' if p + 1 < pattern.len() => {
+                if t >= text.len() || text[t] != pattern[p + 1] {
+                    return false;
+                }
+                p += 2;
+                t += 1;
+            }
+            c => {
+                if t >= text.len() || text[t] != c {
+                    return false;
+                }
+                p += 1;
+                t += 1;
+            }
+        }
+    }
+    t == text.len()
+}
+
+/// Matches a single `*` which does not cross directory separators.
+fn match_star(rest: &[char], text: &[char]) -> bool {
+    for i in 0..=text.len() {
+        if wildmatch(rest, &text[i..]) {
+            return true;
+        }
+        if i < text.len() && text[i] == '/' {
+            break;
+        }
+    }
+    false
+}
+
+/// Returns whether `c` matches the bracket expression at the start of `pattern` and its length,
+/// or `None` if the bracket is not closed.
+fn match_class(pattern: &[char], c: char) -> Option<(bool, usize)> {
+    let mut i = 1;
+    let negated = matches!(pattern.get(i), Some('!') | Some('^'));
+    if negated {
+        i += 1;
+    }
+    let mut matched = false;
+    let mut first = true;
+    while i < pattern.len() {
+        let start = pattern[i];
+        if start == ']' && !first {
+            return Some((matched != negated && c != '/', i + 1));
+        }
+        first = false;
+        if pattern.get(i + 1) == Some(&'-') && pattern.get(i + 2).is_some_and(|end| *end != ']') {
+            matched |= start <= c && c <= pattern[i + 2];
+            i += 3;
+        } else {
+            matched |= start == c;
+            i += 1;
+        }
+    }
+    None
+}
+
+fn trim_trailing_spaces(line: &str) -> &str {
+    let trimmed = line.trim_end_matches(' ');
+    if trimmed.ends_with('\\') && trimmed.len() < line.len() {
+        &line[..trimmed.len() + 1]
+    } else {
+        trimmed
+    }
+}
+
+#[inline]
+fn normalize(path: &str) -> &str {
+    path.trim_start_matches("./").trim_end_matches('/')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ignore::{ignore_file_dir, IgnoreMatcher};
+
+    fn matcher(content: &str) -> IgnoreMatcher {
+        let mut matcher = IgnoreMatcher::default();
+        matcher.add_rules("", content);
+        matcher
+    }
+
+    #[test]
+    fn match_at_any_level_without_slash() {
+        let matcher = matcher("*.log\ntarget");
+        assert!(matcher.is_ignored("debug.log"));
+        assert!(matcher.is_ignored("src/debug.log"));
+        assert!(matcher.is_ignored("target/debug/app"));
+        assert!(matcher.is_ignored("crates/core/target/debug/app"));
+        assert!(!matcher.is_ignored("src/main.rs"));
+        assert!(!matcher.is_ignored("debug.log.txt"));
+    }
+
+    #[test]
+    fn anchored_if_contains_slash() {
+        let matcher = matcher("/build\ndoc/*.html");
+        assert!(matcher.is_ignored("build/out"));
+        assert!(!matcher.is_ignored("src/build/out"));
+        assert!(matcher.is_ignored("doc/index.html"));
+        assert!(!matcher.is_ignored("doc/api/index.html"));
+    }
+
+    #[test]
+    fn double_asterisk() {
+        let matcher = matcher("**/node_modules/\nlogs/**\na/**/b");
+        assert!(matcher.is_ignored("node_modules/react/index.js"));
+        assert!(matcher.is_ignored("web/node_modules/react/index.js"));
+        assert!(matcher.is_ignored("logs/2024/01.txt"));
+        assert!(matcher.is_ignored("a/b"));
+        assert!(matcher.is_ignored("a/x/y/b"));
+        assert!(!matcher.is_ignored("a/x/y/c"));
+    }
+
+    #[test]
+    fn directory_only() {
+        let matcher = matcher("cache/");
+        assert!(matcher.is_ignored("cache/data"));
+        assert!(matcher.is_ignored("src/cache/data"));
+        assert!(!matcher.is_ignored("cache"));
+    }
+
+    #[test]
+    fn negation_and_comments() {
+        let matcher = matcher("# comment\n*.log\n!important.log\n\\#hash\n\\!bang\n");
+        assert!(matcher.is_ignored("debug.log"));
+        assert!(!matcher.is_ignored("important.log"));
+        assert!(!matcher.is_ignored("# comment"));
+        assert!(matcher.is_ignored("#hash"));
+        assert!(matcher.is_ignored("!bang"));
+    }
+
+    #[test]
+    fn cannot_re_include_file_if_parent_dir_excluded() {
+        let target = matcher("target/\n!target/keep");
+        assert!(target.is_ignored("target/keep"));
+
+        let build = matcher("build/\n!*.txt");
+        assert!(build.is_ignored("build/a.txt"));
+        assert!(build.is_ignored("src/build/a.txt"));
+        assert!(!build.is_ignored("a.txt"));
+    }
+
+    #[test]
+    fn re_include_dir_excluded_by_wildcard() {
+        let matcher = matcher("/*\n!/src");
+        assert!(matcher.is_ignored("target/app"));
+        assert!(!matcher.is_ignored("src/main.rs"));
+    }
+
+    #[test]
+    fn question_and_bracket() {
+        let matcher = matcher("file?.txt\n[abc].rs\n[!0-9].md");
+        assert!(matcher.is_ignored("file1.txt"));
+        assert!(!matcher.is_ignored("file10.txt"));
+        assert!(matcher.is_ignored("b.rs"));
+        assert!(!matcher.is_ignored("d.rs"));
+        assert!(matcher.is_ignored("x.md"));
+        assert!(!matcher.is_ignored("1.md"));
+    }
+
+    #[test]
+    fn trailing_spaces() {
+        let matcher = matcher("hello.txt   \nspace\\ ");
+        assert!(matcher.is_ignored("hello.txt"));
+        assert!(matcher.is_ignored("space "));
+    }
+
+    #[test]
+    fn nested_ignore_file_overrides_parent() {
+        let mut matcher = IgnoreMatcher::default();
+        matcher.add_rules("", "*.txt");
+        matcher.add_rules("docs", "!*.txt\n/local");
+        assert!(matcher.is_ignored("readme.txt"));
+        assert!(!matcher.is_ignored("docs/readme.txt"));
+        assert!(matcher.is_ignored("docs/local/a.md"));
+        assert!(!matcher.is_ignored("local/a.md"));
+    }
+
+    #[test]
+    fn find_ignore_files() {
+        assert_eq!(ignore_file_dir(".meltos_ignore", false), Some(""));
+        assert_eq!(ignore_file_dir("./src/.meltos_ignore", false), Some("src"));
+        assert_eq!(ignore_file_dir("src/.gitignore", false), None);
+        assert_eq!(ignore_file_dir("src/.gitignore", true), Some("src"));
+    }
+}
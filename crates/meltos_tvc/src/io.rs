@@ -3,5 +3,6 @@ pub mod bundle;
 pub mod commit_hashes;
 pub mod commit_obj;
 pub mod diff;
+pub mod git;
 pub mod trace_tree;
 pub mod workspace;
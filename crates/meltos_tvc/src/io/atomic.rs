@@ -1,8 +1,15 @@
 //! このモジュール名が指すAtomicとは、個々のファイルのみのI/Oを提供する構造体を指します。
 
+pub mod config;
 pub mod head;
 pub mod local_commits;
+pub mod merge_head;
 pub mod object;
+pub mod pack;
+pub mod reflog;
+pub mod sparse_checkout;
 pub mod staging;
+pub mod stash;
+pub mod tag;
 pub mod trace;
 pub mod work_branch;
@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error;
+use crate::file_system::FileSystem;
+
+const CONFIG: &str = ".meltos/config";
+
+/// The settings of the repository.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RepositoryConfig {
+    /// Whether `.gitignore` files are applied in addition to `.meltos_ignore`.
+    pub honor_gitignore: bool,
+    /// Whether symlinks whose targets are absolute or contain `..` are written into the workspace.
+    pub allow_unsafe_symlinks: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigIo<Fs>(Fs)
+where
+    Fs: FileSystem;
+
+impl<Fs> ConfigIo<Fs>
+where
+    Fs: FileSystem,
+{
+    #[inline]
+    pub const fn new(fs: Fs) -> ConfigIo<Fs> {
+        Self(fs)
+    }
+
+    #[inline]
+    pub async fn write(&self, config: &RepositoryConfig) -> error::Result {
+        self.0
+            .write_file(CONFIG, &serde_json::to_vec(config)?)
+            .await?;
+        Ok(())
+    }
+
+    /// Reads the config, or returns the default if it has not been written.
+    #[inline]
+    pub async fn read(&self) -> error::Result<RepositoryConfig> {
+        let Some(buf) = self.0.read_file(CONFIG).await? else {
+            return Ok(RepositoryConfig::default());
+        };
+        Ok(serde_json::from_slice(&buf)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::file_system::memory::MemoryFileSystem;
+    use crate::io::atomic::config::{ConfigIo, RepositoryConfig};
+
+    #[tokio::test]
+    async fn read_default_if_not_written() {
+        let io = ConfigIo::new(MemoryFileSystem::default());
+        assert_eq!(io.read().await.unwrap(), RepositoryConfig::default());
+
+        let config = RepositoryConfig {
+            honor_gitignore: true,
+            ..RepositoryConfig::default()
+        };
+        io.write(&config).await.unwrap();
+        assert_eq!(io.read().await.unwrap(), config);
+    }
+}
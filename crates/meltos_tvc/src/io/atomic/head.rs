@@ -8,8 +8,10 @@ use crate::branch::BranchName;
 use crate::encode::{Decodable, Encodable};
 use crate::error;
 use crate::file_system::FileSystem;
+use crate::io::atomic::reflog::{append_entry, heads_path, remotes_path, ReflogEntry};
 use crate::object::commit::CommitHash;
 use crate::object::ObjHash;
+use crate::time::since_epoch_secs;
 
 #[derive(Debug, Clone)]
 pub struct HeadIo<Fs>
@@ -35,6 +37,7 @@ where
         branch_name: &BranchName,
         commit_hash: &CommitHash,
     ) -> error::Result {
+        branch_name.validate()?;
         self.fs
             .write_file(
                 &format!(".meltos/refs/remotes/{branch_name}"),
@@ -50,6 +53,7 @@ where
         branch_name: &BranchName,
         commit_hash: &CommitHash,
     ) -> error::Result<()> {
+        branch_name.validate()?;
         self.fs
             .write_file(
                 &format!(".meltos/refs/heads/{branch_name}"),
@@ -59,11 +63,40 @@ where
         Ok(())
     }
 
+    /// Writes the head, and records the movement in the reflog with the name of `operation`.
+    pub async fn update(
+        &self,
+        branch_name: &BranchName,
+        commit_hash: &CommitHash,
+        operation: &str,
+    ) -> error::Result {
+        let old = self.read(branch_name).await?;
+        self.write(branch_name, commit_hash).await?;
+        self.append_reflog(&heads_path(branch_name), old, commit_hash, operation)
+            .await
+    }
+
+    /// Writes the remote head, and records the movement in the reflog with the name of `operation`.
+    pub async fn update_remote(
+        &self,
+        branch_name: &BranchName,
+        commit_hash: &CommitHash,
+        operation: &str,
+    ) -> error::Result {
+        let old = self.read_remote(branch_name).await?;
+        self.write_remote(branch_name, commit_hash).await?;
+        self.append_reflog(&remotes_path(branch_name), old, commit_hash, operation)
+            .await
+    }
+
+    /// Deletes the head and its reflog.
     #[inline]
     pub async fn delete(&self, branch_name: &BranchName) -> error::Result<()> {
+        branch_name.validate()?;
         self.fs
             .delete(&format!(".meltos/refs/heads/{branch_name}"))
             .await?;
+        self.fs.delete(&heads_path(branch_name)).await?;
         Ok(())
     }
 
@@ -91,8 +124,37 @@ where
         self._read(".meltos/refs/heads/", branch_name).await
     }
 
+    #[inline]
     pub async fn read_all(&self) -> error::Result<Vec<(BranchName, CommitHash)>> {
-        let files = self.fs.all_files_in(".meltos/refs/heads/").await?;
+        self._read_all(".meltos/refs/heads/").await
+    }
+
+    #[inline]
+    pub async fn read_all_remotes(&self) -> error::Result<Vec<(BranchName, CommitHash)>> {
+        self._read_all(".meltos/refs/remotes/").await
+    }
+
+    async fn append_reflog(
+        &self,
+        path: &str,
+        old: Option<CommitHash>,
+        new: &CommitHash,
+        operation: &str,
+    ) -> error::Result {
+        if old.as_ref() == Some(new) {
+            return Ok(());
+        }
+        let entry = ReflogEntry {
+            old,
+            new: new.clone(),
+            operation: operation.to_string(),
+            time: since_epoch_secs(),
+        };
+        append_entry(&self.fs, path, &entry).await
+    }
+
+    async fn _read_all(&self, dir: &str) -> error::Result<Vec<(BranchName, CommitHash)>> {
+        let files = self.fs.all_files_in(dir).await?;
         let mut branches = Vec::with_capacity(files.len());
         for path in files {
             let Some(file_name) = Path::new(&path).file_name().and_then(|name| name.to_str())
@@ -100,7 +162,10 @@ where
                 continue;
             };
             let branch_name = BranchName::from(file_name);
-            let commit_hash = self.try_read(&branch_name).await?;
+            let commit_hash = self
+                ._read(dir, &branch_name)
+                .await?
+                .ok_or_else(|| error::Error::NotfoundHead(branch_name.clone()))?;
             branches.push((branch_name, commit_hash))
         }
         Ok(branches)
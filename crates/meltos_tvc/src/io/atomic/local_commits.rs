@@ -56,6 +56,12 @@ where
         Ok(Some(LocalCommitsObj::decode(&buf)?))
     }
 
+    #[inline]
+    pub async fn delete(&self, branch_name: &BranchName) -> error::Result {
+        self.fs.delete(&self.file_path(branch_name)).await?;
+        Ok(())
+    }
+
     #[inline(always)]
     fn file_path(&self, branch_name: &BranchName) -> String {
         format!(".meltos/branches/{branch_name}/LOCAL")
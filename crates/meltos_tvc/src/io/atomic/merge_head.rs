@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+
+use crate::branch::BranchName;
+use crate::error;
+use crate::file_system::{FilePath, FileSystem};
+use crate::object::commit::CommitHash;
+
+const MERGE_HEAD: &str = ".meltos/MERGE_HEAD";
+
+/// The state of the merge, the cherry-pick or the revert that stopped due to conflicts.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MergeHead {
+    /// The commit being merged, cherry-picked or reverted.
+    pub source: CommitHash,
+
+    /// The branch into which `source` is merged.
+    pub dist: BranchName,
+
+    /// The files into which the conflict markers were written.
+    pub conflicts: Vec<FilePath>,
+
+    #[serde(default)]
+    pub kind: MergeKind,
+}
+
+/// The operation which applies the changes of [`MergeHead::source`].
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum MergeKind {
+    /// Creates the merge commit whose parents are the head of `dist` and `source`.
+    #[default]
+    Merge,
+    /// Creates the commit which applies the changes of `source` from its first parent.
+    CherryPick,
+    /// Creates the commit which undoes the changes of `source`.
+    Revert,
+}
+
+impl MergeKind {
+    /// The name recorded in the reflog.
+    #[inline]
+    pub const fn operation_name(&self) -> &'static str {
+        match self {
+            Self::Merge => "merge",
+            Self::CherryPick => "cherry-pick",
+            Self::Revert => "revert",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MergeHeadIo<Fs>(Fs)
+where
+    Fs: FileSystem;
+
+impl<Fs> MergeHeadIo<Fs>
+where
+    Fs: FileSystem,
+{
+    #[inline]
+    pub const fn new(fs: Fs) -> MergeHeadIo<Fs> {
+        Self(fs)
+    }
+
+    #[inline]
+    pub async fn write(&self, merge_head: &MergeHead) -> error::Result {
+        self.0
+            .write_file(MERGE_HEAD, &serde_json::to_vec(merge_head)?)
+            .await?;
+        Ok(())
+    }
+
+    #[inline]
+    pub async fn try_read(&self) -> error::Result<MergeHead> {
+        self.read().await?.ok_or(error::Error::NotfoundMergeHead)
+    }
+
+    #[inline]
+    pub async fn read(&self) -> error::Result<Option<MergeHead>> {
+        let Some(buf) = self.0.read_file(MERGE_HEAD).await? else {
+            return Ok(None);
+        };
+        Ok(Some(serde_json::from_slice(&buf)?))
+    }
+
+    #[inline]
+    pub async fn delete(&self) -> error::Result {
+        self.0.delete(MERGE_HEAD).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::branch::BranchName;
+    use crate::file_system::memory::MemoryFileSystem;
+    use crate::file_system::FilePath;
+    use crate::io::atomic::merge_head::{MergeHead, MergeHeadIo, MergeKind};
+    use crate::object::commit::CommitHash;
+    use crate::object::ObjHash;
+
+    #[tokio::test]
+    async fn read_written_merge_head() {
+        let io = MergeHeadIo::new(MemoryFileSystem::default());
+        let merge_head = MergeHead {
+            source: CommitHash(ObjHash::new(b"source")),
+            dist: BranchName::owner(),
+            conflicts: vec![FilePath::from_path("hello.txt")],
+            kind: MergeKind::CherryPick,
+        };
+        io.write(&merge_head).await.unwrap();
+        assert_eq!(io.read().await.unwrap(), Some(merge_head));
+
+        io.delete().await.unwrap();
+        assert_eq!(io.read().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn read_merge_head_without_kind() {
+        let fs = MemoryFileSystem::default();
+        let source = CommitHash(ObjHash::new(b"source"));
+        fs.write_sync(
+            ".meltos/MERGE_HEAD",
+            format!(r#"{{"source":"{source}","dist":"owner","conflicts":[]}}"#).as_bytes(),
+        );
+        let merge_head = MergeHeadIo::new(fs).try_read().await.unwrap();
+        assert_eq!(merge_head.kind, MergeKind::Merge);
+    }
+}
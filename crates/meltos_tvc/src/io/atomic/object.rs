@@ -1,27 +1,44 @@
+use std::collections::HashSet;
 use std::path::Path;
 
 use crate::error;
 use crate::file_system::FileSystem;
+use crate::io::atomic::pack::PackIo;
 use crate::io::bundle::BundleObject;
 use crate::object::commit::{CommitHash, CommitObj};
 use crate::object::file::FileObj;
 use crate::object::tree::TreeObj;
 use crate::object::{AsMeta, CompressedBuf, Obj, ObjHash};
 
+/// Reads the loose objects under `.meltos/objects` and the packed objects transparently.
+///
+/// New objects are always written as loose objects; [`Repack`](crate::operation::repack::Repack) moves them into a pack.
 #[derive(Debug, Clone, Default)]
-pub struct ObjIo<Fs>(Fs)
+pub struct ObjIo<Fs>
 where
-    Fs: FileSystem;
+    Fs: FileSystem,
+{
+    fs: Fs,
+    pack: PackIo<Fs>,
+}
 
 impl<Fs> ObjIo<Fs>
 where
-    Fs: FileSystem,
+    Fs: FileSystem + Clone,
 {
     #[inline]
-    pub const fn new(fs: Fs) -> ObjIo<Fs> {
-        Self(fs)
+    pub fn new(fs: Fs) -> ObjIo<Fs> {
+        Self {
+            pack: PackIo::new(fs.clone()),
+            fs,
+        }
     }
+}
 
+impl<Fs> ObjIo<Fs>
+where
+    Fs: FileSystem,
+{
     #[inline]
     pub async fn read_to_commit(&self, object_hash: &CommitHash) -> error::Result<CommitObj> {
         let obj = self.try_read_obj(&object_hash.0).await?;
@@ -65,42 +82,79 @@ where
         Ok(Some(Obj::expand(&buf)?))
     }
 
-    #[inline(always)]
+    /// The total size of the loose objects and the pack files.
     pub async fn total_objs_size(&self) -> error::Result<usize> {
-        Ok(self
-            .read_all()
-            .await?
-            .iter()
-            .map(|o| o.compressed_buf.0.len())
-            .sum())
+        let mut size = self.pack.total_size().await?;
+        for path in self.fs.all_files_in(".meltos/objects").await? {
+            if let Some(stat) = self.fs.stat(&path).await? {
+                size += stat.size as usize;
+            }
+        }
+        Ok(size)
     }
 
     pub async fn read_all(&self) -> error::Result<Vec<BundleObject>> {
-        let files = self.0.all_files_in(".meltos/objects").await?;
-        let mut objs = Vec::with_capacity(files.len());
-        for path in files {
-            let buf = self.0.try_read_file(&path).await?;
-            let file_name = Path::new(&path).file_name().unwrap().to_str().unwrap();
+        let hashes = self.read_all_hashes().await?;
+        let mut objs = Vec::with_capacity(hashes.len());
+        for hash in hashes {
+            let compressed_buf = self
+                .read(&hash)
+                .await?
+                .ok_or_else(|| error::Error::NotfoundObj(hash.clone()))?;
             objs.push(BundleObject {
-                hash: ObjHash(file_name.to_string()),
-                compressed_buf: CompressedBuf(buf),
+                hash,
+                compressed_buf,
             });
         }
         Ok(objs)
     }
 
+    pub async fn read_all_hashes(&self) -> error::Result<Vec<ObjHash>> {
+        let mut hashes = self.read_loose_hashes().await?;
+        let loose = hashes.iter().cloned().collect::<HashSet<ObjHash>>();
+        hashes.extend(
+            self.pack
+                .read_all_hashes()
+                .await?
+                .into_iter()
+                .filter(|hash| !loose.contains(hash)),
+        );
+        Ok(hashes)
+    }
+
+    pub async fn read_loose_hashes(&self) -> error::Result<Vec<ObjHash>> {
+        let files = self.fs.all_files_in(".meltos/objects").await?;
+        Ok(files
+            .iter()
+            .filter_map(|path| Path::new(path).file_name()?.to_str())
+            .map(|file_name| ObjHash(file_name.to_string()))
+            .collect())
+    }
+
     pub async fn read(&self, object_hash: &ObjHash) -> error::Result<Option<CompressedBuf>> {
         let Some(buf) = self
-            .0
+            .fs
             .read_file(&format!(".meltos/objects/{}", object_hash))
             .await?
         else {
-            return Ok(None);
+            return self.pack.read(object_hash).await;
         };
 
         Ok(Some(CompressedBuf(buf)))
     }
 
+    pub async fn exists(&self, object_hash: &ObjHash) -> error::Result<bool> {
+        if self
+            .fs
+            .stat(&format!(".meltos/objects/{}", object_hash))
+            .await?
+            .is_some()
+        {
+            return Ok(true);
+        }
+        self.pack.contains(object_hash).await
+    }
+
     pub async fn write_obj(&self, obj: &impl AsMeta) -> error::Result<()> {
         let obj = obj.as_meta()?;
         self.write(&obj.hash, &obj.compressed_buf).await
@@ -117,9 +171,16 @@ where
         Ok(())
     }
 
+    /// Deletes the loose object; the packed objects are only removed by rewriting the packs.
+    #[inline]
+    pub async fn delete(&self, hash: &ObjHash) -> error::Result {
+        self.fs.delete(&format!(".meltos/objects/{}", hash)).await?;
+        Ok(())
+    }
+
     #[inline]
     pub async fn write(&self, hash: &ObjHash, compressed_buf: &CompressedBuf) -> error::Result {
-        self.0
+        self.fs
             .write_file(&format!(".meltos/objects/{}", hash), compressed_buf)
             .await?;
         Ok(())
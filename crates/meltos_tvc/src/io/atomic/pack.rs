@@ -0,0 +1,468 @@
+//! Packs store many objects in one file under `.meltos/packs`.
+//!
+//! Each pack consists of `pack-{hash}.pack` that holds the entries,
+//! and `pack-{hash}.idx` that maps the object hashes to the ranges of the entries.
+//! An entry is either the compressed object or the compressed delta against another object in the same pack.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use meltos_util::compression::gz::Gz;
+use meltos_util::compression::CompressionBuf;
+
+use crate::error;
+use crate::file_system::FileSystem;
+use crate::io::atomic::pack::delta::{invalid, write_varint, Reader};
+use crate::object::{CompressedBuf, ObjHash};
+
+pub mod delta;
+
+const PACKS_DIR: &str = ".meltos/packs";
+const PACK_MAGIC: &[u8] = b"PACK\x00";
+const IDX_MAGIC: &[u8] = b"PIDX\x00";
+
+const FULL: u8 = 0;
+const DELTA: u8 = 1;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum PackEntry {
+    Full {
+        hash: ObjHash,
+        compressed_buf: CompressedBuf,
+    },
+    /// `delta` is created by [`delta::encode`] from the uncompressed buffers of the base and the object.
+    Delta {
+        hash: ObjHash,
+        base: ObjHash,
+        delta: Vec<u8>,
+    },
+}
+
+impl PackEntry {
+    #[inline]
+    pub fn hash(&self) -> &ObjHash {
+        match self {
+            Self::Full { hash, .. } | Self::Delta { hash, .. } => hash,
+        }
+    }
+}
+
+/// The loaded packs are cached because the pack files are never modified once written.
+///
+/// The names of the packs are also cached, and listed again only when an object is not found in the cached packs,
+/// since another [`PackIo`] may have written a new pack since then.
+#[derive(Debug, Clone, Default)]
+pub struct PackIo<Fs>
+where
+    Fs: FileSystem,
+{
+    fs: Fs,
+    names: Arc<Mutex<Option<Vec<String>>>>,
+    packs: Arc<Mutex<HashMap<String, Arc<Pack>>>>,
+}
+
+impl<Fs> PackIo<Fs>
+where
+    Fs: FileSystem,
+{
+    #[inline]
+    pub fn new(fs: Fs) -> PackIo<Fs> {
+        Self {
+            fs,
+            names: Arc::new(Mutex::new(None)),
+            packs: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub async fn read(&self, hash: &ObjHash) -> error::Result<Option<CompressedBuf>> {
+        match self.find_pack(hash).await? {
+            Some(pack) => pack.read(hash),
+            None => Ok(None),
+        }
+    }
+
+    #[inline]
+    pub async fn contains(&self, hash: &ObjHash) -> error::Result<bool> {
+        Ok(self.find_pack(hash).await?.is_some())
+    }
+
+    pub async fn read_all_hashes(&self) -> error::Result<Vec<ObjHash>> {
+        let mut hashes = BTreeSet::new();
+        for pack in self.read_packs().await? {
+            hashes.extend(pack.index.iter().map(|entry| entry.hash.clone()));
+        }
+        Ok(hashes.into_iter().collect())
+    }
+
+    /// Returns the names of the packs such as `pack-{hash}`.
+    pub async fn read_names(&self) -> error::Result<Vec<String>> {
+        let files = self.fs.all_files_in(PACKS_DIR).await?;
+        let mut names = files
+            .iter()
+            .filter_map(|path| Path::new(path).file_name()?.to_str()?.strip_suffix(".idx"))
+            .map(|name| name.to_string())
+            .collect::<Vec<String>>();
+        names.sort();
+        *self.names.lock().unwrap() = Some(names.clone());
+        Ok(names)
+    }
+
+    /// The total size of the pack files.
+    pub async fn total_size(&self) -> error::Result<usize> {
+        let mut size = 0;
+        for path in self.fs.all_files_in(PACKS_DIR).await? {
+            if let Some(stat) = self.fs.stat(&path).await? {
+                size += stat.size as usize;
+            }
+        }
+        Ok(size)
+    }
+
+    /// Writes the entries to a new pack and returns its name.
+    ///
+    /// The base of each delta must be contained in the entries.
+    pub async fn write(&self, entries: &[PackEntry]) -> error::Result<String> {
+        let hashes = entries.iter().map(PackEntry::hash).collect::<BTreeSet<_>>();
+        let mut data = PACK_MAGIC.to_vec();
+        let mut index = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let offset = data.len();
+            match entry {
+                PackEntry::Full { compressed_buf, .. } => {
+                    data.push(FULL);
+                    data.extend_from_slice(compressed_buf);
+                }
+                PackEntry::Delta { base, delta, .. } => {
+                    if !hashes.contains(base) {
+                        return Err(error::Error::NotfoundObj(base.clone()));
+                    }
+                    data.push(DELTA);
+                    write_varint(&mut data, base.0.len());
+                    data.extend_from_slice(base.0.as_bytes());
+                    data.extend_from_slice(&Gz.zip(delta)?);
+                }
+            }
+            index.push(IndexEntry {
+                hash: entry.hash().clone(),
+                offset,
+                len: data.len() - offset,
+            });
+        }
+        index.sort_by(|l, r| l.hash.cmp(&r.hash));
+
+        let name = format!("pack-{}", meltos_util::hash::hash(&data));
+        self.fs
+            .write_file(&format!("{PACKS_DIR}/{name}.pack"), &data)
+            .await?;
+        // The index is written last, so that readers never see a pack whose data is incomplete.
+        self.fs
+            .write_file(&format!("{PACKS_DIR}/{name}.idx"), &encode_index(&index))
+            .await?;
+        *self.names.lock().unwrap() = None;
+        Ok(name)
+    }
+
+    pub async fn delete(&self, name: &str) -> error::Result {
+        self.fs.delete(&format!("{PACKS_DIR}/{name}.idx")).await?;
+        self.fs.delete(&format!("{PACKS_DIR}/{name}.pack")).await?;
+        self.packs.lock().unwrap().remove(name);
+        *self.names.lock().unwrap() = None;
+        Ok(())
+    }
+
+    /// Returns the pack containing the object, looking up the cached packs first.
+    async fn find_pack(&self, hash: &ObjHash) -> error::Result<Option<Arc<Pack>>> {
+        let cached = self.names.lock().unwrap().clone();
+        if let Some(names) = cached.as_ref() {
+            let packs = self.load_packs(names).await?;
+            if let Some(pack) = packs.into_iter().find(|pack| pack.find(hash).is_some()) {
+                return Ok(Some(pack));
+            }
+        }
+        let names = self.read_names().await?;
+        if cached.as_ref() == Some(&names) {
+            return Ok(None);
+        }
+        Ok(self
+            .load_packs(&names)
+            .await?
+            .into_iter()
+            .find(|pack| pack.find(hash).is_some()))
+    }
+
+    #[inline]
+    async fn read_packs(&self) -> error::Result<Vec<Arc<Pack>>> {
+        let names = self.read_names().await?;
+        self.load_packs(&names).await
+    }
+
+    async fn load_packs(&self, names: &[String]) -> error::Result<Vec<Arc<Pack>>> {
+        let mut packs = Vec::new();
+        for name in names {
+            let cached = self.packs.lock().unwrap().get(name).cloned();
+            let pack = match cached {
+                Some(pack) => pack,
+                None => {
+                    let pack = Arc::new(self.load(name).await?);
+                    self.packs.lock().unwrap().insert(name.clone(), Arc::clone(&pack));
+                    pack
+                }
+            };
+            packs.push(pack);
+        }
+        Ok(packs)
+    }
+
+    async fn load(&self, name: &str) -> error::Result<Pack> {
+        let index = self
+            .fs
+            .try_read_file(&format!("{PACKS_DIR}/{name}.idx"))
+            .await?;
+        let data = self
+            .fs
+            .try_read_file(&format!("{PACKS_DIR}/{name}.pack"))
+            .await?;
+        if !data.starts_with(PACK_MAGIC) {
+            return Err(invalid("pack header is invalid"));
+        }
+        let index = decode_index(&index)?;
+        if index.iter().any(|entry| {
+            entry.len == 0
+                || entry
+                    .offset
+                    .checked_add(entry.len)
+                    .is_none_or(|end| data.len() < end)
+        }) {
+            return Err(invalid("index points outside of the pack"));
+        }
+        Ok(Pack { index, data })
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct IndexEntry {
+    hash: ObjHash,
+    offset: usize,
+    len: usize,
+}
+
+#[derive(Debug)]
+struct Pack {
+    /// Sorted by the hashes.
+    index: Vec<IndexEntry>,
+    data: Vec<u8>,
+}
+
+impl Pack {
+    fn read(&self, hash: &ObjHash) -> error::Result<Option<CompressedBuf>> {
+        let Some(entry) = self.entry(hash) else {
+            return Ok(None);
+        };
+        match entry {
+            Entry::Full(compressed_buf) => Ok(Some(CompressedBuf(compressed_buf.to_vec()))),
+            Entry::Delta { .. } => {
+                let buf = self.read_raw(hash)?;
+                Ok(Some(CompressedBuf(Gz.zip(&buf)?)))
+            }
+        }
+    }
+
+    /// Reads the uncompressed buffer by applying the chain of the deltas.
+    fn read_raw(&self, hash: &ObjHash) -> error::Result<Vec<u8>> {
+        let mut deltas = Vec::new();
+        let mut hash = hash.clone();
+        let mut buf = loop {
+            if self.index.len() < deltas.len() {
+                return Err(invalid("delta chain is cyclic"));
+            }
+            match self
+                .entry(&hash)
+                .ok_or_else(|| error::Error::NotfoundObj(hash.clone()))?
+            {
+                Entry::Full(compressed_buf) => break Gz.unzip(compressed_buf)?,
+                Entry::Delta { base, delta } => {
+                    deltas.push(Gz.unzip(delta)?);
+                    hash = base;
+                }
+            }
+        };
+        for delta in deltas.iter().rev() {
+            buf = delta::apply(&buf, delta)?;
+        }
+        Ok(buf)
+    }
+
+    #[inline]
+    fn find(&self, hash: &ObjHash) -> Option<&IndexEntry> {
+        let i = self
+            .index
+            .binary_search_by(|entry| entry.hash.cmp(hash))
+            .ok()?;
+        Some(&self.index[i])
+    }
+
+    fn entry(&self, hash: &ObjHash) -> Option<Entry<'_>> {
+        let &IndexEntry { offset, len, .. } = self.find(hash)?;
+        let buf = &self.data[offset..offset + len];
+        match buf[0] {
+            FULL => Some(Entry::Full(&buf[1..])),
+            DELTA => {
+                let mut reader = Reader { buf, pos: 1 };
+                let base_len = reader.varint().ok()?;
+                let base = std::str::from_utf8(reader.take(base_len).ok()?).ok()?;
+                Some(Entry::Delta {
+                    base: ObjHash(base.to_string()),
+                    delta: &buf[reader.pos..],
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+enum Entry<'a> {
+    Full(&'a [u8]),
+    Delta { base: ObjHash, delta: &'a [u8] },
+}
+
+fn encode_index(index: &[IndexEntry]) -> Vec<u8> {
+    let mut buf = IDX_MAGIC.to_vec();
+    write_varint(&mut buf, index.len());
+    for entry in index {
+        write_varint(&mut buf, entry.hash.0.len());
+        buf.extend_from_slice(entry.hash.0.as_bytes());
+        write_varint(&mut buf, entry.offset);
+        write_varint(&mut buf, entry.len);
+    }
+    buf
+}
+
+fn decode_index(buf: &[u8]) -> error::Result<Vec<IndexEntry>> {
+    if !buf.starts_with(IDX_MAGIC) {
+        return Err(invalid("index header is invalid"));
+    }
+    let mut reader = Reader {
+        buf,
+        pos: IDX_MAGIC.len(),
+    };
+    let count = reader.varint()?;
+    let mut index = Vec::with_capacity(count.min(buf.len()));
+    for _ in 0..count {
+        let hash_len = reader.varint()?;
+        let hash = std::str::from_utf8(reader.take(hash_len)?)?;
+        index.push(IndexEntry {
+            hash: ObjHash(hash.to_string()),
+            offset: reader.varint()?,
+            len: reader.varint()?,
+        });
+    }
+    if index.windows(2).any(|w| w[1].hash <= w[0].hash) {
+        return Err(invalid("index is not sorted"));
+    }
+    Ok(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use meltos_util::compression::gz::Gz;
+    use meltos_util::compression::CompressionBuf;
+
+    use crate::file_system::memory::MemoryFileSystem;
+    use crate::file_system::FileSystem;
+    use crate::io::atomic::pack::delta;
+    use crate::io::atomic::pack::{PackEntry, PackIo};
+    use crate::object::file::FileObj;
+    use crate::object::AsMeta;
+
+    #[tokio::test]
+    async fn read_full_and_delta_entries() {
+        let fs = MemoryFileSystem::default();
+        let pack = PackIo::new(fs.clone());
+        let base = FileObj("hello world\n".repeat(10).into_bytes())
+            .as_meta()
+            .unwrap();
+        let target = FileObj(format!("{}rust\n", "hello world\n".repeat(10)).into_bytes())
+            .as_meta()
+            .unwrap();
+        let name = pack
+            .write(&[
+                PackEntry::Full {
+                    hash: base.hash.clone(),
+                    compressed_buf: base.compressed_buf.clone(),
+                },
+                PackEntry::Delta {
+                    hash: target.hash.clone(),
+                    base: base.hash.clone(),
+                    delta: delta::encode(&base.buf, &target.buf),
+                },
+            ])
+            .await
+            .unwrap();
+
+        assert_eq!(pack.read_names().await.unwrap(), vec![name]);
+        assert_eq!(
+            pack.read(&base.hash).await.unwrap(),
+            Some(base.compressed_buf.clone())
+        );
+        let buf = pack.read(&target.hash).await.unwrap().unwrap();
+        assert_eq!(Gz.unzip(&buf).unwrap(), target.buf);
+        let mut hashes = vec![base.hash, target.hash];
+        hashes.sort();
+        assert_eq!(pack.read_all_hashes().await.unwrap(), hashes);
+    }
+
+    #[tokio::test]
+    async fn failed_if_base_is_not_in_pack() {
+        let pack = PackIo::new(MemoryFileSystem::default());
+        let hello = FileObj(b"hello".to_vec()).as_meta().unwrap();
+        let result = pack
+            .write(&[PackEntry::Delta {
+                hash: hello.hash.clone(),
+                base: FileObj(b"world".to_vec()).as_meta().unwrap().hash,
+                delta: Vec::new(),
+            }])
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn delete_pack() {
+        let fs = MemoryFileSystem::default();
+        let pack = PackIo::new(fs.clone());
+        let hello = FileObj(b"hello".to_vec()).as_meta().unwrap();
+        let name = pack
+            .write(&[PackEntry::Full {
+                hash: hello.hash.clone(),
+                compressed_buf: hello.compressed_buf,
+            }])
+            .await
+            .unwrap();
+        assert!(pack.read(&hello.hash).await.unwrap().is_some());
+
+        pack.delete(&name).await.unwrap();
+        assert!(pack.read(&hello.hash).await.unwrap().is_none());
+        assert!(fs.all_files_in(".meltos/packs").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn read_pack_written_after_names_cached() {
+        let fs = MemoryFileSystem::default();
+        let pack = PackIo::new(fs.clone());
+        let hello = FileObj(b"hello".to_vec()).as_meta().unwrap();
+        assert!(!pack.contains(&hello.hash).await.unwrap());
+
+        PackIo::new(fs.clone())
+            .write(&[PackEntry::Full {
+                hash: hello.hash.clone(),
+                compressed_buf: hello.compressed_buf.clone(),
+            }])
+            .await
+            .unwrap();
+        assert!(pack.contains(&hello.hash).await.unwrap());
+        assert_eq!(
+            pack.read(&hello.hash).await.unwrap(),
+            Some(hello.compressed_buf)
+        );
+    }
+}
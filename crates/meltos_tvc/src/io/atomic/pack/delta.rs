@@ -0,0 +1,184 @@
+//! The delta encoding of the objects in the pack.
+//!
+//! A delta starts with the lengths of the base and the target, followed by the instructions:
+//! `0 {len} {bytes}` inserts the bytes, and `1 {offset} {len}` copies the range of the base.
+//! All numbers are encoded as LEB128.
+
+use std::collections::HashMap;
+
+use crate::error;
+
+/// The length of the blocks of the base used to find the copyable ranges.
+const BLOCK_LEN: usize = 16;
+
+const INSERT: u8 = 0;
+const COPY: u8 = 1;
+
+/// Encodes `target` as the delta against `base`.
+pub fn encode(base: &[u8], target: &[u8]) -> Vec<u8> {
+    let mut blocks = HashMap::new();
+    for offset in (0..base.len().saturating_sub(BLOCK_LEN - 1)).step_by(BLOCK_LEN) {
+        blocks
+            .entry(&base[offset..offset + BLOCK_LEN])
+            .or_insert(offset);
+    }
+
+    let mut delta = Vec::new();
+    write_varint(&mut delta, base.len());
+    write_varint(&mut delta, target.len());
+
+    let mut insert_start = 0;
+    let mut i = 0;
+    while i < target.len() {
+        let matched = target
+            .get(i..i + BLOCK_LEN)
+            .and_then(|block| blocks.get(block));
+        let Some(&offset) = matched else {
+            i += 1;
+            continue;
+        };
+        let len = base[offset..]
+            .iter()
+            .zip(&target[i..])
+            .take_while(|(b, t)| b == t)
+            .count();
+        write_insert(&mut delta, &target[insert_start..i]);
+        delta.push(COPY);
+        write_varint(&mut delta, offset);
+        write_varint(&mut delta, len);
+        i += len;
+        insert_start = i;
+    }
+    write_insert(&mut delta, &target[insert_start..]);
+    delta
+}
+
+/// Restores the target from `base` and the `delta` created by [`encode`].
+pub fn apply(base: &[u8], delta: &[u8]) -> error::Result<Vec<u8>> {
+    let mut reader = Reader { buf: delta, pos: 0 };
+    if reader.varint()? != base.len() {
+        return Err(invalid("base length does not match"));
+    }
+    let target_len = reader.varint()?;
+    let mut target = Vec::with_capacity(target_len);
+    while let Some(op) = reader.byte() {
+        match op {
+            INSERT => {
+                let len = reader.varint()?;
+                target.extend_from_slice(reader.take(len)?);
+            }
+            COPY => {
+                let offset = reader.varint()?;
+                let len = reader.varint()?;
+                let range = base
+                    .get(offset..offset.saturating_add(len))
+                    .ok_or_else(|| invalid("copy range is out of the base"))?;
+                target.extend_from_slice(range);
+            }
+            _ => return Err(invalid("unknown delta instruction")),
+        }
+    }
+    if target.len() != target_len {
+        return Err(invalid("target length does not match"));
+    }
+    Ok(target)
+}
+
+fn write_insert(delta: &mut Vec<u8>, bytes: &[u8]) {
+    if bytes.is_empty() {
+        return;
+    }
+    delta.push(INSERT);
+    write_varint(delta, bytes.len());
+    delta.extend_from_slice(bytes);
+}
+
+pub(crate) fn write_varint(buf: &mut Vec<u8>, mut n: usize) {
+    while n >= 0x80 {
+        buf.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    buf.push(n as u8);
+}
+
+pub(crate) struct Reader<'a> {
+    pub(crate) buf: &'a [u8],
+    pub(crate) pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn byte(&mut self) -> Option<u8> {
+        let b = *self.buf.get(self.pos)?;
+        self.pos += 1;
+        Some(b)
+    }
+
+    pub(crate) fn take(&mut self, len: usize) -> error::Result<&'a [u8]> {
+        let bytes = self
+            .buf
+            .get(self.pos..self.pos.saturating_add(len))
+            .ok_or_else(|| invalid("unexpected end of buffer"))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    pub(crate) fn varint(&mut self) -> error::Result<usize> {
+        let mut n = 0usize;
+        let mut shift = 0;
+        loop {
+            let b = self
+                .byte()
+                .ok_or_else(|| invalid("unexpected end of buffer"))?;
+            if shift >= usize::BITS {
+                return Err(invalid("varint overflows"));
+            }
+            n |= ((b & 0x7f) as usize) << shift;
+            if b & 0x80 == 0 {
+                return Ok(n);
+            }
+            shift += 7;
+        }
+    }
+}
+
+#[inline]
+pub(crate) fn invalid(reason: &str) -> error::Error {
+    error::Error::InvalidPack(reason.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::atomic::pack::delta::{apply, encode};
+
+    #[test]
+    fn restore_modified_text() {
+        let base = "hello world!\n".repeat(100);
+        let target = format!(
+            "{}rust\n{}",
+            "hello world!\n".repeat(40),
+            "hello world!\n".repeat(70)
+        );
+        let delta = encode(base.as_bytes(), target.as_bytes());
+        assert!(delta.len() < target.len() / 10);
+        assert_eq!(apply(base.as_bytes(), &delta).unwrap(), target.as_bytes());
+    }
+
+    #[test]
+    fn restore_unrelated_and_empty_buffers() {
+        for (base, target) in [
+            (&b"abc"[..], &b"xyz"[..]),
+            (b"", b"hello"),
+            (b"hello", b""),
+            (b"", b""),
+        ] {
+            let delta = encode(base, target);
+            assert_eq!(apply(base, &delta).unwrap(), target);
+        }
+    }
+
+    #[test]
+    fn failed_if_base_differs() {
+        let delta = encode(b"hello world hello world", b"hello world");
+        assert!(apply(b"hello", &delta).is_err());
+    }
+}
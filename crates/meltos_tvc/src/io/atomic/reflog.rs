@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+
+use crate::branch::BranchName;
+use crate::error;
+use crate::file_system::FileSystem;
+use crate::object::commit::CommitHash;
+
+const LOGS: &str = ".meltos/logs/refs";
+
+/// The movement of a head.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ReflogEntry {
+    /// The head before the movement; `None` if the head was created.
+    pub old: Option<CommitHash>,
+    pub new: CommitHash,
+    /// The name of the operation which moved the head, such as `commit` or `merge`.
+    pub operation: String,
+    /// Seconds elapsed since the UNIX epoch when moved.
+    pub time: u64,
+}
+
+/// Reads and appends the reflog of each branch under `.meltos/logs/refs`.
+///
+/// The entries are stored one JSON per line, the oldest first.
+#[derive(Debug, Clone)]
+pub struct ReflogIo<Fs>(Fs)
+where
+    Fs: FileSystem;
+
+impl<Fs> ReflogIo<Fs>
+where
+    Fs: FileSystem,
+{
+    #[inline]
+    pub const fn new(fs: Fs) -> ReflogIo<Fs> {
+        Self(fs)
+    }
+
+    #[inline]
+    pub async fn append(&self, branch_name: &BranchName, entry: &ReflogEntry) -> error::Result {
+        append_entry(&self.0, &heads_path(branch_name), entry).await
+    }
+
+    #[inline]
+    pub async fn read(&self, branch_name: &BranchName) -> error::Result<Vec<ReflogEntry>> {
+        read_entries(&self.0, &heads_path(branch_name)).await
+    }
+
+    #[inline]
+    pub async fn read_remote(&self, branch_name: &BranchName) -> error::Result<Vec<ReflogEntry>> {
+        read_entries(&self.0, &remotes_path(branch_name)).await
+    }
+
+    /// Reads the entries of all local and remote branches.
+    pub async fn read_all(&self) -> error::Result<Vec<ReflogEntry>> {
+        let mut entries = Vec::new();
+        for path in self.0.all_files_in(LOGS).await? {
+            entries.extend(read_entries(&self.0, &path).await?);
+        }
+        Ok(entries)
+    }
+
+    /// Deletes the entries of all local and remote branches moved before `time`.
+    pub async fn expire(&self, time: u64) -> error::Result {
+        for path in self.0.all_files_in(LOGS).await? {
+            let entries = read_entries(&self.0, &path).await?;
+            if entries.iter().all(|entry| time <= entry.time) {
+                continue;
+            }
+            let mut buf = Vec::new();
+            for entry in entries.iter().filter(|entry| time <= entry.time) {
+                serde_json::to_writer(&mut buf, entry)?;
+                buf.push(b'\n');
+            }
+            self.0.write_file(&path, &buf).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn rename(&self, old: &BranchName, new: &BranchName) -> error::Result {
+        old.validate()?;
+        new.validate()?;
+        let Some(buf) = self.0.read_file(&heads_path(old)).await? else {
+            return Ok(());
+        };
+        self.0.write_file(&heads_path(new), &buf).await?;
+        self.delete(old).await
+    }
+
+    #[inline]
+    pub async fn delete(&self, branch_name: &BranchName) -> error::Result {
+        self.0.delete(&heads_path(branch_name)).await?;
+        Ok(())
+    }
+}
+
+pub(crate) async fn append_entry<Fs: FileSystem>(
+    fs: &Fs,
+    path: &str,
+    entry: &ReflogEntry,
+) -> error::Result {
+    let mut buf = serde_json::to_vec(entry)?;
+    buf.push(b'\n');
+    fs.append_file(path, &buf).await?;
+    Ok(())
+}
+
+async fn read_entries<Fs: FileSystem>(fs: &Fs, path: &str) -> error::Result<Vec<ReflogEntry>> {
+    let Some(buf) = fs.read_file(path).await? else {
+        return Ok(Vec::new());
+    };
+    let mut entries = Vec::new();
+    for line in buf.split(|b| *b == b'\n').filter(|line| !line.is_empty()) {
+        entries.push(serde_json::from_slice(line)?);
+    }
+    Ok(entries)
+}
+
+#[inline]
+pub(crate) fn heads_path(branch_name: &BranchName) -> String {
+    format!("{LOGS}/heads/{branch_name}")
+}
+
+#[inline]
+pub(crate) fn remotes_path(branch_name: &BranchName) -> String {
+    format!("{LOGS}/remotes/{branch_name}")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::branch::BranchName;
+    use crate::file_system::memory::MemoryFileSystem;
+    use crate::io::atomic::reflog::{ReflogEntry, ReflogIo};
+    use crate::object::commit::CommitHash;
+    use crate::object::ObjHash;
+
+    #[tokio::test]
+    async fn append_entries() {
+        let io = ReflogIo::new(MemoryFileSystem::default());
+        let branch = BranchName::owner();
+        let first = ReflogEntry {
+            old: None,
+            new: CommitHash(ObjHash::new(b"first")),
+            operation: "init".to_string(),
+            time: 1,
+        };
+        let second = ReflogEntry {
+            old: Some(first.new.clone()),
+            new: CommitHash(ObjHash::new(b"second")),
+            operation: "commit".to_string(),
+            time: 2,
+        };
+        io.append(&branch, &first).await.unwrap();
+        io.append(&branch, &second).await.unwrap();
+        assert_eq!(io.read(&branch).await.unwrap(), vec![first, second]);
+
+        let renamed = BranchName::from("renamed");
+        io.rename(&branch, &renamed).await.unwrap();
+        assert!(io.read(&branch).await.unwrap().is_empty());
+        assert_eq!(io.read(&renamed).await.unwrap().len(), 2);
+    }
+}
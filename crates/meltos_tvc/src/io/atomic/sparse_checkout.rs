@@ -0,0 +1,101 @@
+use crate::error;
+use crate::file_system::FileSystem;
+use crate::ignore::IgnoreMatcher;
+
+const SPARSE_CHECKOUT: &str = ".meltos/sparse-checkout";
+
+/// The compiled sparse-checkout patterns.
+///
+/// The patterns follow the gitignore format, but a matched file is written into the workspace instead of being ignored.
+/// All files are matched if no patterns are set.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct SparsePatterns(IgnoreMatcher);
+
+impl SparsePatterns {
+    pub fn new(patterns: &[String]) -> Self {
+        let mut matcher = IgnoreMatcher::default();
+        matcher.add_rules("", &patterns.join("\n"));
+        Self(matcher)
+    }
+
+    /// Returns true if the file should be written into the workspace.
+    #[inline]
+    pub fn contains(&self, path: &str) -> bool {
+        self.0.is_empty() || self.0.matches_nearest(path)
+    }
+
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        !self.0.is_empty()
+    }
+}
+
+/// Reads and writes the sparse-checkout patterns, one pattern per line.
+#[derive(Debug, Clone)]
+pub struct SparseCheckoutIo<Fs>(Fs)
+where
+    Fs: FileSystem;
+
+impl<Fs> SparseCheckoutIo<Fs>
+where
+    Fs: FileSystem,
+{
+    #[inline]
+    pub const fn new(fs: Fs) -> SparseCheckoutIo<Fs> {
+        Self(fs)
+    }
+
+    /// Writes the patterns; an empty list disables the sparse checkout.
+    #[inline]
+    pub async fn write(&self, patterns: &[String]) -> error::Result {
+        if patterns.is_empty() {
+            self.0.delete(SPARSE_CHECKOUT).await?;
+        } else {
+            self.0
+                .write_file(SPARSE_CHECKOUT, patterns.join("\n").as_bytes())
+                .await?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub async fn read_patterns(&self) -> error::Result<Vec<String>> {
+        let Some(buf) = self.0.read_file(SPARSE_CHECKOUT).await? else {
+            return Ok(Vec::new());
+        };
+        Ok(String::from_utf8(buf)?
+            .lines()
+            .map(str::to_string)
+            .collect())
+    }
+
+    #[inline]
+    pub async fn read(&self) -> error::Result<SparsePatterns> {
+        Ok(SparsePatterns::new(&self.read_patterns().await?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::file_system::memory::MemoryFileSystem;
+    use crate::io::atomic::sparse_checkout::SparseCheckoutIo;
+
+    #[tokio::test]
+    async fn contains_all_if_not_written() {
+        let io = SparseCheckoutIo::new(MemoryFileSystem::default());
+        let patterns = io.read().await.unwrap();
+        assert!(!patterns.is_enabled());
+        assert!(patterns.contains("src/main.rs"));
+
+        io.write(&["/src/".to_string(), "!/src/tests/".to_string()])
+            .await
+            .unwrap();
+        let patterns = io.read().await.unwrap();
+        assert!(patterns.contains("src/main.rs"));
+        assert!(!patterns.contains("src/tests/main.rs"));
+        assert!(!patterns.contains("README.md"));
+
+        io.write(&[]).await.unwrap();
+        assert!(io.read_patterns().await.unwrap().is_empty());
+    }
+}
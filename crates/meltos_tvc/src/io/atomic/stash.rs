@@ -0,0 +1,41 @@
+use crate::error;
+use crate::file_system::FileSystem;
+use crate::object::commit::CommitHash;
+
+const STASH: &str = ".meltos/refs/stash";
+
+/// Reads and writes the stash commits; the newest one comes first.
+#[derive(Debug, Clone)]
+pub struct StashIo<Fs>(Fs)
+where
+    Fs: FileSystem;
+
+impl<Fs> StashIo<Fs>
+where
+    Fs: FileSystem,
+{
+    #[inline]
+    pub const fn new(fs: Fs) -> StashIo<Fs> {
+        Self(fs)
+    }
+
+    #[inline]
+    pub async fn write_all(&self, stashes: &[CommitHash]) -> error::Result {
+        if stashes.is_empty() {
+            self.0.delete(STASH).await?;
+        } else {
+            self.0
+                .write_file(STASH, &serde_json::to_vec(stashes)?)
+                .await?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub async fn read_all(&self) -> error::Result<Vec<CommitHash>> {
+        let Some(buf) = self.0.read_file(STASH).await? else {
+            return Ok(Vec::new());
+        };
+        Ok(serde_json::from_slice(&buf)?)
+    }
+}
@@ -0,0 +1,81 @@
+use crate::encode::{Decodable, Encodable};
+use crate::error;
+use crate::file_system::FileSystem;
+use crate::object::ObjHash;
+use crate::tag::TagName;
+
+/// Reads and writes the tags under `.meltos/refs/tags`.
+///
+/// A lightweight tag refers to the commit directly, and an annotated tag refers to its [`TagObj`](crate::object::tag::TagObj).
+#[derive(Debug, Clone)]
+pub struct TagIo<Fs>
+where
+    Fs: FileSystem,
+{
+    fs: Fs,
+}
+
+impl<Fs> TagIo<Fs>
+where
+    Fs: FileSystem,
+{
+    pub const fn new(fs: Fs) -> TagIo<Fs> {
+        Self {
+            fs,
+        }
+    }
+
+    #[inline]
+    pub async fn write(&self, tag_name: &TagName, hash: &ObjHash) -> error::Result {
+        tag_name.validate()?;
+        self.fs
+            .write_file(&format!(".meltos/refs/tags/{tag_name}"), &hash.encode()?)
+            .await?;
+        Ok(())
+    }
+
+    #[inline]
+    pub async fn delete(&self, tag_name: &TagName) -> error::Result {
+        tag_name.validate()?;
+        self.fs
+            .delete(&format!(".meltos/refs/tags/{tag_name}"))
+            .await?;
+        Ok(())
+    }
+
+    #[inline]
+    pub async fn try_read(&self, tag_name: &TagName) -> error::Result<ObjHash> {
+        self.read(tag_name)
+            .await?
+            .ok_or_else(|| error::Error::NotfoundTag(tag_name.clone()))
+    }
+
+    pub async fn read(&self, tag_name: &TagName) -> error::Result<Option<ObjHash>> {
+        tag_name.validate()?;
+        let Some(buf) = self
+            .fs
+            .read_file(&format!(".meltos/refs/tags/{tag_name}"))
+            .await?
+        else {
+            return Ok(None);
+        };
+        Ok(Some(ObjHash::decode(&buf)?))
+    }
+
+    pub async fn read_all(&self) -> error::Result<Vec<(TagName, ObjHash)>> {
+        let files = self.fs.all_files_in(".meltos/refs/tags/").await?;
+        let mut tags = Vec::with_capacity(files.len());
+        for path in files {
+            let Some(name) = path
+                .trim_start_matches("./")
+                .strip_prefix(".meltos/refs/tags/")
+            else {
+                continue;
+            };
+            let tag_name = TagName::from(name);
+            let hash = self.try_read(&tag_name).await?;
+            tags.push((tag_name, hash));
+        }
+        Ok(tags)
+    }
+}
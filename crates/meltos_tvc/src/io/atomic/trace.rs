@@ -40,6 +40,14 @@ where
         Ok(())
     }
 
+    #[inline]
+    pub async fn delete(&self, commit_hash: &CommitHash) -> error::Result {
+        self.fs
+            .delete(&format!(".meltos/traces/{commit_hash}"))
+            .await?;
+        Ok(())
+    }
+
     #[inline]
     pub async fn read_all(&self) -> error::Result<Vec<BundleTrace>> {
         let files = self.fs.all_files_in(".meltos/traces/").await?;
@@ -66,6 +74,12 @@ where
         Ok(traces)
     }
 
+    #[inline]
+    pub async fn exists(&self, commit_hash: &CommitHash) -> error::Result<bool> {
+        let file_path = format!(".meltos/traces/{commit_hash}");
+        Ok(self.fs.stat(&file_path).await?.is_some())
+    }
+
     #[inline]
     pub async fn read(&self, commit_hash: &CommitHash) -> error::Result<ObjHash> {
         let file_path = format!(".meltos/traces/{commit_hash}");
@@ -1,5 +1,8 @@
+use std::collections::HashSet;
 use std::path::Path;
 
+use futures::stream::BoxStream;
+use futures::{stream, StreamExt};
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::wasm_bindgen;
 
@@ -8,9 +11,11 @@ use crate::error;
 use crate::file_system::FileSystem;
 use crate::io::atomic::head::HeadIo;
 use crate::io::atomic::object::ObjIo;
+use crate::io::atomic::tag::TagIo;
 use crate::io::atomic::trace::TraceIo;
 use crate::object::commit::CommitHash;
-use crate::object::{CompressedBuf, ObjHash};
+use crate::object::{CompressedBuf, Obj, ObjHash};
+use crate::tag::TagName;
 
 #[wasm_bindgen(getter_with_clone)]
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Default)]
@@ -18,6 +23,8 @@ pub struct Bundle {
     pub traces: Vec<BundleTrace>,
     pub objs: Vec<BundleObject>,
     pub branches: Vec<BundleBranch>,
+    #[serde(default)]
+    pub tags: Vec<BundleTag>,
 }
 
 #[wasm_bindgen]
@@ -32,6 +39,7 @@ impl Bundle {
             traces,
             objs,
             branches,
+            tags: Vec::new(),
         }
     }
 }
@@ -41,6 +49,68 @@ impl Bundle {
     pub fn obj_data_size(&self) -> usize {
         self.objs.iter().map(|obj| obj.compressed_buf.0.len()).sum()
     }
+
+    /// Returns the hashes of the objects and the commit hashes of the traces.
+    pub fn hashes(&self) -> BundleHashes {
+        BundleHashes {
+            objs: self.objs.iter().map(|obj| obj.hash.clone()).collect(),
+            traces: self
+                .traces
+                .iter()
+                .map(|trace| trace.commit_hash.clone())
+                .collect(),
+        }
+    }
+
+    /// Adds the record received from the bundle stream.
+    pub fn push_record(&mut self, record: BundleRecord) {
+        match record {
+            BundleRecord::Trace(trace) => self.traces.push(trace),
+            BundleRecord::Obj(obj) => self.objs.push(obj),
+            BundleRecord::Branch(branch) => self.branches.push(branch),
+            BundleRecord::Tag(tag) => self.tags.push(tag),
+        }
+    }
+
+    /// Splits the bundle into the records in the same order as [`BundleIo::create_stream`].
+    pub fn into_records(self) -> impl Iterator<Item = BundleRecord> {
+        self.traces
+            .into_iter()
+            .map(BundleRecord::Trace)
+            .chain(self.objs.into_iter().map(BundleRecord::Obj))
+            .chain(self.tags.into_iter().map(BundleRecord::Tag))
+            .chain(self.branches.into_iter().map(BundleRecord::Branch))
+    }
+
+    /// Removes the objects and traces that are not contained in `hashes`.
+    pub fn retain(&mut self, hashes: &BundleHashes) {
+        let objs = hashes.objs.iter().collect::<HashSet<&ObjHash>>();
+        let traces = hashes.traces.iter().collect::<HashSet<&CommitHash>>();
+        self.objs.retain(|obj| objs.contains(&obj.hash));
+        self.traces.retain(|trace| traces.contains(&trace.commit_hash));
+    }
+}
+
+/// A unit of the bundle stream.
+///
+/// The stream yields the traces and the objects first, and the tags and the branches last,
+/// so the refs are not updated until all the objects they refer to have been received.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub enum BundleRecord {
+    Trace(BundleTrace),
+    Obj(BundleObject),
+    Branch(BundleBranch),
+    Tag(BundleTag),
+}
+
+pub type BundleStream = BoxStream<'static, error::Result<BundleRecord>>;
+
+/// The hashes of the objects and the traces in a bundle.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+pub struct BundleHashes {
+    pub objs: Vec<ObjHash>,
+    /// The commit hashes of the traces.
+    pub traces: Vec<CommitHash>,
 }
 
 #[wasm_bindgen(getter_with_clone)]
@@ -100,6 +170,25 @@ impl BundleBranch {
     }
 }
 
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct BundleTag {
+    pub tag_name: TagName,
+    /// The commit hash if it is a lightweight tag, otherwise the hash of the [`TagObj`](crate::object::tag::TagObj).
+    pub hash: ObjHash,
+}
+
+#[wasm_bindgen]
+impl BundleTag {
+    #[wasm_bindgen(constructor)]
+    pub fn wasm_new(tag_name: String, hash: String) -> Self {
+        Self {
+            tag_name: TagName(tag_name),
+            hash: ObjHash(hash),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct BundleIo<Fs>
 where
@@ -107,6 +196,7 @@ where
 {
     object: ObjIo<Fs>,
     trace: TraceIo<Fs>,
+    tag: TagIo<Fs>,
     fs: Fs,
 }
 
@@ -119,6 +209,7 @@ where
         Self {
             object: ObjIo::new(fs.clone()),
             trace: TraceIo::new(fs.clone()),
+            tag: TagIo::new(fs.clone()),
             fs,
         }
     }
@@ -129,9 +220,198 @@ where
             branches,
             objs: self.object.read_all().await?,
             traces: self.trace.read_all().await?,
+            tags: self.read_tags().await?,
+        })
+    }
+
+    /// Creates the bundle that contains only what a client which has `known` commits lacks.
+    ///
+    /// The commits reachable from the heads but not from `known` are bundled with their trees and traces.
+    /// The objects in the trace trees of `known` are assumed to be held by the client and are not bundled.
+    /// Unknown hashes in `known` are ignored.
+    pub async fn create_since(&self, known: &[CommitHash]) -> error::Result<Bundle> {
+        let plan = self.plan_since(known).await?;
+        self.read_plan(plan).await
+    }
+
+    /// Reads all the objects in `plan` into the bundle.
+    pub async fn read_plan(&self, plan: BundlePlan) -> error::Result<Bundle> {
+        let mut objs = Vec::with_capacity(plan.hashes.len());
+        for hash in plan.hashes {
+            objs.push(read_bundle_obj(&self.object, hash).await?);
+        }
+        Ok(Bundle {
+            branches: plan.branches,
+            objs,
+            traces: plan.traces,
+            tags: plan.tags,
+        })
+    }
+
+    /// The stream version of [`BundleIo::read_plan`].
+    ///
+    /// The objects are read while the stream is consumed,
+    /// so the caller must keep them from being deleted until the stream ends.
+    #[inline]
+    pub fn stream_plan(&self, plan: BundlePlan) -> BundleStream
+    where
+        Fs: 'static,
+    {
+        record_stream(self.object.clone(), plan)
+    }
+
+    /// Creates the stream of the whole repository, which reads the objects one by one while being consumed.
+    pub async fn create_stream(&self) -> error::Result<BundleStream>
+    where
+        Fs: 'static,
+    {
+        let plan = BundlePlan {
+            branches: self.read_branch_heads().await?,
+            tags: self.read_tags().await?,
+            traces: self.trace.read_all().await?,
+            hashes: self.object.read_all_hashes().await?,
+        };
+        Ok(self.stream_plan(plan))
+    }
+
+    /// The stream version of [`BundleIo::create_since`].
+    pub async fn create_stream_since(&self, known: &[CommitHash]) -> error::Result<BundleStream>
+    where
+        Fs: 'static,
+    {
+        let plan = self.plan_since(known).await?;
+        Ok(self.stream_plan(plan))
+    }
+
+    /// Returns what to be bundled for [`BundleIo::create_since`].
+    ///
+    /// All tags are bundled, and the commits they refer to are treated like the heads.
+    async fn plan_since(&self, known: &[CommitHash]) -> error::Result<BundlePlan> {
+        let branches = self.read_branch_heads().await?;
+        let tags = self.read_tags().await?;
+        let mut known_commits = HashSet::new();
+        let mut held_objs = HashSet::new();
+        for commit_hash in known {
+            if self.object.read(&commit_hash.0).await?.is_none() {
+                continue;
+            }
+            if let Ok(trace_hash) = self.trace.read(commit_hash).await {
+                held_objs.extend(self.object.read_to_tree(&trace_hash).await?.into_values());
+                held_objs.insert(trace_hash);
+            }
+            self.mark_ancestors(&mut known_commits, commit_hash.clone()).await?;
+        }
+
+        let mut objs = BundleObjs {
+            held: held_objs,
+            objs: Vec::new(),
+        };
+        let mut traces = Vec::new();
+        let mut visited = HashSet::new();
+        let mut commits = branches
+            .iter()
+            .flat_map(|branch| branch.commits.iter().cloned())
+            .collect::<Vec<CommitHash>>();
+        for tag in tags.iter() {
+            match self.object.try_read_obj(&tag.hash).await? {
+                Obj::Tag(tag_obj) => {
+                    objs.push(tag.hash.clone());
+                    commits.push(tag_obj.commit_hash);
+                }
+                _ => commits.push(CommitHash(tag.hash.clone())),
+            }
+        }
+        while let Some(commit_hash) = commits.pop() {
+            if known_commits.contains(&commit_hash) || !visited.insert(commit_hash.clone()) {
+                continue;
+            }
+            let commit = self.object.read_to_commit(&commit_hash).await?;
+            objs.push(commit_hash.0.clone());
+            self.push_tree(&mut objs, commit.committed_objs_tree).await?;
+            if let Ok(trace_hash) = self.trace.read(&commit_hash).await {
+                self.push_tree(&mut objs, trace_hash.clone()).await?;
+                traces.push(BundleTrace {
+                    commit_hash,
+                    obj_hash: trace_hash,
+                });
+            }
+            commits.extend(commit.parents);
+        }
+
+        Ok(BundlePlan {
+            branches,
+            tags,
+            traces,
+            hashes: objs.objs,
         })
     }
 
+    /// Returns the objects and traces in `hashes` that do not exist in this repository.
+    pub async fn missing(&self, hashes: &BundleHashes) -> error::Result<BundleHashes> {
+        let mut missing = BundleHashes::default();
+        for hash in hashes.objs.iter() {
+            if !self.object.exists(hash).await? {
+                missing.objs.push(hash.clone());
+            }
+        }
+        for commit_hash in hashes.traces.iter() {
+            if !self.trace.exists(commit_hash).await? {
+                missing.traces.push(commit_hash.clone());
+            }
+        }
+        Ok(missing)
+    }
+
+    /// Fails if any object or trace referred to by the commits of `branch` does not exist in this repository.
+    ///
+    /// Called before moving the head to the pushed branch,
+    /// so that the head never refers to the objects which were deleted after [`BundleIo::missing`] reported them as present.
+    pub async fn verify_branch(&self, branch: &BundleBranch) -> error::Result {
+        for commit_hash in branch.commits.iter() {
+            let commit = self.object.read_to_commit(commit_hash).await?;
+            for parent in commit.parents.iter() {
+                self.error_if_not_exists(&parent.0).await?;
+            }
+            self.verify_tree(&commit.committed_objs_tree).await?;
+            self.verify_tree(&self.trace.read(commit_hash).await?).await?;
+        }
+        Ok(())
+    }
+
+    async fn verify_tree(&self, tree_hash: &ObjHash) -> error::Result {
+        for hash in self.object.read_to_tree(tree_hash).await?.values() {
+            self.error_if_not_exists(hash).await?;
+        }
+        Ok(())
+    }
+
+    async fn error_if_not_exists(&self, hash: &ObjHash) -> error::Result {
+        if self.object.exists(hash).await? {
+            Ok(())
+        } else {
+            Err(error::Error::NotfoundObj(hash.clone()))
+        }
+    }
+
+    async fn mark_ancestors(&self, marked: &mut HashSet<CommitHash>, from: CommitHash) -> error::Result {
+        let mut commits = vec![from];
+        while let Some(commit_hash) = commits.pop() {
+            if marked.insert(commit_hash.clone()) {
+                commits.extend(self.object.read_to_commit(&commit_hash).await?.parents);
+            }
+        }
+        Ok(())
+    }
+
+    async fn push_tree(&self, objs: &mut BundleObjs, tree_hash: ObjHash) -> error::Result {
+        let tree = self.object.read_to_tree(&tree_hash).await?;
+        objs.push(tree_hash);
+        for hash in tree.into_values() {
+            objs.push(hash);
+        }
+        Ok(())
+    }
+
     async fn read_branch_heads(&self) -> error::Result<Vec<BundleBranch>> {
         let head_files = self.read_all_branch_head_path().await?;
         let mut branches = Vec::with_capacity(head_files.len());
@@ -152,22 +432,116 @@ where
         Ok(branches)
     }
 
+    async fn read_tags(&self) -> error::Result<Vec<BundleTag>> {
+        Ok(self
+            .tag
+            .read_all()
+            .await?
+            .into_iter()
+            .map(|(tag_name, hash)| BundleTag { tag_name, hash })
+            .collect())
+    }
+
     #[inline]
     async fn read_all_branch_head_path(&self) -> error::Result<Vec<String>> {
         Ok(self.fs.all_files_in(".meltos/refs/heads").await?)
     }
 }
 
+/// What to be bundled, without the contents of the objects.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct BundlePlan {
+    pub branches: Vec<BundleBranch>,
+    pub tags: Vec<BundleTag>,
+    pub traces: Vec<BundleTrace>,
+    /// The hashes of the objects.
+    pub hashes: Vec<ObjHash>,
+}
+
+impl BundlePlan {
+    /// Returns the hashes of the objects and the commit hashes of the traces.
+    pub fn hashes(&self) -> BundleHashes {
+        BundleHashes {
+            objs: self.hashes.clone(),
+            traces: self
+                .traces
+                .iter()
+                .map(|trace| trace.commit_hash.clone())
+                .collect(),
+        }
+    }
+
+    /// Removes the objects and traces that are not contained in `hashes`.
+    pub fn retain(&mut self, hashes: &BundleHashes) {
+        let objs = hashes.objs.iter().collect::<HashSet<&ObjHash>>();
+        let traces = hashes.traces.iter().collect::<HashSet<&CommitHash>>();
+        self.hashes.retain(|hash| objs.contains(hash));
+        self.traces.retain(|trace| traces.contains(&trace.commit_hash));
+    }
+}
+
+struct BundleObjs {
+    /// The objects already bundled or held by the receiver.
+    held: HashSet<ObjHash>,
+    objs: Vec<ObjHash>,
+}
+
+impl BundleObjs {
+    fn push(&mut self, hash: ObjHash) {
+        if self.held.insert(hash.clone()) {
+            self.objs.push(hash);
+        }
+    }
+}
+
+/// Yields the records of `plan`, reading each object only when it is polled.
+fn record_stream<Fs>(
+    object: ObjIo<Fs>,
+    plan: BundlePlan,
+) -> BundleStream
+where
+    Fs: FileSystem + Clone + 'static,
+{
+    let traces = stream::iter(plan.traces).map(|trace| Ok(BundleRecord::Trace(trace)));
+    let objs = stream::iter(plan.hashes).then(move |hash| {
+        let object = object.clone();
+        async move { Ok(BundleRecord::Obj(read_bundle_obj(&object, hash).await?)) }
+    });
+    let tags = stream::iter(plan.tags).map(|tag| Ok(BundleRecord::Tag(tag)));
+    let branches = stream::iter(plan.branches).map(|branch| Ok(BundleRecord::Branch(branch)));
+    traces.chain(objs).chain(tags).chain(branches).boxed()
+}
+
+async fn read_bundle_obj<Fs: FileSystem>(object: &ObjIo<Fs>, hash: ObjHash) -> error::Result<BundleObject> {
+    let compressed_buf = object
+        .read(&hash)
+        .await?
+        .ok_or_else(|| error::Error::NotfoundObj(hash.clone()))?;
+    Ok(BundleObject {
+        hash,
+        compressed_buf,
+    })
+}
+
 #[cfg(test)]
 mod tests {
+    use futures::{StreamExt, TryStreamExt};
+
     use crate::branch::BranchName;
+    use crate::error;
     use crate::file_system::memory::MemoryFileSystem;
     use crate::file_system::FileSystem;
     use crate::io::atomic::work_branch::WorkingIo;
-    use crate::io::bundle::BundleIo;
+    use crate::io::atomic::object::ObjIo;
+    use crate::io::bundle::{Bundle, BundleBranch, BundleHashes, BundleIo, BundleRecord};
+    use crate::object::commit::CommitHash;
+    use crate::object::file::FileObj;
+    use crate::object::{AsMeta, ObjHash};
     use crate::operation::commit::Commit;
     use crate::operation::new_branch::NewBranch;
     use crate::operation::stage::Stage;
+    use crate::operation::tag::Tag;
+    use crate::tag::TagName;
     use crate::tests::init_owner_branch;
 
     #[tokio::test]
@@ -228,6 +602,179 @@ mod tests {
         assert_eq!(&bundle.branches[1].commits[0], &null_commit);
     }
 
+    #[tokio::test]
+    async fn create_only_missing_commits() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        fs.write_sync("hello.txt", b"hello");
+        let null_commit = init_owner_branch(fs.clone()).await;
+        fs.write_sync("world.txt", b"world");
+        Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        let commit_hash = Commit::new(fs.clone()).execute(&branch, "commit").await.unwrap();
+
+        let bundle = BundleIo::new(fs.clone())
+            .create_since(&[null_commit])
+            .await
+            .unwrap();
+        assert_eq!(bundle.branches[0].commits, vec![commit_hash.clone()]);
+        assert_eq!(bundle.traces.len(), 1);
+        assert_eq!(bundle.traces[0].commit_hash, commit_hash);
+
+        let bundle_hashes = bundle
+            .objs
+            .iter()
+            .map(|obj| obj.hash.clone())
+            .collect::<Vec<ObjHash>>();
+        let hello = FileObj(b"hello".to_vec()).as_meta().unwrap();
+        let world = FileObj(b"world".to_vec()).as_meta().unwrap();
+        assert!(bundle_hashes.contains(&commit_hash.0));
+        assert!(bundle_hashes.contains(&world.hash));
+        assert!(!bundle_hashes.contains(&hello.hash));
+    }
+
+    #[tokio::test]
+    async fn stream_same_records_as_bundle() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        fs.write_sync("hello.txt", b"hello");
+        let null_commit = init_owner_branch(fs.clone()).await;
+        fs.write_sync("world.txt", b"world");
+        Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        Commit::new(fs.clone()).execute(&branch, "commit").await.unwrap();
+
+        let bundle_io = BundleIo::new(fs.clone());
+        let records = bundle_io
+            .create_stream()
+            .await
+            .unwrap()
+            .try_collect::<Vec<BundleRecord>>()
+            .await
+            .unwrap();
+        assert!(matches!(records.last(), Some(BundleRecord::Branch(_))));
+        let mut streamed = Bundle::default();
+        records.into_iter().for_each(|record| streamed.push_record(record));
+        assert_eq!(streamed, bundle_io.create().await.unwrap());
+
+        let mut streamed = Bundle::default();
+        let mut stream = bundle_io
+            .create_stream_since(std::slice::from_ref(&null_commit))
+            .await
+            .unwrap();
+        while let Some(record) = stream.next().await {
+            streamed.push_record(record.unwrap());
+        }
+        assert_eq!(streamed, bundle_io.create_since(&[null_commit]).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn bundle_tags() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        let null_commit = init_owner_branch(fs.clone()).await;
+        fs.write_sync("hello.txt", b"hello");
+        Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        let commit_hash = Commit::new(fs.clone()).execute(&branch, "commit").await.unwrap();
+        let tag = Tag::new(fs.clone());
+        tag.create(&TagName::from("light"), &null_commit).await.unwrap();
+        let tag_obj = tag
+            .create_annotated(&TagName::from("reviewed"), &commit_hash, &branch, "reviewed")
+            .await
+            .unwrap();
+
+        let bundle_io = BundleIo::new(fs.clone());
+        let mut bundle = bundle_io.create().await.unwrap();
+        bundle.tags.sort();
+        assert_eq!(bundle.tags[0].hash, null_commit.0);
+        assert_eq!(bundle.tags[1].hash, tag_obj);
+
+        let bundle = bundle_io
+            .create_since(std::slice::from_ref(&commit_hash))
+            .await
+            .unwrap();
+        assert_eq!(bundle.tags.len(), 2);
+        assert_eq!(bundle.objs.len(), 1);
+        assert_eq!(bundle.objs[0].hash, tag_obj);
+    }
+
+    #[tokio::test]
+    async fn create_nothing_if_up_to_date() {
+        let fs = MemoryFileSystem::default();
+        let head = init_owner_branch(fs.clone()).await;
+        let bundle = BundleIo::new(fs.clone())
+            .create_since(std::slice::from_ref(&head))
+            .await
+            .unwrap();
+        assert!(bundle.objs.is_empty());
+        assert!(bundle.traces.is_empty());
+        assert_eq!(bundle.branches[0].commits, vec![head]);
+    }
+
+    #[tokio::test]
+    async fn ignore_unknown_commits() {
+        let fs = MemoryFileSystem::default();
+        init_owner_branch(fs.clone()).await;
+        let unknown = CommitHash(ObjHash::new(b"unknown"));
+        let bundle_io = BundleIo::new(fs.clone());
+        let bundle = bundle_io.create_since(&[unknown]).await.unwrap();
+        let mut hashes = bundle
+            .objs
+            .iter()
+            .map(|obj| obj.hash.clone())
+            .collect::<Vec<ObjHash>>();
+        hashes.sort();
+        let mut all = ObjIo::new(fs.clone()).read_all_hashes().await.unwrap();
+        all.sort();
+        assert_eq!(hashes, all);
+    }
+
+    #[tokio::test]
+    async fn missing_hashes() {
+        let fs = MemoryFileSystem::default();
+        let head = init_owner_branch(fs.clone()).await;
+        let bundle_io = BundleIo::new(fs.clone());
+        let mut hashes = bundle_io.create().await.unwrap().hashes();
+        assert_eq!(bundle_io.missing(&hashes).await.unwrap(), BundleHashes::default());
+
+        let unknown = ObjHash::new(b"unknown");
+        hashes.objs.push(unknown.clone());
+        hashes.traces.push(CommitHash(unknown.clone()));
+        let missing = bundle_io.missing(&hashes).await.unwrap();
+        assert_eq!(missing.objs, vec![unknown.clone()]);
+        assert_eq!(missing.traces, vec![CommitHash(unknown)]);
+
+        let mut bundle = bundle_io.create().await.unwrap();
+        bundle.retain(&BundleHashes {
+            objs: vec![head.0.clone()],
+            traces: Vec::new(),
+        });
+        assert_eq!(bundle.objs.len(), 1);
+        assert!(bundle.traces.is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_verify_branch_if_referred_obj_deleted() {
+        let fs = MemoryFileSystem::default();
+        init_owner_branch(fs.clone()).await;
+        let branch = BranchName::owner();
+        fs.write_file("hello.txt", b"hello").await.unwrap();
+        Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        let head = Commit::new(fs.clone()).execute(&branch, "commit").await.unwrap();
+
+        let bundle_io = BundleIo::new(fs.clone());
+        let bundle_branch = BundleBranch {
+            branch_name: branch,
+            commits: vec![head],
+        };
+        bundle_io.verify_branch(&bundle_branch).await.unwrap();
+
+        let hello = FileObj(b"hello".to_vec()).as_meta().unwrap();
+        ObjIo::new(fs.clone()).delete(&hello.hash).await.unwrap();
+        assert!(matches!(
+            bundle_io.verify_branch(&bundle_branch).await,
+            Err(error::Error::NotfoundObj(hash)) if hash == hello.hash
+        ));
+    }
+
     #[tokio::test]
     async fn read_all_objs() {
         let fs = MemoryFileSystem::default();
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use crate::error;
 use crate::file_system::FileSystem;
 use crate::io::commit_obj::CommitObjIo;
@@ -27,31 +29,66 @@ impl<Fs> CommitHashIo<Fs>
 where
     Fs: FileSystem,
 {
+    /// Returns the hashes of `from` and its ancestors in depth-first order.
+    ///
+    /// Commits reachable via multiple merge paths are returned only once,
+    /// and the ancestors of `to` are not traversed.
     pub async fn read_all(
         &self,
         from: CommitHash,
         to: &Option<CommitHash>,
     ) -> error::Result<Vec<CommitHash>> {
         let mut hashes = Vec::new();
-        self.read_obj(&mut hashes, from, to).await?;
+        let mut visited = HashSet::new();
+        let mut stack = vec![from];
+        while let Some(commit_hash) = stack.pop() {
+            if !visited.insert(commit_hash.clone()) {
+                continue;
+            }
+            let obj = self.commit_obj.read(&commit_hash).await?;
+            if to.as_ref().is_none_or(|to| to != &commit_hash) {
+                stack.extend(obj.parents.into_iter().rev());
+            }
+            hashes.push(commit_hash);
+        }
         Ok(hashes)
     }
 
-    #[async_recursion::async_recursion(?Send)]
-    async fn read_obj(
+    /// Returns the best common ancestors of any of `lefts` and `right`,
+    /// which are not ancestors of other common ancestors.
+    ///
+    /// There are multiple merge bases after criss-cross merges, and none if the histories are unrelated.
+    pub async fn merge_bases(
         &self,
-        hashes: &mut Vec<CommitHash>,
-        commit_hash: CommitHash,
-        to: &Option<CommitHash>,
-    ) -> error::Result<()> {
-        let obj = self.commit_obj.read(&commit_hash).await?;
-        hashes.push(commit_hash.clone());
-        if !to.as_ref().is_some_and(|to| to == &commit_hash) {
-            for parent_hash in obj.parents {
-                self.read_obj(hashes, parent_hash, to).await?;
+        lefts: &[CommitHash],
+        right: &CommitHash,
+    ) -> error::Result<Vec<CommitHash>> {
+        let mut left_hashes = HashSet::new();
+        for left in lefts {
+            left_hashes.extend(self.read_all(left.clone(), &None).await?);
+        }
+        let common = self
+            .read_all(right.clone(), &None)
+            .await?
+            .into_iter()
+            .filter(|hash| left_hashes.contains(hash))
+            .collect::<Vec<CommitHash>>();
+
+        // The commits reachable from the parents of the common ancestors are not the best ones.
+        let mut redundant = HashSet::new();
+        let mut stack = Vec::new();
+        for hash in common.iter() {
+            stack.extend(self.commit_obj.read(hash).await?.parents);
+        }
+        while let Some(commit_hash) = stack.pop() {
+            if redundant.insert(commit_hash.clone()) {
+                stack.extend(self.commit_obj.read(&commit_hash).await?.parents);
             }
         }
-        Ok(())
+        Ok(common
+            .into_iter()
+            .filter(|hash| !redundant.contains(hash))
+            .collect())
     }
 }
 
@@ -59,7 +96,10 @@ where
 mod tests {
     use crate::branch::BranchName;
     use crate::file_system::memory::MemoryFileSystem;
+    use crate::io::atomic::head::HeadIo;
+    use crate::operation::checkout::Checkout;
     use crate::operation::commit::Commit;
+    use crate::operation::merge::Merge;
     use crate::operation::stage::Stage;
     use crate::tests::init_owner_branch;
 
@@ -98,4 +138,34 @@ mod tests {
             .unwrap();
         assert_eq!(hashes, vec![commit2, commit1, commit0]);
     }
+
+    #[tokio::test]
+    async fn read_merged_ancestors_only_once() {
+        let fs = MemoryFileSystem::default();
+        let owner = BranchName::owner();
+        let second = BranchName::from("second");
+        let null_commit = init_owner_branch(fs.clone()).await;
+
+        let checkout = Checkout::new(fs.clone());
+        checkout.execute(&second).await.unwrap();
+        fs.write_sync("second.txt", b"second");
+        Stage::new(fs.clone()).execute(&second, "second.txt").await.unwrap();
+        let source = Commit::new(fs.clone()).execute(&second, "commit").await.unwrap();
+
+        checkout.execute(&owner).await.unwrap();
+        fs.write_sync("owner.txt", b"owner");
+        Stage::new(fs.clone()).execute(&owner, "owner.txt").await.unwrap();
+        let dist = Commit::new(fs.clone()).execute(&owner, "commit").await.unwrap();
+        Merge::new(fs.clone())
+            .execute(source.clone(), owner.clone())
+            .await
+            .unwrap();
+        let merged = HeadIo::new(fs.clone()).try_read(&owner).await.unwrap();
+
+        let hashes = crate::io::commit_hashes::CommitHashIo::new(fs.clone())
+            .read_all(merged.clone(), &None)
+            .await
+            .unwrap();
+        assert_eq!(hashes, vec![merged, dist, null_commit, source]);
+    }
 }
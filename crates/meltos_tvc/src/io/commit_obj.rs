@@ -5,11 +5,12 @@ use crate::io::atomic::head::{CommitText, HeadIo};
 use crate::io::atomic::local_commits::LocalCommitsIo;
 use crate::io::atomic::object::ObjIo;
 use crate::io::atomic::trace::TraceIo;
-use crate::io::bundle::BundleObject;
+use crate::io::bundle::{BundleObject, BundleTrace};
 use crate::object::commit::{CommitHash, CommitObj};
 use crate::object::local_commits::LocalCommitsObj;
 use crate::object::tree::TreeObj;
 use crate::object::ObjHash;
+use crate::time::since_epoch_secs;
 use std::collections::HashSet;
 
 #[derive(Debug, Clone)]
@@ -92,6 +93,8 @@ where
             parents,
             text: commit_text.into(),
             committed_objs_tree: staging_hash,
+            author: Some(branch_name.clone()),
+            committed_at: Some(since_epoch_secs()),
         })
     }
 
@@ -106,11 +109,9 @@ where
         &self,
         branch_name: &BranchName,
     ) -> error::Result<Vec<BundleObject>> {
-        let local_commits = self.local_commits.try_read(branch_name).await?;
-        let from = local_commits.0[local_commits.0.len() - 1].clone();
-        let parents = self.read(&local_commits.0[0]).await?.parents;
-        let to = parents.first().cloned();
-        let obj_hashes = self.read_obj_hashes(from, &to).await?;
+        let obj_hashes = self
+            .read_obj_hashes_associated_with_local_commits(branch_name)
+            .await?;
         let mut obj_bufs = Vec::with_capacity(obj_hashes.len());
         for hash in obj_hashes {
             let Some(compressed_buf) = self.object.read(&hash).await? else {
@@ -124,6 +125,47 @@ where
         Ok(obj_bufs)
     }
 
+    pub async fn read_obj_hashes_associated_with_local_commits(
+        &self,
+        branch_name: &BranchName,
+    ) -> error::Result<HashSet<ObjHash>> {
+        let local_commits = self.local_commits.try_read(branch_name).await?;
+        let from = local_commits.0[local_commits.0.len() - 1].clone();
+        let parents = self.read(&local_commits.0[0]).await?.parents;
+        let to = parents.first().cloned();
+        self.read_obj_hashes(from, &to).await
+    }
+
+    /// Returns the traces of the commits whose objects are read by
+    /// [`CommitObjIo::read_obj_hashes_associated_with_local_commits`].
+    pub async fn read_traces_associated_with_local_commits(
+        &self,
+        branch_name: &BranchName,
+    ) -> error::Result<Vec<BundleTrace>> {
+        let local_commits = self.local_commits.try_read(branch_name).await?;
+        let from = local_commits.0[local_commits.0.len() - 1].clone();
+        let parents = self.read(&local_commits.0[0]).await?.parents;
+        let to = parents.first().cloned();
+
+        let mut traces = Vec::new();
+        let mut visited = HashSet::new();
+        let mut commits = vec![from];
+        while let Some(commit_hash) = commits.pop() {
+            if !visited.insert(commit_hash.clone()) {
+                continue;
+            }
+            let commit_obj = self.read(&commit_hash).await?;
+            traces.push(BundleTrace {
+                obj_hash: self.trace.read(&commit_hash).await?,
+                commit_hash,
+            });
+            if !to.as_ref().is_some_and(|p| commit_obj.parents.contains(p)) {
+                commits.extend(commit_obj.parents);
+            }
+        }
+        Ok(traces)
+    }
+
     pub async fn read_obj_hashes(
         &self,
         from: CommitHash,
@@ -166,7 +208,7 @@ where
             .await?;
         obj_hashes.insert(commit_obj.committed_objs_tree.clone());
 
-        for hash in tree.0.into_values() {
+        for hash in tree.into_values() {
             obj_hashes.insert(hash);
         }
 
@@ -1,3 +1,4 @@
 pub mod conflict;
 pub mod file;
+pub mod hunk;
 pub mod merge;
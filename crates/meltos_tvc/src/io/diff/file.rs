@@ -3,6 +3,7 @@ use similar::{DiffOp, TextDiff};
 use crate::error;
 use crate::file_system::FileSystem;
 use crate::io::atomic::object::ObjIo;
+use crate::io::diff::hunk::{DiffHunk, DiffLine};
 use crate::object::ObjHash;
 
 #[derive(Debug, Clone)]
@@ -26,7 +27,7 @@ impl FileDiff {
         rhs: &ObjHash,
     ) -> error::Result<Option<Self>>
     where
-        Fs: FileSystem,
+        Fs: FileSystem + Clone,
     {
         if lhs == rhs {
             Ok(None)
@@ -41,7 +42,7 @@ impl FileDiff {
         }
     }
 
-    pub fn diff(&self) -> TextDiff<str> {
+    pub fn diff(&self) -> TextDiff<'_, '_, '_, str> {
         TextDiff::from_lines(&self.old, &self.new)
     }
 
@@ -62,4 +63,46 @@ impl FileDiff {
             .copied()
             .collect()
     }
+
+    /// Groups the changed lines into hunks with `context_len` lines of context around them.
+    pub fn hunks(&self, context_len: usize) -> Vec<DiffHunk> {
+        let diff = self.diff();
+        diff.grouped_ops(context_len)
+            .into_iter()
+            .filter_map(|ops| {
+                let first = ops.first()?;
+                let last = ops.last()?;
+                let old_range = first.old_range().start..last.old_range().end;
+                let new_range = first.new_range().start..last.new_range().end;
+                let lines = ops
+                    .iter()
+                    .flat_map(|op| diff.iter_changes(op))
+                    .map(|change| {
+                        DiffLine {
+                            tag: change.tag().into(),
+                            old_line: change.old_index().map(|i| i + 1),
+                            new_line: change.new_index().map(|i| i + 1),
+                            text: change.value().to_string(),
+                        }
+                    })
+                    .collect();
+                Some(DiffHunk {
+                    old_start: hunk_start(&old_range),
+                    old_len: old_range.len(),
+                    new_start: hunk_start(&new_range),
+                    new_len: new_range.len(),
+                    lines,
+                })
+            })
+            .collect()
+    }
+}
+
+#[inline]
+fn hunk_start(range: &std::ops::Range<usize>) -> usize {
+    if range.is_empty() {
+        range.start
+    } else {
+        range.start + 1
+    }
 }
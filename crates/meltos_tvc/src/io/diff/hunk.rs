@@ -0,0 +1,94 @@
+use std::fmt::{Display, Formatter};
+
+use similar::ChangeTag;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum DiffLineTag {
+    Equal,
+    Insert,
+    Delete,
+}
+
+impl From<ChangeTag> for DiffLineTag {
+    #[inline]
+    fn from(tag: ChangeTag) -> Self {
+        match tag {
+            ChangeTag::Equal => Self::Equal,
+            ChangeTag::Insert => Self::Insert,
+            ChangeTag::Delete => Self::Delete,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct DiffLine {
+    pub tag: DiffLineTag,
+    /// 1-based line number in the old text; `None` if the line was inserted.
+    pub old_line: Option<usize>,
+    /// 1-based line number in the new text; `None` if the line was deleted.
+    pub new_line: Option<usize>,
+    /// The line text including its line terminator, if any.
+    pub text: String,
+}
+
+/// A group of changed lines with surrounding context lines.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct DiffHunk {
+    /// 1-based line number in the old text where the hunk starts.
+    ///
+    /// Same as unified diff, it is the line before the hunk if `old_len` is 0.
+    pub old_start: usize,
+    pub old_len: usize,
+    /// 1-based line number in the new text where the hunk starts.
+    pub new_start: usize,
+    pub new_len: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+impl Display for DiffHunk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "@@ -{},{} +{},{} @@",
+            self.old_start, self.old_len, self.new_start, self.new_len
+        )?;
+        for line in self.lines.iter() {
+            let sign = match line.tag {
+                DiffLineTag::Equal => ' ',
+                DiffLineTag::Insert => '+',
+                DiffLineTag::Delete => '-',
+            };
+            write!(f, "{sign}{}", line.text)?;
+            if !line.text.ends_with('\n') {
+                writeln!(f)?;
+                writeln!(f, "\\ No newline at end of file")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::diff::file::FileDiff;
+
+    #[test]
+    fn display_unified_hunk() {
+        let diff = FileDiff::from_strings("hello\nworld\n", "hello\nrust\n");
+        let hunks = diff.hunks(3);
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(
+            hunks[0].to_string(),
+            "@@ -1,2 +1,2 @@\n hello\n-world\n+rust\n"
+        );
+    }
+
+    #[test]
+    fn display_no_newline_at_end_of_file() {
+        let diff = FileDiff::from_strings("hello", "world");
+        assert_eq!(
+            diff.hunks(3)[0].to_string(),
+            "@@ -1,1 +1,1 @@\n-hello\n\\ No newline at end of file\n+world\n\\ No newline at end of file\n"
+        );
+    }
+}
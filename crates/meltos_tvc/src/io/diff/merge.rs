@@ -1,290 +1,297 @@
-// use similar::{DiffOp, DiffableStr};
-//
-// use crate::io::diff::conflict::Conflict;
-// use crate::io::diff::file::FileDiff;
-//
-// pub fn merge(lhs: &FileDiff, rhs: &FileDiff) -> String {
-//     let merged = merge_(lhs, rhs);
-//     merged.join("")
-// }
-//
-// pub fn merge_(lhs: &FileDiff, rhs: &FileDiff) -> Vec<String> {
-//     let lhs_diff = lhs.diff();
-//     let rhs_diff = rhs.diff();
-//     let mut lhs_op = lhs_diff.ops().into_iter();
-//     lhs_op.next();
-//     let mut texts = Vec::new();
-//
-//     let mut lhs_changes = MergeFile {
-//         texts: lhs_diff.new_slices(),
-//         ops: lhs_diff.ops().into_iter().copied(),
-//     };
-//     let mut rhs_changes = MergeFile {
-//         texts: rhs_diff.new_slices(),
-//         ops: rhs_diff.ops().into_iter().copied(),
-//     };
-//     let merges = Merges::new(lhs_changes, rhs_changes);
-//     for status in merges {
-//         match status {
-//             MergeStatus::Success(status) => {
-//                 match status {
-//                     Status::Equal {
-//                         text, ..
-//                     } => {
-//                         texts.push(text);
-//                     }
-//                     Status::Insert {
-//                         text, ..
-//                     } => {
-//                         texts.push(text);
-//                     }
-//                     Status::Delete {
-//                         ..
-//                     } => {}
-//                 }
-//             }
-//             _ => {}
-//         }
-//     }
-//
-//     texts
-// }
-//
-// struct Merges<'l, 'r, L, R> {
-//     lhs: MergeFile<'l, L>,
-//     rhs: MergeFile<'r, R>,
-//     lhs_op: Option<Status>,
-//     rhs_op: Option<Status>,
-// }
-//
-// impl<'l, 'r, L, R> Merges<'l, 'r, L, R>
-// where
-//     L: Iterator<Item = DiffOp>,
-//     R: Iterator<Item = DiffOp>,
-// {
-//     pub fn new(mut lhs: MergeFile<'l, L>, mut rhs: MergeFile<'r, R>) -> Merges<'l, 'r, L, R> {
-//         Self {
-//             rhs_op: rhs.next(),
-//             lhs_op: lhs.next(),
-//             rhs,
-//             lhs,
-//         }
-//     }
-//     fn side(&self, status: Status) -> MergeStatus {
-//         MergeStatus::Success(status)
-//     }
-// }
-//
-// impl<'l, 'r, L, R> Iterator for Merges<'l, 'r, L, R>
-// where
-//     L: Iterator<Item = DiffOp>,
-//     R: Iterator<Item = DiffOp>,
-// {
-//     type Item = MergeStatus;
-//
-//     fn next(&mut self) -> Option<Self::Item> {
-//         if self.lhs_op.is_none() && self.rhs_op.is_none() {
-//             return None;
-//         }
-//         if self.rhs_op.is_none() {
-//             let status = MergeStatus::Success(self.lhs_op.clone().unwrap());
-//             self.lhs_op = self.lhs.next();
-//             self.rhs_op = self.rhs.next();
-//             return Some(status);
-//         }
-//         if self.lhs_op.is_none() {
-//             let status = MergeStatus::Success(self.rhs_op.clone().unwrap());
-//             self.lhs_op = self.lhs.next();
-//             self.rhs_op = self.rhs.next();
-//             return Some(status);
-//         }
-//         let lhs_status = self.lhs_op.clone().unwrap();
-//         let rhs_status = self.rhs_op.clone().unwrap();
-//         if lhs_status.conflict(&rhs_status) {
-//             self.rhs_op = self.rhs.next();
-//             self.lhs_op = self.lhs.next();
-//             Some(MergeStatus::Conflicted)
-//         } else if lhs_status.ord(&rhs_status) {
-//             self.lhs_op = self.lhs.next();
-//             Some(MergeStatus::Success(lhs_status))
-//         } else {
-//             self.rhs_op = self.rhs.next();
-//             Some(MergeStatus::Success(rhs_status))
-//         }
-//     }
-// }
-//
-// #[derive(Debug, Clone)]
-// enum MergeStatus {
-//     Success(Status),
-//     Conflicted,
-// }
-//
-// #[derive(Debug, Clone)]
-// enum Status {
-//     Equal {
-//         start: usize,
-//         end: usize,
-//         text: String,
-//     },
-//     Insert {
-//         start: usize,
-//         end: usize,
-//         text: String,
-//     },
-//     Delete {
-//         start: usize,
-//         end: usize,
-//     },
-// }
-//
-// impl Status {
-//     #[inline]
-//     pub fn ord(&self, rhs: &Status) -> bool {
-//         let l = self.range();
-//         let r = rhs.range();
-//         if l.start == r.start {
-//             l.end <= r.end
-//         } else {
-//             l.start < r.start
-//         }
-//     }
-//
-//     #[inline]
-//     pub fn conflict(&self, rhs: &Status) -> bool {
-//         if self.is_equal() || rhs.is_equal() {
-//             return false;
-//         }
-//         let l = self.range();
-//         let r = rhs.range();
-//         r.start <= l.end && l.start <= r.end
-//     }
-//
-//     fn is_equal(&self) -> bool {
-//         matches!(self, Self::Equal { .. })
-//     }
-//
-//     pub fn range(&self) -> std::ops::Range<&usize> {
-//         match self {
-//             Status::Equal {
-//                 start,
-//                 end,
-//                 ..
-//             } => start..end,
-//             Status::Insert {
-//                 start,
-//                 end,
-//                 text: _,
-//             } => start..end,
-//             Status::Delete {
-//                 start,
-//                 end,
-//             } => start..end,
-//         }
-//     }
-// }
-//
-// struct MergeFile<'a, L> {
-//     texts: &'a [&'a str],
-//     ops: L,
-// }
-//
-// impl<'a, L> Iterator for MergeFile<'a, L>
-// where
-//     L: Iterator<Item = DiffOp>,
-// {
-//     type Item = Status;
-//
-//     fn next(&mut self) -> Option<Self::Item> {
-//         match self.ops.next()? {
-//             DiffOp::Delete {
-//                 old_index,
-//                 old_len,
-//                 new_index: _,
-//             } => {
-//                 Some(Status::Delete {
-//                     start: old_index,
-//                     end: old_index + old_len,
-//                 })
-//             }
-//             DiffOp::Insert {
-//                 old_index,
-//                 new_len,
-//                 new_index,
-//             } => {
-//                 Some(Status::Insert {
-//                     start: old_index,
-//                     end: old_index,
-//                     text: self.texts[new_index..new_index + new_len].join(""),
-//                 })
-//             }
-//             DiffOp::Replace {
-//                 old_index,
-//                 old_len,
-//                 new_index,
-//                 new_len,
-//             } => {
-//                 Some(Status::Insert {
-//                     start: old_index,
-//                     end: old_index + old_len,
-//                     text: self.texts[new_index..new_index + new_len].join(""),
-//                 })
-//             }
-//             DiffOp::Equal {
-//                 old_index,
-//                 len,
-//                 new_index,
-//             } => {
-//                 Some(Status::Equal {
-//                     start: old_index,
-//                     end: old_index + len,
-//                     text: self.texts[new_index..new_index + len].join(""),
-//                 })
-//             }
-//         }
-//     }
-// }
-//
-// #[cfg(test)]
-// mod tests {
-//     use similar::{DiffOp, TextDiff};
-//
-//     use crate::io::diff::file::FileDiff;
-//     use crate::io::diff::merge::merge;
-//
-//     #[test]
-//     fn simple_inserted() {
-//         const ORIGINAL: &str = "LINE0\nLINE1\nLINE2";
-//         let diff1 = FileDiff::from_strings(ORIGINAL, "LINE0\nLINE1\nLINE2\nLINE3");
-//         let diff2 = FileDiff::from_strings(ORIGINAL, "LINE0\nLINE3\nLINE1\nLINE2");
-//         let merged = merge(&diff1, &diff2);
-//         assert_eq!(merged, "LINE0\nLINE3\nLINE1\nLINE2\nLINE3\n");
-//     }
-//
-//     #[test]
-//     fn merge2() {
-//         const ORIGINAL: &str = "hello\nworld\ntext";
-//         let diff1 = FileDiff::from_strings(ORIGINAL, "hello\ntext");
-//         let diff2 = FileDiff::from_strings(ORIGINAL, "hello\nworld\nyes\ntext");
-//         let merged = merge(&diff1, &diff2);
-//         assert_eq!(merged, "hello\nyes\ntext");
-//     }
-//
-//     #[test]
-//     fn merge3() {
-//         const ORIGINAL: &str = "hello1\nworld2\nrust3\ntext4";
-//         let diff1 = FileDiff::from_strings(ORIGINAL, "hello1\ntext4");
-//         let diff2 = FileDiff::from_strings(ORIGINAL, "hello1\nworld2\nyes3\ntext4");
-//         let merged = merge(&diff1, &diff2);
-//         assert_eq!(merged, "hello1\nyes3\ntext4");
-//     }
-//
-//     #[test]
-//     fn merge4() {
-//         const ORIGINAL: &str = "hello1\nworld2\nrust3\ntext4";
-//         let diff1 = FileDiff::from_strings(ORIGINAL, "hello1\nstring5\ntext4");
-//         let diff2 = FileDiff::from_strings(ORIGINAL, "hello1\nworld2\nyes3\ntext4");
-//         let merged = merge(&diff1, &diff2);
-//         assert_eq!(merged, "hello1\nyes3\nstring5text4");
-//     }
-// }
+use std::ops::Range;
+
+use similar::{DiffOp, TextDiff};
+
+use crate::io::diff::conflict::{is_conflicted_range, ConflictHunk};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum MergeHunk {
+    Resolved(String),
+    Conflicted(ConflictHunk),
+}
+
+/// The result of the three-way merge of a text file.
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct MergedText(pub Vec<MergeHunk>);
+
+impl MergedText {
+    /// Merges `source` and `dist` line by line based on their common ancestor `base`.
+    ///
+    /// Hunks changed by only one side are taken automatically,
+    /// and hunks changed by both sides in different ways become [`MergeHunk::Conflicted`].
+    pub fn merge(base: &str, source: &str, dist: &str) -> Self {
+        let source_diff = TextDiff::from_lines(base, source);
+        let dist_diff = TextDiff::from_lines(base, dist);
+        let base_lines = source_diff.old_slices();
+        let source_changes = changes(&source_diff);
+        let dist_changes = changes(&dist_diff);
+        let mut source_changes = source_changes.iter().peekable();
+        let mut dist_changes = dist_changes.iter().peekable();
+
+        let mut merged = MergedText::default();
+        let mut base_pos = 0;
+        loop {
+            let first = match (source_changes.peek(), dist_changes.peek()) {
+                (Some(s), Some(d)) if s.range.start <= d.range.start => (source_changes.next(), None),
+                (Some(_), Some(_)) | (None, Some(_)) => (None, dist_changes.next()),
+                (Some(_), None) => (source_changes.next(), None),
+                (None, None) => break,
+            };
+            let mut source_group = Vec::from_iter(first.0);
+            let mut dist_group = Vec::from_iter(first.1);
+            let mut range = source_group
+                .first()
+                .or(dist_group.first())
+                .unwrap()
+                .range
+                .clone();
+
+            loop {
+                if let Some(change) = source_changes.next_if(|c| is_conflicted_range(&range, &c.range)) {
+                    range.end = range.end.max(change.range.end);
+                    source_group.push(change);
+                } else if let Some(change) = dist_changes.next_if(|c| is_conflicted_range(&range, &c.range)) {
+                    range.end = range.end.max(change.range.end);
+                    dist_group.push(change);
+                } else {
+                    break;
+                }
+            }
+
+            merged.push_resolved(base_lines[base_pos..range.start].concat());
+            let source_text = apply_changes(base_lines, &range, &source_group);
+            let dist_text = apply_changes(base_lines, &range, &dist_group);
+            if dist_group.is_empty() || source_text == dist_text {
+                merged.push_resolved(source_text);
+            } else if source_group.is_empty() {
+                merged.push_resolved(dist_text);
+            } else {
+                merged.0.push(MergeHunk::Conflicted(ConflictHunk {
+                    base: base_lines[range.clone()].concat(),
+                    base_range: range.clone(),
+                    source: source_text,
+                    dist: dist_text,
+                }));
+            }
+            base_pos = range.end;
+        }
+        merged.push_resolved(base_lines[base_pos..].concat());
+        merged
+    }
+
+    #[inline]
+    pub fn is_conflicted(&self) -> bool {
+        self.0
+            .iter()
+            .any(|hunk| matches!(hunk, MergeHunk::Conflicted(_)))
+    }
+
+    pub fn conflicts(&self) -> Vec<&ConflictHunk> {
+        self.0
+            .iter()
+            .filter_map(|hunk| {
+                match hunk {
+                    MergeHunk::Conflicted(conflict) => Some(conflict),
+                    MergeHunk::Resolved(_) => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the merged text if there are no conflicts.
+    pub fn merged_text(&self) -> Option<String> {
+        let mut text = String::new();
+        for hunk in self.0.iter() {
+            match hunk {
+                MergeHunk::Resolved(resolved) => text.push_str(resolved),
+                MergeHunk::Conflicted(_) => return None,
+            }
+        }
+        Some(text)
+    }
+
+    /// Returns the merged text in which conflicted hunks are surrounded by conflict markers.
+    ///
+    /// The `dist` side is written first, the same order as git.
+    pub fn text_with_markers(&self, dist_label: &str, source_label: &str) -> String {
+        let mut text = String::new();
+        for hunk in self.0.iter() {
+            match hunk {
+                MergeHunk::Resolved(resolved) => text.push_str(resolved),
+                MergeHunk::Conflicted(conflict) => {
+                    text.push_str(&format!("{CONFLICT_MARKER_BEGIN} {dist_label}\n"));
+                    push_line(&mut text, &conflict.dist);
+                    text.push_str(&format!("{CONFLICT_MARKER_SEPARATOR}\n"));
+                    push_line(&mut text, &conflict.source);
+                    text.push_str(&format!("{CONFLICT_MARKER_END} {source_label}\n"));
+                }
+            }
+        }
+        text
+    }
+
+    fn push_resolved(&mut self, text: String) {
+        if text.is_empty() {
+            return;
+        }
+        if let Some(MergeHunk::Resolved(last)) = self.0.last_mut() {
+            last.push_str(&text);
+        } else {
+            self.0.push(MergeHunk::Resolved(text));
+        }
+    }
+}
+
+pub const CONFLICT_MARKER_BEGIN: &str = "<<<<<<<";
+pub const CONFLICT_MARKER_SEPARATOR: &str = "=======";
+pub const CONFLICT_MARKER_END: &str = ">>>>>>>";
+
+/// Returns true if the text still contains conflict markers.
+pub fn has_conflict_markers(text: &str) -> bool {
+    text.lines().any(|line| {
+        line.starts_with(CONFLICT_MARKER_BEGIN)
+            || line == CONFLICT_MARKER_SEPARATOR
+            || line.starts_with(CONFLICT_MARKER_END)
+    })
+}
+
+fn push_line(text: &mut String, line: &str) {
+    text.push_str(line);
+    if !line.is_empty() && !line.ends_with('\n') {
+        text.push('\n');
+    }
+}
+
+#[derive(Debug)]
+struct Change<'a> {
+    range: Range<usize>,
+    lines: &'a [&'a str],
+}
+
+fn changes<'a>(diff: &'a TextDiff<'a, 'a, 'a, str>) -> Vec<Change<'a>> {
+    diff.ops()
+        .iter()
+        .filter(|op| !matches!(op, DiffOp::Equal { .. }))
+        .map(|op| {
+            Change {
+                range: op.old_range(),
+                lines: &diff.new_slices()[op.new_range()],
+            }
+        })
+        .collect()
+}
+
+fn apply_changes(base_lines: &[&str], range: &Range<usize>, changes: &[&Change]) -> String {
+    let mut text = String::new();
+    let mut pos = range.start;
+    for change in changes {
+        text.push_str(&base_lines[pos..change.range.start].concat());
+        text.push_str(&change.lines.concat());
+        pos = change.range.end;
+    }
+    text.push_str(&base_lines[pos..range.end].concat());
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::diff::conflict::ConflictHunk;
+    use crate::io::diff::merge::{has_conflict_markers, MergeHunk, MergedText};
+
+    #[test]
+    fn merge_non_overlapping_changes() {
+        const BASE: &str = "line1\nline2\nline3\nline4\nline5\n";
+        let merged = MergedText::merge(
+            BASE,
+            "line1\nLINE2\nline3\nline4\nline5\n",
+            "line1\nline2\nline3\nLINE4\nline5\n",
+        );
+        assert_eq!(
+            merged.merged_text().unwrap(),
+            "line1\nLINE2\nline3\nLINE4\nline5\n"
+        );
+    }
+
+    #[test]
+    fn merge_insertion_and_deletion() {
+        const BASE: &str = "hello\nworld\ntext\nrust\n";
+        let merged = MergedText::merge(BASE, "hello\ntext\nrust\n", "hello\nworld\ntext\nrust\nyes\n");
+        assert_eq!(merged.merged_text().unwrap(), "hello\ntext\nrust\nyes\n");
+    }
+
+    #[test]
+    fn take_one_side_if_only_it_changed() {
+        const BASE: &str = "hello\nworld\n";
+        let merged = MergedText::merge(BASE, BASE, "hello\nrust\n");
+        assert_eq!(merged.merged_text().unwrap(), "hello\nrust\n");
+        let merged = MergedText::merge(BASE, "hello\nrust\n", BASE);
+        assert_eq!(merged.merged_text().unwrap(), "hello\nrust\n");
+    }
+
+    #[test]
+    fn resolved_if_same_changes() {
+        const BASE: &str = "hello\nworld\n";
+        let merged = MergedText::merge(BASE, "hello\nrust\n", "hello\nrust\n");
+        assert!(!merged.is_conflicted());
+        assert_eq!(merged.merged_text().unwrap(), "hello\nrust\n");
+    }
+
+    #[test]
+    fn conflicted_if_overlapping_changes() {
+        const BASE: &str = "line1\nline2\nline3\n";
+        let merged = MergedText::merge(
+            BASE,
+            "line1\nsource\nline3\n",
+            "line1\ndist\nline3\n",
+        );
+        assert!(merged.is_conflicted());
+        assert_eq!(merged.merged_text(), None);
+        assert_eq!(
+            merged.0,
+            vec![
+                MergeHunk::Resolved("line1\n".to_string()),
+                MergeHunk::Conflicted(ConflictHunk {
+                    base_range: 1..2,
+                    base: "line2\n".to_string(),
+                    source: "source\n".to_string(),
+                    dist: "dist\n".to_string(),
+                }),
+                MergeHunk::Resolved("line3\n".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn conflicted_if_both_inserted_at_same_position() {
+        let merged = MergedText::merge("", "hello", "HELLO");
+        assert_eq!(merged.conflicts().len(), 1);
+        assert_eq!(merged.conflicts()[0].source, "hello");
+        assert_eq!(merged.conflicts()[0].dist, "HELLO");
+    }
+
+    #[test]
+    fn write_conflict_markers() {
+        const BASE: &str = "line1\nline2\nline3\n";
+        let merged = MergedText::merge(
+            BASE,
+            "line1\nsource\nline3\n",
+            "line1\ndist\nline3\n",
+        );
+        let text = merged.text_with_markers("owner", "session");
+        assert_eq!(
+            text,
+            "line1\n<<<<<<< owner\ndist\n=======\nsource\n>>>>>>> session\nline3\n"
+        );
+        assert!(has_conflict_markers(&text));
+        assert!(!has_conflict_markers(BASE));
+    }
+
+    #[test]
+    fn insert_newline_before_marker_if_not_ends_with_newline() {
+        let merged = MergedText::merge("", "hello", "HELLO");
+        assert_eq!(
+            merged.text_with_markers("owner", "session"),
+            "<<<<<<< owner\nHELLO\n=======\nhello\n>>>>>>> session\n"
+        );
+    }
+}
@@ -0,0 +1,283 @@
+//! Reads the refs and objects of a local git repository without the git binary.
+//!
+//! Both the loose objects under `objects/{xx}/{rest of id}` and the packs under `objects/pack` are supported.
+
+use meltos_util::compression::zlib::Zlib;
+use meltos_util::compression::CompressionBuf;
+
+use crate::error;
+use crate::file_system::{FileMode, FilePath, FileSystem};
+use crate::io::git::object::{GitCommit, GitObj, GitObjKind, GitTreeEntry};
+use crate::io::git::pack::GitPack;
+
+pub mod object;
+pub mod pack;
+
+#[derive(Debug)]
+pub struct GitIo<Fs>
+where
+    Fs: FileSystem,
+{
+    fs: Fs,
+    git_dir: String,
+    packs: Vec<GitPack>,
+}
+
+impl<Fs> GitIo<Fs>
+where
+    Fs: FileSystem,
+{
+    /// Opens the repository at `git_dir`, which is the path to the `.git` directory, and loads its packs.
+    pub async fn open(fs: Fs, git_dir: &str) -> error::Result<GitIo<Fs>> {
+        let git_dir = git_dir.trim_end_matches('/').to_string();
+        let pack_dir = format!("{git_dir}/objects/pack");
+        let mut packs = Vec::new();
+        let mut files = fs.all_files_in(&pack_dir).await?;
+        files.sort();
+        for file in files {
+            let Some(name) = file.strip_suffix(".idx") else {
+                continue;
+            };
+            let idx = fs.try_read_file(&file).await?;
+            let data = fs.try_read_file(&format!("{name}.pack")).await?;
+            packs.push(GitPack::decode(&idx, data)?);
+        }
+        Ok(Self { fs, git_dir, packs })
+    }
+
+    /// Reads the commit id that the branch points to.
+    ///
+    /// `HEAD` is resolved to the checked out branch.
+    pub async fn read_branch(&self, branch: &str) -> error::Result<String> {
+        let ref_name = if branch == "HEAD" {
+            let head = self
+                .fs
+                .read_file(&format!("{}/HEAD", self.git_dir))
+                .await?
+                .ok_or_else(|| error::Error::NotfoundGitRef("HEAD".to_string()))?;
+            let head = String::from_utf8(head)?;
+            match head.trim().strip_prefix("ref: ") {
+                Some(ref_name) => ref_name.to_string(),
+                None => return Ok(head.trim().to_string()),
+            }
+        } else {
+            format!("refs/heads/{branch}")
+        };
+        if let Some(id) = self
+            .fs
+            .read_file(&format!("{}/{ref_name}", self.git_dir))
+            .await?
+        {
+            return Ok(String::from_utf8(id)?.trim().to_string());
+        }
+
+        let packed_refs = self
+            .fs
+            .read_file(&format!("{}/packed-refs", self.git_dir))
+            .await?
+            .unwrap_or_default();
+        String::from_utf8(packed_refs)?
+            .lines()
+            .filter(|line| !line.starts_with('#') && !line.starts_with('^'))
+            .find_map(|line| {
+                let (id, name) = line.split_once(' ')?;
+                (name == ref_name).then(|| id.to_string())
+            })
+            .ok_or(error::Error::NotfoundGitRef(ref_name))
+    }
+
+    pub async fn read(&self, id: &str) -> error::Result<GitObj> {
+        if let Some(loose) = self.read_loose(id).await? {
+            return Ok(loose);
+        }
+        for pack in self.packs.iter() {
+            if let Some(obj) = pack.read(id)? {
+                return Ok(obj);
+            }
+        }
+        Err(error::Error::NotfoundGitObj(id.to_string()))
+    }
+
+    #[inline]
+    pub async fn read_commit(&self, id: &str) -> error::Result<GitCommit> {
+        GitCommit::decode(&self.read(id).await?.expect(GitObjKind::Commit)?)
+    }
+
+    #[inline]
+    pub async fn read_blob(&self, id: &str) -> error::Result<Vec<u8>> {
+        self.read(id).await?.expect(GitObjKind::Blob)
+    }
+
+    /// Reads all files in the tree recursively, and returns the path, the blob id and the mode of each file.
+    ///
+    /// Submodules are skipped because their contents are not in this repository.
+    pub async fn read_tree_files(
+        &self,
+        tree_id: &str,
+    ) -> error::Result<Vec<(FilePath, String, FileMode)>> {
+        let mut files = Vec::new();
+        let mut trees = vec![(String::new(), tree_id.to_string())];
+        while let Some((dir, tree_id)) = trees.pop() {
+            let buf = self.read(&tree_id).await?.expect(GitObjKind::Tree)?;
+            for entry in GitTreeEntry::decode_all(&buf)? {
+                let path = if dir.is_empty() {
+                    entry.name.clone()
+                } else {
+                    format!("{dir}/{}", entry.name)
+                };
+                if entry.is_tree() {
+                    trees.push((path, entry.id));
+                } else if !entry.is_submodule() {
+                    let mode = entry.file_mode();
+                    files.push((FilePath(path), entry.id, mode));
+                }
+            }
+        }
+        Ok(files)
+    }
+
+    async fn read_loose(&self, id: &str) -> error::Result<Option<GitObj>> {
+        if id.len() < 3 {
+            return Ok(None);
+        }
+        let path = format!("{}/objects/{}/{}", self.git_dir, &id[..2], &id[2..]);
+        match self.fs.read_file(&path).await? {
+            Some(buf) => Ok(Some(GitObj::decode_loose(&Zlib.unzip(&buf)?)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use meltos_util::compression::zlib::Zlib;
+    use meltos_util::compression::CompressionBuf;
+
+    use crate::file_system::memory::MemoryFileSystem;
+    use crate::file_system::{FileMode, FilePath, FileSystem};
+    use crate::io::git::object::GitObjKind;
+    use crate::io::git::pack::tests::{build_pack, copy_and_insert, git_id, TestEntry};
+    use crate::io::git::GitIo;
+
+    pub(crate) const GIT_DIR: &str = "repo/.git";
+
+    pub(crate) async fn write_loose(fs: &MemoryFileSystem, kind: GitObjKind, buf: &[u8]) -> String {
+        let id = git_id(kind, buf);
+        let mut obj = format!("{} {}\0", kind.name(), buf.len()).into_bytes();
+        obj.extend(buf);
+        fs.write_file(
+            &format!("{GIT_DIR}/objects/{}/{}", &id[..2], &id[2..]),
+            &Zlib.zip(&obj).unwrap(),
+        )
+        .await
+        .unwrap();
+        id
+    }
+
+    /// Encodes the tree from pairs of the mode, the name and the object id.
+    pub(crate) fn tree_buf(entries: &[(&str, &str, &str)]) -> Vec<u8> {
+        let mut buf = Vec::new();
+        for (mode, name, id) in entries {
+            buf.extend(format!("{mode} {name}\0").as_bytes());
+            buf.extend(
+                (0..40)
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(&id[i..i + 2], 16).unwrap()),
+            );
+        }
+        buf
+    }
+
+    pub(crate) fn commit_buf(tree: &str, parents: &[&str], time: u64, message: &str) -> Vec<u8> {
+        let mut buf = format!("tree {tree}\n");
+        for parent in parents {
+            buf.push_str(&format!("parent {parent}\n"));
+        }
+        buf.push_str(&format!("author a <a@example.com> {time} +0900\n"));
+        buf.push_str(&format!(
+            "committer a <a@example.com> {time} +0900\n\n{message}\n"
+        ));
+        buf.into_bytes()
+    }
+
+    #[tokio::test]
+    async fn read_loose_tree_files() {
+        let fs = MemoryFileSystem::default();
+        let hello = write_loose(&fs, GitObjKind::Blob, b"hello").await;
+        let main = write_loose(&fs, GitObjKind::Blob, b"fn main(){}").await;
+        let src = write_loose(
+            &fs,
+            GitObjKind::Tree,
+            &tree_buf(&[("100644", "main.rs", &main)]),
+        )
+        .await;
+        let root = write_loose(
+            &fs,
+            GitObjKind::Tree,
+            &tree_buf(&[
+                ("100755", "hello.txt", &hello),
+                ("40000", "src", &src),
+                ("160000", "sub", &"1".repeat(40)),
+            ]),
+        )
+        .await;
+
+        let git = GitIo::open(fs, GIT_DIR).await.unwrap();
+        let mut files = git.read_tree_files(&root).await.unwrap();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                (FilePath::from("hello.txt"), hello.clone(), FileMode::Executable),
+                (FilePath::from("src/main.rs"), main, FileMode::Regular),
+            ]
+        );
+        assert_eq!(git.read_blob(&hello).await.unwrap(), b"hello");
+        assert!(git.read_commit(&hello).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn read_packed_objects_and_refs() {
+        let fs = MemoryFileSystem::default();
+        let tree_id = git_id(GitObjKind::Tree, b"");
+        let commit = commit_buf(&tree_id, &[], 1700000000, "init");
+        let commit_id = git_id(GitObjKind::Commit, &commit);
+        let blob_id = git_id(GitObjKind::Blob, b"hello world");
+        let delta_id = git_id(GitObjKind::Blob, b"hello git");
+        let (idx, pack) = build_pack(&[
+            (
+                commit_id.clone(),
+                TestEntry::Base(GitObjKind::Commit, &commit),
+            ),
+            (tree_id, TestEntry::Base(GitObjKind::Tree, b"")),
+            (blob_id, TestEntry::Base(GitObjKind::Blob, b"hello world")),
+            (
+                delta_id.clone(),
+                TestEntry::OfsDelta(2, copy_and_insert(b"hello world", 6, b"git")),
+            ),
+        ]);
+        fs.write_file(&format!("{GIT_DIR}/objects/pack/pack-1.idx"), &idx)
+            .await
+            .unwrap();
+        fs.write_file(&format!("{GIT_DIR}/objects/pack/pack-1.pack"), &pack)
+            .await
+            .unwrap();
+        fs.write_file(
+            &format!("{GIT_DIR}/packed-refs"),
+            format!("# pack-refs with: peeled fully-peeled sorted\n{commit_id} refs/heads/main\n")
+                .as_bytes(),
+        )
+        .await
+        .unwrap();
+        fs.write_file(&format!("{GIT_DIR}/HEAD"), b"ref: refs/heads/main\n")
+            .await
+            .unwrap();
+
+        let git = GitIo::open(fs, GIT_DIR).await.unwrap();
+        assert_eq!(git.read_branch("main").await.unwrap(), commit_id);
+        assert_eq!(git.read_branch("HEAD").await.unwrap(), commit_id);
+        assert!(git.read_branch("develop").await.is_err());
+        assert_eq!(git.read_commit(&commit_id).await.unwrap().message, "init");
+        assert_eq!(git.read_blob(&delta_id).await.unwrap(), b"hello git");
+    }
+}
@@ -0,0 +1,221 @@
+use crate::error;
+use crate::file_system::FileMode;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum GitObjKind {
+    Commit,
+    Tree,
+    Blob,
+    Tag,
+}
+
+impl GitObjKind {
+    pub fn from_name(name: &str) -> error::Result<Self> {
+        match name {
+            "commit" => Ok(Self::Commit),
+            "tree" => Ok(Self::Tree),
+            "blob" => Ok(Self::Blob),
+            "tag" => Ok(Self::Tag),
+            _ => Err(invalid(&format!("unknown object type {name}"))),
+        }
+    }
+
+    #[inline]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Commit => "commit",
+            Self::Tree => "tree",
+            Self::Blob => "blob",
+            Self::Tag => "tag",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GitObj {
+    pub kind: GitObjKind,
+    pub buf: Vec<u8>,
+}
+
+impl GitObj {
+    /// Decodes the inflated loose object, which is formatted as `{type} {size}\0{content}`.
+    pub fn decode_loose(buf: &[u8]) -> error::Result<Self> {
+        let nul = buf
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or_else(|| invalid("loose object header is missing"))?;
+        let header = std::str::from_utf8(&buf[..nul])?;
+        let (kind, size) = header
+            .split_once(' ')
+            .ok_or_else(|| invalid("loose object header is invalid"))?;
+        let content = &buf[nul + 1..];
+        if size.parse::<usize>()? != content.len() {
+            return Err(invalid("loose object size does not match"));
+        }
+        Ok(Self {
+            kind: GitObjKind::from_name(kind)?,
+            buf: content.to_vec(),
+        })
+    }
+
+    pub fn expect(self, kind: GitObjKind) -> error::Result<Vec<u8>> {
+        if self.kind == kind {
+            Ok(self.buf)
+        } else {
+            Err(error::Error::InvalidObjType(
+                kind.name().to_string(),
+                self.kind.name().to_string(),
+            ))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GitCommit {
+    pub tree: String,
+    pub parents: Vec<String>,
+    /// Seconds elapsed since the UNIX epoch when committed.
+    pub committed_at: Option<u64>,
+    pub message: String,
+}
+
+impl GitCommit {
+    pub fn decode(buf: &[u8]) -> error::Result<Self> {
+        let text = String::from_utf8_lossy(buf);
+        let (headers, message) = text.split_once("\n\n").unwrap_or((&text, ""));
+        let mut tree = None;
+        let mut parents = Vec::new();
+        let mut committed_at = None;
+        for line in headers.lines() {
+            // Continuation lines of multi-line headers such as `gpgsig` start with a space.
+            let Some((key, value)) = line.split_once(' ') else {
+                continue;
+            };
+            match key {
+                "tree" => tree = Some(value.to_string()),
+                "parent" => parents.push(value.to_string()),
+                "committer" => {
+                    committed_at = value.rsplit(' ').nth(1).and_then(|time| time.parse().ok());
+                }
+                _ => {}
+            }
+        }
+        Ok(Self {
+            tree: tree.ok_or_else(|| invalid("commit has no tree"))?,
+            parents,
+            committed_at,
+            message: message.trim_end_matches('\n').to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GitTreeEntry {
+    pub mode: String,
+    pub name: String,
+    pub id: String,
+}
+
+impl GitTreeEntry {
+    /// Decodes the entries of the tree, each formatted as `{mode} {name}\0{20 bytes id}`.
+    pub fn decode_all(buf: &[u8]) -> error::Result<Vec<Self>> {
+        let mut entries = Vec::new();
+        let mut rest = buf;
+        while !rest.is_empty() {
+            let nul = rest
+                .iter()
+                .position(|b| *b == 0)
+                .ok_or_else(|| invalid("tree entry is truncated"))?;
+            let (mode, name) = std::str::from_utf8(&rest[..nul])?
+                .split_once(' ')
+                .ok_or_else(|| invalid("tree entry is invalid"))?;
+            let id = rest
+                .get(nul + 1..nul + 21)
+                .ok_or_else(|| invalid("tree entry is truncated"))?;
+            entries.push(Self {
+                mode: mode.to_string(),
+                name: name.to_string(),
+                id: to_hex(id),
+            });
+            rest = &rest[nul + 21..];
+        }
+        Ok(entries)
+    }
+
+    #[inline]
+    pub fn is_tree(&self) -> bool {
+        self.mode == "40000"
+    }
+
+    /// Submodules refer to commits in other repositories, so they have no contents here.
+    #[inline]
+    pub fn is_submodule(&self) -> bool {
+        self.mode == "160000"
+    }
+
+    /// Returns the mode of the file; unknown modes are regarded as [`FileMode::Regular`].
+    #[inline]
+    pub fn file_mode(&self) -> FileMode {
+        FileMode::from_octal(&self.mode).unwrap_or_default()
+    }
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[inline]
+pub(crate) fn invalid(reason: &str) -> error::Error {
+    error::Error::InvalidGitObj(reason.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::git::object::{GitCommit, GitObj, GitObjKind, GitTreeEntry};
+
+    #[test]
+    fn decode_commit() {
+        let buf = b"tree 4b825dc642cb6eb9a060e54bf8d69288fbee4904\n\
+parent 1111111111111111111111111111111111111111\n\
+parent 2222222222222222222222222222222222222222\n\
+author a <a@example.com> 1700000000 +0900\n\
+committer c <c@example.com> 1700000100 +0900\n\
+gpgsig -----BEGIN PGP SIGNATURE-----\n \n -----END PGP SIGNATURE-----\n\
+\n\
+merge\n\nbody\n";
+        let commit = GitCommit::decode(buf).unwrap();
+        assert_eq!(commit.tree, "4b825dc642cb6eb9a060e54bf8d69288fbee4904");
+        assert_eq!(
+            commit.parents,
+            vec![
+                "1111111111111111111111111111111111111111".to_string(),
+                "2222222222222222222222222222222222222222".to_string(),
+            ]
+        );
+        assert_eq!(commit.committed_at, Some(1700000100));
+        assert_eq!(commit.message, "merge\n\nbody");
+    }
+
+    #[test]
+    fn decode_tree_entries() {
+        let mut buf = b"100644 hello.txt\0".to_vec();
+        buf.extend([0xab; 20]);
+        buf.extend(b"40000 src\0");
+        buf.extend([0x01; 20]);
+        let entries = GitTreeEntry::decode_all(&buf).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name, "hello.txt");
+        assert_eq!(entries[0].id, "ab".repeat(20));
+        assert!(entries[1].is_tree());
+        assert!(GitTreeEntry::decode_all(&buf[..buf.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn failed_if_loose_size_differs() {
+        let obj = GitObj::decode_loose(b"blob 5\0hello").unwrap();
+        assert_eq!(obj.kind, GitObjKind::Blob);
+        assert_eq!(obj.buf, b"hello");
+        assert!(GitObj::decode_loose(b"blob 6\0hello").is_err());
+        assert!(GitObj::decode_loose(b"unknown 5\0hello").is_err());
+    }
+}
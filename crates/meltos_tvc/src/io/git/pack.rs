@@ -0,0 +1,414 @@
+//! Git packfiles and their version 2 indexes.
+//!
+//! An entry is either the zlib-compressed object, or the delta against another entry in the same pack
+//! referred by the relative offset (`OFS_DELTA`) or by the object id (`REF_DELTA`).
+
+use meltos_util::compression::zlib::Zlib;
+
+use crate::error;
+use crate::io::atomic::pack::delta::{invalid, Reader};
+use crate::io::git::object::{to_hex, GitObj, GitObjKind};
+
+const PACK_MAGIC: &[u8] = b"PACK";
+const IDX_MAGIC: &[u8] = b"\xfftOc";
+
+const OFS_DELTA: u8 = 6;
+const REF_DELTA: u8 = 7;
+
+#[derive(Debug)]
+pub struct GitPack {
+    /// Pairs of the object id and the offset of the entry, sorted by the ids.
+    index: Vec<(String, usize)>,
+    data: Vec<u8>,
+}
+
+impl GitPack {
+    pub fn decode(idx: &[u8], data: Vec<u8>) -> error::Result<Self> {
+        if !data.starts_with(PACK_MAGIC) {
+            return Err(invalid("git pack header is invalid"));
+        }
+        Ok(Self {
+            index: decode_index(idx, data.len())?,
+            data,
+        })
+    }
+
+    #[inline]
+    pub fn contains(&self, id: &str) -> bool {
+        self.offset(id).is_some()
+    }
+
+    pub fn read(&self, id: &str) -> error::Result<Option<GitObj>> {
+        match self.offset(id) {
+            Some(offset) => Ok(Some(self.read_at(offset)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads the object by applying the chain of the deltas.
+    fn read_at(&self, mut offset: usize) -> error::Result<GitObj> {
+        let mut deltas = Vec::new();
+        let mut obj = loop {
+            if self.index.len() < deltas.len() {
+                return Err(invalid("delta chain is cyclic"));
+            }
+            match self.entry_at(offset)? {
+                Entry::Base(obj) => break obj,
+                Entry::OfsDelta { base_offset, delta } => {
+                    deltas.push(delta);
+                    offset = base_offset;
+                }
+                Entry::RefDelta { base, delta } => {
+                    deltas.push(delta);
+                    // Thin packs are only used for the transfer, so the base is always in the same pack on disk.
+                    offset = self
+                        .offset(&base)
+                        .ok_or(error::Error::NotfoundGitObj(base))?;
+                }
+            }
+        };
+        for delta in deltas.iter().rev() {
+            obj.buf = apply_delta(&obj.buf, delta)?;
+        }
+        Ok(obj)
+    }
+
+    fn entry_at(&self, offset: usize) -> error::Result<Entry> {
+        let mut reader = Reader {
+            buf: &self.data,
+            pos: offset,
+        };
+        let mut b = next_byte(&mut reader)?;
+        let ty = (b >> 4) & 0x07;
+        let mut size = (b & 0x0f) as usize;
+        let mut shift = 4;
+        while b & 0x80 != 0 {
+            b = next_byte(&mut reader)?;
+            if usize::BITS <= shift {
+                return Err(invalid("entry size overflows"));
+            }
+            size |= ((b & 0x7f) as usize) << shift;
+            shift += 7;
+        }
+
+        let entry = match ty {
+            OFS_DELTA => {
+                let mut b = next_byte(&mut reader)?;
+                let mut distance = (b & 0x7f) as usize;
+                while b & 0x80 != 0 {
+                    b = next_byte(&mut reader)?;
+                    distance = distance
+                        .checked_add(1)
+                        .and_then(|d| d.checked_mul(128))
+                        .ok_or_else(|| invalid("delta base offset overflows"))?
+                        | (b & 0x7f) as usize;
+                }
+                let base_offset = offset
+                    .checked_sub(distance)
+                    .filter(|_| 0 < distance)
+                    .ok_or_else(|| invalid("delta base offset is invalid"))?;
+                Entry::OfsDelta {
+                    base_offset,
+                    delta: inflate(&mut reader, size)?,
+                }
+            }
+            REF_DELTA => {
+                let base = to_hex(reader.take(20)?);
+                Entry::RefDelta {
+                    base,
+                    delta: inflate(&mut reader, size)?,
+                }
+            }
+            1..=4 => {
+                let kind = match ty {
+                    1 => GitObjKind::Commit,
+                    2 => GitObjKind::Tree,
+                    3 => GitObjKind::Blob,
+                    _ => GitObjKind::Tag,
+                };
+                Entry::Base(GitObj {
+                    kind,
+                    buf: inflate(&mut reader, size)?,
+                })
+            }
+            _ => return Err(invalid("unknown pack entry type")),
+        };
+        Ok(entry)
+    }
+
+    #[inline]
+    fn offset(&self, id: &str) -> Option<usize> {
+        let i = self
+            .index
+            .binary_search_by(|(entry_id, _)| entry_id.as_str().cmp(id))
+            .ok()?;
+        Some(self.index[i].1)
+    }
+}
+
+enum Entry {
+    Base(GitObj),
+    OfsDelta { base_offset: usize, delta: Vec<u8> },
+    RefDelta { base: String, delta: Vec<u8> },
+}
+
+/// Applies the git delta, which consists of the sizes of the base and the result
+/// followed by the instructions to copy a range of the base or to insert the bytes.
+pub fn apply_delta(base: &[u8], delta: &[u8]) -> error::Result<Vec<u8>> {
+    let mut reader = Reader { buf: delta, pos: 0 };
+    if reader.varint()? != base.len() {
+        return Err(invalid("delta base size does not match"));
+    }
+    let size = reader.varint()?;
+    let mut buf = Vec::with_capacity(size);
+    while let Some(cmd) = reader.byte() {
+        if cmd & 0x80 != 0 {
+            let mut offset = 0usize;
+            for i in 0..4 {
+                if cmd & (1 << i) != 0 {
+                    offset |= (next_byte(&mut reader)? as usize) << (8 * i);
+                }
+            }
+            let mut len = 0usize;
+            for i in 0..3 {
+                if cmd & (0x10 << i) != 0 {
+                    len |= (next_byte(&mut reader)? as usize) << (8 * i);
+                }
+            }
+            if len == 0 {
+                len = 0x10000;
+            }
+            let copied = offset
+                .checked_add(len)
+                .and_then(|end| base.get(offset..end))
+                .ok_or_else(|| invalid("delta copies outside of the base"))?;
+            buf.extend_from_slice(copied);
+        } else if cmd != 0 {
+            buf.extend_from_slice(reader.take(cmd as usize)?);
+        } else {
+            return Err(invalid("delta instruction is reserved"));
+        }
+    }
+    if buf.len() != size {
+        return Err(invalid("delta result size does not match"));
+    }
+    Ok(buf)
+}
+
+fn decode_index(buf: &[u8], pack_len: usize) -> error::Result<Vec<(String, usize)>> {
+    let mut reader = Reader { buf, pos: 0 };
+    if reader.take(4)? != IDX_MAGIC || read_u32(&mut reader)? != 2 {
+        return Err(invalid("only the version 2 of git pack index is supported"));
+    }
+    reader.pos += 255 * 4;
+    let count = read_u32(&mut reader)? as usize;
+    let ids = reader.take(count.saturating_mul(20))?;
+    // Skips the CRC32 of the entries.
+    reader.take(count.saturating_mul(4))?;
+    let offsets = reader.take(count.saturating_mul(4))?;
+    let large_offsets = &buf[reader.pos..];
+
+    let mut index = Vec::with_capacity(count);
+    for (id, offset) in ids.chunks_exact(20).zip(offsets.chunks_exact(4)) {
+        let offset = u32::from_be_bytes(offset.try_into().unwrap());
+        let offset = if offset & 0x8000_0000 == 0 {
+            offset as usize
+        } else {
+            let i = (offset & 0x7fff_ffff) as usize * 8;
+            let large = large_offsets
+                .get(i..i + 8)
+                .ok_or_else(|| invalid("large offset is missing"))?;
+            usize::try_from(u64::from_be_bytes(large.try_into().unwrap()))
+                .map_err(|_| invalid("offset overflows"))?
+        };
+        if pack_len <= offset {
+            return Err(invalid("index points outside of the pack"));
+        }
+        index.push((to_hex(id), offset));
+    }
+    if index.windows(2).any(|w| w[1].0 <= w[0].0) {
+        return Err(invalid("git pack index is not sorted"));
+    }
+    Ok(index)
+}
+
+fn inflate(reader: &mut Reader, size: usize) -> error::Result<Vec<u8>> {
+    let buf = Zlib.unzip_head(&reader.buf[reader.pos..])?;
+    if buf.len() != size {
+        return Err(invalid("inflated entry size does not match"));
+    }
+    Ok(buf)
+}
+
+#[inline]
+fn next_byte(reader: &mut Reader) -> error::Result<u8> {
+    reader
+        .byte()
+        .ok_or_else(|| invalid("unexpected end of buffer"))
+}
+
+#[inline]
+fn read_u32(reader: &mut Reader) -> error::Result<u32> {
+    Ok(u32::from_be_bytes(reader.take(4)?.try_into().unwrap()))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use meltos_util::compression::zlib::Zlib;
+    use meltos_util::compression::CompressionBuf;
+
+    use crate::io::git::object::GitObjKind;
+    use crate::io::git::pack::{apply_delta, GitPack};
+
+    /// An entry of the pack built by [`build_pack`].
+    pub(crate) enum TestEntry<'a> {
+        Base(GitObjKind, &'a [u8]),
+        /// The delta against the entry at the index.
+        OfsDelta(usize, Vec<u8>),
+        /// The delta against the object of the id.
+        RefDelta(String, Vec<u8>),
+    }
+
+    /// Builds the pack and its index from the entries and their object ids.
+    pub(crate) fn build_pack(entries: &[(String, TestEntry)]) -> (Vec<u8>, Vec<u8>) {
+        let mut pack = b"PACK".to_vec();
+        pack.extend(2u32.to_be_bytes());
+        pack.extend((entries.len() as u32).to_be_bytes());
+        let mut offsets = Vec::new();
+        for (_, entry) in entries {
+            let offset = pack.len();
+            let (ty, buf) = match entry {
+                TestEntry::Base(kind, buf) => {
+                    let ty = match kind {
+                        GitObjKind::Commit => 1,
+                        GitObjKind::Tree => 2,
+                        GitObjKind::Blob => 3,
+                        GitObjKind::Tag => 4,
+                    };
+                    (ty, buf.to_vec())
+                }
+                TestEntry::OfsDelta(_, delta) => (6, delta.clone()),
+                TestEntry::RefDelta(_, delta) => (7, delta.clone()),
+            };
+            let mut size = buf.len();
+            let mut b = (ty << 4) | (size & 0x0f) as u8;
+            size >>= 4;
+            while size != 0 {
+                pack.push(b | 0x80);
+                b = (size & 0x7f) as u8;
+                size >>= 7;
+            }
+            pack.push(b);
+            match entry {
+                TestEntry::OfsDelta(base, _) => {
+                    let mut distance = offset - offsets[*base];
+                    let mut bytes = vec![(distance & 0x7f) as u8];
+                    distance >>= 7;
+                    while distance != 0 {
+                        distance -= 1;
+                        bytes.push(0x80 | (distance & 0x7f) as u8);
+                        distance >>= 7;
+                    }
+                    bytes.reverse();
+                    pack.extend(bytes);
+                }
+                TestEntry::RefDelta(base, _) => pack.extend(from_hex(base)),
+                TestEntry::Base(..) => {}
+            }
+            pack.extend(Zlib.zip(&buf).unwrap());
+            offsets.push(offset);
+        }
+
+        let mut sorted = entries
+            .iter()
+            .map(|(id, _)| id.clone())
+            .zip(offsets)
+            .collect::<Vec<_>>();
+        sorted.sort();
+        let mut idx = b"\xfftOc".to_vec();
+        idx.extend(2u32.to_be_bytes());
+        for i in 0..=255u8 {
+            let count = sorted.iter().filter(|(id, _)| from_hex(id)[0] <= i).count();
+            idx.extend((count as u32).to_be_bytes());
+        }
+        for (id, _) in sorted.iter() {
+            idx.extend(from_hex(id));
+        }
+        idx.extend(vec![0; sorted.len() * 4]);
+        for (_, offset) in sorted.iter() {
+            idx.extend((*offset as u32).to_be_bytes());
+        }
+        (idx, pack)
+    }
+
+    /// The delta that copies `base[..copy]` and appends `insert`.
+    pub(crate) fn copy_and_insert(base: &[u8], copy: u8, insert: &[u8]) -> Vec<u8> {
+        let mut delta = vec![base.len() as u8, copy + insert.len() as u8];
+        delta.extend([0x90, copy, insert.len() as u8]);
+        delta.extend(insert);
+        delta
+    }
+
+    pub(crate) fn git_id(kind: GitObjKind, buf: &[u8]) -> String {
+        let mut obj = format!("{} {}\0", kind.name(), buf.len()).into_bytes();
+        obj.extend(buf);
+        meltos_util::hash::hash(&obj)
+    }
+
+    fn from_hex(id: &str) -> Vec<u8> {
+        (0..id.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&id[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn read_base_and_deltas() {
+        let base_id = git_id(GitObjKind::Blob, b"hello world");
+        let ofs_id = git_id(GitObjKind::Blob, b"hello rust");
+        let ref_id = git_id(GitObjKind::Blob, b"hello rust!");
+        let (idx, pack) = build_pack(&[
+            (
+                base_id.clone(),
+                TestEntry::Base(GitObjKind::Blob, b"hello world"),
+            ),
+            (
+                ofs_id.clone(),
+                TestEntry::OfsDelta(0, copy_and_insert(b"hello world", 6, b"rust")),
+            ),
+            (
+                ref_id.clone(),
+                TestEntry::RefDelta(ofs_id.clone(), copy_and_insert(b"hello rust", 10, b"!")),
+            ),
+        ]);
+        let pack = GitPack::decode(&idx, pack).unwrap();
+
+        let obj = pack.read(&base_id).unwrap().unwrap();
+        assert_eq!(obj.kind, GitObjKind::Blob);
+        assert_eq!(obj.buf, b"hello world");
+        assert_eq!(pack.read(&ofs_id).unwrap().unwrap().buf, b"hello rust");
+        assert_eq!(pack.read(&ref_id).unwrap().unwrap().buf, b"hello rust!");
+        assert!(pack.read(&"0".repeat(40)).unwrap().is_none());
+    }
+
+    #[test]
+    fn failed_if_delta_is_broken() {
+        assert_eq!(
+            apply_delta(b"hello", &copy_and_insert(b"hello", 5, b"!")).unwrap(),
+            b"hello!"
+        );
+        assert!(apply_delta(b"hell", &copy_and_insert(b"hello", 5, b"!")).is_err());
+        let mut delta = copy_and_insert(b"hello", 5, b"!");
+        delta[3] = 6;
+        assert!(apply_delta(b"hello", &delta).is_err());
+    }
+
+    #[test]
+    fn failed_if_index_is_invalid() {
+        let id = git_id(GitObjKind::Blob, b"hello");
+        let (mut idx, pack) = build_pack(&[(id, TestEntry::Base(GitObjKind::Blob, b"hello"))]);
+        assert!(GitPack::decode(&idx, b"KCAP".to_vec()).is_err());
+        idx[7] = 1;
+        assert!(GitPack::decode(&idx, pack).is_err());
+    }
+}
@@ -1,7 +1,13 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
 use crate::branch::BranchName;
 use crate::error;
-use crate::file_system::{FilePath, FileSystem};
+use crate::file_system::{parent_dirs, FileMode, FilePath, FileSystem};
+use crate::ignore::{ignore_file_dir, IgnoreMatcher, GIT_IGNORE, MELTOS_IGNORE};
+use crate::io::atomic::config::ConfigIo;
 use crate::io::atomic::head::HeadIo;
+use crate::io::atomic::sparse_checkout::SparseCheckoutIo;
 use crate::io::trace_tree::TraceTreeIo;
 use crate::object::file::FileObj;
 use crate::object::tree::TreeObj;
@@ -26,6 +32,18 @@ where
     fs: Fs,
     head: HeadIo<Fs>,
     trace: TraceTreeIo<Fs>,
+    config: ConfigIo<Fs>,
+    sparse_checkout: SparseCheckoutIo<Fs>,
+    /// The compiled ignore files; reloaded when a walk of the workspace finds any of them changed.
+    ignore: Arc<Mutex<Option<Arc<IgnoreCache>>>>,
+}
+
+#[derive(Debug)]
+struct IgnoreCache {
+    honor_gitignore: bool,
+    /// The contents of the ignore files compiled into `matcher`, keyed by their paths.
+    files: HashMap<String, Vec<u8>>,
+    matcher: Arc<IgnoreMatcher>,
 }
 
 impl<Fs> WorkspaceIo<Fs>
@@ -37,6 +55,9 @@ where
         Self {
             head: HeadIo::new(fs.clone()),
             trace: TraceTreeIo::new(fs.clone()),
+            config: ConfigIo::new(fs.clone()),
+            sparse_checkout: SparseCheckoutIo::new(fs.clone()),
+            ignore: Arc::new(Mutex::new(None)),
             fs,
         }
     }
@@ -65,10 +86,19 @@ where
         Ok(Some(FileObj(buf)))
     }
 
-    pub async fn unpack(&self, file_path: &str, obj: &Obj) -> error::Result<()> {
+    /// Writes the file object with the mode into the workspace, or deletes the file if the object is a deleted object.
+    ///
+    /// Fails if any parent directory of the file is a symlink, so that nothing is written outside the workspace through it.
+    /// The symlinks whose targets are absolute or contain `..` are also refused
+    /// unless [`RepositoryConfig::allow_unsafe_symlinks`](crate::io::atomic::config::RepositoryConfig::allow_unsafe_symlinks) is enabled.
+    pub async fn unpack(&self, file_path: &str, obj: &Obj, mode: FileMode) -> error::Result<()> {
+        self.check_parent_symlinks(file_path).await?;
         match obj {
             Obj::File(file) => {
-                self.fs.write_file(file_path, &file.0).await?;
+                if mode == FileMode::Symlink {
+                    self.check_symlink_target(file_path, &file.0).await?;
+                }
+                self.fs.write_file_with_mode(file_path, &file.0, mode).await?;
                 Ok(())
             }
             Obj::Delete(_) => {
@@ -79,6 +109,41 @@ where
         }
     }
 
+    async fn check_parent_symlinks(&self, file_path: &str) -> error::Result {
+        for parent in parent_dirs(file_path) {
+            let is_symlink = self
+                .fs
+                .stat(&self.as_path(parent))
+                .await?
+                .is_some_and(|stat| stat.mode == FileMode::Symlink);
+            if is_symlink {
+                return Err(error::Error::SymlinkInPath(FilePath::from(file_path)));
+            }
+        }
+        Ok(())
+    }
+
+    async fn check_symlink_target(&self, file_path: &str, target: &[u8]) -> error::Result {
+        let target = String::from_utf8_lossy(target);
+        if is_unsafe_symlink_target(&target) && !self.config.read().await?.allow_unsafe_symlinks {
+            return Err(error::Error::UnsafeSymlink(
+                FilePath::from(file_path),
+                target.to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the mode of the workspace file; [`FileMode::Regular`] if it does not exist.
+    pub async fn mode(&self, file_path: &str) -> error::Result<FileMode> {
+        Ok(self
+            .fs
+            .stat(&self.as_path(file_path))
+            .await?
+            .map(|stat| stat.mode)
+            .unwrap_or_default())
+    }
+
     pub async fn is_change(&self, branch: &BranchName, path: &str) -> error::Result<bool> {
         if self.is_ignore(path).await? {
             return Ok(false);
@@ -92,21 +157,20 @@ where
             if let Some(file_obj) = file_obj {
                 Ok(&file_obj.as_meta()?.hash != current_obj_hash)
             } else {
-                Ok(true)
+                Ok(self.sparse_checkout.read().await?.contains(path))
             }
         } else {
             Ok(file_obj.is_some())
         }
     }
 
-    pub async fn convert_to_objs(&self, path: &str) -> error::Result<ObjectIter<Fs>> {
+    pub async fn convert_to_objs(&self, path: &str) -> error::Result<ObjectIter<'_, Fs>> {
         let files = self.files(path).await?;
-        let mut new_files = Vec::with_capacity(files.len());
-        for file in files {
-            if !self.is_ignore(&file).await? {
-                new_files.push(file);
-            }
-        }
+        let ignore = self.fresh_ignore_matcher(path, &files).await?;
+        let new_files = files
+            .into_iter()
+            .filter(|file| !ignore.is_ignored(file))
+            .collect();
 
         Ok(ObjectIter {
             files: new_files,
@@ -117,30 +181,114 @@ where
 
     pub async fn write_ignores(&self, ignores: Vec<String>) -> error::Result {
         let ignore = ignores.join("\n");
-        let path = self.as_path(&FilePath(".meltos_ignore".to_string()));
+        let path = self.as_path(&FilePath(MELTOS_IGNORE.to_string()));
         self.fs.write_file(&path, ignore.as_bytes()).await?;
+        *self.ignore.lock().unwrap() = None;
         Ok(())
     }
 
+    /// Returns true if the file matches the rules of the ignore files.
+    ///
+    /// The compiled rules are cached until a walk of the workspace finds any ignore file changed,
+    /// or until [`WorkspaceIo::reload_ignore`] is called.
     pub async fn is_ignore(&self, path: &str) -> error::Result<bool> {
-        let Some(file_obj) = self.read(&FilePath(".meltos_ignore".to_string())).await? else {
-            return Ok(false);
+        Ok(self.ignore_matcher().await?.is_ignored(path))
+    }
+
+    pub async fn ignore_matcher(&self) -> error::Result<Arc<IgnoreMatcher>> {
+        if let Some(cache) = self.ignore.lock().unwrap().as_ref() {
+            return Ok(Arc::clone(&cache.matcher));
+        }
+        self.reload_ignore().await
+    }
+
+    /// Returns the cached rules unless any ignore file which affects `files` under `path` has been created, changed or deleted.
+    ///
+    /// Only the ignore files in the parent directories of `path` are looked up besides `files`,
+    /// so the workspace is not walked again unless the rules need to be reloaded.
+    async fn fresh_ignore_matcher(
+        &self,
+        path: &str,
+        files: &[String],
+    ) -> error::Result<Arc<IgnoreMatcher>> {
+        let Some(cache) = self.ignore.lock().unwrap().clone() else {
+            return self.reload_ignore().await;
         };
-        let ignores = String::from_utf8(file_obj.0).unwrap();
-        let mut excludes = ignores.split('\n').filter_map(|pattern| {
-            if pattern.starts_with('!') {
-                Some(pattern.trim_start_matches('!'))
-            } else {
-                None
+        let honor_gitignore = self.config.read().await?.honor_gitignore;
+        if cache.honor_gitignore != honor_gitignore {
+            return self.reload_ignore().await;
+        }
+        for (file, buf) in cache.files.iter() {
+            if self.fs.read_file(file).await?.as_ref() != Some(buf) {
+                return self.reload_ignore().await;
             }
-        });
-        if excludes.any(|pattern| pattern == path) {
-            Ok(false)
+        }
+
+        let mut ignore_files = files
+            .iter()
+            .filter(|file| ignore_file_dir(file, honor_gitignore).is_some())
+            .map(|file| file.trim_start_matches("./").to_string())
+            .collect::<Vec<_>>();
+        let path = path.trim_start_matches("./");
+        for dir in std::iter::once("").chain(parent_dirs(path)) {
+            for name in [MELTOS_IGNORE, GIT_IGNORE] {
+                let file = if dir.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{dir}/{name}")
+                };
+                if ignore_file_dir(&file, honor_gitignore).is_some()
+                    && self.fs.stat(&file).await?.is_some()
+                {
+                    ignore_files.push(file);
+                }
+            }
+        }
+        if ignore_files.iter().all(|file| cache.files.contains_key(file)) {
+            Ok(Arc::clone(&cache.matcher))
         } else {
-            Ok(ignores.split('\n').any(|pattern| path.starts_with(pattern)))
+            self.reload_ignore().await
         }
     }
 
+    /// Reads all ignore files in the workspace and compiles them.
+    ///
+    /// `.gitignore` files are also read if [`RepositoryConfig::honor_gitignore`](crate::io::atomic::config::RepositoryConfig::honor_gitignore) is enabled.
+    pub async fn reload_ignore(&self) -> error::Result<Arc<IgnoreMatcher>> {
+        let honor_gitignore = self.config.read().await?.honor_gitignore;
+        let mut ignore_files = self
+            .fs
+            .all_files_in(".")
+            .await?
+            .into_iter()
+            .filter_map(|path| {
+                let dir = ignore_file_dir(&path, honor_gitignore)?.to_string();
+                (!dir.starts_with(".meltos")).then_some((dir, path))
+            })
+            .collect::<Vec<_>>();
+        // Deeper files take precedence, and `.meltos_ignore` takes precedence over `.gitignore` in the same directory.
+        ignore_files.sort_by_key(|(dir, path)| {
+            let depth = if dir.is_empty() { 0 } else { dir.split('/').count() };
+            (depth, !path.ends_with(GIT_IGNORE), path.clone())
+        });
+
+        let mut matcher = IgnoreMatcher::default();
+        let mut files = HashMap::new();
+        for (dir, path) in ignore_files {
+            if let Some(buf) = self.fs.read_file(&path).await? {
+                matcher.add_rules(&dir, &String::from_utf8_lossy(&buf));
+                files.insert(path.trim_start_matches("./").to_string(), buf);
+            }
+        }
+        let matcher = Arc::new(matcher);
+        *self.ignore.lock().unwrap() = Some(Arc::new(IgnoreCache {
+            honor_gitignore,
+            files,
+            matcher: Arc::clone(&matcher),
+        }));
+        Ok(matcher)
+    }
+
     #[inline(always)]
     pub async fn files(&self, path: &str) -> error::Result<Vec<String>> {
 
@@ -154,6 +302,9 @@ where
         )
     }
 
+    /// Returns the changes of the workspace from the trace tree.
+    ///
+    /// The traced files outside the sparse-checkout patterns are not regarded as deleted.
     pub async fn changed_files(
         &self,
         mut trace_tree: TreeObj,
@@ -161,7 +312,8 @@ where
         let mut changed_files = Vec::new();
         self.compare_trace(&mut trace_tree, &mut changed_files)
             .await?;
-        for (path, _) in trace_tree.0.into_iter() {
+        let sparse = self.sparse_checkout.read().await?;
+        for (path, _) in trace_tree.into_iter().filter(|(path, _)| sparse.contains(path)) {
             changed_files.push(ChangeFileMeta {
                 path,
                 change: ChangeFile::Delete,
@@ -216,6 +368,12 @@ where
     }
 }
 
+fn is_unsafe_symlink_target(target: &str) -> bool {
+    let target = target.replace('\\', "/");
+    let is_absolute = target.starts_with('/') || target.as_bytes().get(1) == Some(&b':');
+    is_absolute || target.split('/').any(|component| component == "..")
+}
+
 pub struct ObjectIter<'a, Fs>
 where
     Fs: FileSystem,
@@ -262,10 +420,12 @@ where
 #[cfg(test)]
 mod tests {
     use std::collections::HashSet;
+    use std::sync::Arc;
 
     use crate::branch::BranchName;
     use crate::file_system::memory::MemoryFileSystem;
-    use crate::file_system::{FilePath, FileSystem};
+    use crate::file_system::{FileMode, FilePath, FileSystem};
+    use crate::io::atomic::config::{ConfigIo, RepositoryConfig};
     use crate::io::atomic::object::ObjIo;
     use crate::io::workspace::WorkspaceIo;
     use crate::object::file::FileObj;
@@ -318,7 +478,7 @@ mod tests {
             .await
             .unwrap();
         workspace
-            .unpack(&FilePath::from_path("hello.txt"), &Obj::File(obj))
+            .unpack(&FilePath::from_path("hello.txt"), &Obj::File(obj), FileMode::Regular)
             .await
             .unwrap();
         assert_eq!(fs.try_read_file("hello.txt").await.unwrap(), b"hello");
@@ -488,7 +648,7 @@ mod tests {
 
         let workspace = WorkspaceIo::new(fs.clone());
         workspace
-            .write_ignores(vec!["dir/".to_string(), "!dir/hello3.txt".to_string()])
+            .write_ignores(vec!["dir/*".to_string(), "!dir/hello3.txt".to_string()])
             .await
             .unwrap();
         fs.write_sync("dir/hello1.txt", b"hello");
@@ -502,4 +662,87 @@ mod tests {
         let ignored = workspace.is_ignore("dir/hello3.txt").await.unwrap();
         assert!(!ignored);
     }
+
+    #[tokio::test]
+    async fn ignore_glob_patterns() {
+        let fs = MemoryFileSystem::default();
+        init_owner_branch(fs.clone()).await;
+
+        let workspace = WorkspaceIo::new(fs.clone());
+        workspace
+            .write_ignores(vec!["*.log".to_string(), "**/node_modules/".to_string()])
+            .await
+            .unwrap();
+        fs.write_sync("debug.log", b"log");
+        fs.write_sync("web/node_modules/react/index.js", b"react");
+        fs.write_sync("web/index.js", b"index");
+
+        let files = workspace
+            .convert_to_objs(".")
+            .await
+            .unwrap()
+            .all()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect::<Vec<_>>();
+        assert_eq!(files, vec![FilePath::from("web/index.js")]);
+    }
+
+    #[tokio::test]
+    async fn apply_nested_ignore_file() {
+        let fs = MemoryFileSystem::default();
+        init_owner_branch(fs.clone()).await;
+        fs.write_sync("web/.meltos_ignore", b"dist/");
+        fs.write_sync("web/dist/index.js", b"dist");
+        fs.write_sync("dist/index.js", b"dist");
+
+        let workspace = WorkspaceIo::new(fs.clone());
+        assert!(workspace.is_ignore("web/dist/index.js").await.unwrap());
+        assert!(!workspace.is_ignore("dist/index.js").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn honor_gitignore_if_enabled() {
+        let fs = MemoryFileSystem::default();
+        init_owner_branch(fs.clone()).await;
+        fs.write_sync(".gitignore", b"target/");
+        fs.write_sync("target/debug/app", b"app");
+
+        let workspace = WorkspaceIo::new(fs.clone());
+        assert!(!workspace.is_ignore("target/debug/app").await.unwrap());
+
+        ConfigIo::new(fs.clone())
+            .write(&RepositoryConfig {
+                honor_gitignore: true,
+                ..RepositoryConfig::default()
+            })
+            .await
+            .unwrap();
+        workspace.reload_ignore().await.unwrap();
+        assert!(workspace.is_ignore("target/debug/app").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn reuse_ignore_rules_until_ignore_file_changed() {
+        let fs = MemoryFileSystem::default();
+        init_owner_branch(fs.clone()).await;
+        fs.write_sync(".meltos_ignore", b"hello.txt");
+        fs.write_sync("hello.txt", b"hello");
+        fs.write_sync("web/dist/index.js", b"index");
+
+        let workspace = WorkspaceIo::new(fs.clone());
+        let matcher = workspace.ignore_matcher().await.unwrap();
+        workspace.convert_to_objs(".").await.unwrap();
+        assert!(Arc::ptr_eq(&matcher, &workspace.ignore_matcher().await.unwrap()));
+
+        fs.write_sync("web/.meltos_ignore", b"dist/");
+        let objs = workspace.convert_to_objs("web").await.unwrap().all().await.unwrap();
+        assert!(objs.iter().all(|(path, _)| !path.contains("dist")));
+
+        fs.write_sync(".meltos_ignore", b"");
+        let objs = workspace.convert_to_objs(".").await.unwrap().all().await.unwrap();
+        assert!(objs.iter().any(|(path, _)| path.ends_with("hello.txt")));
+    }
 }
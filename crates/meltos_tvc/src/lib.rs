@@ -1,9 +1,11 @@
 pub mod branch;
 pub mod error;
 pub mod file_system;
+pub mod ignore;
 pub mod io;
 pub mod object;
 pub mod operation;
+pub mod tag;
 
 pub mod encode;
 mod time;
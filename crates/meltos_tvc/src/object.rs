@@ -16,12 +16,14 @@ use crate::object::commit::CommitObj;
 use crate::object::delete::DeleteObj;
 use crate::object::file::FileObj;
 use crate::object::local_commits::LocalCommitsObj;
+use crate::object::tag::TagObj;
 use crate::object::tree::TreeObj;
 
 pub mod commit;
 pub mod delete;
 pub mod file;
 pub mod local_commits;
+pub mod tag;
 pub mod tree;
 
 #[delegate]
@@ -67,6 +69,7 @@ pub enum Obj {
     Tree(TreeObj),
     Commit(CommitObj),
     LocalCommits(LocalCommitsObj),
+    Tag(TagObj),
 }
 
 impl Obj {
@@ -83,6 +86,8 @@ impl Obj {
             Ok(Obj::Commit(CommitObj::decode(&buf)?))
         } else if buf.starts_with(LocalCommitsObj::HEADER) {
             Ok(Obj::LocalCommits(LocalCommitsObj::decode(&buf)?))
+        } else if buf.starts_with(TagObj::HEADER) {
+            Ok(Obj::Tag(TagObj::decode(&buf)?))
         } else {
             Err(crate::error::Error::InvalidObjBuffer(ObjHash::new(&buf)))
         }
@@ -112,6 +117,18 @@ impl Obj {
         }
     }
 
+    pub fn tag(self) -> error::Result<TagObj> {
+        match self {
+            Self::Tag(tag) => Ok(tag),
+            _ => {
+                Err(error::Error::InvalidObjType(
+                    "Tag".to_string(),
+                    self.to_string(),
+                ))
+            }
+        }
+    }
+
     pub fn tree(self) -> error::Result<TreeObj> {
         match self {
             Self::Tree(tree) => Ok(tree),
@@ -133,6 +150,7 @@ impl Display for Obj {
             Obj::Tree(_) => f.write_str("Tree"),
             Obj::Commit(_) => f.write_str("Commit"),
             Obj::LocalCommits(_) => f.write_str("LocalCommits"),
+            Obj::Tag(_) => f.write_str("Tag"),
         }
     }
 }
@@ -146,6 +164,7 @@ impl AsMeta for Obj {
             Self::Delete(delete) => delete.as_meta(),
             Self::Commit(commit) => commit.as_meta(),
             Self::LocalCommits(local_commits) => local_commits.as_meta(),
+            Self::Tag(tag) => tag.as_meta(),
         }
     }
 }
@@ -6,6 +6,7 @@ use wasm_bindgen::prelude::wasm_bindgen;
 
 use meltos_util::macros::{Deref, Display};
 
+use crate::branch::BranchName;
 use crate::io::atomic::head::CommitText;
 use crate::object::{AsMeta, Decodable, Encodable, ObjHash, ObjMeta};
 use crate::{error, impl_serialize_and_deserialize};
@@ -29,11 +30,23 @@ pub struct CommitObj {
     pub parents: Vec<CommitHash>,
     pub text: CommitText,
     pub committed_objs_tree: ObjHash,
+    /// The branch that created this commit.
+    ///
+    /// `None` if the commit was created before the author was recorded, or it is the null commit.
+    pub author: Option<BranchName>,
+    /// Seconds elapsed since the UNIX epoch when committed.
+    pub committed_at: Option<u64>,
 }
 impl_serialize_and_deserialize!(CommitObj);
 
 impl CommitObj {
     pub const HEADER: &'static [u8] = b"COMMIT\0";
+
+    /// The version of the encoding that contains the author and the commit timestamp.
+    ///
+    /// It is written right after the [`CommitObj::HEADER`].
+    /// The commits encoded without it are decoded as the first version.
+    pub const VERSION_2: &'static [u8] = b"v2";
 }
 
 impl AsMeta for CommitObj {
@@ -55,6 +68,19 @@ impl TryFrom<ObjMeta> for CommitObj {
 impl Encodable for CommitObj {
     fn encode(&self) -> error::Result<Vec<u8>> {
         let mut buf = Self::HEADER.to_vec();
+        if self.author.is_some() || self.committed_at.is_some() {
+            buf.extend(Self::VERSION_2);
+            buf.push(b'\0');
+            if let Some(author) = self.author.as_ref() {
+                buf.extend(author.as_bytes());
+            }
+            buf.push(b'\0');
+            if let Some(committed_at) = self.committed_at {
+                buf.extend(committed_at.to_string().as_bytes());
+            }
+            buf.push(b'\0');
+        }
+
         let parents_count = self.parents.len();
         buf.extend(format!("{parents_count}\0").as_bytes());
         for hash in &self.parents {
@@ -76,23 +102,49 @@ impl Decodable for CommitObj {
         let mut buf = buf[Self::HEADER.len()..]
             .split(|b| b == &b'\0')
             .collect::<VecDeque<&[u8]>>();
+        let (author, committed_at) = if buf.front() == Some(&Self::VERSION_2) {
+            buf.pop_front();
+            decode_signature(&mut buf)?
+        } else {
+            (None, None)
+        };
         let parents = decode_parents(&mut buf)?;
-        let committed_objs_tree = ObjHash::decode(buf.pop_front().unwrap())?;
-        let text = CommitText::decode(buf.pop_front().unwrap())?;
+        let committed_objs_tree = ObjHash::decode(pop_front(&mut buf)?)?;
+        let text = CommitText::decode(pop_front(&mut buf)?)?;
         Ok(Self {
             parents,
             committed_objs_tree,
             text,
+            author,
+            committed_at,
         })
     }
 }
 
+fn decode_signature(buf: &mut VecDeque<&[u8]>) -> error::Result<(Option<BranchName>, Option<u64>)> {
+    let author = std::str::from_utf8(pop_front(buf)?)?;
+    let author = (!author.is_empty()).then(|| BranchName::from(author));
+    let committed_at = std::str::from_utf8(pop_front(buf)?)?;
+    let committed_at = if committed_at.is_empty() {
+        None
+    } else {
+        Some(u64::from_str(committed_at)?)
+    };
+    Ok((author, committed_at))
+}
+
+#[inline]
+fn pop_front<'a>(buf: &mut VecDeque<&'a [u8]>) -> error::Result<&'a [u8]> {
+    buf.pop_front()
+        .ok_or(error::Error::CommitObjBufferIsInValid)
+}
+
 fn decode_parents(buf: &mut VecDeque<&[u8]>) -> error::Result<Vec<CommitHash>> {
-    let count_buf = std::str::from_utf8(buf.pop_front().unwrap())?;
+    let count_buf = std::str::from_utf8(pop_front(buf)?)?;
     let parents_count = usize::from_str(count_buf)?;
     let mut parents = Vec::with_capacity(parents_count);
     for _ in 0..parents_count {
-        parents.push(CommitHash::decode(buf.pop_front().unwrap())?);
+        parents.push(CommitHash::decode(pop_front(buf)?)?);
     }
 
     Ok(parents)
@@ -100,6 +152,7 @@ fn decode_parents(buf: &mut VecDeque<&[u8]>) -> error::Result<Vec<CommitHash>> {
 
 #[cfg(test)]
 mod tests {
+    use crate::branch::BranchName;
     use crate::io::atomic::head::CommitText;
     use crate::object::commit::{CommitHash, CommitObj};
     use crate::object::{Decodable, Encodable, ObjHash};
@@ -115,6 +168,8 @@ mod tests {
             parents: parents.clone(),
             text: commit_text.clone(),
             committed_objs_tree: tree.clone(),
+            author: None,
+            committed_at: None,
         };
         let buf = commit.encode().unwrap();
         let h = CommitObj::HEADER.len();
@@ -135,9 +190,59 @@ mod tests {
             parents: parents.clone(),
             text: commit_text.clone(),
             committed_objs_tree: tree.clone(),
+            author: None,
+            committed_at: None,
+        };
+        let buf = commit.encode().unwrap();
+        let decoded = CommitObj::decode(&buf).unwrap();
+        assert_eq!(decoded, commit);
+    }
+
+    #[test]
+    fn serialize_with_author_and_committed_at() {
+        let commit = CommitObj {
+            parents: vec![CommitHash(ObjHash::new(b"hello"))],
+            text: CommitText::from("commit"),
+            committed_objs_tree: ObjHash::new(b"hash"),
+            author: Some(BranchName::owner()),
+            committed_at: Some(1700000000),
+        };
+        let buf = commit.encode().unwrap();
+        let h = CommitObj::HEADER.len();
+        assert_eq!(&buf[..h], CommitObj::HEADER);
+        assert_eq!(&buf[h..h + 22], b"v2\x00owner\x001700000000\x001\x00");
+    }
+
+    #[test]
+    fn decode_with_author_and_committed_at() {
+        let commit = CommitObj {
+            parents: vec![CommitHash(ObjHash::new(b"hello"))],
+            text: CommitText::from("commit"),
+            committed_objs_tree: ObjHash::new(b"hash"),
+            author: Some(BranchName::owner()),
+            committed_at: Some(1700000000),
         };
         let buf = commit.encode().unwrap();
         let decoded = CommitObj::decode(&buf).unwrap();
         assert_eq!(decoded, commit);
     }
+
+    #[test]
+    fn decode_legacy_commit() {
+        let tree = ObjHash::new(b"hash");
+        let mut buf = b"COMMIT\x000\x00".to_vec();
+        buf.extend(tree.encode().unwrap());
+        buf.extend(b"\0commit");
+        let decoded = CommitObj::decode(&buf).unwrap();
+        assert_eq!(
+            decoded,
+            CommitObj {
+                parents: Vec::new(),
+                text: CommitText::from("commit"),
+                committed_objs_tree: tree,
+                author: None,
+                committed_at: None,
+            }
+        );
+    }
 }
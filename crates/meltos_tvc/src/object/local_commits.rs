@@ -1,7 +1,7 @@
 use std::collections::VecDeque;
 use std::str::FromStr;
 
-use crate::impl_serialize_and_deserialize;
+use crate::{error, impl_serialize_and_deserialize};
 use meltos_util::macros::{Deref, DerefMut};
 
 use crate::object::commit::CommitHash;
@@ -41,15 +41,20 @@ impl Encodable for LocalCommitsObj {
 
 impl Decodable for LocalCommitsObj {
     fn decode(buf: &[u8]) -> crate::error::Result<Self> {
-        let mut buf = buf[Self::HEADER.len()..]
+        let mut buf = buf
+            .strip_prefix(Self::HEADER)
+            .ok_or(error::Error::LocalCommitsObjBufferIsInvalid)?
             .split(|b| b == &b'\0')
             .collect::<VecDeque<&[u8]>>();
-        let hash_count = buf.pop_front().unwrap();
-        let hash_count = usize::from_str(std::str::from_utf8(hash_count)?)?;
+        let mut pop_front = || {
+            buf.pop_front()
+                .ok_or(error::Error::LocalCommitsObjBufferIsInvalid)
+        };
+        let hash_count = usize::from_str(std::str::from_utf8(pop_front()?)?)?;
 
-        let mut hashes = Vec::with_capacity(hash_count);
+        let mut hashes = Vec::new();
         for _ in 0..hash_count {
-            hashes.push(CommitHash::decode(buf.pop_front().unwrap())?);
+            hashes.push(CommitHash::decode(pop_front()?)?);
         }
         Ok(Self(hashes))
     }
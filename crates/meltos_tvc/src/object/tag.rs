@@ -0,0 +1,90 @@
+use std::str::FromStr;
+
+use crate::branch::BranchName;
+use crate::error;
+use crate::object::commit::CommitHash;
+use crate::object::{AsMeta, Decodable, Encodable, ObjHash, ObjMeta};
+use crate::tag::TagName;
+
+/// The annotated tag, which records who tagged the commit and why.
+///
+/// The lightweight tags refer to the commit directly and have no object.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TagObj {
+    pub tag_name: TagName,
+    pub commit_hash: CommitHash,
+    pub tagger: BranchName,
+    /// Seconds elapsed since the UNIX epoch when tagged.
+    pub tagged_at: u64,
+    pub message: String,
+}
+
+impl TagObj {
+    pub const HEADER: &'static [u8] = b"TAG\0";
+}
+
+impl AsMeta for TagObj {
+    #[inline]
+    fn as_meta(&self) -> error::Result<ObjMeta> {
+        Ok(ObjMeta::compress(self.encode()?)?)
+    }
+}
+
+impl Encodable for TagObj {
+    fn encode(&self) -> error::Result<Vec<u8>> {
+        let mut buf = Self::HEADER.to_vec();
+        buf.extend(self.tag_name.as_bytes());
+        buf.push(b'\0');
+        buf.extend(self.commit_hash.encode()?);
+        buf.push(b'\0');
+        buf.extend(self.tagger.as_bytes());
+        buf.push(b'\0');
+        buf.extend(self.tagged_at.to_string().as_bytes());
+        buf.push(b'\0');
+        buf.extend(self.message.as_bytes());
+        Ok(buf)
+    }
+}
+
+impl Decodable for TagObj {
+    fn decode(buf: &[u8]) -> error::Result<Self> {
+        let mut buf = buf[Self::HEADER.len()..].splitn(5, |b| b == &b'\0');
+        let mut next = || buf.next().ok_or(error::Error::TagObjBufferIsInvalid);
+        let tag_name = TagName::from(std::str::from_utf8(next()?)?);
+        let commit_hash = CommitHash(ObjHash::decode(next()?)?);
+        let tagger = BranchName::from(std::str::from_utf8(next()?)?);
+        let tagged_at = u64::from_str(std::str::from_utf8(next()?)?)?;
+        let message = String::from_utf8(next()?.to_vec())?;
+        Ok(Self {
+            tag_name,
+            commit_hash,
+            tagger,
+            tagged_at,
+            message,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::branch::BranchName;
+    use crate::object::commit::CommitHash;
+    use crate::object::tag::TagObj;
+    use crate::object::{Decodable, Encodable, ObjHash};
+    use crate::tag::TagName;
+
+    #[test]
+    fn decode() {
+        let tag = TagObj {
+            tag_name: TagName::from("v1"),
+            commit_hash: CommitHash(ObjHash::new(b"commit")),
+            tagger: BranchName::owner(),
+            tagged_at: 1700000000,
+            message: "reviewed\0by owner".to_string(),
+        };
+        let buf = tag.encode().unwrap();
+        assert!(buf.starts_with(TagObj::HEADER));
+        assert_eq!(TagObj::decode(&buf).unwrap(), tag);
+        assert!(TagObj::decode(TagObj::HEADER).is_err());
+    }
+}
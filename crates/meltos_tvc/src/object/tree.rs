@@ -1,9 +1,7 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{hash_map, HashMap, VecDeque};
 use std::str::FromStr;
 
-use meltos_util::macros::{Deref, DerefMut};
-
-use crate::file_system::{FilePath, FileSystem};
+use crate::file_system::{parent_dirs, FileMode, FilePath, FileSystem};
 use crate::object::{AsMeta, Decodable, Encodable, ObjHash, ObjMeta};
 use crate::{error, impl_serialize_and_deserialize};
 
@@ -55,21 +53,26 @@ where
         Ok(tree.0.get(file_path).cloned())
     }
 
+    /// Writes the entry with its mode, overwriting the mode of the previous entry.
     pub async fn write_object_hash(
         &self,
         target_path: FilePath,
         object_hash: ObjHash,
+        mode: FileMode,
     ) -> error::Result<()> {
         let mut tree = self.read().await?.unwrap_or_default();
-        tree.0.insert(target_path, object_hash);
+        tree.insert_with_mode(target_path, object_hash, mode);
         self.fs.write_file(&self.file_path, &tree.encode()?).await?;
         Ok(())
     }
 }
 
-#[repr(transparent)]
-#[derive(Default, Clone, Deref, DerefMut, Debug, Eq, PartialEq)]
-pub struct TreeObj(pub HashMap<FilePath, ObjHash>);
+/// The map of the file paths and their object hashes.
+///
+/// The modes of the entries are held separately, and only the ones other than [`FileMode::Regular`] are kept,
+/// so that the trees written before the modes were introduced keep the same encoding.
+#[derive(Default, Clone, Debug, Eq, PartialEq)]
+pub struct TreeObj(HashMap<FilePath, ObjHash>, HashMap<FilePath, FileMode>);
 impl_serialize_and_deserialize!(TreeObj);
 
 impl TreeObj {
@@ -83,13 +86,120 @@ impl TreeObj {
         }
     }
 
+    /// Returns true if either the hash or the mode of the entry differs.
+    #[inline]
+    pub fn changed(&self, path: &FilePath, hash: &ObjHash, mode: FileMode) -> bool {
+        self.changed_hash(path, hash) || self.mode(path) != mode
+    }
+
+    /// Returns the mode of the entry; [`FileMode::Regular`] if the entry does not exist.
+    #[inline]
+    pub fn mode(&self, path: &FilePath) -> FileMode {
+        self.1.get(path).copied().unwrap_or_default()
+    }
+
+    /// Returns the symlink entry which is a parent directory of the path, if any.
+    pub fn symlink_parent(&self, path: &str) -> Option<FilePath> {
+        parent_dirs(path)
+            .map(FilePath::from)
+            .find(|parent| self.mode(parent) == FileMode::Symlink)
+    }
+
+    /// Fails if any entry is placed under a symlink entry,
+    /// because writing it would follow the symlink.
+    pub fn check_symlink_parents(&self) -> error::Result {
+        match self.0.keys().find(|path| self.symlink_parent(path).is_some()) {
+            Some(path) => Err(error::Error::SymlinkInPath(path.clone())),
+            None => Ok(()),
+        }
+    }
+
+    #[inline]
+    pub fn get(&self, path: &FilePath) -> Option<&ObjHash> {
+        self.0.get(path)
+    }
+
+    #[inline]
+    pub fn contains_key(&self, path: &FilePath) -> bool {
+        self.0.contains_key(path)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    #[inline]
+    pub fn iter(&self) -> hash_map::Iter<'_, FilePath, ObjHash> {
+        self.0.iter()
+    }
+
+    #[inline]
+    pub fn keys(&self) -> hash_map::Keys<'_, FilePath, ObjHash> {
+        self.0.keys()
+    }
+
+    #[inline]
+    pub fn values(&self) -> hash_map::Values<'_, FilePath, ObjHash> {
+        self.0.values()
+    }
+
+    #[inline]
+    pub fn into_values(self) -> hash_map::IntoValues<FilePath, ObjHash> {
+        self.0.into_values()
+    }
+
+    /// Inserts the entry as a regular file.
+    #[inline]
+    pub fn insert(&mut self, path: FilePath, hash: ObjHash) -> Option<ObjHash> {
+        self.insert_with_mode(path, hash, FileMode::Regular)
+    }
+
+    /// Inserts the entry with its mode, overwriting the mode of the previous entry.
+    pub fn insert_with_mode(
+        &mut self,
+        path: FilePath,
+        hash: ObjHash,
+        mode: FileMode,
+    ) -> Option<ObjHash> {
+        if mode == FileMode::Regular {
+            self.1.remove(&path);
+        } else {
+            self.1.insert(path.clone(), mode);
+        }
+        self.0.insert(path, hash)
+    }
+
+    /// Removes the entry together with its mode.
+    pub fn remove(&mut self, path: &FilePath) -> Option<ObjHash> {
+        self.1.remove(path);
+        self.0.remove(path)
+    }
+
     pub fn replace_by(&mut self, tree: TreeObj) {
-        for (file_path, hash) in tree.0.into_iter() {
-            self.0.insert(file_path, hash);
+        let TreeObj(hashes, modes) = tree;
+        for (file_path, hash) in hashes.into_iter() {
+            let mode = modes.get(&file_path).copied().unwrap_or_default();
+            self.insert_with_mode(file_path, hash, mode);
         }
     }
 }
 
+impl IntoIterator for TreeObj {
+    type Item = (FilePath, ObjHash);
+    type IntoIter = hash_map::IntoIter<FilePath, ObjHash>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
 impl AsMeta for TreeObj {
     fn as_meta(&self) -> error::Result<ObjMeta> {
         let buf = self.encode()?;
@@ -123,39 +233,85 @@ impl Encodable for TreeObj {
             buf.extend(format!("{}\0{}\0", k_v.file_path, k_v.hash).as_bytes());
         }
 
+        // The modes follow the entries only if any entry is not a regular file.
+        let mut modes = self
+            .1
+            .iter()
+            .filter(|(file_path, _)| self.0.contains_key(*file_path))
+            .collect::<Vec<_>>();
+        if !modes.is_empty() {
+            modes.sort_by_key(|(file_path, _)| *file_path);
+            buf.extend(format!("{}\0", modes.len()).as_bytes());
+            for (file_path, mode) in modes {
+                buf.extend(format!("{file_path}\0{}\0", mode.as_octal()).as_bytes());
+            }
+        }
+
         Ok(buf)
     }
 }
 
 impl Decodable for TreeObj {
     fn decode(buf: &[u8]) -> error::Result<Self> {
-        let mut buf = buf[TreeObj::HEADER.len()..]
+        let body = buf
+            .strip_prefix(TreeObj::HEADER)
+            .ok_or(error::Error::TreeObjBufferIsInvalid)?;
+        let mut buf = body
             .split(|b| b == &b'\0')
             .collect::<VecDeque<&[u8]>>();
 
         let entry_count = decode_entry_count(&mut buf)?;
-        let mut tree = TreeObj(HashMap::with_capacity(entry_count));
+        let mut tree = TreeObj(HashMap::with_capacity(entry_count), HashMap::new());
         for _ in 0..entry_count {
-            let entry_buf = buf.pop_front().unwrap();
-            let file_path = FilePath::from_path(String::from_utf8(entry_buf.to_vec())?);
-            let hash = ObjHash::decode(buf.pop_front().unwrap())?;
+            let file_path = decode_file_path(&mut buf)?;
+            let hash = ObjHash::decode(pop_front(&mut buf)?)?;
             tree.0.insert(file_path, hash);
         }
 
+        // The trees written before the modes were introduced end here.
+        if buf.front().is_some_and(|b| !b.is_empty()) {
+            let mode_count = decode_entry_count(&mut buf)?;
+            for _ in 0..mode_count {
+                let file_path = decode_file_path(&mut buf)?;
+                let mode = decode_mode(pop_front(&mut buf)?)?;
+                tree.1.insert(file_path, mode);
+            }
+        }
+
         Ok(tree)
     }
 }
 
+#[inline]
+fn pop_front<'a>(buf: &mut VecDeque<&'a [u8]>) -> error::Result<&'a [u8]> {
+    buf.pop_front().ok_or(error::Error::TreeObjBufferIsInvalid)
+}
+
 fn decode_entry_count(buf: &mut VecDeque<&[u8]>) -> error::Result<usize> {
-    let entry_count_buf = buf.pop_front().unwrap();
-    let entry_count_str = std::str::from_utf8(entry_count_buf)?;
-    Ok(usize::from_str(entry_count_str)?)
+    let entry_count_str = std::str::from_utf8(pop_front(buf)?)?;
+    let entry_count = usize::from_str(entry_count_str)?;
+    // Each entry takes at least two fields, so a larger count means the buffer is truncated.
+    if buf.len() < entry_count.saturating_mul(2) {
+        return Err(error::Error::TreeObjBufferIsInvalid);
+    }
+    Ok(entry_count)
+}
+
+fn decode_file_path(buf: &mut VecDeque<&[u8]>) -> error::Result<FilePath> {
+    let file_path = String::from_utf8(pop_front(buf)?.to_vec())?;
+    Ok(FilePath::from_path(file_path))
+}
+
+fn decode_mode(buf: &[u8]) -> error::Result<FileMode> {
+    let octal = std::str::from_utf8(buf)?;
+    FileMode::from_octal(octal).ok_or_else(|| error::Error::InvalidFileMode(octal.to_string()))
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::file_system::FilePath;
-    use crate::object::tree::TreeObj;
+    use crate::file_system::memory::MemoryFileSystem;
+    use crate::file_system::{FileMode, FilePath};
+    use crate::object::tree::{TreeIo, TreeObj};
     use crate::object::{Decodable, Encodable, ObjHash};
 
     #[test]
@@ -206,4 +362,79 @@ mod tests {
         let decoded: TreeObj = serde_json::from_str(&json).unwrap();
         assert_eq!(decoded, tree);
     }
+
+    #[test]
+    fn decode_with_modes() {
+        let mut tree = TreeObj::default();
+        let p1 = FilePath::from_path("run.sh");
+        let p2 = FilePath::from_path("link");
+        let p3 = FilePath::from_path("hello");
+        tree.insert_with_mode(p1.clone(), ObjHash::new(b"run"), FileMode::Executable);
+        tree.insert_with_mode(p2.clone(), ObjHash::new(b"run.sh"), FileMode::Symlink);
+        tree.insert_with_mode(p3.clone(), ObjHash::new(b"hello"), FileMode::Regular);
+
+        let decoded = TreeObj::decode(&tree.encode().unwrap()).unwrap();
+        assert_eq!(decoded, tree);
+        assert_eq!(decoded.mode(&p1), FileMode::Executable);
+        assert_eq!(decoded.mode(&p2), FileMode::Symlink);
+        assert_eq!(decoded.mode(&p3), FileMode::Regular);
+    }
+
+    #[test]
+    fn decode_tree_written_without_modes() {
+        let p1 = FilePath::from_path("hello");
+        let h1 = ObjHash::new(b"hello");
+        let buf = [TreeObj::HEADER, format!("1\0{p1}\0{h1}\0").as_bytes()].concat();
+
+        let tree = TreeObj::decode(&buf).unwrap();
+        assert_eq!(tree.get(&p1), Some(&h1));
+        assert_eq!(tree.mode(&p1), FileMode::Regular);
+        assert_eq!(tree.encode().unwrap(), buf);
+    }
+
+    #[test]
+    fn failed_if_truncated() {
+        let p1 = FilePath::from_path("run.sh");
+        let h1 = ObjHash::new(b"run");
+        for buf in [
+            b"TRE".to_vec(),
+            TreeObj::HEADER.to_vec(),
+            [TreeObj::HEADER, b"2\0"].concat(),
+            [TreeObj::HEADER, format!("2\0{p1}\0{h1}\0").as_bytes()].concat(),
+            [TreeObj::HEADER, format!("1\0{p1}\0{h1}\0").as_bytes(), b"1\0"].concat(),
+            [TreeObj::HEADER, format!("1\0{p1}\0{h1}\01\0{p1}").as_bytes()].concat(),
+            [TreeObj::HEADER, b"18446744073709551615\0"].concat(),
+        ] {
+            assert!(
+                TreeObj::decode(&buf).is_err(),
+                "decoded: {:?}",
+                String::from_utf8_lossy(&buf)
+            );
+        }
+    }
+
+    #[test]
+    fn remove_mode_with_entry() {
+        let mut tree = TreeObj::default();
+        let path = FilePath::from_path("run.sh");
+        tree.insert_with_mode(path.clone(), ObjHash::new(b"run"), FileMode::Executable);
+        tree.remove(&path);
+        tree.insert(path.clone(), ObjHash::new(b"run"));
+        assert_eq!(tree.mode(&path), FileMode::Regular);
+    }
+
+    #[tokio::test]
+    async fn write_object_hash_with_mode() {
+        let io = TreeIo::new(".meltos/tree", MemoryFileSystem::default());
+        let path = FilePath::from("run.sh");
+        io.write_object_hash(path.clone(), ObjHash::new(b"run"), FileMode::Executable)
+            .await
+            .unwrap();
+        assert_eq!(io.read().await.unwrap().unwrap().mode(&path), FileMode::Executable);
+
+        io.write_object_hash(path.clone(), ObjHash::new(b"run2"), FileMode::Regular)
+            .await
+            .unwrap();
+        assert_eq!(io.read().await.unwrap().unwrap().mode(&path), FileMode::Regular);
+    }
 }
@@ -3,26 +3,62 @@ use crate::file_system::FileSystem;
 use crate::io::atomic::local_commits::LocalCommitsIo;
 use crate::io::atomic::work_branch::WorkingIo;
 use crate::io::bundle::BundleIo;
+use crate::operation::blame::Blame;
 use crate::operation::checkout::Checkout;
+use crate::operation::cherry_pick::CherryPick;
 use crate::operation::commit::Commit;
+use crate::operation::delete_branch::DeleteBranch;
+use crate::operation::diff::Diff;
+use crate::operation::export_git::ExportGit;
+use crate::operation::fsck::Fsck;
+use crate::operation::gc::Gc;
+use crate::operation::import_git::ImportGit;
 use crate::operation::init::Init;
+use crate::operation::log::Log;
 use crate::operation::merge::Merge;
 use crate::operation::patch::Patch;
 use crate::operation::push::Push;
+use crate::operation::rename_branch::RenameBranch;
+use crate::operation::reflog::Reflog;
+use crate::operation::repack::Repack;
+use crate::operation::reset::Reset;
+use crate::operation::revert::Revert;
 use crate::operation::save::Save;
+use crate::operation::sparse_checkout::SparseCheckout;
 use crate::operation::stage::Stage;
+use crate::operation::stash::Stash;
+use crate::operation::status::Status;
+use crate::operation::tag::Tag;
 use crate::operation::un_stage::UnStage;
 use crate::operation::unzip::UnZip;
 
+pub mod blame;
 pub mod checkout;
+pub mod cherry_pick;
 pub mod commit;
+pub mod delete_branch;
+pub mod diff;
+pub mod export_git;
+pub mod fsck;
+pub mod gc;
+pub mod import_git;
 pub mod init;
+pub mod log;
 pub mod merge;
 pub mod new_branch;
 pub mod patch;
 pub mod push;
+pub mod rename_branch;
+pub mod reflog;
+pub mod repack;
+pub mod reset;
+pub mod revert;
 pub mod save;
+pub mod sparse_checkout;
 pub mod stage;
+pub mod stash;
+pub mod status;
+pub mod tag;
 pub mod un_stage;
 pub mod unzip;
 
@@ -34,14 +70,32 @@ where
     pub init: Init<Fs>,
     pub patch: Patch<Fs>,
     pub stage: Stage<Fs>,
+    pub stash: Stash<Fs>,
     pub un_stage: UnStage<Fs>,
+    pub reset: Reset<Fs>,
+    pub reflog: Reflog<Fs>,
     pub commit: Commit<Fs>,
     pub push: Push<Fs>,
     pub save: Save<Fs>,
     pub bundle: BundleIo<Fs>,
     pub checkout: Checkout<Fs>,
+    pub delete_branch: DeleteBranch<Fs>,
+    pub rename_branch: RenameBranch<Fs>,
     pub unzip: UnZip<Fs>,
+    pub sparse_checkout: SparseCheckout<Fs>,
     pub merge: Merge<Fs>,
+    pub cherry_pick: CherryPick<Fs>,
+    pub revert: Revert<Fs>,
+    pub log: Log<Fs>,
+    pub diff: Diff<Fs>,
+    pub blame: Blame<Fs>,
+    pub status: Status<Fs>,
+    pub gc: Gc<Fs>,
+    pub fsck: Fsck<Fs>,
+    pub repack: Repack<Fs>,
+    pub import_git: ImportGit<Fs>,
+    pub export_git: ExportGit<Fs>,
+    pub tag: Tag<Fs>,
     pub local_commits: LocalCommitsIo<Fs>,
     pub working: WorkingIo<Fs>,
     fs: Fs,
@@ -56,14 +110,32 @@ where
             init: Init::new(fs.clone()),
             patch: Patch::new(fs.clone()),
             stage: Stage::new(fs.clone()),
+            stash: Stash::new(fs.clone()),
             un_stage: UnStage::new(fs.clone()),
+            reset: Reset::new(fs.clone()),
+            reflog: Reflog::new(fs.clone()),
             commit: Commit::new(fs.clone()),
             push: Push::new(fs.clone()),
             save: Save::new(fs.clone()),
             bundle: BundleIo::new(fs.clone()),
             checkout: Checkout::new(fs.clone()),
+            delete_branch: DeleteBranch::new(fs.clone()),
+            rename_branch: RenameBranch::new(fs.clone()),
             unzip: UnZip::new(fs.clone()),
+            sparse_checkout: SparseCheckout::new(fs.clone()),
             merge: Merge::new(fs.clone()),
+            cherry_pick: CherryPick::new(fs.clone()),
+            revert: Revert::new(fs.clone()),
+            log: Log::new(fs.clone()),
+            diff: Diff::new(fs.clone()),
+            blame: Blame::new(fs.clone()),
+            status: Status::new(fs.clone()),
+            gc: Gc::new(fs.clone()),
+            fsck: Fsck::new(fs.clone()),
+            repack: Repack::new(fs.clone()),
+            import_git: ImportGit::new(fs.clone()),
+            export_git: ExportGit::new(fs.clone()),
+            tag: Tag::new(fs.clone()),
             local_commits: LocalCommitsIo::new(fs.clone()),
             working: WorkingIo::new(fs.clone()),
             fs,
@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+
+use similar::DiffOp;
+
+use crate::branch::BranchName;
+use crate::error;
+use crate::file_system::{FilePath, FileSystem};
+use crate::io::atomic::head::HeadIo;
+use crate::io::atomic::object::ObjIo;
+use crate::io::diff::file::FileDiff;
+use crate::io::trace_tree::TraceTreeIo;
+use crate::object::commit::{CommitHash, CommitObj};
+use crate::object::{Obj, ObjHash};
+use crate::operation::log::{Log, LogOptions};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BlameLine {
+    /// 1-based line number in the file.
+    pub line: usize,
+    /// The line text without its line terminator.
+    pub text: String,
+    /// The commit which last changed the line.
+    pub commit_hash: CommitHash,
+    pub author: Option<BranchName>,
+    /// Seconds elapsed since the UNIX epoch when committed.
+    pub committed_at: Option<u64>,
+}
+
+/// Finds the commit which last changed each line of the file.
+///
+/// The history is walked from the head through the trace trees,
+/// and the lines unchanged from a parent are passed on to it by [`FileDiff`].
+/// If a merge commit has the same file as one of its parents, the lines are passed on to that parent only.
+#[derive(Debug, Clone)]
+pub struct Blame<Fs>
+where
+    Fs: FileSystem,
+{
+    head: HeadIo<Fs>,
+    log: Log<Fs>,
+    trace_tree: TraceTreeIo<Fs>,
+    object: ObjIo<Fs>,
+}
+
+impl<Fs> Blame<Fs>
+where
+    Fs: FileSystem + Clone,
+{
+    pub fn new(fs: Fs) -> Blame<Fs> {
+        Self {
+            head: HeadIo::new(fs.clone()),
+            log: Log::new(fs.clone()),
+            trace_tree: TraceTreeIo::new(fs.clone()),
+            object: ObjIo::new(fs),
+        }
+    }
+}
+
+impl<Fs> Blame<Fs>
+where
+    Fs: FileSystem,
+{
+    pub async fn execute(
+        &self,
+        branch_name: &BranchName,
+        file_path: &FilePath,
+    ) -> error::Result<Vec<BlameLine>> {
+        let head = self.head.try_read(branch_name).await?;
+        let Some(file) = self.read_file(&head, file_path).await? else {
+            return Err(error::Error::NotfoundCommittedFile(file_path.clone(), head));
+        };
+        let text = String::from_utf8(self.read_buf(&file).await?.unwrap_or_default())?;
+        let lines = split_lines(&text);
+
+        let mut blamed = vec![None; lines.len()];
+        let mut remaining = lines.len();
+        let mut pending = HashMap::from([(
+            head.clone(),
+            PendingFile {
+                hash: file,
+                text: text.clone(),
+                lines: (0..lines.len()).map(|i| (i, i)).collect(),
+            },
+        )]);
+        let options = LogOptions {
+            topo_order: true,
+            ..LogOptions::default()
+        };
+        let mut log = self.log.iter(head, options).await?;
+        while 0 < remaining {
+            let Some(entry) = log.next().await? else {
+                break;
+            };
+            let Some(file) = pending.remove(&entry.hash) else {
+                continue;
+            };
+            let unchanged = self
+                .pass_to_parents(&entry.commit, file_path, file, &mut pending)
+                .await?;
+            for line in unchanged {
+                blamed[line] = Some((entry.hash.clone(), entry.commit.clone()));
+                remaining -= 1;
+            }
+        }
+
+        Ok(lines
+            .into_iter()
+            .zip(blamed)
+            .enumerate()
+            .filter_map(|(i, (text, blamed))| {
+                let (commit_hash, commit) = blamed?;
+                Some(BlameLine {
+                    line: i + 1,
+                    text: text.trim_end_matches(['\r', '\n']).to_string(),
+                    commit_hash,
+                    author: commit.author,
+                    committed_at: commit.committed_at,
+                })
+            })
+            .collect())
+    }
+
+    /// Passes the lines that exist in the parents on to them,
+    /// and returns the lines changed by the commit.
+    async fn pass_to_parents(
+        &self,
+        commit: &CommitObj,
+        file_path: &FilePath,
+        file: PendingFile,
+        pending: &mut HashMap<CommitHash, PendingFile>,
+    ) -> error::Result<Vec<usize>> {
+        let PendingFile {
+            hash,
+            text,
+            mut lines,
+        } = file;
+        let mut parent_files = Vec::with_capacity(commit.parents.len());
+        for parent in commit.parents.iter() {
+            if let Some(parent_hash) = self.read_file(parent, file_path).await? {
+                if parent_hash == hash {
+                    push_pending(pending, parent, &hash, &text, lines);
+                    return Ok(Vec::new());
+                }
+                parent_files.push((parent, parent_hash));
+            }
+        }
+
+        for (parent, parent_hash) in parent_files {
+            let Some(parent_buf) = self.read_buf(&parent_hash).await? else {
+                continue;
+            };
+            let parent_text = String::from_utf8(parent_buf)?;
+            let diff = FileDiff::from_strings(parent_text.as_str(), text.as_str());
+            let mut to_parent = HashMap::new();
+            for op in diff.diff().ops() {
+                if let DiffOp::Equal {
+                    old_index,
+                    new_index,
+                    len,
+                } = *op
+                {
+                    to_parent.extend((0..len).map(|i| (new_index + i, old_index + i)));
+                }
+            }
+            let (passed, rest) = lines
+                .into_iter()
+                .partition::<Vec<_>, _>(|(_, line)| to_parent.contains_key(line));
+            lines = rest;
+            if !passed.is_empty() {
+                let passed = passed
+                    .into_iter()
+                    .map(|(i, line)| (i, to_parent[&line]))
+                    .collect();
+                push_pending(pending, parent, &parent_hash, &parent_text, passed);
+            }
+        }
+        Ok(lines.into_iter().map(|(i, _)| i).collect())
+    }
+
+    /// Reads the hash of the file in the commit; `None` if it does not exist or is deleted.
+    async fn read_file(
+        &self,
+        commit_hash: &CommitHash,
+        file_path: &FilePath,
+    ) -> error::Result<Option<ObjHash>> {
+        let trace = self.trace_tree.read(commit_hash).await?;
+        let Some(hash) = trace.get(file_path) else {
+            return Ok(None);
+        };
+        match self.object.try_read_obj(hash).await? {
+            Obj::Delete(_) => Ok(None),
+            _ => Ok(Some(hash.clone())),
+        }
+    }
+
+    async fn read_buf(&self, hash: &ObjHash) -> error::Result<Option<Vec<u8>>> {
+        Ok(self.object.try_read_to_file(hash).await?.map(|file| file.0))
+    }
+}
+
+/// The file of the commit whose lines have not been blamed yet.
+#[derive(Debug)]
+struct PendingFile {
+    hash: ObjHash,
+    text: String,
+    /// The pairs of the line index in the blamed file and in this file.
+    lines: Vec<(usize, usize)>,
+}
+
+fn push_pending(
+    pending: &mut HashMap<CommitHash, PendingFile>,
+    commit_hash: &CommitHash,
+    hash: &ObjHash,
+    text: &str,
+    lines: Vec<(usize, usize)>,
+) {
+    pending
+        .entry(commit_hash.clone())
+        .or_insert_with(|| PendingFile {
+            hash: hash.clone(),
+            text: text.to_string(),
+            lines: Vec::new(),
+        })
+        .lines
+        .extend(lines);
+}
+
+#[inline]
+fn split_lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::branch::BranchName;
+    use crate::error;
+    use crate::file_system::memory::MemoryFileSystem;
+    use crate::file_system::FilePath;
+    use crate::object::commit::CommitHash;
+    use crate::operation::blame::Blame;
+    use crate::operation::checkout::Checkout;
+    use crate::operation::commit::Commit;
+    use crate::operation::merge::Merge;
+    use crate::operation::stage::Stage;
+    use crate::tests::init_owner_branch;
+
+    async fn commit(fs: &MemoryFileSystem, branch: &BranchName, text: &str) -> CommitHash {
+        fs.write_sync("hello.txt", text.as_bytes());
+        Stage::new(fs.clone()).execute(branch, ".").await.unwrap();
+        Commit::new(fs.clone())
+            .execute(branch, "commit")
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn blame_lines() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        init_owner_branch(fs.clone()).await;
+        let first = commit(&fs, &branch, "line1\nline2\nline3\n").await;
+        let second = commit(&fs, &branch, "line1\nchanged\nline3\nadded\n").await;
+
+        let lines = Blame::new(fs.clone())
+            .execute(&branch, &FilePath::from("hello.txt"))
+            .await
+            .unwrap();
+        let commits = lines
+            .iter()
+            .map(|line| line.commit_hash.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(commits, vec![first.clone(), second.clone(), first, second]);
+        assert_eq!(lines[1].text, "changed");
+        assert_eq!(lines[1].line, 2);
+        assert_eq!(lines[1].author, Some(branch));
+        assert!(lines[1].committed_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn follow_merged_lines() {
+        let fs = MemoryFileSystem::default();
+        let owner = BranchName::owner();
+        let second = BranchName::from("second");
+        init_owner_branch(fs.clone()).await;
+        let base = commit(&fs, &owner, "line1\nline2\nline3\n").await;
+        Checkout::new(fs.clone()).execute(&second).await.unwrap();
+        let from_second = commit(&fs, &second, "line1\nline2\nsecond\n").await;
+        Checkout::new(fs.clone()).execute(&owner).await.unwrap();
+        let from_owner = commit(&fs, &owner, "owner\nline2\nline3\n").await;
+        Merge::new(fs.clone())
+            .execute(from_second.clone(), owner.clone())
+            .await
+            .unwrap();
+
+        let commits = Blame::new(fs.clone())
+            .execute(&owner, &FilePath::from("hello.txt"))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|line| line.commit_hash)
+            .collect::<Vec<_>>();
+        assert_eq!(commits, vec![from_owner, base, from_second]);
+    }
+
+    #[tokio::test]
+    async fn failed_if_not_committed() {
+        let fs = MemoryFileSystem::default();
+        init_owner_branch(fs.clone()).await;
+        let result = Blame::new(fs.clone())
+            .execute(&BranchName::owner(), &FilePath::from("hello.txt"))
+            .await;
+        assert!(matches!(
+            result,
+            Err(error::Error::NotfoundCommittedFile(..))
+        ));
+    }
+}
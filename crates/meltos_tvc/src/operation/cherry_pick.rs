@@ -0,0 +1,207 @@
+use crate::branch::BranchName;
+use crate::error;
+use crate::file_system::FileSystem;
+use crate::io::atomic::merge_head::MergeKind;
+use crate::io::commit_obj::CommitObjIo;
+use crate::io::trace_tree::TraceTreeIo;
+use crate::object::commit::CommitHash;
+use crate::object::tree::TreeObj;
+use crate::operation::merge::{Merge, MergedStatus};
+
+/// Applies the changes of a commit from its first parent onto the branch.
+#[derive(Debug)]
+pub struct CherryPick<Fs>
+where
+    Fs: FileSystem,
+{
+    merge: Merge<Fs>,
+    commit_obj: CommitObjIo<Fs>,
+    trace_tree: TraceTreeIo<Fs>,
+}
+
+impl<Fs> CherryPick<Fs>
+where
+    Fs: FileSystem + Clone,
+{
+    pub fn new(fs: Fs) -> CherryPick<Fs> {
+        Self {
+            merge: Merge::new(fs.clone()),
+            commit_obj: CommitObjIo::new(fs.clone()),
+            trace_tree: TraceTreeIo::new(fs),
+        }
+    }
+
+    /// Creates the commit which has the same changes and text as `commit_hash` on top of `dist`.
+    ///
+    /// If the changes conflict, the merge remains in progress in the same way as [`Merge::execute`],
+    /// and the commit is created by [`Merge::continue_merge`].
+    pub async fn execute(
+        &self,
+        commit_hash: CommitHash,
+        dist: BranchName,
+    ) -> error::Result<MergedStatus> {
+        let commit = self.commit_obj.read(&commit_hash).await?;
+        let parent_tree = match commit.parents.first() {
+            Some(parent) => self.trace_tree.read(parent).await?,
+            None => TreeObj::default(),
+        };
+        let changes = self.commit_obj.read_commit_tree(&commit_hash).await?;
+        self.merge
+            .apply_changes(
+                MergeKind::CherryPick,
+                commit_hash,
+                &parent_tree,
+                &changes,
+                dist,
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::branch::BranchName;
+    use crate::error;
+    use crate::file_system::memory::MemoryFileSystem;
+    use crate::file_system::{FilePath, FileSystem};
+    use crate::io::atomic::head::HeadIo;
+    use crate::io::atomic::merge_head::MergeKind;
+    use crate::io::commit_obj::CommitObjIo;
+    use crate::operation::checkout::Checkout;
+    use crate::operation::cherry_pick::CherryPick;
+    use crate::operation::commit::Commit;
+    use crate::operation::merge::{Merge, MergedStatus};
+    use crate::operation::stage::Stage;
+    use crate::tests::init_owner_branch;
+
+    #[tokio::test]
+    async fn pick_only_the_commit() {
+        let fs = MemoryFileSystem::default();
+        let owner = BranchName::owner();
+        let second = BranchName::from("second");
+        init_owner_branch(fs.clone()).await;
+        Checkout::new(fs.clone()).execute(&second).await.unwrap();
+
+        fs.write_sync("first.txt", b"first");
+        Stage::new(fs.clone()).execute(&second, ".").await.unwrap();
+        Commit::new(fs.clone())
+            .execute(&second, "first")
+            .await
+            .unwrap();
+        fs.write_sync("picked.txt", b"picked");
+        Stage::new(fs.clone()).execute(&second, ".").await.unwrap();
+        let picked = Commit::new(fs.clone())
+            .execute(&second, "picked")
+            .await
+            .unwrap();
+        fs.delete("first.txt").await.unwrap();
+        fs.delete("picked.txt").await.unwrap();
+
+        Checkout::new(fs.clone()).execute(&owner).await.unwrap();
+        let status = CherryPick::new(fs.clone())
+            .execute(picked.clone(), owner.clone())
+            .await
+            .unwrap();
+        assert_eq!(status, MergedStatus::Merged);
+        assert_eq!(fs.try_read_file("picked.txt").await.unwrap(), b"picked");
+        assert!(fs.read_file("first.txt").await.unwrap().is_none());
+
+        let head = HeadIo::new(fs.clone()).try_read(&owner).await.unwrap();
+        let commit = CommitObjIo::new(fs.clone()).read(&head).await.unwrap();
+        assert_eq!(commit.text.0, "picked");
+        assert_eq!(commit.parents.len(), 1);
+        assert_ne!(head, picked);
+    }
+
+    #[tokio::test]
+    async fn already_picked() {
+        let fs = MemoryFileSystem::default();
+        let owner = BranchName::owner();
+        init_owner_branch(fs.clone()).await;
+        fs.write_sync("hello.txt", b"hello");
+        Stage::new(fs.clone()).execute(&owner, ".").await.unwrap();
+        let commit_hash = Commit::new(fs.clone())
+            .execute(&owner, "hello")
+            .await
+            .unwrap();
+
+        let status = CherryPick::new(fs.clone())
+            .execute(commit_hash, owner)
+            .await
+            .unwrap();
+        assert_eq!(status, MergedStatus::FastDist);
+    }
+
+    #[tokio::test]
+    async fn continue_after_conflict() {
+        let fs = MemoryFileSystem::default();
+        let owner = BranchName::owner();
+        let second = BranchName::from("second");
+        fs.write_sync("hello.txt", b"hello");
+        init_owner_branch(fs.clone()).await;
+        Checkout::new(fs.clone()).execute(&second).await.unwrap();
+        fs.write_sync("hello.txt", b"second");
+        Stage::new(fs.clone()).execute(&second, ".").await.unwrap();
+        let picked = Commit::new(fs.clone())
+            .execute(&second, "picked")
+            .await
+            .unwrap();
+
+        Checkout::new(fs.clone()).execute(&owner).await.unwrap();
+        fs.write_sync("hello.txt", b"owner");
+        Stage::new(fs.clone()).execute(&owner, ".").await.unwrap();
+        Commit::new(fs.clone())
+            .execute(&owner, "owner")
+            .await
+            .unwrap();
+
+        let status = CherryPick::new(fs.clone())
+            .execute(picked.clone(), owner.clone())
+            .await
+            .unwrap();
+        assert!(matches!(status, MergedStatus::Conflicted(_)));
+        let merge = Merge::new(fs.clone());
+        let merge_head = merge.merge_head().await.unwrap().unwrap();
+        assert_eq!(merge_head.kind, MergeKind::CherryPick);
+        assert_eq!(merge_head.source, picked);
+
+        fs.write_sync("hello.txt", b"resolved");
+        let commit_hash = merge.continue_merge().await.unwrap();
+        let commit = CommitObjIo::new(fs.clone())
+            .read(&commit_hash)
+            .await
+            .unwrap();
+        assert_eq!(commit.text.0, "picked");
+        assert_eq!(commit.parents.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn failed_if_unrelated_file_staged() {
+        let fs = MemoryFileSystem::default();
+        let owner = BranchName::owner();
+        let second = BranchName::from("second");
+        init_owner_branch(fs.clone()).await;
+        Checkout::new(fs.clone()).execute(&second).await.unwrap();
+
+        fs.write_sync("picked.txt", b"picked");
+        Stage::new(fs.clone()).execute(&second, ".").await.unwrap();
+        let picked = Commit::new(fs.clone())
+            .execute(&second, "picked")
+            .await
+            .unwrap();
+        fs.delete("picked.txt").await.unwrap();
+
+        Checkout::new(fs.clone()).execute(&owner).await.unwrap();
+        fs.write_sync("staged.txt", b"staged");
+        Stage::new(fs.clone()).execute(&owner, ".").await.unwrap();
+
+        let result = CherryPick::new(fs.clone()).execute(picked, owner).await;
+        match result {
+            Err(error::Error::LocalChangesWouldBeOverwritten(files)) => {
+                assert_eq!(files, vec![FilePath::from("staged.txt")]);
+            }
+            _ => panic!("expected LocalChangesWouldBeOverwritten but was {result:?}"),
+        }
+        assert!(fs.read_file("picked.txt").await.unwrap().is_none());
+    }
+}
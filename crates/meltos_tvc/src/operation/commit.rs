@@ -1,4 +1,3 @@
-use meltos_util::console_log;
 use crate::branch::BranchName;
 use crate::error;
 use crate::file_system::FileSystem;
@@ -49,6 +48,41 @@ where
         &self,
         branch_name: &BranchName,
         commit_text: impl Into<CommitText>,
+    ) -> error::Result<CommitHash> {
+        self.commit_stage(branch_name, commit_text, None, "commit")
+            .await
+    }
+
+    /// Commits the stage, and records the movement of the head in the reflog with the name of `operation`.
+    pub(crate) async fn execute_as(
+        &self,
+        branch_name: &BranchName,
+        commit_text: impl Into<CommitText>,
+        operation: &str,
+    ) -> error::Result<CommitHash> {
+        self.commit_stage(branch_name, commit_text, None, operation)
+            .await
+    }
+
+    /// Commits the staged merge result.
+    ///
+    /// The created commit has two parents: the head of `branch_name` and `merge_source`.
+    pub async fn execute_merge(
+        &self,
+        branch_name: &BranchName,
+        commit_text: impl Into<CommitText>,
+        merge_source: &CommitHash,
+    ) -> error::Result<CommitHash> {
+        self.commit_stage(branch_name, commit_text, Some(merge_source), "merge")
+            .await
+    }
+
+    async fn commit_stage(
+        &self,
+        branch_name: &BranchName,
+        commit_text: impl Into<CommitText>,
+        merge_source: Option<&CommitHash>,
+        operation: &str,
     ) -> error::Result<CommitHash> {
         let Some(stage_tree) = self.staging.read().await? else {
             return Err(error::Error::NotfoundStages);
@@ -57,12 +91,13 @@ where
         let stage_meta = stage_tree.as_meta()?;
         self.object.write_obj(&stage_tree).await?;
 
-        let commit = self
+        let mut commit = self
             .commit_obj
             .create(commit_text, stage_meta.hash, branch_name)
             .await?;
+        commit.parents.extend(merge_source.cloned());
         let pre_head = self.head.read(branch_name).await?;
-        let head_commit_hash = self.commit(branch_name, commit).await?;
+        let head_commit_hash = self.commit(branch_name, commit, operation).await?;
         self.update_trace(stage_tree, &head_commit_hash, &pre_head)
             .await?;
         self.head.write(branch_name, &head_commit_hash).await?;
@@ -78,9 +113,9 @@ where
         let null_staging_meta = null_staging.as_meta()?;
         let null_commit = self.create_null_commit(null_staging_meta);
         self.head
-            .write(branch_name, &CommitHash(null_commit.as_meta()?.hash))
+            .update(branch_name, &CommitHash(null_commit.as_meta()?.hash), "init")
             .await?;
-        let commit_hash = self.commit(branch_name, null_commit).await?;
+        let commit_hash = self.commit(branch_name, null_commit, "init").await?;
         self.update_trace(null_staging, &commit_hash, &None).await?;
         self.staging.reset().await?;
         self.head.write(branch_name, &commit_hash).await?;
@@ -92,6 +127,8 @@ where
             parents: Vec::with_capacity(0),
             text: CommitText::from("Initial Commit"),
             committed_objs_tree: null_staging.hash,
+            author: None,
+            committed_at: None,
         }
     }
 
@@ -107,7 +144,6 @@ where
         };
 
         trace_tree.replace_by(staging_tree);
-        console_log!("{trace_tree:#?}");
         self.trace_tree.write(&trace_tree, commit_hash).await?;
 
         Ok(())
@@ -117,11 +153,12 @@ where
         &self,
         branch_name: &BranchName,
         commit: CommitObj,
+        operation: &str,
     ) -> error::Result<CommitHash> {
         let commit_meta = commit.as_meta()?;
         self.object.write_obj(&commit).await?;
         self.head
-            .write(branch_name, &CommitHash(commit_meta.hash.clone()))
+            .update(branch_name, &CommitHash(commit_meta.hash.clone()), operation)
             .await?;
         self.local_commits
             .append(CommitHash(commit_meta.hash.clone()), branch_name)
@@ -201,8 +238,11 @@ mod tests {
                 parents: vec![null_commit_hash],
                 text: CommitText::from("test"),
                 committed_objs_tree: tree.as_meta().unwrap().hash,
+                author: Some(branch),
+                committed_at: commit.committed_at,
             }
         );
+        assert!(commit.committed_at.is_some());
     }
 
     #[tokio::test]
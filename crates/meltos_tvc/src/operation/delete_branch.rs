@@ -0,0 +1,203 @@
+use crate::branch::BranchName;
+use crate::error;
+use crate::file_system::FileSystem;
+use crate::io::atomic::head::HeadIo;
+use crate::io::atomic::local_commits::LocalCommitsIo;
+use crate::io::atomic::merge_head::MergeHeadIo;
+use crate::io::atomic::work_branch::WorkingIo;
+use crate::io::commit_hashes::CommitHashIo;
+use crate::object::commit::CommitHash;
+
+/// Deletes the head, the local commits and the reflog of the branch.
+///
+/// The commits themselves are left for [`Gc`](crate::operation::gc::Gc).
+#[derive(Debug, Clone)]
+pub struct DeleteBranch<Fs>
+where
+    Fs: FileSystem,
+{
+    head: HeadIo<Fs>,
+    local_commits: LocalCommitsIo<Fs>,
+    merge_head: MergeHeadIo<Fs>,
+    working: WorkingIo<Fs>,
+    commit_hashes: CommitHashIo<Fs>,
+}
+
+impl<Fs> DeleteBranch<Fs>
+where
+    Fs: FileSystem + Clone,
+{
+    #[inline]
+    pub fn new(fs: Fs) -> DeleteBranch<Fs> {
+        Self {
+            head: HeadIo::new(fs.clone()),
+            local_commits: LocalCommitsIo::new(fs.clone()),
+            merge_head: MergeHeadIo::new(fs.clone()),
+            working: WorkingIo::new(fs.clone()),
+            commit_hashes: CommitHashIo::new(fs),
+        }
+    }
+}
+
+impl<Fs> DeleteBranch<Fs>
+where
+    Fs: FileSystem,
+{
+    /// The working branch and the branch being merged into cannot be deleted.
+    ///
+    /// If the branch has local commits that are not reachable from other branches,
+    /// it is deleted only if `force` is true, because those commits would be lost.
+    pub async fn execute(&self, branch_name: &BranchName, force: bool) -> error::Result {
+        branch_name.validate()?;
+        let head = self.head.try_read(branch_name).await?;
+        let working = self.working.read().await?.unwrap_or(BranchName::owner());
+        if &working == branch_name {
+            return Err(error::Error::DeleteWorkingBranch(branch_name.clone()));
+        }
+        if self
+            .merge_head
+            .read()
+            .await?
+            .is_some_and(|merge_head| &merge_head.dist == branch_name)
+        {
+            return Err(error::Error::MergeInProgress(branch_name.clone()));
+        }
+        let has_local_commits = self
+            .local_commits
+            .read(branch_name)
+            .await?
+            .is_some_and(|local_commits| !local_commits.is_empty());
+        if !force && has_local_commits && !self.merged_into_others(branch_name, &head).await? {
+            return Err(error::Error::UnmergedLocalCommits(branch_name.clone()));
+        }
+
+        self.head.delete(branch_name).await?;
+        self.local_commits.delete(branch_name).await
+    }
+
+    async fn merged_into_others(
+        &self,
+        branch_name: &BranchName,
+        head: &CommitHash,
+    ) -> error::Result<bool> {
+        for (other, other_head) in self.head.read_all().await? {
+            if &other == branch_name {
+                continue;
+            }
+            if self
+                .commit_hashes
+                .read_all(other_head, &None)
+                .await?
+                .contains(head)
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::branch::BranchName;
+    use crate::error;
+    use crate::file_system::memory::MemoryFileSystem;
+    use crate::io::atomic::head::HeadIo;
+    use crate::io::atomic::local_commits::LocalCommitsIo;
+    use crate::operation::checkout::Checkout;
+    use crate::operation::commit::Commit;
+    use crate::operation::delete_branch::DeleteBranch;
+    use crate::operation::merge::Merge;
+    use crate::operation::stage::Stage;
+    use crate::tests::init_owner_branch;
+
+    #[tokio::test]
+    async fn delete_branch() {
+        let fs = MemoryFileSystem::default();
+        let second = BranchName::from("second");
+        init_owner_branch(fs.clone()).await;
+        Checkout::new(fs.clone()).execute(&second).await.unwrap();
+        Checkout::new(fs.clone())
+            .execute(&BranchName::owner())
+            .await
+            .unwrap();
+
+        DeleteBranch::new(fs.clone())
+            .execute(&second, false)
+            .await
+            .unwrap();
+        assert!(HeadIo::new(fs.clone()).read(&second).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn failed_if_working_branch() {
+        let fs = MemoryFileSystem::default();
+        init_owner_branch(fs.clone()).await;
+        let result = DeleteBranch::new(fs.clone())
+            .execute(&BranchName::owner(), true)
+            .await;
+        assert!(matches!(result, Err(error::Error::DeleteWorkingBranch(_))));
+    }
+
+    #[tokio::test]
+    async fn failed_if_unmerged_local_commits() {
+        let fs = MemoryFileSystem::default();
+        let owner = BranchName::owner();
+        let second = BranchName::from("second");
+        init_owner_branch(fs.clone()).await;
+        let checkout = Checkout::new(fs.clone());
+        checkout.execute(&second).await.unwrap();
+        fs.write_sync("second.txt", b"second");
+        Stage::new(fs.clone()).execute(&second, ".").await.unwrap();
+        let source = Commit::new(fs.clone())
+            .execute(&second, "second")
+            .await
+            .unwrap();
+        checkout.execute(&owner).await.unwrap();
+
+        let delete_branch = DeleteBranch::new(fs.clone());
+        let result = delete_branch.execute(&second, false).await;
+        assert!(matches!(result, Err(error::Error::UnmergedLocalCommits(_))));
+
+        Merge::new(fs.clone())
+            .execute(source, owner.clone())
+            .await
+            .unwrap();
+        delete_branch.execute(&second, false).await.unwrap();
+        let local_commits = LocalCommitsIo::new(fs.clone());
+        assert!(local_commits.read(&second).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn delete_unmerged_branch_if_forced() {
+        let fs = MemoryFileSystem::default();
+        let second = BranchName::from("second");
+        init_owner_branch(fs.clone()).await;
+        let checkout = Checkout::new(fs.clone());
+        checkout.execute(&second).await.unwrap();
+        fs.write_sync("second.txt", b"second");
+        Stage::new(fs.clone()).execute(&second, ".").await.unwrap();
+        Commit::new(fs.clone())
+            .execute(&second, "second")
+            .await
+            .unwrap();
+        checkout.execute(&BranchName::owner()).await.unwrap();
+
+        DeleteBranch::new(fs.clone())
+            .execute(&second, true)
+            .await
+            .unwrap();
+        assert!(HeadIo::new(fs.clone()).read(&second).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn failed_if_branch_name_is_invalid() {
+        let fs = MemoryFileSystem::default();
+        init_owner_branch(fs.clone()).await;
+        let result = DeleteBranch::new(fs.clone())
+            .execute(&BranchName::from("../../objects/.."), true)
+            .await;
+        assert!(matches!(result, Err(error::Error::InvalidBranchName(_))));
+        assert!(HeadIo::new(fs.clone()).read(&BranchName::owner()).await.unwrap().is_some());
+    }
+}
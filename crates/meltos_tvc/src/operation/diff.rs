@@ -0,0 +1,397 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::branch::BranchName;
+use crate::error;
+use crate::file_system::{FilePath, FileSystem};
+use crate::io::atomic::head::HeadIo;
+use crate::io::atomic::object::ObjIo;
+use crate::io::atomic::sparse_checkout::SparseCheckoutIo;
+use crate::io::atomic::staging::StagingIo;
+use crate::io::diff::file::FileDiff;
+use crate::io::diff::hunk::DiffHunk;
+use crate::io::trace_tree::TraceTreeIo;
+use crate::io::workspace::WorkspaceIo;
+use crate::object::commit::CommitHash;
+use crate::object::tree::TreeObj;
+use crate::object::{AsMeta, Obj, ObjHash};
+
+/// The number of unchanged lines shown around each hunk.
+pub const CONTEXT_LEN: usize = 3;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum DiffTarget {
+    /// The files in the workspace, except ignored files.
+    Workspace,
+    /// The head of the branch with the staged files applied.
+    Stage,
+    /// The head of the branch.
+    Head,
+    Commit(CommitHash),
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum FileStatus {
+    Added,
+    Modified,
+    Deleted,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct FileChange {
+    pub file_path: FilePath,
+    pub status: FileStatus,
+    pub old_hash: Option<ObjHash>,
+    pub new_hash: Option<ObjHash>,
+    /// `None` if either side is not a utf-8 text.
+    pub hunks: Option<Vec<DiffHunk>>,
+}
+
+impl FileChange {
+    /// Renders the change in the unified diff format.
+    pub fn unified_diff(&self) -> String {
+        let old_path = match self.status {
+            FileStatus::Added => "/dev/null".to_string(),
+            _ => format!("a/{}", self.file_path),
+        };
+        let new_path = match self.status {
+            FileStatus::Deleted => "/dev/null".to_string(),
+            _ => format!("b/{}", self.file_path),
+        };
+        let Some(hunks) = self.hunks.as_ref() else {
+            return format!("Binary files {old_path} and {new_path} differ\n");
+        };
+
+        let mut text = format!("--- {old_path}\n+++ {new_path}\n");
+        for hunk in hunks {
+            text.push_str(&hunk.to_string());
+        }
+        text
+    }
+}
+
+/// Renders all changes in the unified diff format.
+pub fn unified_diff(changes: &[FileChange]) -> String {
+    changes.iter().map(FileChange::unified_diff).collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct Diff<Fs>
+where
+    Fs: FileSystem,
+{
+    head: HeadIo<Fs>,
+    staging: StagingIo<Fs>,
+    trace_tree: TraceTreeIo<Fs>,
+    object: ObjIo<Fs>,
+    workspace: WorkspaceIo<Fs>,
+    sparse_checkout: SparseCheckoutIo<Fs>,
+}
+
+impl<Fs> Diff<Fs>
+where
+    Fs: FileSystem + Clone,
+{
+    #[inline]
+    pub fn new(fs: Fs) -> Diff<Fs> {
+        Self {
+            head: HeadIo::new(fs.clone()),
+            staging: StagingIo::new(fs.clone()),
+            trace_tree: TraceTreeIo::new(fs.clone()),
+            object: ObjIo::new(fs.clone()),
+            workspace: WorkspaceIo::new(fs.clone()),
+            sparse_checkout: SparseCheckoutIo::new(fs),
+        }
+    }
+}
+
+impl<Fs> Diff<Fs>
+where
+    Fs: FileSystem,
+{
+    /// Returns the changed files from `old` to `new` sorted by their paths.
+    ///
+    /// `branch_name` is used to resolve [`DiffTarget::Head`] and [`DiffTarget::Stage`].
+    /// The files outside the sparse-checkout patterns are not regarded as deleted from the workspace.
+    pub async fn execute(
+        &self,
+        branch_name: &BranchName,
+        old: &DiffTarget,
+        new: &DiffTarget,
+    ) -> error::Result<Vec<FileChange>> {
+        let old = self.snapshot(branch_name, old).await?;
+        let new = self.snapshot(branch_name, new).await?;
+        let file_paths = old
+            .tree
+            .keys()
+            .chain(new.tree.keys())
+            .collect::<BTreeSet<&FilePath>>();
+        let sparse = self.sparse_checkout.read().await?;
+
+        let mut changes = Vec::new();
+        for file_path in file_paths {
+            let old_hash = old.tree.get(file_path);
+            let new_hash = new.tree.get(file_path);
+            if old_hash == new_hash {
+                continue;
+            }
+            let not_checked_out = (old.workspace && old_hash.is_none())
+                || (new.workspace && new_hash.is_none());
+            if not_checked_out && !sparse.contains(file_path) {
+                continue;
+            }
+            let old_buf = self.read_file(&old, old_hash).await?;
+            let new_buf = self.read_file(&new, new_hash).await?;
+            let status = match (&old_buf, &new_buf) {
+                (None, None) => continue,
+                (None, Some(_)) => FileStatus::Added,
+                (Some(_), None) => FileStatus::Deleted,
+                (Some(_), Some(_)) => FileStatus::Modified,
+            };
+            changes.push(FileChange {
+                file_path: file_path.clone(),
+                status,
+                old_hash: old_buf.as_ref().and(old_hash.cloned()),
+                new_hash: new_buf.as_ref().and(new_hash.cloned()),
+                hunks: hunks(old_buf, new_buf),
+            });
+        }
+        Ok(changes)
+    }
+
+    async fn snapshot(&self, branch_name: &BranchName, target: &DiffTarget) -> error::Result<Snapshot> {
+        match target {
+            DiffTarget::Workspace => self.workspace_snapshot().await,
+            DiffTarget::Stage => {
+                let mut tree = self.head_tree(branch_name).await?;
+                if let Some(stage) = self.staging.read().await? {
+                    tree.replace_by(stage);
+                }
+                Ok(Snapshot::from(tree))
+            }
+            DiffTarget::Head => Ok(Snapshot::from(self.head_tree(branch_name).await?)),
+            DiffTarget::Commit(commit_hash) => {
+                Ok(Snapshot::from(self.trace_tree.read(commit_hash).await?))
+            }
+        }
+    }
+
+    async fn workspace_snapshot(&self) -> error::Result<Snapshot> {
+        let mut snapshot = Snapshot {
+            workspace: true,
+            ..Snapshot::default()
+        };
+        let mut objs = self.workspace.convert_to_objs(".").await?;
+        while let Some(result) = objs.next().await {
+            let (file_path, file_obj) = result?;
+            let hash = file_obj.as_meta()?.hash;
+            snapshot.tree.insert(file_path, hash.clone());
+            snapshot.bufs.insert(hash, file_obj.0);
+        }
+        Ok(snapshot)
+    }
+
+    async fn head_tree(&self, branch_name: &BranchName) -> error::Result<TreeObj> {
+        match self.head.read(branch_name).await? {
+            Some(head) => self.trace_tree.read(&head).await,
+            None => Ok(TreeObj::default()),
+        }
+    }
+
+    /// Reads the file content; `None` if the file does not exist or was deleted.
+    async fn read_file(
+        &self,
+        snapshot: &Snapshot,
+        hash: Option<&ObjHash>,
+    ) -> error::Result<Option<Vec<u8>>> {
+        let Some(hash) = hash else {
+            return Ok(None);
+        };
+        if let Some(buf) = snapshot.bufs.get(hash) {
+            return Ok(Some(buf.clone()));
+        }
+        match self.object.try_read_obj(hash).await? {
+            Obj::File(file) => Ok(Some(file.0)),
+            Obj::Delete(_) => Ok(None),
+            obj => Err(error::Error::InvalidWorkspaceObj(obj.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Snapshot {
+    tree: TreeObj,
+    /// The contents of the files not written to the objects, such as the workspace files.
+    bufs: HashMap<ObjHash, Vec<u8>>,
+    /// Whether the snapshot is of the workspace, where the files outside the sparse-checkout patterns do not exist.
+    workspace: bool,
+}
+
+impl From<TreeObj> for Snapshot {
+    #[inline]
+    fn from(tree: TreeObj) -> Self {
+        Self {
+            tree,
+            bufs: HashMap::new(),
+            workspace: false,
+        }
+    }
+}
+
+fn hunks(old: Option<Vec<u8>>, new: Option<Vec<u8>>) -> Option<Vec<DiffHunk>> {
+    let old = String::from_utf8(old.unwrap_or_default()).ok()?;
+    let new = String::from_utf8(new.unwrap_or_default()).ok()?;
+    Some(FileDiff::from_strings(old, new).hunks(CONTEXT_LEN))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::branch::BranchName;
+    use crate::file_system::memory::MemoryFileSystem;
+    use crate::file_system::{FilePath, FileSystem};
+    use crate::operation::commit::Commit;
+    use crate::operation::diff::{unified_diff, Diff, DiffTarget, FileStatus};
+    use crate::operation::sparse_checkout::SparseCheckout;
+    use crate::operation::stage::Stage;
+    use crate::tests::init_owner_branch;
+
+    #[tokio::test]
+    async fn workspace_and_head() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        fs.write_sync("modified.txt", b"hello\nworld\n");
+        fs.write_sync("deleted.txt", b"deleted");
+        init_owner_branch(fs.clone()).await;
+
+        fs.write_sync("modified.txt", b"hello\nrust\n");
+        fs.write_sync("added.txt", b"added\n");
+        fs.delete("deleted.txt").await.unwrap();
+
+        let changes = Diff::new(fs.clone())
+            .execute(&branch, &DiffTarget::Head, &DiffTarget::Workspace)
+            .await
+            .unwrap();
+        let statuses = changes
+            .iter()
+            .map(|change| (change.file_path.clone(), change.status))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            vec![
+                (FilePath::from("added.txt"), FileStatus::Added),
+                (FilePath::from("deleted.txt"), FileStatus::Deleted),
+                (FilePath::from("modified.txt"), FileStatus::Modified),
+            ]
+        );
+
+        let hunks = changes[2].hunks.as_ref().unwrap();
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0].lines[1].old_line, Some(2));
+        assert_eq!(hunks[0].lines[1].new_line, None);
+        assert_eq!(hunks[0].lines[2].old_line, None);
+        assert_eq!(hunks[0].lines[2].new_line, Some(2));
+    }
+
+    #[tokio::test]
+    async fn files_outside_sparse_patterns_are_not_deleted() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        fs.write_sync("src/main.rs", b"main");
+        fs.write_sync("docs/README.md", b"readme");
+        init_owner_branch(fs.clone()).await;
+        SparseCheckout::new(fs.clone())
+            .set(&["/src/".to_string()])
+            .await
+            .unwrap();
+        fs.write_sync("src/main.rs", b"changed");
+
+        let changes = Diff::new(fs.clone())
+            .execute(&branch, &DiffTarget::Head, &DiffTarget::Workspace)
+            .await
+            .unwrap();
+        let statuses = changes
+            .iter()
+            .map(|change| (change.file_path.clone(), change.status))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            vec![(FilePath::from("src/main.rs"), FileStatus::Modified)]
+        );
+    }
+
+    #[tokio::test]
+    async fn stage_and_head() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        init_owner_branch(fs.clone()).await;
+
+        fs.write_sync("staged.txt", b"staged");
+        Stage::new(fs.clone()).execute(&branch, "staged.txt").await.unwrap();
+        fs.write_sync("unstaged.txt", b"unstaged");
+
+        let changes = Diff::new(fs.clone())
+            .execute(&branch, &DiffTarget::Head, &DiffTarget::Stage)
+            .await
+            .unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].file_path, FilePath::from("staged.txt"));
+        assert_eq!(changes[0].status, FileStatus::Added);
+    }
+
+    #[tokio::test]
+    async fn between_commits() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        fs.write_sync("hello.txt", b"hello");
+        let commit1 = init_owner_branch(fs.clone()).await;
+
+        fs.delete("hello.txt").await.unwrap();
+        Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        let commit2 = Commit::new(fs.clone()).execute(&branch, "delete").await.unwrap();
+
+        let diff = Diff::new(fs.clone());
+        let changes = diff
+            .execute(
+                &branch,
+                &DiffTarget::Commit(commit1.clone()),
+                &DiffTarget::Commit(commit2.clone()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].status, FileStatus::Deleted);
+        assert!(changes[0].old_hash.is_some());
+        assert_eq!(changes[0].new_hash, None);
+
+        let changes = diff
+            .execute(&branch, &DiffTarget::Commit(commit2), &DiffTarget::Commit(commit1))
+            .await
+            .unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].status, FileStatus::Added);
+    }
+
+    #[tokio::test]
+    async fn render_unified_diff() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        fs.write_sync("hello.txt", b"hello\nworld\n");
+        init_owner_branch(fs.clone()).await;
+
+        fs.write_sync("hello.txt", b"hello\nrust\n");
+        fs.write_sync("binary", &[0xff, 0xfe]);
+        let changes = Diff::new(fs.clone())
+            .execute(&branch, &DiffTarget::Head, &DiffTarget::Workspace)
+            .await
+            .unwrap();
+        assert_eq!(
+            unified_diff(&changes),
+            "Binary files /dev/null and b/binary differ\n\
+            --- a/hello.txt\n\
+            +++ b/hello.txt\n\
+            @@ -1,2 +1,2 @@\n \
+            hello\n\
+            -world\n\
+            +rust\n"
+        );
+    }
+}
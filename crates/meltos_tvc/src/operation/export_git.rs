@@ -0,0 +1,308 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::branch::BranchName;
+use crate::error;
+use crate::file_system::{FileMode, FilePath, FileSystem};
+use crate::io::atomic::head::HeadIo;
+use crate::io::atomic::object::ObjIo;
+use crate::io::commit_hashes::CommitHashIo;
+use crate::io::commit_obj::CommitObjIo;
+use crate::io::trace_tree::TraceTreeIo;
+use crate::object::commit::{CommitHash, CommitObj};
+use crate::object::tree::TreeObj;
+use crate::object::{Obj, ObjHash};
+
+/// Writes the history of a branch as a `git fast-import` stream,
+/// so that the work done in a room can be brought back into a git repository.
+///
+/// Each commit modifies the files changed from its first parent, and the other parents are written as merges.
+#[derive(Debug, Clone)]
+pub struct ExportGit<Fs>
+where
+    Fs: FileSystem,
+{
+    head: HeadIo<Fs>,
+    commit_hashes: CommitHashIo<Fs>,
+    commit_obj: CommitObjIo<Fs>,
+    trace_tree: TraceTreeIo<Fs>,
+    object: ObjIo<Fs>,
+}
+
+impl<Fs> ExportGit<Fs>
+where
+    Fs: FileSystem + Clone,
+{
+    pub fn new(fs: Fs) -> ExportGit<Fs> {
+        Self {
+            head: HeadIo::new(fs.clone()),
+            commit_hashes: CommitHashIo::new(fs.clone()),
+            commit_obj: CommitObjIo::new(fs.clone()),
+            trace_tree: TraceTreeIo::new(fs.clone()),
+            object: ObjIo::new(fs),
+        }
+    }
+}
+
+impl<Fs> ExportGit<Fs>
+where
+    Fs: FileSystem,
+{
+    /// Returns the stream that updates `refs/heads/{branch_name}` to the head of the branch.
+    pub async fn execute(&self, branch_name: &BranchName) -> error::Result<Vec<u8>> {
+        let head = self.head.try_read(branch_name).await?;
+        let mut commits = HashMap::new();
+        for commit_hash in self.commit_hashes.read_all(head.clone(), &None).await? {
+            let commit = self.commit_obj.read(&commit_hash).await?;
+            commits.insert(commit_hash, commit);
+        }
+
+        let ref_name = format!("refs/heads/{branch_name}");
+        let mut stream = FastImportStream::default();
+        for commit_hash in parents_first(&head, &commits) {
+            let commit = &commits[&commit_hash];
+            let trace_tree = self.trace_tree.read(&commit_hash).await?;
+            let parent_trace_tree = match commit.parents.first() {
+                Some(parent) => self.trace_tree.read(parent).await?,
+                None => TreeObj::default(),
+            };
+
+            let mut changes = Vec::new();
+            for (file_path, hash) in sorted(&trace_tree) {
+                let mode = trace_tree.mode(file_path);
+                if !parent_trace_tree.changed(file_path, hash, mode) {
+                    continue;
+                }
+                match self.object.try_read_obj(hash).await? {
+                    Obj::File(file) => {
+                        let mark = stream.blob(hash, &file.0);
+                        changes.push(Change::Modify(file_path.clone(), mark, mode));
+                    }
+                    Obj::Delete(_) => changes.push(Change::Delete(file_path.clone())),
+                    obj => {
+                        return Err(error::Error::InvalidWorkspaceObj(obj.to_string()));
+                    }
+                }
+            }
+            stream.commit(&ref_name, &commit_hash, commit, &changes);
+        }
+        Ok(stream.buf)
+    }
+}
+
+enum Change {
+    Modify(FilePath, usize, FileMode),
+    Delete(FilePath),
+}
+
+#[derive(Default)]
+struct FastImportStream {
+    buf: Vec<u8>,
+    next_mark: usize,
+    blob_marks: HashMap<ObjHash, usize>,
+    commit_marks: HashMap<CommitHash, usize>,
+}
+
+impl FastImportStream {
+    /// Writes the file contents if they have not been written yet, and returns the mark referring to them.
+    fn blob(&mut self, hash: &ObjHash, contents: &[u8]) -> usize {
+        if let Some(mark) = self.blob_marks.get(hash) {
+            return *mark;
+        }
+        let mark = self.mark();
+        self.buf.extend(format!("blob\nmark :{mark}\n").as_bytes());
+        self.data(contents);
+        self.blob_marks.insert(hash.clone(), mark);
+        mark
+    }
+
+    fn commit(
+        &mut self,
+        ref_name: &str,
+        commit_hash: &CommitHash,
+        commit: &CommitObj,
+        changes: &[Change],
+    ) {
+        if commit.parents.is_empty() {
+            // Otherwise the root commit would be based on the commit the ref already points to.
+            self.buf.extend(format!("reset {ref_name}\n").as_bytes());
+        }
+        let mark = self.mark();
+        let author = commit
+            .author
+            .as_ref()
+            .map(|author| author.0.as_str())
+            .unwrap_or("meltos");
+        let committed_at = commit.committed_at.unwrap_or_default();
+        self.buf.extend(
+            format!(
+                "commit {ref_name}\nmark :{mark}\ncommitter {author} <> {committed_at} +0000\n"
+            )
+            .as_bytes(),
+        );
+        self.data(commit.text.as_bytes());
+        for (i, parent) in commit.parents.iter().enumerate() {
+            let command = if i == 0 { "from" } else { "merge" };
+            let parent_mark = self.commit_marks[parent];
+            self.buf
+                .extend(format!("{command} :{parent_mark}\n").as_bytes());
+        }
+        for change in changes {
+            let line = match change {
+                Change::Modify(file_path, mark, mode) => {
+                    format!("M {} :{mark} {}\n", mode.as_octal(), quote(file_path))
+                }
+                Change::Delete(file_path) => format!("D {}\n", quote(file_path)),
+            };
+            self.buf.extend(line.as_bytes());
+        }
+        self.buf.push(b'\n');
+        self.commit_marks.insert(commit_hash.clone(), mark);
+    }
+
+    fn data(&mut self, data: &[u8]) {
+        self.buf.extend(format!("data {}\n", data.len()).as_bytes());
+        self.buf.extend(data);
+        self.buf.push(b'\n');
+    }
+
+    #[inline]
+    fn mark(&mut self) -> usize {
+        self.next_mark += 1;
+        self.next_mark
+    }
+}
+
+/// Orders the commits reachable from `head` so that the parents come first.
+fn parents_first(head: &CommitHash, commits: &HashMap<CommitHash, CommitObj>) -> Vec<CommitHash> {
+    let mut order = Vec::with_capacity(commits.len());
+    let mut visited = HashSet::new();
+    let mut stack = vec![(head.clone(), false)];
+    while let Some((commit_hash, visited_parents)) = stack.pop() {
+        if visited_parents {
+            order.push(commit_hash);
+            continue;
+        }
+        if !visited.insert(commit_hash.clone()) {
+            continue;
+        }
+        stack.push((commit_hash.clone(), true));
+        for parent in commits[&commit_hash].parents.iter().rev() {
+            if !visited.contains(parent) {
+                stack.push((parent.clone(), false));
+            }
+        }
+    }
+    order
+}
+
+fn sorted(tree: &TreeObj) -> Vec<(&FilePath, &ObjHash)> {
+    let mut entries = tree.iter().collect::<Vec<_>>();
+    entries.sort();
+    entries
+}
+
+/// Quotes the path in the C style if it cannot be written as is.
+fn quote(file_path: &FilePath) -> String {
+    if !file_path.starts_with('"') && !file_path.contains('\n') {
+        return file_path.0.clone();
+    }
+    let mut quoted = String::from("\"");
+    for c in file_path.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::branch::BranchName;
+    use crate::file_system::memory::MemoryFileSystem;
+    use crate::file_system::FileSystem;
+    use crate::operation::checkout::Checkout;
+    use crate::operation::commit::Commit;
+    use crate::operation::export_git::ExportGit;
+    use crate::operation::merge::Merge;
+    use crate::operation::stage::Stage;
+    use crate::tests::init_owner_branch;
+
+    #[tokio::test]
+    async fn export_commits_and_changes() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        init_owner_branch(fs.clone()).await;
+        let stage = Stage::new(fs.clone());
+        let commit = Commit::new(fs.clone());
+
+        fs.write_sync("hello.txt", b"hello");
+        fs.write_sync("src/main.rs", b"fn main(){}");
+        stage.execute(&branch, ".").await.unwrap();
+        commit.execute(&branch, "add files").await.unwrap();
+
+        fs.delete("src/main.rs").await.unwrap();
+        fs.write_sync("hello.txt", b"hello");
+        stage.execute(&branch, ".").await.unwrap();
+        commit.execute(&branch, "remove main").await.unwrap();
+
+        let stream = ExportGit::new(fs.clone()).execute(&branch).await.unwrap();
+        let stream = String::from_utf8(stream).unwrap();
+        let commits = stream
+            .split("commit refs/heads/owner\n")
+            .collect::<Vec<_>>();
+        assert_eq!(commits.len(), 4);
+        assert!(commits[0].starts_with("reset refs/heads/owner\n"));
+        assert!(commits[2].contains("data 9\nadd files\nfrom :1\n"));
+        assert!(commits[2].contains("M 100644 :3 src/main.rs\n"));
+        assert!(commits[1].contains("blob\nmark :2\ndata 5\nhello\n"));
+        assert!(commits[3].contains("D src/main.rs\n"));
+        assert!(!commits[3].contains("hello.txt"));
+    }
+
+    #[tokio::test]
+    async fn export_merge() {
+        let fs = MemoryFileSystem::default();
+        let owner = BranchName::owner();
+        let second = BranchName::from("second");
+        init_owner_branch(fs.clone()).await;
+
+        let checkout = Checkout::new(fs.clone());
+        checkout.execute(&second).await.unwrap();
+        fs.write_sync("second.txt", b"second");
+        Stage::new(fs.clone())
+            .execute(&second, "second.txt")
+            .await
+            .unwrap();
+        let source = Commit::new(fs.clone())
+            .execute(&second, "second")
+            .await
+            .unwrap();
+
+        checkout.execute(&owner).await.unwrap();
+        fs.write_sync("owner.txt", b"owner");
+        Stage::new(fs.clone())
+            .execute(&owner, "owner.txt")
+            .await
+            .unwrap();
+        Commit::new(fs.clone())
+            .execute(&owner, "owner")
+            .await
+            .unwrap();
+        Merge::new(fs.clone())
+            .execute(source, owner.clone())
+            .await
+            .unwrap();
+
+        let stream = ExportGit::new(fs.clone()).execute(&owner).await.unwrap();
+        let stream = String::from_utf8(stream).unwrap();
+        let merge = stream.rsplit("commit refs/heads/owner\n").next().unwrap();
+        assert_eq!(stream.matches("commit refs/heads/owner\n").count(), 4);
+        assert!(merge.contains("merge :"));
+        assert!(merge.contains("second.txt\n"));
+        assert!(!merge.contains("owner.txt"));
+    }
+}
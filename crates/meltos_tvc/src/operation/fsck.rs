@@ -0,0 +1,400 @@
+use std::collections::HashMap;
+
+use meltos_util::compression::gz::Gz;
+use meltos_util::compression::CompressionBuf;
+use serde::{Deserialize, Serialize};
+
+use crate::error;
+use crate::file_system::{FilePath, FileSystem};
+use crate::io::atomic::head::HeadIo;
+use crate::io::atomic::local_commits::LocalCommitsIo;
+use crate::io::atomic::object::ObjIo;
+use crate::io::atomic::stash::StashIo;
+use crate::io::atomic::tag::TagIo;
+use crate::io::atomic::trace::TraceIo;
+use crate::object::commit::{CommitHash, CommitObj};
+use crate::object::tree::TreeObj;
+use crate::object::{CompressedBuf, Obj, ObjHash};
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FsckProblem {
+    /// The object could not be decompressed or decoded.
+    CorruptObj {
+        hash: ObjHash,
+        reason: String,
+    },
+    /// The hash of the object contents differs from its name.
+    HashMismatch {
+        hash: ObjHash,
+        actual: ObjHash,
+    },
+    /// The object referenced from `referrer` does not exist.
+    MissingObj {
+        referrer: ObjHash,
+        hash: ObjHash,
+    },
+    /// The object referenced from `referrer` exists, but its type is not the expected one.
+    InvalidObjType {
+        referrer: ObjHash,
+        hash: ObjHash,
+        expected: String,
+        actual: String,
+    },
+    /// The tree entry points to an object which does not exist.
+    MissingTreeEntry {
+        tree: ObjHash,
+        file_path: FilePath,
+        hash: ObjHash,
+    },
+    MissingTrace {
+        commit: CommitHash,
+    },
+    /// The ref such as `refs/heads/owner` does not point to a commit object,
+    /// or the tag does not point to a commit or a tag object.
+    BrokenRef {
+        name: String,
+        hash: CommitHash,
+    },
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct FsckReport {
+    /// The number of the checked objects.
+    pub objs: usize,
+    pub problems: Vec<FsckProblem>,
+}
+
+impl FsckReport {
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Verifies the integrity of the repository.
+///
+/// Each object is checked that its hash matches its name and that it can be decoded,
+/// and then the references between the commits, the trees, the traces and the refs are checked.
+#[derive(Debug, Clone)]
+pub struct Fsck<Fs>
+where
+    Fs: FileSystem,
+{
+    head: HeadIo<Fs>,
+    local_commits: LocalCommitsIo<Fs>,
+    trace: TraceIo<Fs>,
+    stash: StashIo<Fs>,
+    tag: TagIo<Fs>,
+    object: ObjIo<Fs>,
+}
+
+impl<Fs> Fsck<Fs>
+where
+    Fs: FileSystem + Clone,
+{
+    #[inline]
+    pub fn new(fs: Fs) -> Fsck<Fs> {
+        Self {
+            head: HeadIo::new(fs.clone()),
+            local_commits: LocalCommitsIo::new(fs.clone()),
+            trace: TraceIo::new(fs.clone()),
+            stash: StashIo::new(fs.clone()),
+            tag: TagIo::new(fs.clone()),
+            object: ObjIo::new(fs),
+        }
+    }
+}
+
+impl<Fs> Fsck<Fs>
+where
+    Fs: FileSystem,
+{
+    pub async fn execute(&self) -> error::Result<FsckReport> {
+        let mut report = FsckReport::default();
+        let mut types = HashMap::new();
+        let mut commits = Vec::new();
+        let mut trees = Vec::new();
+        let mut tags = Vec::new();
+        for hash in self.object.read_all_hashes().await? {
+            report.objs += 1;
+            let obj = match self.object.read(&hash).await {
+                Ok(Some(buf)) => check_obj(&hash, &buf),
+                Ok(None) => continue,
+                Err(e) => Err(FsckProblem::CorruptObj {
+                    hash: hash.clone(),
+                    reason: e.to_string(),
+                }),
+            };
+            match obj {
+                Ok(obj) => {
+                    types.insert(hash.clone(), obj.to_string());
+                    match obj {
+                        Obj::Commit(commit) => commits.push((CommitHash(hash), commit)),
+                        Obj::Tree(tree) => trees.push((hash, tree)),
+                        Obj::Tag(tag) => tags.push((hash, tag.commit_hash)),
+                        _ => {}
+                    }
+                }
+                Err(problem) => report.problems.push(problem),
+            }
+        }
+
+        let checker = Checker { types: &types };
+        for (hash, tree) in trees.iter() {
+            checker.check_tree(&mut report, hash, tree);
+        }
+        for (hash, commit_hash) in tags.iter() {
+            checker.check_ref(&mut report, hash, &commit_hash.0, "Commit");
+        }
+        for (commit_hash, commit) in commits.iter() {
+            checker.check_commit(&mut report, commit_hash, commit);
+            match self.trace.read(commit_hash).await {
+                Ok(trace_hash) => {
+                    checker.check_ref(&mut report, &commit_hash.0, &trace_hash, "Tree")
+                }
+                Err(error::Error::NotfoundTrace(_)) => {
+                    report.problems.push(FsckProblem::MissingTrace {
+                        commit: commit_hash.clone(),
+                    })
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        for (name, hash) in self.read_refs().await? {
+            if types.get(&hash.0).map(String::as_str) != Some("Commit") {
+                report.problems.push(FsckProblem::BrokenRef { name, hash });
+            }
+        }
+        for (tag_name, hash) in self.tag.read_all().await? {
+            if !matches!(types.get(&hash).map(String::as_str), Some("Commit") | Some("Tag")) {
+                report.problems.push(FsckProblem::BrokenRef {
+                    name: format!("refs/tags/{tag_name}"),
+                    hash: CommitHash(hash),
+                });
+            }
+        }
+        Ok(report)
+    }
+
+    async fn read_refs(&self) -> error::Result<Vec<(String, CommitHash)>> {
+        let heads = self.head.read_all().await?;
+        let mut refs = Vec::new();
+        for (branch_name, _) in heads.iter() {
+            if let Some(local_commits) = self.local_commits.read(branch_name).await? {
+                refs.extend(
+                    local_commits
+                        .0
+                        .into_iter()
+                        .map(|hash| (format!("branches/{branch_name}/LOCAL"), hash)),
+                );
+            }
+        }
+        refs.extend(
+            heads
+                .into_iter()
+                .map(|(branch_name, hash)| (format!("refs/heads/{branch_name}"), hash)),
+        );
+        refs.extend(
+            self.head
+                .read_all_remotes()
+                .await?
+                .into_iter()
+                .map(|(branch_name, hash)| (format!("refs/remotes/{branch_name}"), hash)),
+        );
+        refs.extend(
+            self.stash
+                .read_all()
+                .await?
+                .into_iter()
+                .enumerate()
+                .map(|(i, hash)| (format!("refs/stash@{{{i}}}"), hash)),
+        );
+        Ok(refs)
+    }
+}
+
+fn check_obj(hash: &ObjHash, buf: &CompressedBuf) -> Result<Obj, FsckProblem> {
+    let corrupt = |e: &dyn std::fmt::Display| FsckProblem::CorruptObj {
+        hash: hash.clone(),
+        reason: e.to_string(),
+    };
+    let expanded = Gz.unzip(buf).map_err(|e| corrupt(&e))?;
+    let actual = ObjHash::new(&expanded);
+    if &actual != hash {
+        return Err(FsckProblem::HashMismatch {
+            hash: hash.clone(),
+            actual,
+        });
+    }
+    Obj::expand(buf).map_err(|e| corrupt(&e))
+}
+
+struct Checker<'a> {
+    /// The types of the valid objects.
+    types: &'a HashMap<ObjHash, String>,
+}
+
+impl Checker<'_> {
+    fn check_commit(&self, report: &mut FsckReport, commit_hash: &CommitHash, commit: &CommitObj) {
+        for parent in commit.parents.iter() {
+            self.check_ref(report, &commit_hash.0, &parent.0, "Commit");
+        }
+        self.check_ref(report, &commit_hash.0, &commit.committed_objs_tree, "Tree");
+    }
+
+    fn check_tree(&self, report: &mut FsckReport, hash: &ObjHash, tree: &TreeObj) {
+        for (file_path, entry) in tree.iter() {
+            match self.types.get(entry).map(String::as_str) {
+                Some("File") | Some("Delete") => {}
+                Some(actual) => report.problems.push(FsckProblem::InvalidObjType {
+                    referrer: hash.clone(),
+                    hash: entry.clone(),
+                    expected: "File".to_string(),
+                    actual: actual.to_string(),
+                }),
+                None => report.problems.push(FsckProblem::MissingTreeEntry {
+                    tree: hash.clone(),
+                    file_path: file_path.clone(),
+                    hash: entry.clone(),
+                }),
+            }
+        }
+    }
+
+    fn check_ref(
+        &self,
+        report: &mut FsckReport,
+        referrer: &ObjHash,
+        hash: &ObjHash,
+        expected: &str,
+    ) {
+        match self.types.get(hash) {
+            Some(actual) if actual == expected => {}
+            Some(actual) => report.problems.push(FsckProblem::InvalidObjType {
+                referrer: referrer.clone(),
+                hash: hash.clone(),
+                expected: expected.to_string(),
+                actual: actual.clone(),
+            }),
+            None => report.problems.push(FsckProblem::MissingObj {
+                referrer: referrer.clone(),
+                hash: hash.clone(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use meltos_util::compression::gz::Gz;
+    use meltos_util::compression::CompressionBuf;
+
+    use crate::branch::BranchName;
+    use crate::file_system::memory::MemoryFileSystem;
+    use crate::file_system::{FilePath, FileSystem};
+    use crate::io::atomic::head::HeadIo;
+    use crate::io::atomic::object::ObjIo;
+    use crate::io::atomic::trace::TraceIo;
+    use crate::object::commit::CommitHash;
+    use crate::object::file::FileObj;
+    use crate::object::{AsMeta, ObjHash};
+    use crate::operation::commit::Commit;
+    use crate::operation::fsck::{Fsck, FsckProblem};
+    use crate::operation::repack::Repack;
+    use crate::operation::stage::Stage;
+    use crate::tests::init_owner_branch;
+
+    #[tokio::test]
+    async fn no_problems_in_valid_repository() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        fs.write_sync("hello.txt", b"hello");
+        init_owner_branch(fs.clone()).await;
+        fs.delete("hello.txt").await.unwrap();
+        Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        Commit::new(fs.clone())
+            .execute(&branch, "delete")
+            .await
+            .unwrap();
+        Repack::new(fs.clone()).execute().await.unwrap();
+
+        let report = Fsck::new(fs).execute().await.unwrap();
+        assert!(report.is_ok(), "{:?}", report.problems);
+        assert!(0 < report.objs);
+    }
+
+    #[tokio::test]
+    async fn detect_corrupt_objects() {
+        let fs = MemoryFileSystem::default();
+        init_owner_branch(fs.clone()).await;
+        let hello = FileObj(b"hello".to_vec()).as_meta().unwrap();
+        fs.write_sync(&format!(".meltos/objects/{}", hello.hash), b"truncated");
+        let world = FileObj(b"world".to_vec()).as_meta().unwrap();
+        fs.write_sync(
+            &format!(".meltos/objects/{}", world.hash),
+            &Gz.zip(b"FILE\x00hello").unwrap(),
+        );
+
+        let problems = Fsck::new(fs).execute().await.unwrap().problems;
+        assert!(problems.iter().any(|problem| matches!(
+            problem,
+            FsckProblem::CorruptObj { hash, .. } if hash == &hello.hash
+        )));
+        assert!(problems.contains(&FsckProblem::HashMismatch {
+            hash: world.hash,
+            actual: hello.hash,
+        }));
+    }
+
+    #[tokio::test]
+    async fn detect_truncated_tree() {
+        let fs = MemoryFileSystem::default();
+        init_owner_branch(fs.clone()).await;
+        let buf = b"TREE\x003\x00hello.txt\x00";
+        let hash = ObjHash::new(buf);
+        fs.write_sync(&format!(".meltos/objects/{hash}"), &Gz.zip(buf).unwrap());
+
+        let problems = Fsck::new(fs).execute().await.unwrap().problems;
+        assert!(problems.iter().any(|problem| matches!(
+            problem,
+            FsckProblem::CorruptObj { hash: corrupt, .. } if corrupt == &hash
+        )));
+    }
+
+    #[tokio::test]
+    async fn detect_missing_references() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        fs.write_sync("hello.txt", b"hello");
+        let commit_hash = init_owner_branch(fs.clone()).await;
+        let obj = ObjIo::new(fs.clone());
+        let hello = FileObj(b"hello".to_vec()).as_meta().unwrap();
+        obj.delete(&hello.hash).await.unwrap();
+        TraceIo::new(fs.clone()).delete(&commit_hash).await.unwrap();
+        let broken = CommitHash(hello.hash.clone());
+        HeadIo::new(fs.clone())
+            .write_remote(&branch, &broken)
+            .await
+            .unwrap();
+
+        let problems = Fsck::new(fs).execute().await.unwrap().problems;
+        let tree = obj
+            .read_to_commit(&commit_hash)
+            .await
+            .unwrap()
+            .committed_objs_tree;
+        assert!(problems.contains(&FsckProblem::MissingTreeEntry {
+            tree,
+            file_path: FilePath::from("hello.txt"),
+            hash: hello.hash,
+        }));
+        assert!(problems.contains(&FsckProblem::MissingTrace {
+            commit: commit_hash,
+        }));
+        assert!(problems.contains(&FsckProblem::BrokenRef {
+            name: format!("refs/remotes/{branch}"),
+            hash: broken,
+        }));
+    }
+}
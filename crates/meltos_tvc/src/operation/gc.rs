@@ -0,0 +1,399 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error;
+use crate::file_system::FileSystem;
+use crate::io::atomic::head::HeadIo;
+use crate::io::atomic::local_commits::LocalCommitsIo;
+use crate::io::atomic::merge_head::MergeHeadIo;
+use crate::io::atomic::object::ObjIo;
+use crate::io::atomic::pack::PackIo;
+use crate::io::atomic::reflog::ReflogIo;
+use crate::io::atomic::staging::StagingIo;
+use crate::io::atomic::stash::StashIo;
+use crate::io::atomic::tag::TagIo;
+use crate::io::atomic::trace::TraceIo;
+use crate::object::commit::CommitHash;
+use crate::object::tree::TreeObj;
+use crate::object::{Obj, ObjHash};
+use crate::operation::repack::Repack;
+use crate::time::since_epoch_secs;
+
+/// The reflog entries older than this many seconds are expired by [`Gc`] and no longer keep the commits alive.
+pub const REFLOG_EXPIRE_SECS: u64 = 90 * 24 * 60 * 60;
+
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct GcReport {
+    /// The unreachable objects; they have not been deleted if it was a dry run.
+    pub objs: Vec<ObjHash>,
+    /// The commit hashes of the traces of unreachable commits.
+    pub traces: Vec<CommitHash>,
+    /// The total compressed size of `objs`.
+    pub freed_bytes: usize,
+    pub dry_run: bool,
+}
+
+/// Deletes the objects and traces that are not reachable from any refs.
+///
+/// The roots are the heads, the remote heads, the reflog entries not expired yet, the tags, the stashes,
+/// the local commits, the stage and the merge in progress.
+/// The expired reflog entries are deleted as well; see [`REFLOG_EXPIRE_SECS`].
+/// If any unreachable objects are packed, all objects are repacked without them.
+#[derive(Debug, Clone)]
+pub struct Gc<Fs>
+where
+    Fs: FileSystem,
+{
+    head: HeadIo<Fs>,
+    local_commits: LocalCommitsIo<Fs>,
+    merge_head: MergeHeadIo<Fs>,
+    staging: StagingIo<Fs>,
+    stash: StashIo<Fs>,
+    reflog: ReflogIo<Fs>,
+    tag: TagIo<Fs>,
+    trace: TraceIo<Fs>,
+    object: ObjIo<Fs>,
+    pack: PackIo<Fs>,
+    repack: Repack<Fs>,
+}
+
+impl<Fs> Gc<Fs>
+where
+    Fs: FileSystem + Clone,
+{
+    #[inline]
+    pub fn new(fs: Fs) -> Gc<Fs> {
+        Self {
+            head: HeadIo::new(fs.clone()),
+            local_commits: LocalCommitsIo::new(fs.clone()),
+            merge_head: MergeHeadIo::new(fs.clone()),
+            staging: StagingIo::new(fs.clone()),
+            stash: StashIo::new(fs.clone()),
+            reflog: ReflogIo::new(fs.clone()),
+            tag: TagIo::new(fs.clone()),
+            trace: TraceIo::new(fs.clone()),
+            object: ObjIo::new(fs.clone()),
+            pack: PackIo::new(fs.clone()),
+            repack: Repack::new(fs),
+        }
+    }
+}
+
+impl<Fs> Gc<Fs>
+where
+    Fs: FileSystem,
+{
+    /// If `dry_run` is true, only reports what would be deleted.
+    pub async fn execute(&self, dry_run: bool) -> error::Result<GcReport> {
+        let mut marked = Marked::default();
+        let reflog_expire = since_epoch_secs().saturating_sub(REFLOG_EXPIRE_SECS);
+        let mut roots = self.root_commits(reflog_expire).await?;
+        roots.extend(self.mark_tags(&mut marked).await?);
+        for commit_hash in roots {
+            self.mark_commits(&mut marked, commit_hash).await?;
+        }
+        if let Some(stage) = self.staging.read().await? {
+            marked.mark_tree_entries(&stage);
+        }
+
+        let mut report = GcReport {
+            dry_run,
+            ..GcReport::default()
+        };
+        for trace in self.trace.read_all().await? {
+            if !marked.commits.contains(&trace.commit_hash) {
+                report.traces.push(trace.commit_hash);
+            }
+        }
+        for hash in self.object.read_all_hashes().await? {
+            if marked.objs.contains(&hash) {
+                continue;
+            }
+            if let Some(buf) = self.object.read(&hash).await? {
+                report.freed_bytes += buf.0.len();
+            }
+            report.objs.push(hash);
+        }
+        report.objs.sort();
+        report.traces.sort();
+
+        if !dry_run {
+            self.reflog.expire(reflog_expire).await?;
+            for commit_hash in report.traces.iter() {
+                self.trace.delete(commit_hash).await?;
+            }
+            let unreachable = report.objs.iter().cloned().collect::<HashSet<ObjHash>>();
+            let packed = self.pack.read_all_hashes().await?;
+            if packed.iter().any(|hash| unreachable.contains(hash)) {
+                self.repack.execute_without(&unreachable).await?;
+            } else {
+                for hash in report.objs.iter() {
+                    self.object.delete(hash).await?;
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    async fn root_commits(&self, reflog_expire: u64) -> error::Result<Vec<CommitHash>> {
+        let heads = self.head.read_all().await?;
+        let mut roots = Vec::new();
+        for (branch_name, _) in heads.iter() {
+            if let Some(local_commits) = self.local_commits.read(branch_name).await? {
+                roots.extend(local_commits.0);
+            }
+        }
+        roots.extend(heads.into_iter().map(|(_, head)| head));
+        roots.extend(
+            self.head
+                .read_all_remotes()
+                .await?
+                .into_iter()
+                .map(|(_, head)| head),
+        );
+        roots.extend(self.stash.read_all().await?);
+        for entry in self
+            .reflog
+            .read_all()
+            .await?
+            .into_iter()
+            .filter(|entry| reflog_expire <= entry.time)
+        {
+            roots.extend(entry.old);
+            roots.push(entry.new);
+        }
+        if let Some(merge_head) = self.merge_head.read().await? {
+            roots.push(merge_head.source);
+        }
+        Ok(roots)
+    }
+
+    /// Marks the annotated tag objects, and returns the tagged commits.
+    async fn mark_tags(&self, marked: &mut Marked) -> error::Result<Vec<CommitHash>> {
+        let mut commits = Vec::new();
+        for (_, hash) in self.tag.read_all().await? {
+            match self.object.try_read_obj(&hash).await? {
+                Obj::Tag(tag_obj) => {
+                    marked.objs.insert(hash);
+                    commits.push(tag_obj.commit_hash);
+                }
+                _ => commits.push(CommitHash(hash)),
+            }
+        }
+        Ok(commits)
+    }
+
+    async fn mark_commits(&self, marked: &mut Marked, from: CommitHash) -> error::Result {
+        let mut commits = vec![from];
+        while let Some(commit_hash) = commits.pop() {
+            if !marked.commits.insert(commit_hash.clone()) {
+                continue;
+            }
+            let commit = self.object.read_to_commit(&commit_hash).await?;
+            marked.objs.insert(commit_hash.0.clone());
+            self.mark_tree(marked, commit.committed_objs_tree).await?;
+
+            match self.trace.read(&commit_hash).await {
+                Ok(trace_hash) => self.mark_tree(marked, trace_hash).await?,
+                Err(error::Error::NotfoundTrace(_)) => {}
+                Err(e) => return Err(e),
+            }
+            commits.extend(commit.parents);
+        }
+        Ok(())
+    }
+
+    async fn mark_tree(&self, marked: &mut Marked, tree_hash: ObjHash) -> error::Result {
+        if !marked.objs.insert(tree_hash.clone()) {
+            return Ok(());
+        }
+        let tree = self.object.read_to_tree(&tree_hash).await?;
+        marked.mark_tree_entries(&tree);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct Marked {
+    commits: HashSet<CommitHash>,
+    objs: HashSet<ObjHash>,
+}
+
+impl Marked {
+    #[inline]
+    fn mark_tree_entries(&mut self, tree: &TreeObj) {
+        self.objs.extend(tree.values().cloned());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::branch::BranchName;
+    use crate::file_system::memory::MemoryFileSystem;
+    use crate::io::atomic::head::HeadIo;
+    use crate::io::atomic::object::ObjIo;
+    use crate::io::atomic::reflog::{ReflogEntry, ReflogIo};
+    use crate::io::atomic::trace::TraceIo;
+    use crate::io::commit_obj::CommitObjIo;
+    use crate::object::file::FileObj;
+    use crate::object::AsMeta;
+    use crate::operation::commit::Commit;
+    use crate::operation::gc::Gc;
+    use crate::operation::repack::Repack;
+    use crate::operation::reset::{Reset, ResetMode};
+    use crate::operation::stage::Stage;
+    use crate::operation::tag::Tag;
+    use crate::operation::un_stage::UnStage;
+    use crate::tag::TagName;
+    use crate::tests::init_owner_branch;
+
+    #[tokio::test]
+    async fn nothing_to_delete_after_commits() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        init_owner_branch(fs.clone()).await;
+        fs.write_sync("hello.txt", b"hello");
+        Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        Commit::new(fs.clone()).execute(&branch, "commit").await.unwrap();
+        fs.write_sync("staged.txt", b"staged");
+        Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+
+        let report = Gc::new(fs.clone()).execute(false).await.unwrap();
+        assert!(report.objs.is_empty());
+        assert!(report.traces.is_empty());
+        assert_eq!(report.freed_bytes, 0);
+    }
+
+    #[tokio::test]
+    async fn delete_abandoned_stage_objects() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        init_owner_branch(fs.clone()).await;
+        fs.write_sync("hello.txt", b"hello");
+        Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        UnStage::new(fs.clone()).execute_all().await.unwrap();
+
+        let obj = ObjIo::new(fs.clone());
+        let hello = FileObj(b"hello".to_vec()).as_meta().unwrap();
+        let report = Gc::new(fs.clone()).execute(false).await.unwrap();
+        assert_eq!(report.objs, vec![hello.hash.clone()]);
+        assert_eq!(report.freed_bytes, hello.compressed_buf.0.len());
+        assert!(obj.read(&hello.hash).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn delete_commits_of_deleted_branch() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::from("session");
+        init_owner_branch(fs.clone()).await;
+        let head = HeadIo::new(fs.clone());
+        head.write(&branch, &head.try_read(&BranchName::owner()).await.unwrap())
+            .await
+            .unwrap();
+        fs.write_sync("hello.txt", b"hello");
+        Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        let commit_hash = Commit::new(fs.clone()).execute(&branch, "commit").await.unwrap();
+        head.delete(&branch).await.unwrap();
+        CommitObjIo::new(fs.clone())
+            .reset_local_commits(&branch)
+            .await
+            .unwrap();
+
+        let report = Gc::new(fs.clone()).execute(false).await.unwrap();
+        assert_eq!(report.traces, vec![commit_hash.clone()]);
+        assert!(report.objs.contains(&commit_hash.0));
+        assert!(TraceIo::new(fs.clone()).read(&commit_hash).await.is_err());
+        assert!(ObjIo::new(fs.clone()).read(&commit_hash.0).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn keep_tagged_commits_of_deleted_branch() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::from("session");
+        init_owner_branch(fs.clone()).await;
+        let head = HeadIo::new(fs.clone());
+        head.write(&branch, &head.try_read(&BranchName::owner()).await.unwrap())
+            .await
+            .unwrap();
+        fs.write_sync("hello.txt", b"hello");
+        Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        let commit_hash = Commit::new(fs.clone()).execute(&branch, "commit").await.unwrap();
+        let tag_obj = Tag::new(fs.clone())
+            .create_annotated(&TagName::from("reviewed"), &commit_hash, &branch, "reviewed")
+            .await
+            .unwrap();
+        head.delete(&branch).await.unwrap();
+        CommitObjIo::new(fs.clone())
+            .reset_local_commits(&branch)
+            .await
+            .unwrap();
+
+        let report = Gc::new(fs.clone()).execute(false).await.unwrap();
+        assert!(report.objs.is_empty());
+        assert!(report.traces.is_empty());
+        assert!(ObjIo::new(fs.clone()).read(&tag_obj).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn delete_packed_objects() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        init_owner_branch(fs.clone()).await;
+        fs.write_sync("hello.txt", b"hello");
+        Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        Repack::new(fs.clone()).execute().await.unwrap();
+        UnStage::new(fs.clone()).execute_all().await.unwrap();
+
+        let hello = FileObj(b"hello".to_vec()).as_meta().unwrap();
+        let report = Gc::new(fs.clone()).execute(false).await.unwrap();
+        assert_eq!(report.objs, vec![hello.hash.clone()]);
+        let obj = ObjIo::new(fs.clone());
+        assert!(obj.read(&hello.hash).await.unwrap().is_none());
+        assert!(!obj.read_all_hashes().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn not_delete_if_dry_run() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        init_owner_branch(fs.clone()).await;
+        fs.write_sync("hello.txt", b"hello");
+        Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        UnStage::new(fs.clone()).execute_all().await.unwrap();
+
+        let report = Gc::new(fs.clone()).execute(true).await.unwrap();
+        assert_eq!(report.objs.len(), 1);
+        assert!(report.dry_run);
+        assert!(ObjIo::new(fs.clone()).read(&report.objs[0]).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn delete_commits_only_referred_by_expired_reflog() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        let init = init_owner_branch(fs.clone()).await;
+        fs.write_sync("hello.txt", b"hello");
+        Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        let commit_hash = Commit::new(fs.clone()).execute(&branch, "commit").await.unwrap();
+        Reset::new(fs.clone())
+            .execute(&init, ResetMode::Hard)
+            .await
+            .unwrap();
+
+        let reflog = ReflogIo::new(fs.clone());
+        let entries = reflog.read(&branch).await.unwrap();
+        reflog.delete(&branch).await.unwrap();
+        for entry in entries {
+            reflog
+                .append(&branch, &ReflogEntry { time: 1, ..entry })
+                .await
+                .unwrap();
+        }
+
+        let report = Gc::new(fs.clone()).execute(false).await.unwrap();
+        assert!(report.objs.contains(&commit_hash.0));
+        assert!(reflog.read(&branch).await.unwrap().is_empty());
+        assert_eq!(HeadIo::new(fs.clone()).try_read(&branch).await.unwrap(), init);
+    }
+}
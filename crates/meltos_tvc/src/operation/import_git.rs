@@ -0,0 +1,333 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::branch::BranchName;
+use crate::error;
+use crate::file_system::FileSystem;
+use crate::io::atomic::head::CommitText;
+use crate::io::bundle::{Bundle, BundleBranch, BundleObject, BundleTrace};
+use crate::io::git::object::GitCommit;
+use crate::io::git::GitIo;
+use crate::object::commit::{CommitHash, CommitObj};
+use crate::object::delete::DeleteObj;
+use crate::object::file::FileObj;
+use crate::object::tree::TreeObj;
+use crate::object::{AsMeta, ObjHash, ObjMeta};
+
+/// Converts the history of a branch in a local git repository into a [`Bundle`],
+/// which can be passed to `room/open`.
+///
+/// The commit graph and the messages are kept, and each commit stages the files changed from its first parent.
+#[derive(Debug, Clone)]
+pub struct ImportGit<Fs>
+where
+    Fs: FileSystem,
+{
+    fs: Fs,
+}
+
+impl<Fs> ImportGit<Fs>
+where
+    Fs: FileSystem + Clone,
+{
+    #[inline]
+    pub fn new(fs: Fs) -> ImportGit<Fs> {
+        Self { fs }
+    }
+
+    /// Imports `git_branch` of the repository at `git_dir`, which is the path to the `.git` directory.
+    ///
+    /// The imported commits are authored by `branch_name`, and the bundle contains it as the branch.
+    pub async fn execute(
+        &self,
+        git_dir: &str,
+        git_branch: &str,
+        branch_name: &BranchName,
+    ) -> error::Result<Bundle> {
+        let git = GitIo::open(self.fs.clone(), git_dir).await?;
+        let head = git.read_branch(git_branch).await?;
+        let (order, commits) = read_commits(&git, &head).await?;
+
+        let mut importer = Importer::default();
+        for id in order {
+            let commit = &commits[&id];
+            let files = git.read_tree_files(&commit.tree).await?;
+            let mut tree = TreeObj::default();
+            for (file_path, blob_id, mode) in files {
+                let hash = importer.import_blob(&git, &blob_id).await?;
+                tree.insert_with_mode(file_path, hash, mode);
+            }
+            importer.import_commit(id, commit, tree, branch_name)?;
+        }
+
+        Ok(Bundle {
+            traces: importer.traces,
+            objs: importer.objs,
+            branches: vec![BundleBranch {
+                branch_name: branch_name.clone(),
+                commits: vec![importer.commits[&head].clone()],
+            }],
+            tags: Vec::new(),
+        })
+    }
+}
+
+#[derive(Default)]
+struct Importer {
+    objs: Vec<BundleObject>,
+    traces: Vec<BundleTrace>,
+    obj_hashes: HashSet<ObjHash>,
+    /// The hashes of [`DeleteObj`], which remain in the trace trees after the files are deleted.
+    delete_hashes: HashSet<ObjHash>,
+    /// The file obj hashes of the git blobs.
+    blobs: HashMap<String, ObjHash>,
+    /// The commit hashes of the imported git commits.
+    commits: HashMap<String, CommitHash>,
+    /// The trace trees of the imported git commits.
+    trace_trees: HashMap<String, TreeObj>,
+}
+
+impl Importer {
+    async fn import_blob<Fs: FileSystem>(
+        &mut self,
+        git: &GitIo<Fs>,
+        blob_id: &str,
+    ) -> error::Result<ObjHash> {
+        if let Some(hash) = self.blobs.get(blob_id) {
+            return Ok(hash.clone());
+        }
+        let meta = FileObj(git.read_blob(blob_id).await?).as_meta()?;
+        let hash = self.push_obj(meta);
+        self.blobs.insert(blob_id.to_string(), hash.clone());
+        Ok(hash)
+    }
+
+    /// `tree` is the snapshot of the commit, which maps all the files to the hashes of [`FileObj`].
+    fn import_commit(
+        &mut self,
+        id: String,
+        commit: &GitCommit,
+        tree: TreeObj,
+        branch_name: &BranchName,
+    ) -> error::Result {
+        let mut trace_tree = commit
+            .parents
+            .first()
+            .map(|parent| self.trace_trees[parent].clone())
+            .unwrap_or_default();
+        let mut staged = TreeObj::default();
+        for (file_path, hash) in trace_tree.iter() {
+            if !tree.contains_key(file_path) && !self.delete_hashes.contains(hash) {
+                let hash = self.push_obj(DeleteObj(hash.clone()).as_meta()?);
+                self.delete_hashes.insert(hash.clone());
+                staged.insert(file_path.clone(), hash);
+            }
+        }
+        for (file_path, hash) in tree.iter() {
+            let mode = tree.mode(file_path);
+            if trace_tree.changed(file_path, hash, mode) {
+                staged.insert_with_mode(file_path.clone(), hash.clone(), mode);
+            }
+        }
+        let staged_hash = self.push_obj(staged.as_meta()?);
+        trace_tree.replace_by(staged);
+        let trace_hash = self.push_obj(trace_tree.as_meta()?);
+
+        let commit_obj = CommitObj {
+            parents: commit
+                .parents
+                .iter()
+                .map(|parent| self.commits[parent].clone())
+                .collect(),
+            text: CommitText::from(commit.message.as_str()),
+            committed_objs_tree: staged_hash,
+            author: Some(branch_name.clone()),
+            committed_at: commit.committed_at,
+        };
+        let commit_hash = CommitHash(self.push_obj(commit_obj.as_meta()?));
+        self.traces.push(BundleTrace {
+            commit_hash: commit_hash.clone(),
+            obj_hash: trace_hash,
+        });
+        self.commits.insert(id.clone(), commit_hash);
+        self.trace_trees.insert(id, trace_tree);
+        Ok(())
+    }
+
+    fn push_obj(&mut self, meta: ObjMeta) -> ObjHash {
+        if self.obj_hashes.insert(meta.hash.clone()) {
+            self.objs.push(BundleObject {
+                hash: meta.hash.clone(),
+                compressed_buf: meta.compressed_buf,
+            });
+        }
+        meta.hash
+    }
+}
+
+/// Reads the commits reachable from `head`, and orders them so that the parents come first.
+async fn read_commits<Fs: FileSystem>(
+    git: &GitIo<Fs>,
+    head: &str,
+) -> error::Result<(Vec<String>, HashMap<String, GitCommit>)> {
+    let mut order = Vec::new();
+    let mut commits = HashMap::new();
+    let mut stack = vec![(head.to_string(), false)];
+    while let Some((id, visited_parents)) = stack.pop() {
+        if visited_parents {
+            order.push(id);
+            continue;
+        }
+        if commits.contains_key(&id) {
+            continue;
+        }
+        let commit = git.read_commit(&id).await?;
+        stack.push((id.clone(), true));
+        for parent in commit.parents.iter().rev() {
+            if !commits.contains_key(parent) {
+                stack.push((parent.clone(), false));
+            }
+        }
+        commits.insert(id, commit);
+    }
+    Ok((order, commits))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::branch::BranchName;
+    use crate::file_system::memory::MemoryFileSystem;
+    use crate::file_system::{FilePath, FileSystem};
+    use crate::io::atomic::head::HeadIo;
+    use crate::io::atomic::object::ObjIo;
+    use crate::io::git::object::GitObjKind;
+    use crate::io::git::tests::{commit_buf, tree_buf, write_loose, GIT_DIR};
+    use crate::io::trace_tree::TraceTreeIo;
+    use crate::object::delete::DeleteObj;
+    use crate::object::file::FileObj;
+    use crate::object::{AsMeta, Obj};
+    use crate::operation::import_git::ImportGit;
+    use crate::operation::save::Save;
+
+    #[tokio::test]
+    async fn import_history_with_merge() {
+        let git_fs = MemoryFileSystem::default();
+        let hello = write_loose(&git_fs, GitObjKind::Blob, b"hello").await;
+        let hello2 = write_loose(&git_fs, GitObjKind::Blob, b"hello2").await;
+        let main = write_loose(&git_fs, GitObjKind::Blob, b"fn main(){}").await;
+        let src = write_loose(
+            &git_fs,
+            GitObjKind::Tree,
+            &tree_buf(&[("100644", "main.rs", &main)]),
+        )
+        .await;
+
+        let tree1 = write_loose(
+            &git_fs,
+            GitObjKind::Tree,
+            &tree_buf(&[("100644", "hello.txt", &hello), ("40000", "src", &src)]),
+        )
+        .await;
+        let c1 = write_loose(
+            &git_fs,
+            GitObjKind::Commit,
+            &commit_buf(&tree1, &[], 100, "init"),
+        )
+        .await;
+        let tree2 = write_loose(
+            &git_fs,
+            GitObjKind::Tree,
+            &tree_buf(&[("100644", "hello.txt", &hello2)]),
+        )
+        .await;
+        let c2 = write_loose(
+            &git_fs,
+            GitObjKind::Commit,
+            &commit_buf(&tree2, &[&c1], 200, "update"),
+        )
+        .await;
+        let tree3 = write_loose(
+            &git_fs,
+            GitObjKind::Tree,
+            &tree_buf(&[
+                ("100644", "hello.txt", &hello),
+                ("100644", "world.txt", &hello),
+            ]),
+        )
+        .await;
+        let c3 = write_loose(
+            &git_fs,
+            GitObjKind::Commit,
+            &commit_buf(&tree3, &[&c1], 300, "world"),
+        )
+        .await;
+        let c4 = write_loose(
+            &git_fs,
+            GitObjKind::Commit,
+            &commit_buf(&tree3, &[&c2, &c3], 400, "merge\n\nbody"),
+        )
+        .await;
+        git_fs
+            .write_file(
+                &format!("{GIT_DIR}/refs/heads/main"),
+                format!("{c4}\n").as_bytes(),
+            )
+            .await
+            .unwrap();
+
+        let branch = BranchName::owner();
+        let bundle = ImportGit::new(git_fs)
+            .execute(GIT_DIR, "main", &branch)
+            .await
+            .unwrap();
+        assert_eq!(bundle.traces.len(), 4);
+
+        let fs = MemoryFileSystem::default();
+        Save::new(fs.clone()).execute(bundle).await.unwrap();
+        let head = HeadIo::new(fs.clone()).try_read(&branch).await.unwrap();
+        let object = ObjIo::new(fs.clone());
+        let merge = object.read_to_commit(&head).await.unwrap();
+        assert_eq!(merge.text.0, "merge\n\nbody");
+        assert_eq!(merge.committed_at, Some(400));
+        assert_eq!(merge.author, Some(branch.clone()));
+        assert_eq!(merge.parents.len(), 2);
+        let update = object.read_to_commit(&merge.parents[0]).await.unwrap();
+        assert_eq!(update.text.0, "update");
+
+        let hello_hash = FileObj(b"hello".to_vec()).as_meta().unwrap().hash;
+        let main_hash = FileObj(b"fn main(){}".to_vec()).as_meta().unwrap().hash;
+        let deleted_main = DeleteObj(main_hash).as_meta().unwrap().hash;
+        let trace = TraceTreeIo::new(fs.clone()).read(&head).await.unwrap();
+        assert_eq!(trace.get(&FilePath::from("hello.txt")), Some(&hello_hash));
+        assert_eq!(trace.get(&FilePath::from("world.txt")), Some(&hello_hash));
+        assert_eq!(
+            trace.get(&FilePath::from("src/main.rs")),
+            Some(&deleted_main)
+        );
+
+        let update_trace = TraceTreeIo::new(fs.clone())
+            .read(&merge.parents[0])
+            .await
+            .unwrap();
+        assert_eq!(
+            update_trace.get(&FilePath::from("src/main.rs")),
+            Some(&deleted_main)
+        );
+        let staged = object
+            .read_to_tree(&update.committed_objs_tree)
+            .await
+            .unwrap();
+        assert_eq!(staged.len(), 2);
+        assert!(matches!(
+            object.try_read_obj(&deleted_main).await.unwrap(),
+            Obj::Delete(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn failed_if_branch_not_exists() {
+        let result = ImportGit::new(MemoryFileSystem::default())
+            .execute(GIT_DIR, "main", &BranchName::owner())
+            .await;
+        assert!(result.is_err());
+    }
+}
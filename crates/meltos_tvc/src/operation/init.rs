@@ -50,7 +50,7 @@ where
         self.working.write(branch_name).await?;
 
         if self.stage.execute(branch_name, ".").await.is_ok() {
-            self.commit.execute(branch_name, "INIT").await
+            self.commit.execute_as(branch_name, "INIT", "init").await
         } else {
             self.commit.execute_null_commit(branch_name).await
         }
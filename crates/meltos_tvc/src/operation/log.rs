@@ -0,0 +1,426 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+
+use crate::error;
+use crate::file_system::{FilePath, FileSystem};
+use crate::io::atomic::object::ObjIo;
+use crate::object::commit::{CommitHash, CommitObj};
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct LogOptions {
+    /// The number of commits to skip before starting to yield.
+    pub skip: usize,
+    /// The maximum number of commits to yield.
+    pub limit: Option<usize>,
+    /// Follows only the first parent of merge commits.
+    pub first_parent: bool,
+    /// Yields only commits that touch this file, or any file under this directory.
+    pub path: Option<FilePath>,
+    /// Never yields a commit before any of its children, even if their commit times are out of order.
+    ///
+    /// All reachable commits are read before the first one is yielded.
+    pub topo_order: bool,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LogEntry {
+    pub hash: CommitHash,
+    pub commit: CommitObj,
+}
+
+#[derive(Debug, Clone)]
+pub struct Log<Fs>
+where
+    Fs: FileSystem,
+{
+    object: ObjIo<Fs>,
+}
+
+impl<Fs> Log<Fs>
+where
+    Fs: FileSystem + Clone,
+{
+    #[inline]
+    pub fn new(fs: Fs) -> Log<Fs> {
+        Self {
+            object: ObjIo::new(fs),
+        }
+    }
+}
+
+impl<Fs> Log<Fs>
+where
+    Fs: FileSystem,
+{
+    /// Returns the commits reachable from `from` according to `options`.
+    pub async fn execute(
+        &self,
+        from: CommitHash,
+        options: LogOptions,
+    ) -> error::Result<Vec<LogEntry>> {
+        let mut iter = self.iter(from, options).await?;
+        let mut entries = Vec::new();
+        while let Some(entry) = iter.next().await? {
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+
+    /// Creates the iterator that yields the commits reachable from `from`.
+    ///
+    /// Commits are yielded newest first by their commit times,
+    /// and each commit is yielded only once even if it is reachable via multiple merge paths.
+    /// The commits are read while iterating, unless [`LogOptions::topo_order`] is set.
+    pub async fn iter(&self, from: CommitHash, options: LogOptions) -> error::Result<LogIter<'_, Fs>> {
+        let mut commits = HashMap::new();
+        let mut children = HashMap::<CommitHash, usize>::new();
+        let mut discovered = HashMap::new();
+        discovered.insert(from.clone(), 0);
+
+        if options.topo_order {
+            let mut queue = VecDeque::from([from.clone()]);
+            while let Some(hash) = queue.pop_front() {
+                let commit = self.object.read_to_commit(&hash).await?;
+                for parent in parents(&commit, options.first_parent) {
+                    *children.entry(parent.clone()).or_default() += 1;
+                    if !discovered.contains_key(parent) {
+                        discovered.insert(parent.clone(), discovered.len());
+                        queue.push_back(parent.clone());
+                    }
+                }
+                commits.insert(hash, commit);
+            }
+        } else {
+            commits.insert(from.clone(), self.object.read_to_commit(&from).await?);
+        }
+
+        let mut iter = LogIter {
+            object: &self.object,
+            options,
+            commits,
+            children,
+            discovered,
+            ready: BinaryHeap::new(),
+            unread_parents: Vec::new(),
+            skipped: 0,
+            yielded: 0,
+        };
+        iter.push_ready(from);
+        Ok(iter)
+    }
+}
+
+pub struct LogIter<'a, Fs>
+where
+    Fs: FileSystem,
+{
+    object: &'a ObjIo<Fs>,
+    options: LogOptions,
+    /// The commits read but not yet yielded.
+    commits: HashMap<CommitHash, CommitObj>,
+    /// The number of children not yet yielded for each commit; used only in the topological order.
+    children: HashMap<CommitHash, usize>,
+    /// The order in which each commit was discovered, used to break ties of the commit time.
+    discovered: HashMap<CommitHash, usize>,
+    ready: BinaryHeap<Ready>,
+    /// The parents of the last popped commit, read only when the next commit is requested.
+    unread_parents: Vec<CommitHash>,
+    skipped: usize,
+    yielded: usize,
+}
+
+impl<'a, Fs> LogIter<'a, Fs>
+where
+    Fs: FileSystem,
+{
+    pub async fn next(&mut self) -> error::Result<Option<LogEntry>> {
+        while self
+            .options
+            .limit
+            .is_none_or(|limit| self.yielded < limit)
+        {
+            for parent in std::mem::take(&mut self.unread_parents) {
+                let parent_commit = self.object.read_to_commit(&parent).await?;
+                self.commits.insert(parent.clone(), parent_commit);
+                self.push_ready(parent);
+            }
+            let Some(Ready { hash, .. }) = self.ready.pop() else {
+                return Ok(None);
+            };
+            let commit = self
+                .commits
+                .remove(&hash)
+                .ok_or_else(|| error::Error::NotfoundObj(hash.0.clone()))?;
+            for parent in parents(&commit, self.options.first_parent) {
+                if self.options.topo_order {
+                    let Some(count) = self.children.get_mut(parent) else {
+                        continue;
+                    };
+                    *count -= 1;
+                    if *count == 0 {
+                        self.push_ready(parent.clone());
+                    }
+                } else if !self.discovered.contains_key(parent) {
+                    self.discovered.insert(parent.clone(), self.discovered.len());
+                    self.unread_parents.push(parent.clone());
+                }
+            }
+
+            if !self.touches_path(&commit).await? {
+                continue;
+            }
+            if self.skipped < self.options.skip {
+                self.skipped += 1;
+                continue;
+            }
+            self.yielded += 1;
+            return Ok(Some(LogEntry { hash, commit }));
+        }
+        Ok(None)
+    }
+
+    fn push_ready(&mut self, hash: CommitHash) {
+        let Some(commit) = self.commits.get(&hash) else {
+            return;
+        };
+        self.ready.push(Ready {
+            committed_at: commit.committed_at.unwrap_or_default(),
+            discovered: Reverse(self.discovered.get(&hash).copied().unwrap_or_default()),
+            hash,
+        });
+    }
+
+    async fn touches_path(&self, commit: &CommitObj) -> error::Result<bool> {
+        let Some(path) = self.options.path.as_ref() else {
+            return Ok(true);
+        };
+        let path = path.trim_start_matches("./").trim_end_matches('/');
+        let tree = self.object.read_to_tree(&commit.committed_objs_tree).await?;
+        Ok(tree.keys().any(|file_path| {
+            file_path.as_str() == path
+                || path.is_empty()
+                || file_path
+                    .strip_prefix(path)
+                    .is_some_and(|rest| rest.starts_with('/'))
+        }))
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+struct Ready {
+    committed_at: u64,
+    discovered: Reverse<usize>,
+    hash: CommitHash,
+}
+
+impl PartialOrd for Ready {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ready {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.committed_at, self.discovered).cmp(&(other.committed_at, other.discovered))
+    }
+}
+
+#[inline]
+fn parents(commit: &CommitObj, first_parent: bool) -> &[CommitHash] {
+    if first_parent {
+        &commit.parents[..commit.parents.len().min(1)]
+    } else {
+        &commit.parents
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::branch::BranchName;
+    use crate::file_system::memory::MemoryFileSystem;
+    use crate::file_system::{FilePath, FileSystem};
+    use crate::io::atomic::head::HeadIo;
+    use crate::object::commit::CommitHash;
+    use crate::operation::checkout::Checkout;
+    use crate::operation::commit::Commit;
+    use crate::operation::log::{Log, LogOptions};
+    use crate::operation::merge::{Merge, MergedStatus};
+    use crate::operation::stage::Stage;
+    use crate::tests::init_owner_branch;
+
+    #[tokio::test]
+    async fn yield_commits_from_head() {
+        let fs = MemoryFileSystem::default();
+        let null_commit = init_owner_branch(fs.clone()).await;
+        let commit1 = commit_file(&fs, &BranchName::owner(), "hello.txt").await;
+        let commit2 = commit_file(&fs, &BranchName::owner(), "world.txt").await;
+
+        let hashes = log(&fs, commit2.clone(), LogOptions::default()).await;
+        assert_eq!(hashes, vec![commit2, commit1, null_commit]);
+    }
+
+    #[tokio::test]
+    async fn skip_and_limit() {
+        let fs = MemoryFileSystem::default();
+        init_owner_branch(fs.clone()).await;
+        let commit1 = commit_file(&fs, &BranchName::owner(), "hello.txt").await;
+        let commit2 = commit_file(&fs, &BranchName::owner(), "world.txt").await;
+        commit_file(&fs, &BranchName::owner(), "rust.txt").await;
+        let head = commit_file(&fs, &BranchName::owner(), "meltos.txt").await;
+
+        let hashes = log(
+            &fs,
+            head,
+            LogOptions {
+                skip: 2,
+                limit: Some(2),
+                ..LogOptions::default()
+            },
+        )
+        .await;
+        assert_eq!(hashes, vec![commit2, commit1]);
+    }
+
+    #[tokio::test]
+    async fn read_commits_only_as_yielded() {
+        let fs = MemoryFileSystem::default();
+        let null_commit = init_owner_branch(fs.clone()).await;
+        let commit1 = commit_file(&fs, &BranchName::owner(), "hello.txt").await;
+        let commit2 = commit_file(&fs, &BranchName::owner(), "world.txt").await;
+        fs.delete(&format!(".meltos/objects/{null_commit}"))
+            .await
+            .unwrap();
+
+        let hashes = log(
+            &fs,
+            commit2.clone(),
+            LogOptions {
+                limit: Some(2),
+                ..LogOptions::default()
+            },
+        )
+        .await;
+        assert_eq!(hashes, vec![commit2.clone(), commit1]);
+        assert!(Log::new(fs.clone())
+            .execute(
+                commit2,
+                LogOptions {
+                    limit: Some(2),
+                    topo_order: true,
+                    ..LogOptions::default()
+                },
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn yield_merge_ancestors_only_once() {
+        let fs = MemoryFileSystem::default();
+        let (merge_commit, dist_head, source_head) = merged_branches(&fs).await;
+
+        let hashes = log(&fs, merge_commit.clone(), LogOptions::default()).await;
+        assert_eq!(hashes.len(), 5);
+        assert_eq!(hashes[0], merge_commit);
+        for (i, hash) in hashes.iter().enumerate() {
+            assert!(!hashes[i + 1..].contains(hash));
+        }
+        assert!(hashes.contains(&dist_head));
+        assert!(hashes.contains(&source_head));
+    }
+
+    #[tokio::test]
+    async fn follow_only_first_parent() {
+        let fs = MemoryFileSystem::default();
+        let (merge_commit, dist_head, source_head) = merged_branches(&fs).await;
+
+        let hashes = log(
+            &fs,
+            merge_commit.clone(),
+            LogOptions {
+                first_parent: true,
+                ..LogOptions::default()
+            },
+        )
+        .await;
+        assert_eq!(hashes.len(), 4);
+        assert_eq!(hashes[0..2], [merge_commit, dist_head]);
+        assert!(!hashes.contains(&source_head));
+    }
+
+    #[tokio::test]
+    async fn yield_only_commits_touching_path() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        init_owner_branch(fs.clone()).await;
+        let commit1 = commit_file(&fs, &branch, "src/hello.txt").await;
+        commit_file(&fs, &branch, "world.txt").await;
+        let commit3 = commit_file(&fs, &branch, "src/rust.txt").await;
+
+        let hashes = log(
+            &fs,
+            commit3.clone(),
+            LogOptions {
+                path: Some(FilePath::from("src")),
+                ..LogOptions::default()
+            },
+        )
+        .await;
+        assert_eq!(hashes, vec![commit3.clone(), commit1]);
+
+        let hashes = log(
+            &fs,
+            commit3.clone(),
+            LogOptions {
+                path: Some(FilePath::from("src/rust.txt")),
+                ..LogOptions::default()
+            },
+        )
+        .await;
+        assert_eq!(hashes, vec![commit3]);
+    }
+
+    async fn log(fs: &MemoryFileSystem, from: CommitHash, options: LogOptions) -> Vec<CommitHash> {
+        Log::new(fs.clone())
+            .execute(from, options)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.hash)
+            .collect()
+    }
+
+    async fn commit_file(fs: &MemoryFileSystem, branch: &BranchName, path: &str) -> CommitHash {
+        fs.write_sync(path, path.as_bytes());
+        Stage::new(fs.clone()).execute(branch, path).await.unwrap();
+        Commit::new(fs.clone()).execute(branch, path).await.unwrap()
+    }
+
+    /// Returns the merge commit and its parents.
+    async fn merged_branches(fs: &MemoryFileSystem) -> (CommitHash, CommitHash, CommitHash) {
+        let owner = BranchName::owner();
+        let second = BranchName::from("second");
+        init_owner_branch(fs.clone()).await;
+        commit_file(fs, &owner, "base.txt").await;
+
+        let checkout = Checkout::new(fs.clone());
+        checkout.execute(&second).await.unwrap();
+        let source_head = commit_file(fs, &second, "second.txt").await;
+
+        checkout.execute(&owner).await.unwrap();
+        let dist_head = commit_file(fs, &owner, "owner.txt").await;
+
+        let status = Merge::new(fs.clone())
+            .execute(source_head.clone(), owner.clone())
+            .await
+            .unwrap();
+        assert_eq!(status, MergedStatus::Merged);
+        let merge_commit = HeadIo::new(fs.clone())
+            .try_read(&owner)
+            .await
+            .unwrap();
+        (merge_commit, dist_head, source_head)
+    }
+}
@@ -1,17 +1,26 @@
-use std::collections::{HashSet, VecDeque};
+use std::collections::HashSet;
 
 use wasm_bindgen::prelude::wasm_bindgen;
 
 use crate::branch::BranchName;
-use crate::file_system::{FilePath, FileSystem};
+use crate::error;
+use crate::file_system::{FileMode, FilePath, FileSystem};
 use crate::io::atomic::head::HeadIo;
+use crate::io::atomic::merge_head::{MergeHead, MergeHeadIo, MergeKind};
+use crate::io::atomic::object::ObjIo;
+use crate::io::atomic::sparse_checkout::SparseCheckoutIo;
 use crate::io::atomic::staging::StagingIo;
 use crate::io::commit_hashes::CommitHashIo;
-use crate::io::commit_obj::CommitObjIo;
+use crate::io::diff::merge::{has_conflict_markers, MergedText};
+use crate::io::trace_tree::TraceTreeIo;
+use crate::io::workspace::WorkspaceIo;
 use crate::object::commit::CommitHash;
+use crate::object::delete::DeleteObj;
+use crate::object::file::FileObj;
 use crate::object::tree::TreeObj;
-use crate::object::ObjHash;
+use crate::object::{AsMeta, Obj, ObjHash};
 use crate::operation::commit::Commit;
+use crate::operation::status::Status;
 use crate::operation::unzip::UnZip;
 
 #[derive(Debug)]
@@ -24,6 +33,12 @@ where
     commit: Commit<Fs>,
     unzip: UnZip<Fs>,
     staging: StagingIo<Fs>,
+    trace_tree: TraceTreeIo<Fs>,
+    object: ObjIo<Fs>,
+    workspace: WorkspaceIo<Fs>,
+    merge_head: MergeHeadIo<Fs>,
+    sparse_checkout: SparseCheckoutIo<Fs>,
+    status: Status<Fs>,
     fs: Fs,
 }
 
@@ -38,6 +53,12 @@ where
             commit: Commit::new(fs.clone()),
             unzip: UnZip::new(fs.clone()),
             staging: StagingIo::new(fs.clone()),
+            trace_tree: TraceTreeIo::new(fs.clone()),
+            object: ObjIo::new(fs.clone()),
+            workspace: WorkspaceIo::new(fs.clone()),
+            merge_head: MergeHeadIo::new(fs.clone()),
+            sparse_checkout: SparseCheckoutIo::new(fs.clone()),
+            status: Status::new(fs.clone()),
             fs,
         }
     }
@@ -68,16 +89,27 @@ where
         &self,
         source: BranchName,
         dist: BranchName,
-    ) -> crate::error::Result<MergedStatus> {
+    ) -> error::Result<MergedStatus> {
         let source_head = self.read_source_head(&source).await?;
         self.execute(source_head, dist).await
     }
 
+    /// Merges `source` into `dist`.
+    ///
+    /// If the changes conflict, the files with conflict markers are written into the workspace
+    /// and the merge remains in progress until [`Merge::continue_merge`] or [`Merge::abort`] is called.
+    ///
+    /// Fails with [`error::Error::LocalChangesWouldBeOverwritten`] if there are staged changes
+    /// or the workspace has changes that the merge would overwrite.
     pub async fn execute(
         &self,
         source: CommitHash,
         dist: BranchName,
-    ) -> crate::error::Result<MergedStatus> {
+    ) -> error::Result<MergedStatus> {
+        if let Some(merge_head) = self.merge_head.read().await? {
+            return Err(error::Error::MergeInProgress(merge_head.dist));
+        }
+
         let dist_head = self.head.try_read(&dist).await?;
         let source_hashes = self.commit_hashes.read_all(source.clone(), &None).await?;
         let dist_hashes = self
@@ -86,7 +118,14 @@ where
             .await?;
 
         if source_hashes.contains(&dist_head) {
-            self.head.write(&dist, &source).await?;
+            let source_tree = self.trace_tree.read(&source).await?;
+            let inspected = Inspected {
+                tree: source_tree,
+                conflicts: Vec::new(),
+            };
+            self.check_local_changes(&dist, &dist_head, &inspected)
+                .await?;
+            self.head.update(&dist, &source, "merge").await?;
             self.unzip.execute(&dist).await?;
             return Ok(MergedStatus::FastSource);
         }
@@ -95,21 +134,237 @@ where
             return Ok(MergedStatus::FastDist);
         }
 
-        match self.inspect_merges(source_hashes, dist_hashes).await? {
-            InspectStatus::CanMerge(tree) => {
-                self.head.write(&dist, &source).await?;
-                self.staging.write_tree(&tree).await?;
+        let merge_base_tree = self.merge_base_tree(&source, &dist_head).await?;
+        let inspected = self
+            .inspect_merges(
+                &merge_base_tree,
+                &self.trace_tree.read(&source).await?,
+                &dist_head,
+                &source.to_string(),
+                &dist,
+            )
+            .await?;
+        self.check_local_changes(&dist, &dist_head, &inspected)
+            .await?;
+        self.staging.write_tree(&inspected.tree).await?;
+
+        if inspected.conflicts.is_empty() {
+            self.commit
+                .execute_merge(&dist, merge_commit_text(&source, &dist), &source)
+                .await?;
+            self.unzip.execute(&dist).await?;
+            return Ok(MergedStatus::Merged);
+        }
+
+        self.write_merged_files(&inspected).await?;
+        self.merge_head
+            .write(&MergeHead {
+                source,
+                dist,
+                conflicts: inspected
+                    .conflicts
+                    .iter()
+                    .map(|c| c.conflict.file_path.clone())
+                    .collect(),
+                kind: MergeKind::Merge,
+            })
+            .await?;
+        Ok(MergedStatus::Conflicted(
+            inspected.conflicts.into_iter().map(|c| c.conflict).collect(),
+        ))
+    }
+
+    /// Applies the changes from `origin_tree` to `source_tree` onto the head of `dist`,
+    /// and commits them with the head as the only parent.
+    ///
+    /// This is the common part of [`CherryPick`](crate::operation::cherry_pick::CherryPick)
+    /// and [`Revert`](crate::operation::revert::Revert);
+    /// `source` is the commit being applied, and the conflicts are handled in the same way as [`Merge::execute`].
+    ///
+    /// Returns [`MergedStatus::FastDist`] if `dist` already has the changes.
+    pub(crate) async fn apply_changes(
+        &self,
+        kind: MergeKind,
+        source: CommitHash,
+        origin_tree: &TreeObj,
+        source_tree: &TreeObj,
+        dist: BranchName,
+    ) -> error::Result<MergedStatus> {
+        if let Some(merge_head) = self.merge_head.read().await? {
+            return Err(error::Error::MergeInProgress(merge_head.dist));
+        }
+
+        let dist_head = self.head.try_read(&dist).await?;
+        let source_label = match kind {
+            MergeKind::Revert => format!("parent of {source}"),
+            _ => source.to_string(),
+        };
+        let inspected = self
+            .inspect_merges(origin_tree, source_tree, &dist_head, &source_label, &dist)
+            .await?;
+        if inspected.tree.is_empty() && inspected.conflicts.is_empty() {
+            return Ok(MergedStatus::FastDist);
+        }
+        self.check_local_changes(&dist, &dist_head, &inspected)
+            .await?;
+        self.staging.write_tree(&inspected.tree).await?;
+
+        if inspected.conflicts.is_empty() {
+            let commit_text = self.commit_text(kind, &source, &dist).await?;
+            self.commit
+                .execute_as(&dist, commit_text, kind.operation_name())
+                .await?;
+            self.unzip.execute(&dist).await?;
+            return Ok(MergedStatus::Merged);
+        }
+
+        self.write_merged_files(&inspected).await?;
+        self.merge_head
+            .write(&MergeHead {
+                source,
+                dist,
+                conflicts: inspected
+                    .conflicts
+                    .iter()
+                    .map(|c| c.conflict.file_path.clone())
+                    .collect(),
+                kind,
+            })
+            .await?;
+        Ok(MergedStatus::Conflicted(
+            inspected.conflicts.into_iter().map(|c| c.conflict).collect(),
+        ))
+    }
+
+    /// Creates the merge commit after all conflicts have been resolved in the workspace.
+    ///
+    /// * stage the conflicted files.
+    /// * commit with two parents: the head of `dist` and `source`.
+    ///   If it is a cherry-pick or a revert, the head of `dist` is the only parent.
+    /// * delete `MERGE_HEAD`.
+    pub async fn continue_merge(&self) -> error::Result<CommitHash> {
+        let MergeHead {
+            source,
+            dist,
+            conflicts,
+            kind,
+        } = self.merge_head.try_read().await?;
+
+        let mut unresolved = Vec::new();
+        for path in conflicts.iter() {
+            let Some(file) = self.workspace.read(path).await? else {
+                continue;
+            };
+            if std::str::from_utf8(&file.0).is_ok_and(has_conflict_markers) {
+                unresolved.push(path.clone());
+            }
+        }
+        if !unresolved.is_empty() {
+            return Err(error::Error::UnresolvedConflicts(unresolved));
+        }
+
+        self.stage_resolved_files(&dist, &conflicts).await?;
+        let commit_text = self.commit_text(kind, &source, &dist).await?;
+        let commit_hash = match kind {
+            MergeKind::Merge => self.commit.execute_merge(&dist, commit_text, &source).await?,
+            MergeKind::CherryPick | MergeKind::Revert => {
                 self.commit
-                    .execute(&dist, format!("merged {source} to {dist}"))
+                    .execute_as(&dist, commit_text, kind.operation_name())
+                    .await?
+            }
+        };
+        self.merge_head.delete().await?;
+        Ok(commit_hash)
+    }
+
+    /// Cancels the merge in progress.
+    ///
+    /// The files written by the merge are restored to the head of `dist`,
+    /// and the conflicted files outside the sparse-checkout patterns are removed.
+    pub async fn abort(&self) -> error::Result {
+        let merge_head = self.merge_head.try_read().await?;
+        let dist_head = self.head.try_read(&merge_head.dist).await?;
+        let dist_tree = self.trace_tree.read(&dist_head).await?;
+        let stage_tree = self.staging.read().await?.unwrap_or_default();
+        let sparse = self.sparse_checkout.read().await?;
+
+        for path in stage_tree.keys().chain(merge_head.conflicts.iter()) {
+            if !sparse.contains(path) {
+                self.fs.delete(path).await?;
+            } else if let Some(hash) = dist_tree.get(path) {
+                self.workspace
+                    .unpack(
+                        path,
+                        &self.object.try_read_obj(hash).await?,
+                        dist_tree.mode(path),
+                    )
                     .await?;
-                self.unzip.execute(&dist).await?;
-                Ok(MergedStatus::Merged)
+            } else {
+                self.fs.delete(path).await?;
             }
-            InspectStatus::Conflict(conflicts) => Ok(MergedStatus::Conflicted(conflicts)),
         }
+
+        self.staging.reset().await?;
+        self.merge_head.delete().await?;
+        Ok(())
+    }
+
+    /// Returns the merge in progress, if any.
+    #[inline]
+    pub async fn merge_head(&self) -> error::Result<Option<MergeHead>> {
+        self.merge_head.read().await
+    }
+
+    /// Fails if there are changes that would be lost by writing `inspected` on top of `dist_head`.
+    ///
+    /// The stage is replaced by the merge, so any staged file is refused.
+    /// The changed files in the workspace are refused if the merge writes different contents to them,
+    /// or if they are conflicted.
+    async fn check_local_changes(
+        &self,
+        dist: &BranchName,
+        dist_head: &CommitHash,
+        inspected: &Inspected,
+    ) -> error::Result {
+        let status = self.status.execute(dist).await?;
+        let mut merged_tree = self.trace_tree.read(dist_head).await?;
+        merged_tree.replace_by(inspected.tree.clone());
+
+        let mut overwritten = status
+            .staged
+            .into_iter()
+            .map(|staged| staged.file_path)
+            .collect::<Vec<FilePath>>();
+        for path in status
+            .modified
+            .into_iter()
+            .chain(status.deleted)
+            .chain(status.untracked)
+        {
+            let conflicted = inspected
+                .conflicts
+                .iter()
+                .any(|conflict| conflict.conflict.file_path == path);
+            let Some(hash) = merged_tree.get(&path) else {
+                continue;
+            };
+            let kept = match self.workspace.read(&path).await? {
+                Some(file) => &file.as_meta()?.hash == hash,
+                None => self.read_buf(hash).await?.is_none(),
+            };
+            if conflicted || !kept {
+                overwritten.push(path);
+            }
+        }
+        if overwritten.is_empty() {
+            return Ok(());
+        }
+        overwritten.sort();
+        overwritten.dedup();
+        Err(error::Error::LocalChangesWouldBeOverwritten(overwritten))
     }
 
-    async fn read_source_head(&self, source: &BranchName) -> crate::error::Result<CommitHash> {
+    async fn read_source_head(&self, source: &BranchName) -> error::Result<CommitHash> {
         if let Some(head) = self.head.read(source).await? {
             Ok(head)
         } else {
@@ -117,92 +372,329 @@ where
         }
     }
 
+    /// Inspects the files changed from `origin_tree` to `source_tree` and on `dist`.
+    ///
+    /// Returns the tree that should be committed on top of `dist_head`;
+    /// the files changed on both sides are merged line by line.
     async fn inspect_merges(
         &self,
-        source_hashes: Vec<CommitHash>,
-        dist_hashes: Vec<CommitHash>,
-    ) -> crate::error::Result<InspectStatus> {
-        let merge_origin = self.merge_origin(&source_hashes, &dist_hashes)?;
-        let mut dist_tree = self
-            .commit_tree(dist_hashes.into_iter().collect(), &merge_origin)
-            .await?;
-        let source_tree = self
-            .commit_tree(source_hashes.into_iter().collect(), &merge_origin)
-            .await?;
-        let conflicts = Vec::new();
+        origin_tree: &TreeObj,
+        source_tree: &TreeObj,
+        dist_head: &CommitHash,
+        source_label: &str,
+        dist: &BranchName,
+    ) -> error::Result<Inspected> {
+        let dist_tree = self.trace_tree.read(dist_head).await?;
+        let mut inspected = Inspected::default();
 
-        for (path, _) in source_tree.iter() {
-            if dist_tree.contains_key(path) {
-                dist_tree.remove(path);
+        for (path, source_hash) in source_tree.iter() {
+            let origin_hash = origin_tree.get(path);
+            let source_mode = source_tree.mode(path);
+            if !origin_tree.changed(path, source_hash, source_mode) {
+                continue;
+            }
+            let Some(dist_hash) = dist_tree.get(path) else {
+                inspected
+                    .tree
+                    .insert_with_mode(path.clone(), source_hash.clone(), source_mode);
+                continue;
+            };
+            // The mode changed on the source is taken unless the mode was also changed on `dist`.
+            let dist_mode = dist_tree.mode(path);
+            let mode = if origin_tree.mode(path) == dist_mode {
+                source_mode
+            } else {
+                dist_mode
+            };
+            if source_hash == dist_hash {
+                if mode != dist_mode {
+                    inspected
+                        .tree
+                        .insert_with_mode(path.clone(), dist_hash.clone(), mode);
+                }
+                continue;
+            }
+            if origin_hash == Some(dist_hash) {
+                inspected
+                    .tree
+                    .insert_with_mode(path.clone(), source_hash.clone(), mode);
+                continue;
             }
 
-            // TODO: 現状はコンフリクトは検査せずに相手側のブランチをすべて取り込むようにします。
-            // else {
-            //     conflicts.push(Conflict {
-            //         source: source_hash,
-            //         dist: dist_hash.clone(),
-            //         file_path: path.clone(),
-            //     });
-            // }
+            match self
+                .merge_file(origin_hash, source_hash, dist_hash, source_label, dist)
+                .await?
+            {
+                MergedFile::Merged(merged_hash) => {
+                    if &merged_hash != dist_hash || mode != dist_mode {
+                        inspected.tree.insert_with_mode(path.clone(), merged_hash, mode);
+                    }
+                }
+                MergedFile::Conflicted(marked_text) => {
+                    inspected.conflicts.push(ConflictFile {
+                        conflict: Conflict {
+                            file_path: path.clone(),
+                            source: source_hash.clone(),
+                            dist: dist_hash.clone(),
+                        },
+                        marked_text,
+                    });
+                }
+            }
         }
 
-        if conflicts.is_empty() {
-            Ok(InspectStatus::CanMerge(dist_tree))
-        } else {
-            Ok(InspectStatus::Conflict(conflicts))
-        }
+        inspected
+            .conflicts
+            .sort_by(|c1, c2| c1.conflict.file_path.cmp(&c2.conflict.file_path));
+        Ok(inspected)
     }
 
-    async fn commit_tree(
+    /// Merges the file changed on both branches.
+    async fn merge_file(
         &self,
-        mut commit_hashes: VecDeque<CommitHash>,
-        merge_origin: &CommitHash,
-    ) -> crate::error::Result<TreeObj> {
-        let commit_obj_io = CommitObjIo::new(self.fs.clone());
-        let mut tree = TreeObj::default();
-
-        while let Some(hash) = commit_hashes.pop_front() {
-            if merge_origin == &hash {
-                break;
+        origin: Option<&ObjHash>,
+        source: &ObjHash,
+        dist: &ObjHash,
+        source_label: &str,
+        dist_label: &str,
+    ) -> error::Result<MergedFile> {
+        let (source_buf, dist_buf) = match (self.read_buf(source).await?, self.read_buf(dist).await?) {
+            (None, None) => return Ok(MergedFile::Merged(dist.clone())),
+            (Some(source_buf), Some(dist_buf)) => (source_buf, dist_buf),
+            // modified on one side and deleted on the other.
+            _ => return Ok(MergedFile::Conflicted(None)),
+        };
+        let origin_buf = match origin {
+            Some(origin) => self.read_buf(origin).await?.unwrap_or_default(),
+            None => Vec::with_capacity(0),
+        };
+
+        let (Ok(origin_text), Ok(source_text), Ok(dist_text)) = (
+            String::from_utf8(origin_buf),
+            String::from_utf8(source_buf),
+            String::from_utf8(dist_buf),
+        ) else {
+            // binary files can not be merged.
+            return Ok(MergedFile::Conflicted(None));
+        };
+
+        let merged = MergedText::merge(&origin_text, &source_text, &dist_text);
+        let Some(merged_text) = merged.merged_text() else {
+            return Ok(MergedFile::Conflicted(Some(
+                merged.text_with_markers(dist_label, source_label),
+            )));
+        };
+        let file = FileObj(merged_text.into_bytes());
+        let meta = file.as_meta()?;
+        self.object.write_obj(&file).await?;
+        Ok(MergedFile::Merged(meta.hash))
+    }
+
+    /// Reads the buffer of the file object.
+    ///
+    /// Returns `None` if the object is a deleted object.
+    async fn read_buf(&self, hash: &ObjHash) -> error::Result<Option<Vec<u8>>> {
+        match self.object.try_read_obj(hash).await? {
+            Obj::File(file) => Ok(Some(file.0)),
+            Obj::Delete(_) => Ok(None),
+            obj => Err(error::Error::InvalidWorkspaceObj(obj.to_string())),
+        }
+    }
+
+    /// Writes the automatically merged files and the files with conflict markers into the workspace.
+    ///
+    /// The merged files outside the sparse-checkout patterns are not written,
+    /// but the conflicted files are always written so that they can be resolved.
+    async fn write_merged_files(&self, inspected: &Inspected) -> error::Result {
+        inspected.tree.check_symlink_parents()?;
+        let sparse = self.sparse_checkout.read().await?;
+        for (path, hash) in inspected.tree.iter().filter(|(path, _)| sparse.contains(path)) {
+            self.workspace
+                .unpack(
+                    path,
+                    &self.object.try_read_obj(hash).await?,
+                    inspected.tree.mode(path),
+                )
+                .await?;
+        }
+        for conflict in inspected.conflicts.iter() {
+            if let Some(marked_text) = conflict.marked_text.as_ref() {
+                let file = Obj::File(FileObj(marked_text.as_bytes().to_vec()));
+                self.workspace
+                    .unpack(&conflict.conflict.file_path, &file, FileMode::Regular)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn stage_resolved_files(&self, dist: &BranchName, conflicts: &[FilePath]) -> error::Result {
+        let mut stage_tree = self.staging.read().await?.unwrap_or_default();
+        let dist_head = self.head.try_read(dist).await?;
+        let dist_tree = self.trace_tree.read(&dist_head).await?;
+
+        for path in conflicts {
+            if let Some(file) = self.workspace.read(path).await? {
+                let meta = file.as_meta()?;
+                let mode = self.workspace.mode(path).await?;
+                self.object.write_obj(&file).await?;
+                if dist_tree.changed(path, &meta.hash, mode) {
+                    stage_tree.insert_with_mode(path.clone(), meta.hash, mode);
+                } else {
+                    stage_tree.remove(path);
+                }
+            } else if let Some(dist_hash) = dist_tree.get(path) {
+                let delete_obj = DeleteObj(dist_hash.clone());
+                self.object.write_obj(&delete_obj).await?;
+                stage_tree.insert(path.clone(), delete_obj.as_meta()?.hash);
             }
-            let commit_tree = commit_obj_io.read_commit_tree(&hash).await?;
-            tree.replace_by(commit_tree);
+        }
+
+        self.staging.write_tree(&stage_tree).await
+    }
+
+    async fn commit_text(
+        &self,
+        kind: MergeKind,
+        source: &CommitHash,
+        dist: &BranchName,
+    ) -> error::Result<String> {
+        Ok(match kind {
+            MergeKind::Merge => merge_commit_text(source, dist),
+            MergeKind::CherryPick => self.object.read_to_commit(source).await?.text.0,
+            MergeKind::Revert => format!("reverted {source}"),
+        })
+    }
+
+    /// Returns the tree of the merge base of `source` and `dist_head`.
+    ///
+    /// If there are multiple merge bases such as after criss-cross merges,
+    /// they are merged into a virtual merge base as git's recursive strategy does.
+    async fn merge_base_tree(
+        &self,
+        source: &CommitHash,
+        dist_head: &CommitHash,
+    ) -> error::Result<TreeObj> {
+        let bases = self
+            .commit_hashes
+            .merge_bases(std::slice::from_ref(source), dist_head)
+            .await?;
+        if bases.is_empty() {
+            return Err(error::Error::NotfoundMergeBase(
+                source.clone(),
+                dist_head.clone(),
+            ));
+        }
+        self.virtual_base_tree(&bases).await
+    }
+
+    async fn virtual_base_tree(&self, bases: &[CommitHash]) -> error::Result<TreeObj> {
+        let mut tree = self.trace_tree.read(&bases[0]).await?;
+        for (i, base) in bases.iter().enumerate().skip(1) {
+            let origin_bases = self.commit_hashes.merge_bases(&bases[..i], base).await?;
+            let origin_tree = if origin_bases.is_empty() {
+                TreeObj::default()
+            } else {
+                Box::pin(self.virtual_base_tree(&origin_bases)).await?
+            };
+            let base_tree = self.trace_tree.read(base).await?;
+            tree = self.merge_trees(&origin_tree, &tree, &base_tree).await?;
         }
         Ok(tree)
     }
 
-    fn merge_origin(
+    /// Merges the trees of two merge bases into the tree of the virtual merge base.
+    ///
+    /// The conflicted files are kept with the conflict markers,
+    /// or as in `left` if they can not be merged as text.
+    async fn merge_trees(
         &self,
-        source_hashes: &[CommitHash],
-        dist_hashes: &[CommitHash],
-    ) -> crate::error::Result<CommitHash> {
-        let s = source_hashes.iter().collect::<HashSet<&CommitHash>>();
-        let d = dist_hashes.iter().collect::<HashSet<&CommitHash>>();
-        let same_commits = s.intersection(&d).collect::<Vec<&&CommitHash>>();
-        let merge_origin = source_hashes
-            .iter()
-            .find(|hash| same_commits.contains(&hash))
-            .unwrap();
-        Ok(merge_origin.clone())
+        origin: &TreeObj,
+        left: &TreeObj,
+        right: &TreeObj,
+    ) -> error::Result<TreeObj> {
+        let mut merged = TreeObj::default();
+        let paths = left
+            .keys()
+            .chain(right.keys())
+            .collect::<HashSet<&FilePath>>();
+        for path in paths {
+            let origin_hash = origin.get(path);
+            let (left_hash, right_hash) = (left.get(path), right.get(path));
+            let (hash, mode) = if left_hash == right_hash || right_hash == origin_hash {
+                (left_hash.cloned(), left.mode(path))
+            } else if left_hash == origin_hash {
+                (right_hash.cloned(), right.mode(path))
+            } else if let (Some(left_hash), Some(right_hash)) = (left_hash, right_hash) {
+                let hash = match self
+                    .merge_file(origin_hash, right_hash, left_hash, "virtual", "virtual")
+                    .await?
+                {
+                    MergedFile::Merged(hash) => hash,
+                    MergedFile::Conflicted(Some(marked_text)) => {
+                        let file = FileObj(marked_text.into_bytes());
+                        self.object.write_obj(&file).await?;
+                        file.as_meta()?.hash
+                    }
+                    MergedFile::Conflicted(None) => left_hash.clone(),
+                };
+                (Some(hash), left.mode(path))
+            } else {
+                // modified on one side and deleted on the other.
+                (left_hash.cloned(), left.mode(path))
+            };
+            if let Some(hash) = hash {
+                merged.insert_with_mode(path.clone(), hash, mode);
+            }
+        }
+        Ok(merged)
     }
 }
 
+#[inline]
+fn merge_commit_text(source: &CommitHash, dist: &BranchName) -> String {
+    format!("merged {source} to {dist}")
+}
+
+#[derive(Debug, Clone, Default)]
+struct Inspected {
+    tree: TreeObj,
+    conflicts: Vec<ConflictFile>,
+}
+
+#[derive(Debug, Clone)]
+struct ConflictFile {
+    conflict: Conflict,
+    /// The merged text with conflict markers; `None` if the file can not be merged as text.
+    marked_text: Option<String>,
+}
+
 #[derive(Debug, Clone)]
-enum InspectStatus {
-    CanMerge(TreeObj),
-    Conflict(Vec<Conflict>),
+enum MergedFile {
+    Merged(ObjHash),
+    Conflicted(Option<String>),
 }
 
 #[cfg(test)]
 mod tests {
     use crate::branch::BranchName;
+    use crate::error;
+    use crate::io::atomic::head::HeadIo;
+    use crate::io::atomic::merge_head::{MergeHead, MergeKind};
+    use crate::io::atomic::object::ObjIo;
+    use crate::io::commit_obj::CommitObjIo;
+    use crate::io::trace_tree::TraceTreeIo;
+    use crate::object::commit::{CommitHash, CommitObj};
     use crate::file_system::memory::MemoryFileSystem;
     use crate::file_system::{FilePath, FileSystem};
     use crate::io::workspace::WorkspaceIo;
     use crate::operation::checkout::Checkout;
     use crate::operation::commit::Commit;
     use crate::operation::init::Init;
-    use crate::operation::merge::{Merge, MergedStatus};
+    use crate::object::file::FileObj;
+    use crate::object::tree::TreeObj;
+    use crate::object::AsMeta;
+    use crate::operation::merge::{Conflict, Merge, MergedStatus};
     use crate::operation::stage::Stage;
     use crate::tests::init_owner_branch;
 
@@ -296,29 +788,240 @@ mod tests {
         assert!(fs.read_file("test.txt").await.unwrap().is_some());
     }
 
-    // TODO: 現状はコンフリクト関連が未実装のため実装された際にこのテストも展開します。
-    //     #[test]
-    // fn conflicts() {
-    //     let fs = MockFileSystem::default();
-    //
-    //     let b1 = BranchName::owner();
-    //     let b2 = BranchName::from("session");
-    //     let init = Init::new(b1.clone(), fs.clone());
-    //     let checkout = Checkout::new(fs.clone());
-    //     init.execute().unwrap();
-    //     checkout.execute(&b2).unwrap();
-    //     checkout.execute(&b1).unwrap();
-    //     fs.force_write("hello.txt", b"hello");
-    //     Stage::new(b1.clone(), fs.clone()).execute(".").unwrap();
-    //     Commit::new(b1.clone(), fs.clone()).execute("TEXT").unwrap();
-    //
-    //     checkout.execute(&b2).unwrap();
-    //     fs.force_write("hello.txt", b"HELLO");
-    //     Stage::new(b2.clone(), fs.clone()).execute(".").unwrap();
-    //     Commit::new(b2.clone(), fs.clone()).execute("TEXT").unwrap();
-    //
-    //     let merge = Merge::new(fs.clone());
-    //     let status = merge.execute(b1, b2).unwrap();
-    //     assert!(matches!(status, MergedStatus::Conflicted(_)));
-    // }
+    #[tokio::test]
+    async fn merged_non_overlapping_changes_in_same_file() {
+        let fs = MemoryFileSystem::default();
+        let b1 = BranchName::owner();
+        let b2 = BranchName::from("session");
+        fs.write_sync("hello.txt", b"line1\nline2\nline3\n");
+        Init::new(fs.clone()).execute(&b1).await.unwrap();
+        let checkout = Checkout::new(fs.clone());
+        checkout.execute(&b2).await.unwrap();
+
+        fs.write_sync("hello.txt", b"line1\nline2\nLINE3\n");
+        Stage::new(fs.clone()).execute(&b2, ".").await.unwrap();
+        Commit::new(fs.clone()).execute(&b2, "TEXT").await.unwrap();
+
+        checkout.execute(&b1).await.unwrap();
+        fs.write_sync("hello.txt", b"LINE1\nline2\nline3\n");
+        Stage::new(fs.clone()).execute(&b1, ".").await.unwrap();
+        Commit::new(fs.clone()).execute(&b1, "TEXT").await.unwrap();
+
+        let status = Merge::new(fs.clone())
+            .execute_from_branch(b2, b1)
+            .await
+            .unwrap();
+        assert_eq!(status, MergedStatus::Merged);
+        assert_eq!(
+            fs.read_file("hello.txt").await.unwrap(),
+            Some(b"LINE1\nline2\nLINE3\n".to_vec())
+        );
+    }
+
+    #[tokio::test]
+    async fn conflicts() {
+        let fs = MemoryFileSystem::default();
+
+        let b1 = BranchName::owner();
+        let b2 = BranchName::from("session");
+        let init = Init::new(fs.clone());
+        let checkout = Checkout::new(fs.clone());
+        init.execute(&b1).await.unwrap();
+        checkout.execute(&b2).await.unwrap();
+        checkout.execute(&b1).await.unwrap();
+        fs.write_sync("hello.txt", b"hello");
+        Stage::new(fs.clone()).execute(&b1, ".").await.unwrap();
+        Commit::new(fs.clone()).execute(&b1, "TEXT").await.unwrap();
+
+        checkout.execute(&b2).await.unwrap();
+        fs.write_sync("hello.txt", b"HELLO");
+        Stage::new(fs.clone()).execute(&b2, ".").await.unwrap();
+        Commit::new(fs.clone()).execute(&b2, "TEXT").await.unwrap();
+
+        let merge = Merge::new(fs.clone());
+        let status = merge.execute_from_branch(b1, b2).await.unwrap();
+        assert_eq!(
+            status,
+            MergedStatus::Conflicted(vec![Conflict {
+                file_path: FilePath::from_path("hello.txt"),
+                source: FileObj(b"hello".to_vec()).as_meta().unwrap().hash,
+                dist: FileObj(b"HELLO".to_vec()).as_meta().unwrap().hash,
+            }])
+        );
+    }
+
+    #[tokio::test]
+    async fn write_conflict_markers_into_workspace() {
+        let fs = MemoryFileSystem::default();
+        let (source_head, dist) = conflict_branches(&fs).await;
+
+        let merge = Merge::new(fs.clone());
+        let status = merge.execute(source_head.clone(), dist.clone()).await.unwrap();
+        assert!(matches!(status, MergedStatus::Conflicted(_)));
+        assert_eq!(
+            fs.read_file("hello.txt").await.unwrap().unwrap(),
+            format!("line1\n<<<<<<< session\ndist\n=======\nsource\n>>>>>>> {source_head}\nline3\n")
+                .into_bytes()
+        );
+        assert_eq!(
+            fs.read_file("test.txt").await.unwrap(),
+            Some(b"test".to_vec())
+        );
+        assert_eq!(
+            merge.merge_head().await.unwrap(),
+            Some(MergeHead {
+                source: source_head,
+                dist,
+                conflicts: vec![FilePath::from_path("hello.txt")],
+                kind: MergeKind::Merge,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_if_merge_in_progress() {
+        let fs = MemoryFileSystem::default();
+        let (source_head, dist) = conflict_branches(&fs).await;
+
+        let merge = Merge::new(fs.clone());
+        merge.execute(source_head.clone(), dist.clone()).await.unwrap();
+        let result = merge.execute(source_head, dist).await;
+        assert!(matches!(result, Err(error::Error::MergeInProgress(_))));
+    }
+
+    #[tokio::test]
+    async fn failed_continue_if_unresolved() {
+        let fs = MemoryFileSystem::default();
+        let (source_head, dist) = conflict_branches(&fs).await;
+
+        let merge = Merge::new(fs.clone());
+        merge.execute(source_head, dist).await.unwrap();
+        let result = merge.continue_merge().await;
+        assert!(matches!(result, Err(error::Error::UnresolvedConflicts(files)) if files == vec![FilePath::from_path("hello.txt")]));
+    }
+
+    #[tokio::test]
+    async fn create_merge_commit_after_resolved() {
+        let fs = MemoryFileSystem::default();
+        let (source_head, dist) = conflict_branches(&fs).await;
+        let dist_head = HeadIo::new(fs.clone()).try_read(&dist).await.unwrap();
+
+        let merge = Merge::new(fs.clone());
+        merge.execute(source_head.clone(), dist.clone()).await.unwrap();
+        fs.write_sync("hello.txt", b"line1\nresolved\nline3\n");
+        let merge_commit = merge.continue_merge().await.unwrap();
+
+        let commit = CommitObjIo::new(fs.clone()).read(&merge_commit).await.unwrap();
+        assert_eq!(commit.parents, vec![dist_head, source_head]);
+        assert_eq!(merge.merge_head().await.unwrap(), None);
+
+        let trace = TraceTreeIo::new(fs.clone()).read(&merge_commit).await.unwrap();
+        assert_eq!(
+            trace.get(&FilePath::from_path("hello.txt")),
+            Some(&FileObj(b"line1\nresolved\nline3\n".to_vec()).as_meta().unwrap().hash)
+        );
+        assert_eq!(
+            trace.get(&FilePath::from_path("test.txt")),
+            Some(&FileObj(b"test".to_vec()).as_meta().unwrap().hash)
+        );
+    }
+
+    #[tokio::test]
+    async fn restore_dist_tree_if_aborted() {
+        let fs = MemoryFileSystem::default();
+        let (source_head, dist) = conflict_branches(&fs).await;
+
+        let merge = Merge::new(fs.clone());
+        merge.execute(source_head, dist).await.unwrap();
+        merge.abort().await.unwrap();
+
+        assert_eq!(
+            fs.read_file("hello.txt").await.unwrap(),
+            Some(b"line1\ndist\nline3\n".to_vec())
+        );
+        assert_eq!(fs.read_file("test.txt").await.unwrap(), None);
+        assert_eq!(merge.merge_head().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn merge_with_virtual_base_after_criss_cross_merges() {
+        let fs = MemoryFileSystem::default();
+        let owner = BranchName::owner();
+        let second = BranchName::from("second");
+        fs.write_sync("hello.txt", b"line1\nline2\nline3\n");
+        Init::new(fs.clone()).execute(&owner).await.unwrap();
+        let checkout = Checkout::new(fs.clone());
+        let merge = Merge::new(fs.clone());
+        checkout.execute(&second).await.unwrap();
+        fs.write_sync("hello.txt", b"line1\nline2\nLINE3\n");
+        Stage::new(fs.clone()).execute(&second, ".").await.unwrap();
+        let second_head = Commit::new(fs.clone()).execute(&second, "second").await.unwrap();
+
+        checkout.execute(&owner).await.unwrap();
+        fs.write_sync("hello.txt", b"LINE1\nline2\nline3\n");
+        Stage::new(fs.clone()).execute(&owner, ".").await.unwrap();
+        let owner_head = Commit::new(fs.clone()).execute(&owner, "owner").await.unwrap();
+
+        // Both branches merge each other, so that they have two merge bases.
+        merge.execute(second_head, owner.clone()).await.unwrap();
+        checkout.execute(&second).await.unwrap();
+        merge.execute(owner_head, second.clone()).await.unwrap();
+        checkout.execute(&owner).await.unwrap();
+        fs.write_sync("hello.txt", b"LINE1\nLINE2\nLINE3\n");
+        Stage::new(fs.clone()).execute(&owner, ".").await.unwrap();
+        Commit::new(fs.clone()).execute(&owner, "owner").await.unwrap();
+
+        let status = merge.execute_from_branch(second, owner).await.unwrap();
+        assert_eq!(status, MergedStatus::Merged);
+        assert_eq!(
+            fs.try_read_file("hello.txt").await.unwrap(),
+            b"LINE1\nLINE2\nLINE3\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_if_histories_are_unrelated() {
+        let fs = MemoryFileSystem::default();
+        let owner = BranchName::owner();
+        let owner_head = init_owner_branch(fs.clone()).await;
+        let unrelated = CommitObj {
+            parents: Vec::new(),
+            text: "unrelated".into(),
+            committed_objs_tree: TreeObj::default().as_meta().unwrap().hash,
+            author: None,
+            committed_at: None,
+        };
+        ObjIo::new(fs.clone()).write_obj(&unrelated).await.unwrap();
+        let unrelated = CommitHash(unrelated.as_meta().unwrap().hash);
+
+        let result = Merge::new(fs.clone()).execute(unrelated.clone(), owner).await;
+        assert!(matches!(
+            result,
+            Err(error::Error::NotfoundMergeBase(source, dist)) if source == unrelated && dist == owner_head
+        ));
+    }
+
+    /// Creates the branches which change the same line of `hello.txt`.
+    ///
+    /// Returns the head of the source branch and the dist branch.
+    async fn conflict_branches(fs: &MemoryFileSystem) -> (CommitHash, BranchName) {
+        let b1 = BranchName::owner();
+        let b2 = BranchName::from("session");
+        fs.write_sync("hello.txt", b"line1\nline2\nline3\n");
+        Init::new(fs.clone()).execute(&b1).await.unwrap();
+        let checkout = Checkout::new(fs.clone());
+        checkout.execute(&b2).await.unwrap();
+
+        fs.write_sync("hello.txt", b"line1\ndist\nline3\n");
+        Stage::new(fs.clone()).execute(&b2, ".").await.unwrap();
+        Commit::new(fs.clone()).execute(&b2, "TEXT").await.unwrap();
+
+        checkout.execute(&b1).await.unwrap();
+        fs.write_sync("hello.txt", b"line1\nsource\nline3\n");
+        fs.write_sync("test.txt", b"test");
+        Stage::new(fs.clone()).execute(&b1, ".").await.unwrap();
+        let source_head = Commit::new(fs.clone()).execute(&b1, "TEXT").await.unwrap();
+        checkout.execute(&b2).await.unwrap();
+        (source_head, b2)
+    }
 }
@@ -28,7 +28,7 @@ where
     /// * writes the `working` to new branch
     pub async fn execute(&self, old: BranchName, new: BranchName) -> error::Result {
         let old_branch_head = self.head.try_read(&old).await?;
-        self.head.write(&new, &old_branch_head).await?;
+        self.head.update(&new, &old_branch_head, "checkout").await?;
         Ok(())
     }
 }
@@ -36,9 +36,10 @@ where
         self.trace.write_all(&bundle.traces).await?;
         for branch in &bundle.branches {
             self.head
-                .write_remote(
+                .update_remote(
                     &branch.branch_name,
                     &branch.commits[branch.commits.len() - 1],
+                    "patch",
                 )
                 .await?;
         }
@@ -1,22 +1,48 @@
 use std::fmt::Display;
 
 use async_trait::async_trait;
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 
 use crate::branch::BranchName;
 use crate::error;
 use crate::file_system::FileSystem;
 use crate::io::atomic::local_commits::LocalCommitsIo;
-use crate::io::atomic::trace::TraceIo;
-use crate::io::bundle::{Bundle, BundleBranch, BundleObject, BundleTrace};
+use crate::io::atomic::object::ObjIo;
+use crate::io::atomic::tag::TagIo;
+use crate::io::bundle::{
+    Bundle, BundleBranch, BundleHashes, BundleIo, BundleObject, BundlePlan, BundleStream, BundleTag,
+    BundleTrace,
+};
 use crate::io::commit_obj::CommitObjIo;
 use crate::object::commit::CommitObj;
+use crate::object::{Obj, ObjHash};
 
 #[async_trait(? Send)]
 pub trait Pushable<Output> {
     type Error: Display;
 
+    /// Returns the objects and traces in `hashes` that the remote does not have.
+    ///
+    /// By default, the remote is assumed to have nothing.
+    async fn missing(&mut self, hashes: BundleHashes) -> std::result::Result<BundleHashes, Self::Error> {
+        Ok(hashes)
+    }
+
     async fn push(&mut self, bundle: Bundle) -> std::result::Result<Output, Self::Error>;
+
+    /// Sends the records of the bundle as they are read.
+    ///
+    /// By default, the records are collected into a bundle and sent by [`Pushable::push`].
+    async fn push_stream(&mut self, mut stream: BundleStream) -> error::Result<Output> {
+        let mut bundle = Bundle::default();
+        while let Some(record) = stream.next().await {
+            bundle.push_record(record?);
+        }
+        self.push(bundle)
+            .await
+            .map_err(|e| error::Error::FailedConnectServer(format!("{e}")))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -26,7 +52,9 @@ where
 {
     commit_obj: CommitObjIo<Fs>,
     local_commits: LocalCommitsIo<Fs>,
-    trace: TraceIo<Fs>,
+    tag: TagIo<Fs>,
+    object: ObjIo<Fs>,
+    bundle: BundleIo<Fs>,
 }
 
 impl<Fs> Push<Fs>
@@ -37,7 +65,9 @@ where
     pub fn new(fs: Fs) -> Push<Fs> {
         Self {
             commit_obj: CommitObjIo::new(fs.clone()),
-            trace: TraceIo::new(fs.clone()),
+            tag: TagIo::new(fs.clone()),
+            object: ObjIo::new(fs.clone()),
+            bundle: BundleIo::new(fs.clone()),
             local_commits: LocalCommitsIo::new(fs),
         }
     }
@@ -48,24 +78,42 @@ where
     Fs: FileSystem,
 {
     /// Sends the currently locally committed data to the remote.
-    /// * push local commits to remote server.
+    /// * push local commits and all tags to remote server.
     /// * clear local commits
+    ///
+    /// The objects are read one by one while being sent by [`Pushable::push_stream`].
     pub async fn execute<Output>(
         &self,
         branch_name: BranchName,
         remote: &mut impl Pushable<Output>,
-    ) -> error::Result<Output> {
-        let bundle = self.create_push_bundle(branch_name.clone()).await?;
-
-        let output = remote
-            .push(bundle)
+    ) -> error::Result<Output>
+    where
+        Fs: Clone + 'static,
+    {
+        let mut plan = self.create_push_plan(branch_name.clone()).await?;
+        let missing = remote
+            .missing(plan.hashes())
             .await
             .map_err(|e| error::Error::FailedConnectServer(format!("{e}")))?;
+        plan.retain(&missing);
+
+        let output = remote.push_stream(self.bundle.stream_plan(plan)).await?;
         self.commit_obj.reset_local_commits(&branch_name).await?;
         Ok(output)
     }
 
-    pub async fn create_push_bundle(&self, branch_name: BranchName) -> error::Result<Bundle> {
+    pub async fn create_push_bundle(&self, branch_name: BranchName) -> error::Result<Bundle>
+    where
+        Fs: Clone,
+    {
+        let plan = self.create_push_plan(branch_name).await?;
+        self.bundle.read_plan(plan).await
+    }
+
+    /// Returns what to be pushed without reading the contents of the objects.
+    ///
+    /// Only the traces of the commits being pushed are included.
+    pub async fn create_push_plan(&self, branch_name: BranchName) -> error::Result<BundlePlan> {
         let local_commits = self
             .local_commits
             .read(&branch_name)
@@ -74,21 +122,43 @@ where
         if local_commits.is_empty() {
             return Err(error::Error::NotfoundLocalCommits);
         }
-        let traces = self.trace.read_all().await?;
-        let objs = self
+        let traces = self
             .commit_obj
-            .read_objs_associated_with_local_commits(&branch_name)
+            .read_traces_associated_with_local_commits(&branch_name)
             .await?;
+        let mut hashes = self
+            .commit_obj
+            .read_obj_hashes_associated_with_local_commits(&branch_name)
+            .await?
+            .into_iter()
+            .collect();
+        let tags = self.read_tags(&mut hashes).await?;
 
-        Ok(Bundle {
-            objs,
+        Ok(BundlePlan {
+            hashes,
             traces,
             branches: vec![BundleBranch {
                 branch_name,
                 commits: local_commits.0,
             }],
+            tags,
         })
     }
+
+    /// Reads all tags, and adds the hashes of the annotated tag objects to `hashes`.
+    async fn read_tags(&self, hashes: &mut Vec<ObjHash>) -> error::Result<Vec<BundleTag>> {
+        let mut tags = Vec::new();
+        for (tag_name, hash) in self.tag.read_all().await? {
+            if let Obj::Tag(_) = self.object.try_read_obj(&hash).await? {
+                hashes.push(hash.clone());
+            }
+            tags.push(BundleTag {
+                tag_name,
+                hash,
+            });
+        }
+        Ok(tags)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
@@ -108,16 +178,22 @@ mod tests {
     use crate::file_system::memory::MemoryFileSystem;
     use crate::file_system::FileSystem;
     use crate::io::atomic::head::HeadIo;
-    use crate::io::bundle::Bundle;
+    use crate::io::bundle::{Bundle, BundleHashes};
     use crate::io::commit_obj::CommitObjIo;
+    use crate::object::file::FileObj;
+    use crate::object::{AsMeta, ObjHash};
     use crate::operation::commit::Commit;
     use crate::operation::push::{Push, Pushable};
     use crate::operation::stage::Stage;
+    use crate::operation::tag::Tag;
+    use crate::tag::TagName;
     use crate::tests::init_owner_branch;
 
     #[derive(Debug, Default)]
     struct MockRemoteClient {
         pub bundle: Option<Bundle>,
+        /// The objects the remote already has.
+        pub known: Vec<ObjHash>,
     }
 
     unsafe impl Send for MockRemoteClient {}
@@ -128,6 +204,11 @@ mod tests {
     impl Pushable<()> for MockRemoteClient {
         type Error = String;
 
+        async fn missing(&mut self, mut hashes: BundleHashes) -> Result<BundleHashes, Self::Error> {
+            hashes.objs.retain(|hash| !self.known.contains(hash));
+            Ok(hashes)
+        }
+
         async fn push(&mut self, bundle: Bundle) -> Result<(), Self::Error> {
             self.bundle.replace(bundle);
             Ok(())
@@ -167,6 +248,59 @@ mod tests {
             .is_ok());
     }
 
+    #[tokio::test]
+    async fn upload_only_missing_objs() {
+        let fs = MemoryFileSystem::default();
+        init_owner_branch(fs.clone()).await;
+        let branch = BranchName::owner();
+        fs.write_file("hello.txt", b"hello").await.unwrap();
+        fs.write_file("world.txt", b"world").await.unwrap();
+        Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        Commit::new(fs.clone())
+            .execute(&branch, "commit text")
+            .await
+            .unwrap();
+
+        let hello = FileObj(b"hello".to_vec()).as_meta().unwrap().hash;
+        let world = FileObj(b"world".to_vec()).as_meta().unwrap().hash;
+        let mut remote = MockRemoteClient {
+            known: vec![hello.clone()],
+            ..MockRemoteClient::default()
+        };
+        Push::new(fs).execute(branch, &mut remote).await.unwrap();
+        let bundle = remote.bundle.unwrap();
+        assert!(bundle.objs.iter().all(|obj| obj.hash != hello));
+        assert!(bundle.objs.iter().any(|obj| obj.hash == world));
+    }
+
+    #[tokio::test]
+    async fn push_tags() {
+        let fs = MemoryFileSystem::default();
+        init_owner_branch(fs.clone()).await;
+        let branch = BranchName::owner();
+        fs.write_file("hello.txt", b"hello").await.unwrap();
+        Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        let head = Commit::new(fs.clone())
+            .execute(&branch, "commit text")
+            .await
+            .unwrap();
+        let tag = Tag::new(fs.clone());
+        tag.create(&TagName::from("light"), &head).await.unwrap();
+        let tag_obj = tag
+            .create_annotated(&TagName::from("reviewed"), &head, &branch, "reviewed")
+            .await
+            .unwrap();
+
+        let mut remote = MockRemoteClient::default();
+        Push::new(fs).execute(branch, &mut remote).await.unwrap();
+        let mut bundle = remote.bundle.unwrap();
+        bundle.tags.sort();
+        assert_eq!(bundle.tags.len(), 2);
+        assert_eq!(bundle.tags[0].hash, head.0);
+        assert_eq!(bundle.tags[1].hash, tag_obj);
+        assert!(bundle.objs.iter().any(|obj| obj.hash == tag_obj));
+    }
+
     #[tokio::test]
     async fn local_commits_is_cleared_if_succeed() {
         let fs = MemoryFileSystem::default();
@@ -213,4 +347,33 @@ mod tests {
             &head.try_read(&branch).await.unwrap()
         );
     }
+
+    #[tokio::test]
+    async fn push_only_traces_of_local_commits() {
+        let fs = MemoryFileSystem::default();
+        init_owner_branch(fs.clone()).await;
+        let branch = BranchName::owner();
+        let push = Push::new(fs.clone());
+        fs.write_file("hello.txt", b"hello").await.unwrap();
+        Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        Commit::new(fs.clone())
+            .execute(&branch, "hello")
+            .await
+            .unwrap();
+        push.execute(branch.clone(), &mut MockRemoteClient::default())
+            .await
+            .unwrap();
+
+        fs.write_file("world.txt", b"world").await.unwrap();
+        Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        let head = Commit::new(fs.clone())
+            .execute(&branch, "world")
+            .await
+            .unwrap();
+        let mut remote = MockRemoteClient::default();
+        push.execute(branch, &mut remote).await.unwrap();
+        let bundle = remote.bundle.unwrap();
+        assert_eq!(bundle.traces.len(), 1);
+        assert_eq!(bundle.traces[0].commit_hash, head);
+    }
 }
@@ -0,0 +1,170 @@
+use crate::branch::BranchName;
+use crate::error;
+use crate::file_system::FileSystem;
+use crate::io::atomic::head::HeadIo;
+use crate::io::atomic::merge_head::MergeHeadIo;
+use crate::io::atomic::reflog::{ReflogEntry, ReflogIo};
+
+/// Lists the movements of the heads, and restores a head to an earlier one.
+///
+/// Only the head is moved by [`Reflog::restore`];
+/// use [`Reset`](crate::operation::reset::Reset) to restore the stage and the workspace of the working branch.
+#[derive(Debug, Clone)]
+pub struct Reflog<Fs>
+where
+    Fs: FileSystem,
+{
+    head: HeadIo<Fs>,
+    reflog: ReflogIo<Fs>,
+    merge_head: MergeHeadIo<Fs>,
+}
+
+impl<Fs> Reflog<Fs>
+where
+    Fs: FileSystem + Clone,
+{
+    #[inline]
+    pub fn new(fs: Fs) -> Reflog<Fs> {
+        Self {
+            head: HeadIo::new(fs.clone()),
+            reflog: ReflogIo::new(fs.clone()),
+            merge_head: MergeHeadIo::new(fs),
+        }
+    }
+}
+
+impl<Fs> Reflog<Fs>
+where
+    Fs: FileSystem,
+{
+    /// Returns the entries of the branch; the newest one comes first.
+    pub async fn list(&self, branch_name: &BranchName) -> error::Result<Vec<ReflogEntry>> {
+        let mut entries = self.reflog.read(branch_name).await?;
+        entries.reverse();
+        Ok(entries)
+    }
+
+    /// Moves the head of the branch to the commit recorded in the `index`-th entry of [`Reflog::list`].
+    ///
+    /// The restoration itself is recorded as a new entry, so it can be undone in the same way.
+    pub async fn restore(&self, branch_name: &BranchName, index: usize) -> error::Result {
+        if self
+            .merge_head
+            .read()
+            .await?
+            .is_some_and(|merge_head| &merge_head.dist == branch_name)
+        {
+            return Err(error::Error::MergeInProgress(branch_name.clone()));
+        }
+        let entry = self
+            .list(branch_name)
+            .await?
+            .into_iter()
+            .nth(index)
+            .ok_or_else(|| error::Error::NotfoundReflogEntry(branch_name.clone(), index))?;
+        self.head.update(branch_name, &entry.new, "restore").await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::branch::BranchName;
+    use crate::error;
+    use crate::file_system::memory::MemoryFileSystem;
+    use crate::io::atomic::head::HeadIo;
+    use crate::operation::checkout::Checkout;
+    use crate::operation::commit::Commit;
+    use crate::operation::gc::Gc;
+    use crate::operation::merge::{Merge, MergedStatus};
+    use crate::operation::reflog::Reflog;
+    use crate::operation::reset::{Reset, ResetMode};
+    use crate::operation::stage::Stage;
+    use crate::tests::init_owner_branch;
+
+    #[tokio::test]
+    async fn record_head_movements() {
+        let fs = MemoryFileSystem::default();
+        let owner = BranchName::owner();
+        let init = init_owner_branch(fs.clone()).await;
+        fs.write_sync("hello.txt", b"hello");
+        Stage::new(fs.clone()).execute(&owner, ".").await.unwrap();
+        let commit_hash = Commit::new(fs.clone())
+            .execute(&owner, "commit")
+            .await
+            .unwrap();
+
+        let entries = Reflog::new(fs.clone()).list(&owner).await.unwrap();
+        let operations = entries
+            .iter()
+            .map(|entry| entry.operation.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(operations, vec!["commit", "init"]);
+        assert_eq!(entries[0].old, Some(init.clone()));
+        assert_eq!(entries[0].new, commit_hash);
+        assert_eq!(entries[1].old, None);
+    }
+
+    #[tokio::test]
+    async fn undo_fast_forward_merge() {
+        let fs = MemoryFileSystem::default();
+        let owner = BranchName::owner();
+        let second = BranchName::from("second");
+        let init = init_owner_branch(fs.clone()).await;
+        Checkout::new(fs.clone()).execute(&second).await.unwrap();
+        fs.write_sync("hello.txt", b"hello");
+        Stage::new(fs.clone()).execute(&second, ".").await.unwrap();
+        Commit::new(fs.clone())
+            .execute(&second, "commit")
+            .await
+            .unwrap();
+        Checkout::new(fs.clone()).execute(&owner).await.unwrap();
+        let status = Merge::new(fs.clone())
+            .execute_from_branch(second, owner.clone())
+            .await
+            .unwrap();
+        assert_eq!(status, MergedStatus::FastSource);
+
+        let reflog = Reflog::new(fs.clone());
+        assert_eq!(reflog.list(&owner).await.unwrap()[0].operation, "merge");
+        reflog.restore(&owner, 1).await.unwrap();
+        assert_eq!(
+            HeadIo::new(fs.clone()).try_read(&owner).await.unwrap(),
+            init
+        );
+        assert_eq!(reflog.list(&owner).await.unwrap()[0].operation, "restore");
+        assert!(matches!(
+            reflog.restore(&owner, 10).await,
+            Err(error::Error::NotfoundReflogEntry(_, 10))
+        ));
+    }
+
+    #[tokio::test]
+    async fn keep_commits_dropped_by_reset() {
+        let fs = MemoryFileSystem::default();
+        let owner = BranchName::owner();
+        let init = init_owner_branch(fs.clone()).await;
+        fs.write_sync("hello.txt", b"hello");
+        Stage::new(fs.clone()).execute(&owner, ".").await.unwrap();
+        Commit::new(fs.clone())
+            .execute(&owner, "commit")
+            .await
+            .unwrap();
+        Reset::new(fs.clone())
+            .execute(&init, ResetMode::Hard)
+            .await
+            .unwrap();
+
+        assert!(Gc::new(fs.clone())
+            .execute(true)
+            .await
+            .unwrap()
+            .objs
+            .is_empty());
+        let reflog = Reflog::new(fs.clone());
+        reflog.restore(&owner, 1).await.unwrap();
+        assert_ne!(
+            HeadIo::new(fs.clone()).try_read(&owner).await.unwrap(),
+            init
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use crate::branch::BranchName;
+use crate::error;
+use crate::file_system::FileSystem;
+use crate::io::atomic::head::HeadIo;
+use crate::io::atomic::local_commits::LocalCommitsIo;
+use crate::io::atomic::merge_head::MergeHeadIo;
+use crate::io::atomic::reflog::ReflogIo;
+use crate::io::atomic::work_branch::WorkingIo;
+
+/// Renames the branch, moving its head, its local commits, its reflog and the working branch if it is checked out.
+///
+/// The remote head is not renamed, because it refers to the branch in the room.
+#[derive(Debug, Clone)]
+pub struct RenameBranch<Fs>
+where
+    Fs: FileSystem,
+{
+    head: HeadIo<Fs>,
+    local_commits: LocalCommitsIo<Fs>,
+    merge_head: MergeHeadIo<Fs>,
+    reflog: ReflogIo<Fs>,
+    working: WorkingIo<Fs>,
+}
+
+impl<Fs> RenameBranch<Fs>
+where
+    Fs: FileSystem + Clone,
+{
+    #[inline]
+    pub fn new(fs: Fs) -> RenameBranch<Fs> {
+        Self {
+            head: HeadIo::new(fs.clone()),
+            local_commits: LocalCommitsIo::new(fs.clone()),
+            merge_head: MergeHeadIo::new(fs.clone()),
+            reflog: ReflogIo::new(fs.clone()),
+            working: WorkingIo::new(fs),
+        }
+    }
+}
+
+impl<Fs> RenameBranch<Fs>
+where
+    Fs: FileSystem,
+{
+    pub async fn execute(&self, old: &BranchName, new: &BranchName) -> error::Result {
+        old.validate()?;
+        new.validate()?;
+        let head = self.head.try_read(old).await?;
+        if self.head.read(new).await?.is_some() {
+            return Err(error::Error::BranchAlreadyExists(new.clone()));
+        }
+        if self
+            .merge_head
+            .read()
+            .await?
+            .is_some_and(|merge_head| &merge_head.dist == old)
+        {
+            return Err(error::Error::MergeInProgress(old.clone()));
+        }
+
+        self.head.write(new, &head).await?;
+        if let Some(local_commits) = self.local_commits.read(old).await? {
+            self.local_commits.write(&local_commits, new).await?;
+            self.local_commits.delete(old).await?;
+        }
+        self.reflog.rename(old, new).await?;
+        let working = self.working.read().await?.unwrap_or(BranchName::owner());
+        if &working == old {
+            self.working.write(new).await?;
+        }
+        self.head.delete(old).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::branch::BranchName;
+    use crate::error;
+    use crate::file_system::memory::MemoryFileSystem;
+    use crate::file_system::FileSystem;
+    use crate::io::atomic::head::HeadIo;
+    use crate::io::atomic::local_commits::LocalCommitsIo;
+    use crate::io::atomic::work_branch::WorkingIo;
+    use crate::operation::checkout::Checkout;
+    use crate::operation::commit::Commit;
+    use crate::operation::rename_branch::RenameBranch;
+    use crate::operation::stage::Stage;
+    use crate::tests::init_owner_branch;
+
+    #[tokio::test]
+    async fn move_head_local_commits_and_working() {
+        let fs = MemoryFileSystem::default();
+        let owner = BranchName::owner();
+        let renamed = BranchName::from("renamed");
+        init_owner_branch(fs.clone()).await;
+        fs.write_sync("hello.txt", b"hello");
+        Stage::new(fs.clone()).execute(&owner, ".").await.unwrap();
+        let commit_hash = Commit::new(fs.clone())
+            .execute(&owner, "commit")
+            .await
+            .unwrap();
+
+        RenameBranch::new(fs.clone())
+            .execute(&owner, &renamed)
+            .await
+            .unwrap();
+        let head = HeadIo::new(fs.clone());
+        assert!(head.read(&owner).await.unwrap().is_none());
+        assert_eq!(head.try_read(&renamed).await.unwrap(), commit_hash);
+        let local_commits = LocalCommitsIo::new(fs.clone());
+        assert!(local_commits.read(&owner).await.unwrap().is_none());
+        assert!(local_commits
+            .try_read(&renamed)
+            .await
+            .unwrap()
+            .contains(&commit_hash));
+        assert_eq!(WorkingIo::new(fs.clone()).try_read().await.unwrap(), renamed);
+    }
+
+    #[tokio::test]
+    async fn keep_working_if_other_branch() {
+        let fs = MemoryFileSystem::default();
+        let second = BranchName::from("second");
+        init_owner_branch(fs.clone()).await;
+        let checkout = Checkout::new(fs.clone());
+        checkout.execute(&second).await.unwrap();
+        checkout.execute(&BranchName::owner()).await.unwrap();
+
+        RenameBranch::new(fs.clone())
+            .execute(&second, &BranchName::from("third"))
+            .await
+            .unwrap();
+        assert_eq!(
+            WorkingIo::new(fs.clone()).try_read().await.unwrap(),
+            BranchName::owner()
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_if_new_branch_exists() {
+        let fs = MemoryFileSystem::default();
+        let second = BranchName::from("second");
+        init_owner_branch(fs.clone()).await;
+        Checkout::new(fs.clone()).execute(&second).await.unwrap();
+
+        let result = RenameBranch::new(fs.clone())
+            .execute(&BranchName::owner(), &second)
+            .await;
+        assert!(matches!(result, Err(error::Error::BranchAlreadyExists(_))));
+        assert!(fs.read_file(".meltos/refs/heads/owner").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn failed_if_branch_name_is_invalid() {
+        let fs = MemoryFileSystem::default();
+        init_owner_branch(fs.clone()).await;
+        let result = RenameBranch::new(fs.clone())
+            .execute(&BranchName::owner(), &BranchName::from("../tags/v1"))
+            .await;
+        assert!(matches!(result, Err(error::Error::InvalidBranchName(_))));
+        assert!(fs.read_file(".meltos/refs/tags/v1").await.unwrap().is_none());
+        assert!(HeadIo::new(fs.clone()).read(&BranchName::owner()).await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,265 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+use meltos_util::compression::gz::Gz;
+use meltos_util::compression::CompressionBuf;
+use serde::{Deserialize, Serialize};
+
+use crate::error;
+use crate::file_system::FileSystem;
+use crate::io::atomic::object::ObjIo;
+use crate::io::atomic::pack::{delta, PackEntry, PackIo};
+use crate::object::ObjHash;
+
+/// The number of the preceding objects tried as the delta base.
+const WINDOW: usize = 10;
+
+/// The maximum length of the delta chains, which limits the cost of reading an object.
+const MAX_DEPTH: usize = 10;
+
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RepackReport {
+    /// The name of the new pack; `None` if there were no objects.
+    pub pack: Option<String>,
+    pub objs: usize,
+    /// The number of the objects stored as deltas.
+    pub deltas: usize,
+}
+
+/// Moves all loose and packed objects into a single new pack.
+///
+/// Each object is stored as the delta against a similar object of the same type if it is small enough.
+#[derive(Debug, Clone)]
+pub struct Repack<Fs>
+where
+    Fs: FileSystem,
+{
+    object: ObjIo<Fs>,
+    pack: PackIo<Fs>,
+}
+
+impl<Fs> Repack<Fs>
+where
+    Fs: FileSystem + Clone,
+{
+    #[inline]
+    pub fn new(fs: Fs) -> Repack<Fs> {
+        Self {
+            object: ObjIo::new(fs.clone()),
+            pack: PackIo::new(fs),
+        }
+    }
+}
+
+impl<Fs> Repack<Fs>
+where
+    Fs: FileSystem,
+{
+    #[inline]
+    pub async fn execute(&self) -> error::Result<RepackReport> {
+        self.execute_without(&HashSet::new()).await
+    }
+
+    /// Repacks except `excludes`, which are deleted from the loose objects and the packs.
+    pub async fn execute_without(
+        &self,
+        excludes: &HashSet<ObjHash>,
+    ) -> error::Result<RepackReport> {
+        let old_packs = self.pack.read_names().await?;
+        // Only the loose objects listed here are deleted after packing,
+        // so that the objects written in the meantime are kept.
+        let loose_hashes = self.object.read_loose_hashes().await?;
+        let loose = loose_hashes.iter().collect::<HashSet<&ObjHash>>();
+        let packed_hashes = self.pack.read_all_hashes().await?;
+        let mut objs = Vec::new();
+        for hash in loose_hashes
+            .iter()
+            .chain(packed_hashes.iter().filter(|hash| !loose.contains(hash)))
+        {
+            if excludes.contains(hash) {
+                continue;
+            }
+            let compressed_buf = self
+                .object
+                .read(hash)
+                .await?
+                .ok_or_else(|| error::Error::NotfoundObj(hash.clone()))?;
+            let buf = Gz.unzip(&compressed_buf)?;
+            objs.push((hash.clone(), compressed_buf, buf));
+        }
+        // Similar objects are likely to have the same type and close sizes,
+        // and the larger ones become the bases because deletions are cheaper than insertions.
+        objs.sort_by(|l, r| {
+            obj_type(&l.2)
+                .cmp(obj_type(&r.2))
+                .then(Reverse(l.2.len()).cmp(&Reverse(r.2.len())))
+                .then(l.0.cmp(&r.0))
+        });
+
+        let mut report = RepackReport {
+            objs: objs.len(),
+            ..RepackReport::default()
+        };
+        let mut depths = HashMap::<&ObjHash, usize>::new();
+        let mut entries = Vec::with_capacity(objs.len());
+        for (i, (hash, compressed_buf, buf)) in objs.iter().enumerate() {
+            let mut best: Option<(&ObjHash, Vec<u8>)> = None;
+            for (base_hash, _, base_buf) in objs[i.saturating_sub(WINDOW)..i].iter() {
+                if obj_type(base_buf) != obj_type(buf)
+                    || MAX_DEPTH <= depths.get(base_hash).copied().unwrap_or_default()
+                {
+                    continue;
+                }
+                let delta = delta::encode(base_buf, buf);
+                let smaller = best
+                    .as_ref()
+                    .map_or(delta.len() < buf.len() / 2, |(_, best)| {
+                        delta.len() < best.len()
+                    });
+                if smaller {
+                    best = Some((base_hash, delta));
+                }
+            }
+
+            match best {
+                Some((base, delta)) => {
+                    depths.insert(hash, depths.get(base).copied().unwrap_or_default() + 1);
+                    report.deltas += 1;
+                    entries.push(PackEntry::Delta {
+                        hash: hash.clone(),
+                        base: base.clone(),
+                        delta,
+                    });
+                }
+                None => entries.push(PackEntry::Full {
+                    hash: hash.clone(),
+                    compressed_buf: compressed_buf.clone(),
+                }),
+            }
+        }
+
+        if !entries.is_empty() {
+            report.pack = Some(self.pack.write(&entries).await?);
+        }
+        for name in old_packs {
+            if report.pack.as_ref() != Some(&name) {
+                self.pack.delete(&name).await?;
+            }
+        }
+        for hash in loose_hashes.iter() {
+            self.object.delete(hash).await?;
+        }
+        Ok(report)
+    }
+}
+
+/// Returns the header of the object such as `FILE`.
+#[inline]
+fn obj_type(buf: &[u8]) -> &[u8] {
+    buf.split(|b| *b == 0).next().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::branch::BranchName;
+    use crate::file_system::memory::MemoryFileSystem;
+    use crate::file_system::FileSystem;
+    use crate::io::atomic::object::ObjIo;
+    use crate::io::atomic::pack::PackIo;
+    use crate::object::file::FileObj;
+    use crate::object::AsMeta;
+    use crate::operation::commit::Commit;
+    use crate::operation::log::{Log, LogOptions};
+    use crate::operation::repack::Repack;
+    use crate::operation::stage::Stage;
+    use crate::tests::init_owner_branch;
+
+    #[tokio::test]
+    async fn read_objects_after_repack() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        let text = "hello world!\n".repeat(100);
+        fs.write_sync("hello.txt", text.as_bytes());
+        init_owner_branch(fs.clone()).await;
+        fs.write_sync("hello.txt", format!("{text}rust\n").as_bytes());
+        Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        let head = Commit::new(fs.clone())
+            .execute(&branch, "commit")
+            .await
+            .unwrap();
+
+        let obj = ObjIo::new(fs.clone());
+        let mut hashes = obj.read_all_hashes().await.unwrap();
+        hashes.sort();
+        let objs = obj.read_all().await.unwrap();
+
+        let report = Repack::new(fs.clone()).execute().await.unwrap();
+        assert_eq!(report.objs, hashes.len());
+        assert!(0 < report.deltas);
+        assert!(fs.all_files_in(".meltos/objects").await.unwrap().is_empty());
+
+        let obj = ObjIo::new(fs.clone());
+        let mut packed_hashes = obj.read_all_hashes().await.unwrap();
+        packed_hashes.sort();
+        assert_eq!(packed_hashes, hashes);
+        for expect in objs {
+            let packed = obj.try_read_obj(&expect.hash).await.unwrap();
+            assert_eq!(packed.as_meta().unwrap().hash, expect.hash);
+        }
+        let file = obj
+            .read_to_file(
+                &FileObj(format!("{text}rust\n").into_bytes())
+                    .as_meta()
+                    .unwrap()
+                    .hash,
+            )
+            .await
+            .unwrap();
+        assert_eq!(file.0, format!("{text}rust\n").into_bytes());
+        let log = Log::new(fs.clone())
+            .execute(head, LogOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(log.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn repack_twice() {
+        let fs = MemoryFileSystem::default();
+        fs.write_sync("hello.txt", b"hello");
+        init_owner_branch(fs.clone()).await;
+        let repack = Repack::new(fs.clone());
+        repack.execute().await.unwrap();
+        fs.write_sync("world.txt", b"world");
+        Stage::new(fs.clone())
+            .execute(&BranchName::owner(), ".")
+            .await
+            .unwrap();
+
+        let report = repack.execute().await.unwrap();
+        assert_eq!(
+            PackIo::new(fs.clone()).read_names().await.unwrap(),
+            vec![report.pack.unwrap()]
+        );
+        let world = FileObj(b"world".to_vec()).as_meta().unwrap();
+        assert!(ObjIo::new(fs).read(&world.hash).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn exclude_objects() {
+        let fs = MemoryFileSystem::default();
+        fs.write_sync("hello.txt", b"hello");
+        init_owner_branch(fs.clone()).await;
+        let hello = FileObj(b"hello".to_vec()).as_meta().unwrap();
+        let repack = Repack::new(fs.clone());
+        repack.execute().await.unwrap();
+
+        repack
+            .execute_without(&HashSet::from([hello.hash.clone()]))
+            .await
+            .unwrap();
+        assert!(ObjIo::new(fs).read(&hello.hash).await.unwrap().is_none());
+    }
+}
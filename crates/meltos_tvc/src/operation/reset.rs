@@ -0,0 +1,284 @@
+use std::collections::HashSet;
+
+use crate::branch::BranchName;
+use crate::error;
+use crate::file_system::FileSystem;
+use crate::io::atomic::head::HeadIo;
+use crate::io::atomic::local_commits::LocalCommitsIo;
+use crate::io::atomic::merge_head::MergeHeadIo;
+use crate::io::atomic::object::ObjIo;
+use crate::io::atomic::sparse_checkout::SparseCheckoutIo;
+use crate::io::atomic::staging::StagingIo;
+use crate::io::atomic::work_branch::WorkingIo;
+use crate::io::commit_hashes::CommitHashIo;
+use crate::io::trace_tree::TraceTreeIo;
+use crate::object::commit::CommitHash;
+use crate::object::delete::DeleteObj;
+use crate::object::local_commits::LocalCommitsObj;
+use crate::object::tree::TreeObj;
+use crate::object::{AsMeta, Obj, ObjHash};
+use crate::operation::unzip::UnZip;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ResetMode {
+    /// Keeps the staged files and the workspace;
+    /// the changes of the dropped commits are staged.
+    Soft,
+    /// Keeps the workspace, and rebuilds the stage from the new head;
+    /// the changes of the dropped commits remain only in the workspace.
+    Mixed,
+    /// Restores the stage and the workspace to the new head.
+    Hard,
+}
+
+/// Moves the head of the working branch to the commit.
+///
+/// The local commits that are no longer reachable from the new head are dropped,
+/// so that they are not pushed later.
+#[derive(Debug, Clone)]
+pub struct Reset<Fs>
+where
+    Fs: FileSystem,
+{
+    head: HeadIo<Fs>,
+    local_commits: LocalCommitsIo<Fs>,
+    merge_head: MergeHeadIo<Fs>,
+    staging: StagingIo<Fs>,
+    working: WorkingIo<Fs>,
+    commit_hashes: CommitHashIo<Fs>,
+    trace_tree: TraceTreeIo<Fs>,
+    object: ObjIo<Fs>,
+    sparse_checkout: SparseCheckoutIo<Fs>,
+    unzip: UnZip<Fs>,
+    fs: Fs,
+}
+
+impl<Fs> Reset<Fs>
+where
+    Fs: FileSystem + Clone,
+{
+    pub fn new(fs: Fs) -> Reset<Fs> {
+        Self {
+            head: HeadIo::new(fs.clone()),
+            local_commits: LocalCommitsIo::new(fs.clone()),
+            merge_head: MergeHeadIo::new(fs.clone()),
+            staging: StagingIo::new(fs.clone()),
+            working: WorkingIo::new(fs.clone()),
+            commit_hashes: CommitHashIo::new(fs.clone()),
+            trace_tree: TraceTreeIo::new(fs.clone()),
+            object: ObjIo::new(fs.clone()),
+            sparse_checkout: SparseCheckoutIo::new(fs.clone()),
+            unzip: UnZip::new(fs.clone()),
+            fs,
+        }
+    }
+}
+
+impl<Fs> Reset<Fs>
+where
+    Fs: FileSystem,
+{
+    pub async fn execute(&self, commit_hash: &CommitHash, mode: ResetMode) -> error::Result {
+        if let Some(merge_head) = self.merge_head.read().await? {
+            return Err(error::Error::MergeInProgress(merge_head.dist));
+        }
+        let branch_name = self.working.read().await?.unwrap_or(BranchName::owner());
+        let head = self.head.try_read(&branch_name).await?;
+        let new_trace = self.trace_tree.read(commit_hash).await?;
+        let mut old_index = self.trace_tree.read(&head).await?;
+        let stage = self.staging.read().await?.unwrap_or_default();
+        old_index.replace_by(stage);
+
+        self.drop_local_commits(&branch_name, commit_hash).await?;
+        self.head.update(&branch_name, commit_hash, "reset").await?;
+        match mode {
+            ResetMode::Soft => {
+                let stage = self.diff_tree(&new_trace, &old_index).await?;
+                self.staging.write_tree(&stage).await
+            }
+            ResetMode::Mixed => self.staging.reset().await,
+            ResetMode::Hard => {
+                self.staging.reset().await?;
+                let sparse = self.sparse_checkout.read().await?;
+                for path in old_index.keys() {
+                    if !new_trace.contains_key(path) && sparse.contains(path) {
+                        self.fs.delete(path).await?;
+                    }
+                }
+                self.unzip.execute(&branch_name).await
+            }
+        }
+    }
+
+    async fn drop_local_commits(
+        &self,
+        branch_name: &BranchName,
+        commit_hash: &CommitHash,
+    ) -> error::Result {
+        let Some(local_commits) = self.local_commits.read(branch_name).await? else {
+            return Ok(());
+        };
+        let reachable = self
+            .commit_hashes
+            .read_all(commit_hash.clone(), &None)
+            .await?
+            .into_iter()
+            .collect::<HashSet<_>>();
+        let local_commits = local_commits
+            .0
+            .into_iter()
+            .filter(|hash| reachable.contains(hash))
+            .collect();
+        self.local_commits
+            .write(&LocalCommitsObj(local_commits), branch_name)
+            .await
+    }
+
+    /// Returns the tree which should be staged on top of `base` to get `target`.
+    async fn diff_tree(&self, base: &TreeObj, target: &TreeObj) -> error::Result<TreeObj> {
+        let mut tree = TreeObj::default();
+        for (path, hash) in target.iter() {
+            let mode = target.mode(path);
+            if !base.changed(path, hash, mode) {
+                continue;
+            }
+            if self.is_deleted(Some(hash)).await? && self.is_deleted(base.get(path)).await? {
+                continue;
+            }
+            tree.insert_with_mode(path.clone(), hash.clone(), mode);
+        }
+        for (path, hash) in base.iter() {
+            if target.contains_key(path) || self.is_deleted(Some(hash)).await? {
+                continue;
+            }
+            let delete_obj = DeleteObj(hash.clone());
+            self.object.write_obj(&delete_obj).await?;
+            tree.insert(path.clone(), delete_obj.as_meta()?.hash);
+        }
+        Ok(tree)
+    }
+
+    async fn is_deleted(&self, hash: Option<&ObjHash>) -> error::Result<bool> {
+        let Some(hash) = hash else {
+            return Ok(true);
+        };
+        Ok(matches!(
+            self.object.try_read_obj(hash).await?,
+            Obj::Delete(_)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::branch::BranchName;
+    use crate::file_system::memory::MemoryFileSystem;
+    use crate::file_system::{FilePath, FileSystem};
+    use crate::io::atomic::head::HeadIo;
+    use crate::io::atomic::local_commits::LocalCommitsIo;
+    use crate::io::atomic::staging::StagingIo;
+    use crate::object::commit::CommitHash;
+    use crate::operation::commit::Commit;
+    use crate::operation::reset::{Reset, ResetMode};
+    use crate::operation::stage::Stage;
+    use crate::tests::init_owner_branch;
+
+    async fn commit_twice(fs: &MemoryFileSystem) -> (CommitHash, CommitHash) {
+        let branch = BranchName::owner();
+        fs.write_sync("hello.txt", b"hello");
+        init_owner_branch(fs.clone()).await;
+        fs.write_sync("hello.txt", b"first");
+        Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        let first = Commit::new(fs.clone())
+            .execute(&branch, "first")
+            .await
+            .unwrap();
+        fs.write_sync("hello.txt", b"second");
+        fs.write_sync("added.txt", b"added");
+        Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        let second = Commit::new(fs.clone())
+            .execute(&branch, "second")
+            .await
+            .unwrap();
+        (first, second)
+    }
+
+    #[tokio::test]
+    async fn drop_local_commits() {
+        let fs = MemoryFileSystem::default();
+        let (first, second) = commit_twice(&fs).await;
+        Reset::new(fs.clone())
+            .execute(&first, ResetMode::Mixed)
+            .await
+            .unwrap();
+
+        let branch = BranchName::owner();
+        assert_eq!(
+            HeadIo::new(fs.clone()).try_read(&branch).await.unwrap(),
+            first
+        );
+        let local_commits = LocalCommitsIo::new(fs.clone())
+            .try_read(&branch)
+            .await
+            .unwrap();
+        assert!(local_commits.contains(&first));
+        assert!(!local_commits.contains(&second));
+    }
+
+    #[tokio::test]
+    async fn soft_stages_dropped_changes() {
+        let fs = MemoryFileSystem::default();
+        let (first, _) = commit_twice(&fs).await;
+        Reset::new(fs.clone())
+            .execute(&first, ResetMode::Soft)
+            .await
+            .unwrap();
+
+        let stage = StagingIo::new(fs.clone()).read().await.unwrap().unwrap();
+        assert!(stage.contains_key(&FilePath::from("hello.txt")));
+        assert!(stage.contains_key(&FilePath::from("added.txt")));
+        assert_eq!(fs.try_read_file("hello.txt").await.unwrap(), b"second");
+    }
+
+    #[tokio::test]
+    async fn mixed_keeps_workspace() {
+        let fs = MemoryFileSystem::default();
+        let (first, _) = commit_twice(&fs).await;
+        fs.write_sync("staged.txt", b"staged");
+        Stage::new(fs.clone())
+            .execute(&BranchName::owner(), ".")
+            .await
+            .unwrap();
+        Reset::new(fs.clone())
+            .execute(&first, ResetMode::Mixed)
+            .await
+            .unwrap();
+
+        assert!(StagingIo::new(fs.clone())
+            .read()
+            .await
+            .unwrap()
+            .is_none_or(|stage| stage.is_empty()));
+        assert_eq!(fs.try_read_file("hello.txt").await.unwrap(), b"second");
+        assert_eq!(fs.try_read_file("staged.txt").await.unwrap(), b"staged");
+    }
+
+    #[tokio::test]
+    async fn hard_restores_workspace() {
+        let fs = MemoryFileSystem::default();
+        let (first, _) = commit_twice(&fs).await;
+        fs.write_sync("hello.txt", b"modified");
+        Reset::new(fs.clone())
+            .execute(&first, ResetMode::Hard)
+            .await
+            .unwrap();
+
+        assert!(StagingIo::new(fs.clone())
+            .read()
+            .await
+            .unwrap()
+            .is_none_or(|stage| stage.is_empty()));
+        assert_eq!(fs.try_read_file("hello.txt").await.unwrap(), b"first");
+        assert!(fs.read_file("added.txt").await.unwrap().is_none());
+    }
+}
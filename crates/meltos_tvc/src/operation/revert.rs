@@ -0,0 +1,241 @@
+use crate::branch::BranchName;
+use crate::error;
+use crate::file_system::FileSystem;
+use crate::io::atomic::merge_head::MergeKind;
+use crate::io::atomic::object::ObjIo;
+use crate::io::commit_obj::CommitObjIo;
+use crate::io::trace_tree::TraceTreeIo;
+use crate::object::commit::CommitHash;
+use crate::object::delete::DeleteObj;
+use crate::object::tree::TreeObj;
+use crate::object::AsMeta;
+use crate::operation::merge::{Merge, MergedStatus};
+
+/// Undoes the changes of a commit by creating a new commit.
+#[derive(Debug)]
+pub struct Revert<Fs>
+where
+    Fs: FileSystem,
+{
+    merge: Merge<Fs>,
+    commit_obj: CommitObjIo<Fs>,
+    trace_tree: TraceTreeIo<Fs>,
+    object: ObjIo<Fs>,
+}
+
+impl<Fs> Revert<Fs>
+where
+    Fs: FileSystem + Clone,
+{
+    pub fn new(fs: Fs) -> Revert<Fs> {
+        Self {
+            merge: Merge::new(fs.clone()),
+            commit_obj: CommitObjIo::new(fs.clone()),
+            trace_tree: TraceTreeIo::new(fs.clone()),
+            object: ObjIo::new(fs),
+        }
+    }
+
+    /// Creates the commit which restores the files changed by `commit_hash` to its first parent on top of `dist`.
+    ///
+    /// If the files have been changed since then, they are merged line by line,
+    /// and the conflicts are handled in the same way as [`Merge::execute`].
+    pub async fn execute(
+        &self,
+        commit_hash: CommitHash,
+        dist: BranchName,
+    ) -> error::Result<MergedStatus> {
+        let commit = self.commit_obj.read(&commit_hash).await?;
+        let parent_tree = match commit.parents.first() {
+            Some(parent) => self.trace_tree.read(parent).await?,
+            None => TreeObj::default(),
+        };
+        let commit_tree = self.trace_tree.read(&commit_hash).await?;
+        let changes = self.commit_obj.read_commit_tree(&commit_hash).await?;
+
+        let mut reverted = TreeObj::default();
+        for (path, hash) in changes.iter() {
+            if let Some(parent_hash) = parent_tree.get(path) {
+                reverted.insert_with_mode(
+                    path.clone(),
+                    parent_hash.clone(),
+                    parent_tree.mode(path),
+                );
+            } else {
+                // the file added by the commit is deleted.
+                let delete_obj = DeleteObj(commit_tree.get(path).unwrap_or(hash).clone());
+                self.object.write_obj(&delete_obj).await?;
+                reverted.insert(path.clone(), delete_obj.as_meta()?.hash);
+            }
+        }
+        self.merge
+            .apply_changes(
+                MergeKind::Revert,
+                commit_hash,
+                &commit_tree,
+                &reverted,
+                dist,
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::branch::BranchName;
+    use crate::error;
+    use crate::file_system::memory::MemoryFileSystem;
+    use crate::file_system::{FilePath, FileSystem};
+    use crate::io::atomic::head::HeadIo;
+    use crate::io::commit_obj::CommitObjIo;
+    use crate::operation::commit::Commit;
+    use crate::operation::merge::{Merge, MergedStatus};
+    use crate::operation::revert::Revert;
+    use crate::operation::stage::Stage;
+    use crate::tests::init_owner_branch;
+
+    #[tokio::test]
+    async fn revert_commit() {
+        let fs = MemoryFileSystem::default();
+        let owner = BranchName::owner();
+        fs.write_sync("hello.txt", b"hello");
+        init_owner_branch(fs.clone()).await;
+
+        fs.write_sync("hello.txt", b"bad");
+        fs.write_sync("added.txt", b"added");
+        Stage::new(fs.clone()).execute(&owner, ".").await.unwrap();
+        let bad = Commit::new(fs.clone())
+            .execute(&owner, "bad")
+            .await
+            .unwrap();
+        fs.write_sync("other.txt", b"other");
+        Stage::new(fs.clone()).execute(&owner, ".").await.unwrap();
+        Commit::new(fs.clone())
+            .execute(&owner, "other")
+            .await
+            .unwrap();
+
+        let status = Revert::new(fs.clone())
+            .execute(bad.clone(), owner.clone())
+            .await
+            .unwrap();
+        assert_eq!(status, MergedStatus::Merged);
+        assert_eq!(fs.try_read_file("hello.txt").await.unwrap(), b"hello");
+        assert!(fs.read_file("added.txt").await.unwrap().is_none());
+        assert_eq!(fs.try_read_file("other.txt").await.unwrap(), b"other");
+
+        let head = HeadIo::new(fs.clone()).try_read(&owner).await.unwrap();
+        let commit = CommitObjIo::new(fs.clone()).read(&head).await.unwrap();
+        assert_eq!(commit.text.0, format!("reverted {bad}"));
+    }
+
+    #[tokio::test]
+    async fn merge_changes_made_after_commit() {
+        let fs = MemoryFileSystem::default();
+        let owner = BranchName::owner();
+        fs.write_sync("hello.txt", b"line1\nline2\nline3\n");
+        init_owner_branch(fs.clone()).await;
+
+        fs.write_sync("hello.txt", b"bad\nline2\nline3\n");
+        Stage::new(fs.clone()).execute(&owner, ".").await.unwrap();
+        let bad = Commit::new(fs.clone())
+            .execute(&owner, "bad")
+            .await
+            .unwrap();
+        fs.write_sync("hello.txt", b"bad\nline2\ngood\n");
+        Stage::new(fs.clone()).execute(&owner, ".").await.unwrap();
+        Commit::new(fs.clone())
+            .execute(&owner, "good")
+            .await
+            .unwrap();
+
+        let status = Revert::new(fs.clone()).execute(bad, owner).await.unwrap();
+        assert_eq!(status, MergedStatus::Merged);
+        assert_eq!(
+            fs.try_read_file("hello.txt").await.unwrap(),
+            b"line1\nline2\ngood\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn conflict_with_later_commit() {
+        let fs = MemoryFileSystem::default();
+        let owner = BranchName::owner();
+        fs.write_sync("hello.txt", b"hello");
+        init_owner_branch(fs.clone()).await;
+
+        fs.write_sync("hello.txt", b"bad");
+        Stage::new(fs.clone()).execute(&owner, ".").await.unwrap();
+        let bad = Commit::new(fs.clone())
+            .execute(&owner, "bad")
+            .await
+            .unwrap();
+        fs.write_sync("hello.txt", b"later");
+        Stage::new(fs.clone()).execute(&owner, ".").await.unwrap();
+        Commit::new(fs.clone())
+            .execute(&owner, "later")
+            .await
+            .unwrap();
+
+        let status = Revert::new(fs.clone())
+            .execute(bad.clone(), owner)
+            .await
+            .unwrap();
+        assert!(matches!(status, MergedStatus::Conflicted(_)));
+        let text = String::from_utf8(fs.try_read_file("hello.txt").await.unwrap()).unwrap();
+        assert!(text.contains(&format!(">>>>>>> parent of {bad}")));
+
+        Merge::new(fs.clone()).abort().await.unwrap();
+        assert_eq!(fs.try_read_file("hello.txt").await.unwrap(), b"later");
+    }
+
+    #[tokio::test]
+    async fn failed_if_unrelated_file_staged() {
+        let fs = MemoryFileSystem::default();
+        let owner = BranchName::owner();
+        fs.write_sync("hello.txt", b"hello");
+        init_owner_branch(fs.clone()).await;
+
+        fs.write_sync("hello.txt", b"bad");
+        Stage::new(fs.clone()).execute(&owner, ".").await.unwrap();
+        let bad = Commit::new(fs.clone())
+            .execute(&owner, "bad")
+            .await
+            .unwrap();
+        fs.write_sync("staged.txt", b"staged");
+        Stage::new(fs.clone()).execute(&owner, ".").await.unwrap();
+
+        let result = Revert::new(fs.clone()).execute(bad, owner.clone()).await;
+        match result {
+            Err(error::Error::LocalChangesWouldBeOverwritten(files)) => {
+                assert_eq!(files, vec![FilePath::from("staged.txt")]);
+            }
+            _ => panic!("expected LocalChangesWouldBeOverwritten but was {result:?}"),
+        }
+        assert_eq!(fs.try_read_file("hello.txt").await.unwrap(), b"bad");
+        assert_eq!(fs.try_read_file("staged.txt").await.unwrap(), b"staged");
+    }
+
+    #[tokio::test]
+    async fn failed_if_reverted_file_modified() {
+        let fs = MemoryFileSystem::default();
+        let owner = BranchName::owner();
+        fs.write_sync("hello.txt", b"hello");
+        init_owner_branch(fs.clone()).await;
+
+        fs.write_sync("hello.txt", b"bad");
+        Stage::new(fs.clone()).execute(&owner, ".").await.unwrap();
+        let bad = Commit::new(fs.clone())
+            .execute(&owner, "bad")
+            .await
+            .unwrap();
+        fs.write_sync("hello.txt", b"editing");
+
+        let result = Revert::new(fs.clone()).execute(bad, owner).await;
+        assert!(matches!(
+            result,
+            Err(error::Error::LocalChangesWouldBeOverwritten(_))
+        ));
+        assert_eq!(fs.try_read_file("hello.txt").await.unwrap(), b"editing");
+    }
+}
@@ -1,10 +1,13 @@
+use futures::{Stream, StreamExt};
+
 use crate::branch::BranchName;
 use crate::error;
 use crate::file_system::FileSystem;
 use crate::io::atomic::head::HeadIo;
 use crate::io::atomic::object::ObjIo;
+use crate::io::atomic::tag::TagIo;
 use crate::io::atomic::trace::TraceIo;
-use crate::io::bundle::{Bundle, BundleBranch, BundleObject, BundleTrace};
+use crate::io::bundle::{Bundle, BundleBranch, BundleObject, BundleRecord, BundleTag, BundleTrace};
 use crate::object::commit::CommitHash;
 
 #[derive(Debug, Clone)]
@@ -15,6 +18,7 @@ where
     trace: TraceIo<Fs>,
     object: ObjIo<Fs>,
     head: HeadIo<Fs>,
+    tag: TagIo<Fs>,
 }
 
 impl<Fs> Save<Fs>
@@ -25,19 +29,44 @@ where
         Self {
             trace: TraceIo::new(fs.clone()),
             object: ObjIo::new(fs.clone()),
-            head: HeadIo::new(fs),
+            head: HeadIo::new(fs.clone()),
+            tag: TagIo::new(fs),
         }
     }
 
     /// * write objs.
+    /// * write tags which do not exist yet.
     /// * write head.
     /// * write traces related to commits.
     pub async fn execute(&self, bundle: Bundle) -> error::Result {
         self.write_objs(bundle.objs).await?;
+        self.write_tags(&bundle.tags).await?;
         self.write_branches(&bundle.branches).await?;
         self.write_traces(bundle.traces).await
     }
 
+    /// Writes the records one by one as they are received,
+    /// so the whole bundle is never held in memory.
+    pub async fn execute_stream<S>(&self, records: S) -> error::Result
+    where
+        S: Stream<Item = error::Result<BundleRecord>>,
+    {
+        futures::pin_mut!(records);
+        while let Some(record) = records.next().await {
+            self.write_record(record?).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn write_record(&self, record: BundleRecord) -> error::Result {
+        match record {
+            BundleRecord::Trace(trace) => self.write_traces(vec![trace]).await,
+            BundleRecord::Obj(obj) => self.write_objs(vec![obj]).await,
+            BundleRecord::Branch(branch) => self.write_branches(&[branch]).await,
+            BundleRecord::Tag(tag) => self.write_tags(&[tag]).await,
+        }
+    }
+
     async fn write_objs(&self, objs: Vec<BundleObject>) -> error::Result {
         for obj in objs {
             self.object.write(&obj.hash, &obj.compressed_buf).await?;
@@ -48,18 +77,27 @@ where
 
     async fn write_branches(&self, branches: &[BundleBranch]) -> error::Result {
         for branch in branches {
-            self.write_head(
-                &branch.branch_name,
-                &branch.commits[branch.commits.len() - 1],
-            )
-            .await?;
+            let Some(head) = branch.commits.last() else {
+                return Err(error::Error::NotfoundHead(branch.branch_name.clone()));
+            };
+            self.write_head(&branch.branch_name, head).await?;
+        }
+        Ok(())
+    }
+
+    /// Tags are immutable, so the existing tags are kept even if the bundle refers to another commit.
+    async fn write_tags(&self, tags: &[BundleTag]) -> error::Result {
+        for tag in tags {
+            if self.tag.read(&tag.tag_name).await?.is_none() {
+                self.tag.write(&tag.tag_name, &tag.hash).await?;
+            }
         }
         Ok(())
     }
 
     #[inline]
     async fn write_head(&self, branch: &BranchName, head_hash: &CommitHash) -> error::Result {
-        self.head.write(branch, head_hash).await?;
+        self.head.update(branch, head_hash, "save").await?;
         Ok(())
     }
 
@@ -77,12 +115,36 @@ where
 mod tests {
     use crate::branch::BranchName;
     use crate::encode::Encodable;
+    use crate::error;
     use crate::file_system::memory::MemoryFileSystem;
     use crate::file_system::FileSystem;
-    use crate::io::bundle::{Bundle, BundleBranch};
+    use crate::io::atomic::head::HeadIo;
+    use crate::io::atomic::object::ObjIo;
+    use crate::io::atomic::trace::TraceIo;
+    use crate::io::atomic::tag::TagIo;
+    use crate::io::bundle::{Bundle, BundleBranch, BundleIo, BundleTag};
     use crate::object::commit::CommitHash;
-    use crate::object::ObjHash;
+    use crate::object::file::FileObj;
+    use crate::object::{AsMeta, ObjHash};
     use crate::operation::save::Save;
+    use crate::operation::tag::Tag;
+    use crate::tag::TagName;
+    use crate::tests::init_owner_branch;
+
+    #[tokio::test]
+    async fn save_stream() {
+        let fs = MemoryFileSystem::default();
+        fs.write_sync("hello.txt", b"hello");
+        let head = init_owner_branch(fs.clone()).await;
+        let stream = BundleIo::new(fs.clone()).create_stream().await.unwrap();
+
+        let fs2 = MemoryFileSystem::default();
+        Save::new(fs2.clone()).execute_stream(stream).await.unwrap();
+        assert_eq!(HeadIo::new(fs2.clone()).try_read(&BranchName::owner()).await.unwrap(), head);
+        let hello = FileObj(b"hello".to_vec()).as_meta().unwrap();
+        assert!(ObjIo::new(fs2.clone()).read(&hello.hash).await.unwrap().is_some());
+        assert!(TraceIo::new(fs2).exists(&head).await.unwrap());
+    }
 
     #[tokio::test]
     async fn created_head_file() {
@@ -97,9 +159,69 @@ mod tests {
             }],
             traces: Vec::with_capacity(0),
             objs: Vec::with_capacity(0),
+            tags: Vec::with_capacity(0),
         };
         save.execute(bundle).await.unwrap();
         let actual = fs.try_read_file(".meltos/refs/heads/owner").await.unwrap();
         assert_eq!(actual, head.encode().unwrap());
     }
+
+    #[tokio::test]
+    async fn keep_existing_tags() {
+        let fs = MemoryFileSystem::default();
+        let head = init_owner_branch(fs.clone()).await;
+        let tag_name = TagName::from("v1");
+        Tag::new(fs.clone()).create(&tag_name, &head).await.unwrap();
+
+        let other = ObjHash::new(b"other");
+        let bundle = Bundle {
+            tags: vec![
+                BundleTag {
+                    tag_name: tag_name.clone(),
+                    hash: other.clone(),
+                },
+                BundleTag {
+                    tag_name: TagName::from("v2"),
+                    hash: other.clone(),
+                },
+            ],
+            ..Bundle::default()
+        };
+        Save::new(fs.clone()).execute(bundle).await.unwrap();
+        let tag = TagIo::new(fs.clone());
+        assert_eq!(tag.try_read(&tag_name).await.unwrap(), head.0);
+        assert_eq!(tag.try_read(&TagName::from("v2")).await.unwrap(), other);
+    }
+
+    #[tokio::test]
+    async fn failed_if_tag_name_escapes_tags_dir() {
+        let fs = MemoryFileSystem::default();
+        let head = init_owner_branch(fs.clone()).await;
+        let bundle = Bundle {
+            tags: vec![BundleTag {
+                tag_name: TagName::from("../heads/owner"),
+                hash: ObjHash::new(b"other"),
+            }],
+            ..Bundle::default()
+        };
+        let result = Save::new(fs.clone()).execute(bundle).await;
+        assert!(matches!(result, Err(error::Error::InvalidTagName(_))));
+        assert_eq!(HeadIo::new(fs).try_read(&BranchName::owner()).await.unwrap(), head);
+    }
+
+    #[tokio::test]
+    async fn failed_if_branch_has_no_commits() {
+        let fs = MemoryFileSystem::default();
+        let head = init_owner_branch(fs.clone()).await;
+        let bundle = Bundle {
+            branches: vec![BundleBranch {
+                branch_name: BranchName::owner(),
+                commits: Vec::new(),
+            }],
+            ..Bundle::default()
+        };
+        let result = Save::new(fs.clone()).execute(bundle).await;
+        assert!(matches!(result, Err(error::Error::NotfoundHead(_))));
+        assert_eq!(HeadIo::new(fs).try_read(&BranchName::owner()).await.unwrap(), head);
+    }
 }
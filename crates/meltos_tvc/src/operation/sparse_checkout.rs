@@ -0,0 +1,222 @@
+use crate::branch::BranchName;
+use crate::error;
+use crate::file_system::FileSystem;
+use crate::io::atomic::head::HeadIo;
+use crate::io::atomic::merge_head::MergeHeadIo;
+use crate::io::atomic::object::ObjIo;
+use crate::io::atomic::sparse_checkout::{SparseCheckoutIo, SparsePatterns};
+use crate::io::atomic::work_branch::WorkingIo;
+use crate::io::trace_tree::TraceTreeIo;
+use crate::io::workspace::WorkspaceIo;
+use crate::object::{AsMeta, Obj};
+
+/// Limits the files written into the workspace to the ones matching the patterns.
+///
+/// The files outside the patterns remain in the trace trees,
+/// and they are neither written by [`UnZip`](crate::operation::unzip::UnZip) nor staged as deleted.
+#[derive(Debug, Clone)]
+pub struct SparseCheckout<Fs>
+where
+    Fs: FileSystem,
+{
+    sparse_checkout: SparseCheckoutIo<Fs>,
+    working: WorkingIo<Fs>,
+    head: HeadIo<Fs>,
+    merge_head: MergeHeadIo<Fs>,
+    trace_tree: TraceTreeIo<Fs>,
+    object: ObjIo<Fs>,
+    workspace: WorkspaceIo<Fs>,
+    fs: Fs,
+}
+
+impl<Fs> SparseCheckout<Fs>
+where
+    Fs: FileSystem + Clone,
+{
+    pub fn new(fs: Fs) -> SparseCheckout<Fs> {
+        Self {
+            sparse_checkout: SparseCheckoutIo::new(fs.clone()),
+            working: WorkingIo::new(fs.clone()),
+            head: HeadIo::new(fs.clone()),
+            merge_head: MergeHeadIo::new(fs.clone()),
+            trace_tree: TraceTreeIo::new(fs.clone()),
+            object: ObjIo::new(fs.clone()),
+            workspace: WorkspaceIo::new(fs.clone()),
+            fs,
+        }
+    }
+}
+
+impl<Fs> SparseCheckout<Fs>
+where
+    Fs: FileSystem,
+{
+    /// Replaces the patterns and updates the workspace of the working branch.
+    ///
+    /// The files that newly match the patterns are written,
+    /// and the files that no longer match are removed unless they have been modified.
+    /// An empty list disables the sparse checkout.
+    pub async fn set(&self, patterns: &[String]) -> error::Result {
+        if let Some(merge_head) = self.merge_head.read().await? {
+            return Err(error::Error::MergeInProgress(merge_head.dist));
+        }
+        let old = self.sparse_checkout.read().await?;
+        self.sparse_checkout.write(patterns).await?;
+        self.update_workspace(&old, &SparsePatterns::new(patterns))
+            .await
+    }
+
+    #[inline]
+    pub async fn disable(&self) -> error::Result {
+        self.set(&[]).await
+    }
+
+    #[inline]
+    pub async fn patterns(&self) -> error::Result<Vec<String>> {
+        self.sparse_checkout.read_patterns().await
+    }
+
+    async fn update_workspace(&self, old: &SparsePatterns, new: &SparsePatterns) -> error::Result {
+        let branch_name = self.working.read().await?.unwrap_or(BranchName::owner());
+        let Some(head) = self.head.read(&branch_name).await? else {
+            return Ok(());
+        };
+        let trace_tree = self.trace_tree.read(&head).await?;
+        for (path, hash) in trace_tree.iter() {
+            let obj = self.object.try_read_obj(hash).await?;
+            if !matches!(obj, Obj::File(_)) {
+                continue;
+            }
+            match (old.contains(path), new.contains(path)) {
+                (false, true) if self.workspace.read(path).await?.is_none() => {
+                    self.workspace
+                        .unpack(path, &obj, trace_tree.mode(path))
+                        .await?;
+                }
+                (true, false) => {
+                    let unchanged = self
+                        .workspace
+                        .read(path)
+                        .await?
+                        .map(|current| current.as_meta().map(|meta| &meta.hash == hash))
+                        .transpose()?;
+                    if unchanged == Some(true) {
+                        self.fs.delete(path).await?;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::branch::BranchName;
+    use crate::error;
+    use crate::file_system::memory::MemoryFileSystem;
+    use crate::file_system::{FilePath, FileSystem};
+    use crate::io::atomic::staging::StagingIo;
+    use crate::operation::checkout::Checkout;
+    use crate::operation::commit::Commit;
+    use crate::operation::merge::{Merge, MergedStatus};
+    use crate::operation::sparse_checkout::SparseCheckout;
+    use crate::operation::stage::Stage;
+    use crate::operation::status::Status;
+    use crate::tests::init_owner_branch;
+
+    async fn commit_files(fs: &MemoryFileSystem, branch: &BranchName) {
+        fs.write_sync("src/main.rs", b"main");
+        fs.write_sync("docs/README.md", b"readme");
+        Stage::new(fs.clone()).execute(branch, ".").await.unwrap();
+        Commit::new(fs.clone())
+            .execute(branch, "commit")
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn remove_files_outside_patterns() {
+        let fs = MemoryFileSystem::default();
+        let owner = BranchName::owner();
+        init_owner_branch(fs.clone()).await;
+        commit_files(&fs, &owner).await;
+
+        let sparse = SparseCheckout::new(fs.clone());
+        sparse.set(&["/src/".to_string()]).await.unwrap();
+        assert_eq!(sparse.patterns().await.unwrap(), vec!["/src/".to_string()]);
+        assert_eq!(fs.try_read_file("src/main.rs").await.unwrap(), b"main");
+        assert!(fs.read_file("docs/README.md").await.unwrap().is_none());
+        assert!(Status::new(fs.clone())
+            .execute(&owner)
+            .await
+            .unwrap()
+            .deleted
+            .is_empty());
+
+        sparse.disable().await.unwrap();
+        assert!(sparse.patterns().await.unwrap().is_empty());
+        assert_eq!(fs.try_read_file("docs/README.md").await.unwrap(), b"readme");
+    }
+
+    #[tokio::test]
+    async fn not_staged_as_deleted() {
+        let fs = MemoryFileSystem::default();
+        let owner = BranchName::owner();
+        init_owner_branch(fs.clone()).await;
+        commit_files(&fs, &owner).await;
+        SparseCheckout::new(fs.clone())
+            .set(&["/src/".to_string()])
+            .await
+            .unwrap();
+
+        let stage = Stage::new(fs.clone());
+        assert!(matches!(
+            stage.execute(&owner, ".").await,
+            Err(error::Error::ChangedFileNotExits)
+        ));
+        fs.write_sync("src/main.rs", b"changed");
+        stage.execute(&owner, ".").await.unwrap();
+        let stage_tree = StagingIo::new(fs.clone()).read().await.unwrap().unwrap();
+        assert!(stage_tree.contains_key(&FilePath::from("src/main.rs")));
+        assert!(!stage_tree.contains_key(&FilePath::from("docs/README.md")));
+    }
+
+    #[tokio::test]
+    async fn checkout_and_merge_only_write_matched_files() {
+        let fs = MemoryFileSystem::default();
+        let owner = BranchName::owner();
+        let second = BranchName::from("second");
+        init_owner_branch(fs.clone()).await;
+        SparseCheckout::new(fs.clone())
+            .set(&["/src/".to_string()])
+            .await
+            .unwrap();
+        Checkout::new(fs.clone()).execute(&second).await.unwrap();
+        commit_files(&fs, &second).await;
+        fs.delete("src/main.rs").await.unwrap();
+        fs.delete("docs/README.md").await.unwrap();
+
+        Checkout::new(fs.clone()).execute(&owner).await.unwrap();
+        Checkout::new(fs.clone()).execute(&second).await.unwrap();
+        assert_eq!(fs.try_read_file("src/main.rs").await.unwrap(), b"main");
+        assert!(fs.read_file("docs/README.md").await.unwrap().is_none());
+
+        fs.delete("src/main.rs").await.unwrap();
+        Checkout::new(fs.clone()).execute(&owner).await.unwrap();
+        fs.write_sync("src/lib.rs", b"lib");
+        Stage::new(fs.clone()).execute(&owner, ".").await.unwrap();
+        Commit::new(fs.clone())
+            .execute(&owner, "lib")
+            .await
+            .unwrap();
+        let status = Merge::new(fs.clone())
+            .execute_from_branch(second, owner)
+            .await
+            .unwrap();
+        assert_eq!(status, MergedStatus::Merged);
+        assert_eq!(fs.try_read_file("src/main.rs").await.unwrap(), b"main");
+        assert!(fs.read_file("docs/README.md").await.unwrap().is_none());
+    }
+}
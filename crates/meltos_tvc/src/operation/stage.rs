@@ -1,8 +1,9 @@
 use crate::branch::BranchName;
 use crate::error;
-use crate::file_system::{FilePath, FileSystem};
+use crate::file_system::{FileMode, FilePath, FileSystem};
 use crate::io::atomic::head::HeadIo;
 use crate::io::atomic::object::ObjIo;
+use crate::io::atomic::sparse_checkout::SparseCheckoutIo;
 use crate::io::atomic::staging::StagingIo;
 use crate::io::trace_tree::TraceTreeIo;
 use crate::io::workspace::WorkspaceIo;
@@ -21,6 +22,7 @@ where
     object: ObjIo<Fs>,
     head: HeadIo<Fs>,
     workspace: WorkspaceIo<Fs>,
+    sparse_checkout: SparseCheckoutIo<Fs>,
 }
 
 impl<Fs> Stage<Fs>
@@ -35,6 +37,7 @@ where
             trace_tree: TraceTreeIo::new(fs.clone()),
             head: HeadIo::new(fs.clone()),
             object: ObjIo::new(fs.clone()),
+            sparse_checkout: SparseCheckoutIo::new(fs.clone()),
         }
     }
 }
@@ -58,12 +61,14 @@ where
         let mut objs = self.workspace.convert_to_objs(workspace_path).await?;
         while let Some(result) = objs.next().await {
             let (file_path, file_obj) = result?;
+            let mode = self.workspace.mode(&file_path).await?;
             self.stage_file(
                 &mut stage_tree,
                 &mut changed,
                 &trace_tree,
                 file_path,
                 file_obj,
+                mode,
             )
             .await?;
         }
@@ -91,16 +96,17 @@ where
         trace: &TreeObj,
         file_path: FilePath,
         file_obj: FileObj,
+        mode: FileMode,
     ) -> error::Result {
         let meta = file_obj.as_meta()?;
-        if !trace.changed_hash(&file_path, &meta.hash) {
+        if !trace.changed(&file_path, &meta.hash, mode) {
             return Ok(());
         }
 
-        if stage.changed_hash(&file_path, &meta.hash) {
+        if stage.changed(&file_path, &meta.hash, mode) {
             *changed = true;
             self.object.write_obj(&file_obj).await?;
-            stage.insert(file_path, meta.hash);
+            stage.insert_with_mode(file_path, meta.hash, mode);
         }
         Ok(())
     }
@@ -122,6 +128,9 @@ where
         Ok(())
     }
 
+    /// Returns the traced files which no longer exist in the workspace.
+    ///
+    /// The files outside the sparse-checkout patterns are not written into the workspace, so they are not regarded as deleted.
     async fn scan_deleted_files(
         &self,
         trace_tree: &TreeObj,
@@ -129,10 +138,11 @@ where
         _workspace_path: &str,
     ) -> error::Result<Vec<(FilePath, ObjHash)>> {
         let work_space_files = self.workspace.files(".").await?;
+        let sparse = self.sparse_checkout.read().await?;
         Ok(trace_tree
             .iter()
             .filter_map(|(path, hash)| {
-                if work_space_files.contains(&path.0) {
+                if work_space_files.contains(&path.0) || !sparse.contains(path) {
                     None
                 } else {
                     Some((path.clone(), hash.clone()))
@@ -147,8 +157,9 @@ mod tests {
     use crate::branch::BranchName;
     use crate::error;
     use crate::file_system::memory::MemoryFileSystem;
-    use crate::file_system::{FilePath, FileSystem};
+    use crate::file_system::{FileMode, FilePath, FileSystem};
     use crate::io::atomic::object::ObjIo;
+    use crate::io::atomic::staging::StagingIo;
     use crate::object::delete::DeleteObj;
     use crate::object::file::FileObj;
     use crate::object::{AsMeta, ObjHash};
@@ -247,4 +258,28 @@ mod tests {
             _ => panic!("expected the [error::Error::ChangedFileNotExits] bad was."),
         }
     }
+
+    #[tokio::test]
+    async fn stage_changed_mode() {
+        let fs = MemoryFileSystem::default();
+        init_owner_branch(fs.clone()).await;
+        let branch = BranchName::owner();
+        let stage = Stage::new(fs.clone());
+        fs.write_file("run.sh", b"echo hi").await.unwrap();
+        stage.execute(&branch, ".").await.unwrap();
+        Commit::new(fs.clone())
+            .execute(&branch, "add run.sh")
+            .await
+            .unwrap();
+
+        fs.write_file_with_mode("run.sh", b"echo hi", FileMode::Executable)
+            .await
+            .unwrap();
+        stage.execute(&branch, ".").await.unwrap();
+        let stage_tree = StagingIo::new(fs.clone()).read().await.unwrap().unwrap();
+        assert_eq!(
+            stage_tree.mode(&FilePath::from("run.sh")),
+            FileMode::Executable
+        );
+    }
 }
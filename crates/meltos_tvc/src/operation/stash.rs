@@ -0,0 +1,572 @@
+use std::collections::HashSet;
+
+use crate::branch::BranchName;
+use crate::error;
+use crate::file_system::{FileMode, FilePath, FileSystem};
+use crate::io::atomic::head::{CommitText, HeadIo};
+use crate::io::atomic::merge_head::MergeHeadIo;
+use crate::io::atomic::object::ObjIo;
+use crate::io::atomic::sparse_checkout::SparseCheckoutIo;
+use crate::io::atomic::staging::StagingIo;
+use crate::io::atomic::stash::StashIo;
+use crate::io::diff::merge::MergedText;
+use crate::io::trace_tree::TraceTreeIo;
+use crate::io::workspace::WorkspaceIo;
+use crate::object::commit::{CommitHash, CommitObj};
+use crate::object::delete::DeleteObj;
+use crate::object::file::FileObj;
+use crate::object::tree::TreeObj;
+use crate::object::{AsMeta, Obj, ObjHash};
+use crate::time::since_epoch_secs;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct StashEntry {
+    /// The position in the stash list; `0` is the newest.
+    pub index: usize,
+    pub commit_hash: CommitHash,
+    /// The branch on which the changes were stashed.
+    pub branch_name: Option<BranchName>,
+    pub message: String,
+    /// Seconds elapsed since the UNIX epoch when stashed.
+    pub stashed_at: Option<u64>,
+}
+
+/// Saves the uncommitted changes and restores them later.
+///
+/// A stash is recorded as two commits on top of the head:
+/// the index commit holds the staged changes, and the stash commit holds the changes in the workspace,
+/// with the head and the index commit as its parents.
+/// Their traces are written too, so the stashed files are read in the same way as the committed ones.
+#[derive(Debug, Clone)]
+pub struct Stash<Fs>
+where
+    Fs: FileSystem,
+{
+    head: HeadIo<Fs>,
+    stash: StashIo<Fs>,
+    staging: StagingIo<Fs>,
+    merge_head: MergeHeadIo<Fs>,
+    trace_tree: TraceTreeIo<Fs>,
+    object: ObjIo<Fs>,
+    workspace: WorkspaceIo<Fs>,
+    sparse_checkout: SparseCheckoutIo<Fs>,
+    fs: Fs,
+}
+
+impl<Fs> Stash<Fs>
+where
+    Fs: FileSystem + Clone,
+{
+    pub fn new(fs: Fs) -> Stash<Fs> {
+        Self {
+            head: HeadIo::new(fs.clone()),
+            stash: StashIo::new(fs.clone()),
+            staging: StagingIo::new(fs.clone()),
+            merge_head: MergeHeadIo::new(fs.clone()),
+            trace_tree: TraceTreeIo::new(fs.clone()),
+            object: ObjIo::new(fs.clone()),
+            workspace: WorkspaceIo::new(fs.clone()),
+            sparse_checkout: SparseCheckoutIo::new(fs.clone()),
+            fs,
+        }
+    }
+}
+
+impl<Fs> Stash<Fs>
+where
+    Fs: FileSystem,
+{
+    /// Stashes the staged and unstaged changes, and resets the workspace and the stage to the head.
+    ///
+    /// The ignored files are not stashed,
+    /// and the files outside the sparse-checkout patterns are not regarded as deleted.
+    pub async fn save(
+        &self,
+        branch_name: &BranchName,
+        message: Option<&str>,
+    ) -> error::Result<CommitHash> {
+        if let Some(merge_head) = self.merge_head.read().await? {
+            return Err(error::Error::MergeInProgress(merge_head.dist));
+        }
+        let head = self.head.try_read(branch_name).await?;
+        let head_trace = self.trace_tree.read(&head).await?;
+        let stage = self.staging.read().await?.unwrap_or_default();
+        let mut index_trace = head_trace.clone();
+        index_trace.replace_by(stage.clone());
+        let changes = self.workspace_changes(&index_trace).await?;
+        if stage.is_empty() && changes.is_empty() {
+            return Err(error::Error::NothingToStash);
+        }
+
+        let index_commit = self
+            .write_commit(
+                CommitObj {
+                    parents: vec![head.clone()],
+                    text: CommitText::from(format!("index on {branch_name}")),
+                    committed_objs_tree: stage.as_meta()?.hash,
+                    author: Some(branch_name.clone()),
+                    committed_at: Some(since_epoch_secs()),
+                },
+                &stage,
+                &index_trace,
+            )
+            .await?;
+        let mut stash_trace = index_trace;
+        stash_trace.replace_by(changes.clone());
+        let message = message
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("WIP on {branch_name}"));
+        let stash_commit = self
+            .write_commit(
+                CommitObj {
+                    parents: vec![head, index_commit],
+                    text: CommitText::from(message),
+                    committed_objs_tree: changes.as_meta()?.hash,
+                    author: Some(branch_name.clone()),
+                    committed_at: Some(since_epoch_secs()),
+                },
+                &changes,
+                &stash_trace,
+            )
+            .await?;
+
+        let mut stashes = self.stash.read_all().await?;
+        stashes.insert(0, stash_commit.clone());
+        self.stash.write_all(&stashes).await?;
+
+        let sparse = self.sparse_checkout.read().await?;
+        for path in stage.keys().chain(changes.keys()) {
+            match head_trace.get(path).filter(|_| sparse.contains(path)) {
+                Some(hash) => {
+                    self.workspace
+                        .unpack(
+                            path,
+                            &self.object.try_read_obj(hash).await?,
+                            head_trace.mode(path),
+                        )
+                        .await?
+                }
+                None => self.fs.delete(path).await?,
+            }
+        }
+        self.staging.reset().await?;
+        Ok(stash_commit)
+    }
+
+    /// Restores the stashed changes onto the head of `branch_name`, and keeps the stash.
+    ///
+    /// The files changed since the stash was made are merged line by line,
+    /// and the files that could not be merged are written with the conflict markers.
+    /// The staged changes are staged again unless the file was changed since the stash was made.
+    ///
+    /// Returns the conflicted files.
+    pub async fn apply(
+        &self,
+        branch_name: &BranchName,
+        index: usize,
+    ) -> error::Result<Vec<FilePath>> {
+        let stash_hash = self.read_stash(index).await?;
+        let stash_commit = self.object.read_to_commit(&stash_hash).await?;
+        let (base, index_commit) = match stash_commit.parents.as_slice() {
+            [base, index_commit] => (base, index_commit),
+            _ => return Err(error::Error::NotfoundStash(index)),
+        };
+        let base_trace = self.trace_tree.read(base).await?;
+        let stash_trace = self.trace_tree.read(&stash_hash).await?;
+        let head = self.head.try_read(branch_name).await?;
+        let head_trace = self.trace_tree.read(&head).await?;
+
+        let mut changed = Vec::new();
+        for (path, hash) in stash_trace.iter() {
+            if base_trace.get(path) == Some(hash) {
+                continue;
+            }
+            let stashed = self.read_buf(Some(hash)).await?;
+            let base_buf = self.read_buf(base_trace.get(path)).await?;
+            if stashed != base_buf {
+                changed.push((path.clone(), base_buf, stashed));
+            }
+        }
+        changed.sort_by(|(p1, ..), (p2, ..)| p1.cmp(p2));
+
+        let mut overwritten = Vec::new();
+        for (path, ..) in changed.iter() {
+            let current = self.workspace.read(path).await?.map(|file| file.0);
+            if current != self.read_buf(head_trace.get(path)).await? {
+                overwritten.push(path.clone());
+            }
+        }
+        if !overwritten.is_empty() {
+            return Err(error::Error::LocalChangesWouldBeOverwritten(overwritten));
+        }
+
+        let stash_label = format!("stash@{{{index}}}");
+        let mut conflicts = Vec::new();
+        let mut unchanged_since_stash = HashSet::new();
+        for (path, base_buf, stashed) in changed {
+            let current = self.read_buf(head_trace.get(&path)).await?;
+            let head_mode = head_trace.mode(&path);
+            if current == base_buf {
+                unchanged_since_stash.insert(path.clone());
+                self.write_workspace(&path, stashed.as_deref(), stash_trace.mode(&path))
+                    .await?;
+            } else if current != stashed {
+                match merge_text(base_buf, stashed, current, &stash_label) {
+                    Ok(merged) => {
+                        self.write_workspace(&path, Some(merged.as_bytes()), head_mode)
+                            .await?
+                    }
+                    Err(Some(marked_text)) => {
+                        self.write_workspace(&path, Some(marked_text.as_bytes()), head_mode)
+                            .await?;
+                        conflicts.push(path);
+                    }
+                    Err(None) => conflicts.push(path),
+                }
+            }
+        }
+
+        let index_tree = self
+            .object
+            .read_to_commit(index_commit)
+            .await?
+            .committed_objs_tree;
+        let index_tree = self.object.read_to_tree(&index_tree).await?;
+        let mut stage = self.staging.read().await?.unwrap_or_default();
+        let mut restaged = false;
+        for (path, hash) in index_tree.iter() {
+            if unchanged_since_stash.contains(path) {
+                stage.insert_with_mode(path.clone(), hash.clone(), index_tree.mode(path));
+                restaged = true;
+            }
+        }
+        if restaged {
+            self.staging.write_tree(&stage).await?;
+        }
+        Ok(conflicts)
+    }
+
+    /// Applies the stash, and drops it if there are no conflicts.
+    pub async fn pop(
+        &self,
+        branch_name: &BranchName,
+        index: usize,
+    ) -> error::Result<Vec<FilePath>> {
+        let conflicts = self.apply(branch_name, index).await?;
+        if conflicts.is_empty() {
+            self.drop(index).await?;
+        }
+        Ok(conflicts)
+    }
+
+    pub async fn list(&self) -> error::Result<Vec<StashEntry>> {
+        let mut entries = Vec::new();
+        for (index, commit_hash) in self.stash.read_all().await?.into_iter().enumerate() {
+            let commit = self.object.read_to_commit(&commit_hash).await?;
+            entries.push(StashEntry {
+                index,
+                commit_hash,
+                branch_name: commit.author,
+                message: commit.text.0,
+                stashed_at: commit.committed_at,
+            });
+        }
+        Ok(entries)
+    }
+
+    /// Removes the stash from the list; its objects are left for [`Gc`](crate::operation::gc::Gc).
+    pub async fn drop(&self, index: usize) -> error::Result {
+        let mut stashes = self.stash.read_all().await?;
+        if stashes.len() <= index {
+            return Err(error::Error::NotfoundStash(index));
+        }
+        stashes.remove(index);
+        self.stash.write_all(&stashes).await
+    }
+
+    /// Returns the files in the workspace that differ from `index_trace`.
+    ///
+    /// The deleted files are recorded as [`DeleteObj`] in the same way as [`Stage`](crate::operation::stage::Stage),
+    /// except for the files outside the sparse-checkout patterns which are not written into the workspace.
+    async fn workspace_changes(&self, index_trace: &TreeObj) -> error::Result<TreeObj> {
+        let sparse = self.sparse_checkout.read().await?;
+        let mut changes = TreeObj::default();
+        let mut exists = HashSet::new();
+        for (path, file) in self.workspace.convert_to_objs(".").await?.all().await? {
+            let meta = file.as_meta()?;
+            let mode = self.workspace.mode(&path).await?;
+            if index_trace.changed(&path, &meta.hash, mode) {
+                self.object.write_obj(&file).await?;
+                changes.insert_with_mode(path.clone(), meta.hash, mode);
+            }
+            exists.insert(path);
+        }
+        for (path, hash) in index_trace.iter() {
+            if exists.contains(path)
+                || !sparse.contains(path)
+                || self.read_buf(Some(hash)).await?.is_none()
+            {
+                continue;
+            }
+            let delete_obj = DeleteObj(hash.clone());
+            self.object.write_obj(&delete_obj).await?;
+            changes.insert(path.clone(), delete_obj.as_meta()?.hash);
+        }
+        Ok(changes)
+    }
+
+    async fn write_commit(
+        &self,
+        commit: CommitObj,
+        tree: &TreeObj,
+        trace: &TreeObj,
+    ) -> error::Result<CommitHash> {
+        self.object.write_obj(tree).await?;
+        self.object.write_obj(&commit).await?;
+        let commit_hash = CommitHash(commit.as_meta()?.hash);
+        self.trace_tree.write(trace, &commit_hash).await?;
+        Ok(commit_hash)
+    }
+
+    async fn read_stash(&self, index: usize) -> error::Result<CommitHash> {
+        self.stash
+            .read_all()
+            .await?
+            .into_iter()
+            .nth(index)
+            .ok_or(error::Error::NotfoundStash(index))
+    }
+
+    async fn write_workspace(
+        &self,
+        path: &FilePath,
+        buf: Option<&[u8]>,
+        mode: FileMode,
+    ) -> error::Result {
+        match buf {
+            Some(buf) => {
+                self.workspace
+                    .unpack(path, &Obj::File(FileObj(buf.to_vec())), mode)
+                    .await
+            }
+            None => Ok(self.fs.delete(path).await?),
+        }
+    }
+
+    /// Reads the buffer of the file object.
+    ///
+    /// Returns `None` if the file does not exist or the object is a deleted object.
+    async fn read_buf(&self, hash: Option<&ObjHash>) -> error::Result<Option<Vec<u8>>> {
+        let Some(hash) = hash else {
+            return Ok(None);
+        };
+        match self.object.try_read_obj(hash).await? {
+            Obj::File(file) => Ok(Some(file.0)),
+            Obj::Delete(_) => Ok(None),
+            obj => Err(error::Error::InvalidWorkspaceObj(obj.to_string())),
+        }
+    }
+}
+
+/// Merges the stashed text into the current text.
+///
+/// Returns the text with the conflict markers if they conflict,
+/// or `None` if the file was deleted on either side or is not a text.
+fn merge_text(
+    base: Option<Vec<u8>>,
+    stashed: Option<Vec<u8>>,
+    current: Option<Vec<u8>>,
+    stash_label: &str,
+) -> Result<String, Option<String>> {
+    let (Some(stashed), Some(current)) = (stashed, current) else {
+        return Err(None);
+    };
+    let (Ok(base), Ok(stashed), Ok(current)) = (
+        String::from_utf8(base.unwrap_or_default()),
+        String::from_utf8(stashed),
+        String::from_utf8(current),
+    ) else {
+        return Err(None);
+    };
+    let merged = MergedText::merge(&base, &stashed, &current);
+    merged
+        .merged_text()
+        .ok_or_else(|| Some(merged.text_with_markers("HEAD", stash_label)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::branch::BranchName;
+    use crate::error;
+    use crate::file_system::memory::MemoryFileSystem;
+    use crate::file_system::{FilePath, FileSystem};
+    use crate::io::atomic::staging::StagingIo;
+    use crate::operation::commit::Commit;
+    use crate::operation::fsck::Fsck;
+    use crate::operation::gc::Gc;
+    use crate::operation::sparse_checkout::SparseCheckout;
+    use crate::operation::stage::Stage;
+    use crate::operation::stash::Stash;
+    use crate::tests::init_owner_branch;
+
+    #[tokio::test]
+    async fn save_and_pop() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        fs.write_sync("hello.txt", b"hello");
+        fs.write_sync("deleted.txt", b"deleted");
+        init_owner_branch(fs.clone()).await;
+
+        fs.write_sync("staged.txt", b"staged");
+        Stage::new(fs.clone())
+            .execute(&branch, "staged.txt")
+            .await
+            .unwrap();
+        fs.write_sync("hello.txt", b"hello world");
+        fs.delete("deleted.txt").await.unwrap();
+
+        let stash = Stash::new(fs.clone());
+        stash.save(&branch, Some("half done")).await.unwrap();
+        assert_eq!(fs.try_read_file("hello.txt").await.unwrap(), b"hello");
+        assert_eq!(fs.try_read_file("deleted.txt").await.unwrap(), b"deleted");
+        assert!(fs.read_file("staged.txt").await.unwrap().is_none());
+        assert!(StagingIo::new(fs.clone())
+            .read()
+            .await
+            .unwrap()
+            .is_none_or(|stage| stage.is_empty()));
+        let entries = stash.list().await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].message, "half done");
+        assert_eq!(entries[0].branch_name, Some(branch.clone()));
+
+        let report = Fsck::new(fs.clone()).execute().await.unwrap();
+        assert!(report.is_ok(), "{:?}", report.problems);
+        assert!(Gc::new(fs.clone())
+            .execute(true)
+            .await
+            .unwrap()
+            .objs
+            .is_empty());
+
+        let conflicts = stash.pop(&branch, 0).await.unwrap();
+        assert!(conflicts.is_empty());
+        assert_eq!(fs.try_read_file("hello.txt").await.unwrap(), b"hello world");
+        assert_eq!(fs.try_read_file("staged.txt").await.unwrap(), b"staged");
+        assert!(fs.read_file("deleted.txt").await.unwrap().is_none());
+        let stage = StagingIo::new(fs.clone()).read().await.unwrap().unwrap();
+        assert!(stage.contains_key(&FilePath::from("staged.txt")));
+        assert!(stash.list().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_if_nothing_to_stash() {
+        let fs = MemoryFileSystem::default();
+        init_owner_branch(fs.clone()).await;
+        let result = Stash::new(fs.clone())
+            .save(&BranchName::owner(), None)
+            .await;
+        assert!(matches!(result, Err(error::Error::NothingToStash)));
+    }
+
+    #[tokio::test]
+    async fn merge_changes_committed_after_stash() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        fs.write_sync("hello.txt", b"line1\nline2\nline3\n");
+        init_owner_branch(fs.clone()).await;
+        fs.write_sync("hello.txt", b"line1\nline2\nstashed\n");
+        let stash = Stash::new(fs.clone());
+        stash.save(&branch, None).await.unwrap();
+
+        fs.write_sync("hello.txt", b"committed\nline2\nline3\n");
+        Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        Commit::new(fs.clone())
+            .execute(&branch, "commit")
+            .await
+            .unwrap();
+
+        let conflicts = stash.apply(&branch, 0).await.unwrap();
+        assert!(conflicts.is_empty());
+        assert_eq!(
+            fs.try_read_file("hello.txt").await.unwrap(),
+            b"committed\nline2\nstashed\n"
+        );
+        assert_eq!(stash.list().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn keep_stash_if_conflicted() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        fs.write_sync("hello.txt", b"hello");
+        init_owner_branch(fs.clone()).await;
+        fs.write_sync("hello.txt", b"stashed");
+        let stash = Stash::new(fs.clone());
+        stash.save(&branch, None).await.unwrap();
+
+        fs.write_sync("hello.txt", b"committed");
+        Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        Commit::new(fs.clone())
+            .execute(&branch, "commit")
+            .await
+            .unwrap();
+
+        let conflicts = stash.pop(&branch, 0).await.unwrap();
+        assert_eq!(conflicts, vec![FilePath::from("hello.txt")]);
+        let text = String::from_utf8(fs.try_read_file("hello.txt").await.unwrap()).unwrap();
+        assert!(text.contains("<<<<<<< HEAD"));
+        assert!(text.contains(">>>>>>> stash@{0}"));
+        assert_eq!(stash.list().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn failed_if_local_changes_would_be_overwritten() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        fs.write_sync("hello.txt", b"hello");
+        init_owner_branch(fs.clone()).await;
+        fs.write_sync("hello.txt", b"stashed");
+        let stash = Stash::new(fs.clone());
+        stash.save(&branch, None).await.unwrap();
+
+        fs.write_sync("hello.txt", b"local");
+        let result = stash.apply(&branch, 0).await;
+        assert!(matches!(
+            result,
+            Err(error::Error::LocalChangesWouldBeOverwritten(files)) if files == vec![FilePath::from("hello.txt")]
+        ));
+        assert!(matches!(
+            stash.drop(1).await,
+            Err(error::Error::NotfoundStash(1))
+        ));
+    }
+
+    #[tokio::test]
+    async fn files_outside_sparse_patterns_are_not_stashed() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        fs.write_sync("src/main.rs", b"main");
+        fs.write_sync("docs/README.md", b"readme");
+        init_owner_branch(fs.clone()).await;
+        SparseCheckout::new(fs.clone())
+            .set(&["/src/".to_string()])
+            .await
+            .unwrap();
+
+        let stash = Stash::new(fs.clone());
+        assert!(matches!(
+            stash.save(&branch, None).await,
+            Err(error::Error::NothingToStash)
+        ));
+
+        fs.write_sync("src/main.rs", b"changed");
+        stash.save(&branch, None).await.unwrap();
+        assert_eq!(fs.try_read_file("src/main.rs").await.unwrap(), b"main");
+        assert!(fs.read_file("docs/README.md").await.unwrap().is_none());
+
+        stash.pop(&branch, 0).await.unwrap();
+        assert_eq!(fs.try_read_file("src/main.rs").await.unwrap(), b"changed");
+        assert!(fs.read_file("docs/README.md").await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,269 @@
+use crate::branch::BranchName;
+use crate::error;
+use crate::file_system::{FilePath, FileSystem};
+use crate::io::atomic::head::HeadIo;
+use crate::io::atomic::object::ObjIo;
+use crate::io::atomic::sparse_checkout::SparseCheckoutIo;
+use crate::io::atomic::staging::StagingIo;
+use crate::io::trace_tree::TraceTreeIo;
+use crate::io::workspace::WorkspaceIo;
+use crate::object::tree::TreeObj;
+use crate::object::{AsMeta, Obj, ObjHash};
+use crate::operation::diff::FileStatus;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct StagedFile {
+    pub file_path: FilePath,
+    pub status: FileStatus,
+}
+
+/// All paths are sorted.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct WorkspaceStatus {
+    /// The changes staged against the head of the branch.
+    pub staged: Vec<StagedFile>,
+    /// The tracked files whose contents or modes differ from the staged or committed ones.
+    pub modified: Vec<FilePath>,
+    /// The tracked files that no longer exist in the workspace.
+    pub deleted: Vec<FilePath>,
+    /// The files that are neither committed nor staged.
+    pub untracked: Vec<FilePath>,
+}
+
+impl WorkspaceStatus {
+    #[inline]
+    pub fn is_clean(&self) -> bool {
+        self.staged.is_empty()
+            && self.modified.is_empty()
+            && self.deleted.is_empty()
+            && self.untracked.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Status<Fs>
+where
+    Fs: FileSystem,
+{
+    head: HeadIo<Fs>,
+    staging: StagingIo<Fs>,
+    trace_tree: TraceTreeIo<Fs>,
+    object: ObjIo<Fs>,
+    workspace: WorkspaceIo<Fs>,
+    sparse_checkout: SparseCheckoutIo<Fs>,
+}
+
+impl<Fs> Status<Fs>
+where
+    Fs: FileSystem + Clone,
+{
+    #[inline]
+    pub fn new(fs: Fs) -> Status<Fs> {
+        Self {
+            head: HeadIo::new(fs.clone()),
+            staging: StagingIo::new(fs.clone()),
+            trace_tree: TraceTreeIo::new(fs.clone()),
+            object: ObjIo::new(fs.clone()),
+            workspace: WorkspaceIo::new(fs.clone()),
+            sparse_checkout: SparseCheckoutIo::new(fs),
+        }
+    }
+}
+
+impl<Fs> Status<Fs>
+where
+    Fs: FileSystem,
+{
+    /// Reads the head, the stage and the workspace files only once, and classifies every changed file.
+    ///
+    /// Unstaged changes are compared with the staged contents if the file is staged,
+    /// otherwise with the trace tree of the head.
+    /// Ignored files, and missing files outside the sparse-checkout patterns, are excluded.
+    pub async fn execute(&self, branch_name: &BranchName) -> error::Result<WorkspaceStatus> {
+        let trace_tree = match self.head.read(branch_name).await? {
+            Some(head) => self.trace_tree.read(&head).await?,
+            None => TreeObj::default(),
+        };
+        let stage_tree = self.staging.read().await?.unwrap_or_default();
+
+        let mut status = WorkspaceStatus::default();
+        for (file_path, hash) in stage_tree.iter() {
+            let file_status = if self.is_deleted(hash).await? {
+                FileStatus::Deleted
+            } else if self.is_tracked(trace_tree.get(file_path)).await? {
+                FileStatus::Modified
+            } else {
+                FileStatus::Added
+            };
+            status.staged.push(StagedFile {
+                file_path: file_path.clone(),
+                status: file_status,
+            });
+        }
+
+        let mut index = trace_tree;
+        index.replace_by(stage_tree);
+        let mut objs = self.workspace.convert_to_objs(".").await?;
+        while let Some(result) = objs.next().await {
+            let (file_path, file_obj) = result?;
+            let unchanged_mode = index.mode(&file_path) == self.workspace.mode(&file_path).await?;
+            match index.remove(&file_path) {
+                Some(hash) if hash == file_obj.as_meta()?.hash && unchanged_mode => {}
+                Some(hash) if !self.is_deleted(&hash).await? => status.modified.push(file_path),
+                _ => status.untracked.push(file_path),
+            }
+        }
+        let sparse = self.sparse_checkout.read().await?;
+        for (file_path, hash) in index {
+            if sparse.contains(&file_path)
+                && !self.workspace.is_ignore(&file_path).await?
+                && !self.is_deleted(&hash).await?
+            {
+                status.deleted.push(file_path);
+            }
+        }
+
+        status.staged.sort_by(|l, r| l.file_path.cmp(&r.file_path));
+        status.modified.sort();
+        status.deleted.sort();
+        status.untracked.sort();
+        Ok(status)
+    }
+
+    #[inline]
+    async fn is_tracked(&self, hash: Option<&ObjHash>) -> error::Result<bool> {
+        match hash {
+            Some(hash) => Ok(!self.is_deleted(hash).await?),
+            None => Ok(false),
+        }
+    }
+
+    #[inline]
+    async fn is_deleted(&self, hash: &ObjHash) -> error::Result<bool> {
+        Ok(matches!(self.object.try_read_obj(hash).await?, Obj::Delete(_)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::branch::BranchName;
+    use crate::file_system::memory::MemoryFileSystem;
+    use crate::file_system::{FilePath, FileSystem};
+    use crate::io::workspace::WorkspaceIo;
+    use crate::operation::commit::Commit;
+    use crate::operation::diff::FileStatus;
+    use crate::operation::stage::Stage;
+    use crate::operation::status::{StagedFile, Status, WorkspaceStatus};
+    use crate::tests::init_owner_branch;
+
+    #[tokio::test]
+    async fn clean_after_init() {
+        let fs = MemoryFileSystem::default();
+        fs.write_sync("hello.txt", b"hello");
+        init_owner_branch(fs.clone()).await;
+
+        let status = Status::new(fs.clone())
+            .execute(&BranchName::owner())
+            .await
+            .unwrap();
+        assert!(status.is_clean());
+    }
+
+    #[tokio::test]
+    async fn unstaged_changes() {
+        let fs = MemoryFileSystem::default();
+        fs.write_sync("modified.txt", b"hello");
+        fs.write_sync("deleted.txt", b"hello");
+        init_owner_branch(fs.clone()).await;
+
+        fs.write_sync("modified.txt", b"world");
+        fs.delete("deleted.txt").await.unwrap();
+        fs.write_sync("src/untracked.txt", b"untracked");
+
+        let status = Status::new(fs.clone())
+            .execute(&BranchName::owner())
+            .await
+            .unwrap();
+        assert_eq!(
+            status,
+            WorkspaceStatus {
+                staged: Vec::new(),
+                modified: vec![FilePath::from("modified.txt")],
+                deleted: vec![FilePath::from("deleted.txt")],
+                untracked: vec![FilePath::from("src/untracked.txt")],
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn staged_changes() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        fs.write_sync("modified.txt", b"hello");
+        fs.write_sync("deleted.txt", b"hello");
+        init_owner_branch(fs.clone()).await;
+
+        fs.write_sync("modified.txt", b"world");
+        fs.delete("deleted.txt").await.unwrap();
+        fs.write_sync("added.txt", b"added");
+        Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        fs.write_sync("modified.txt", b"rust");
+
+        let status = Status::new(fs.clone()).execute(&branch).await.unwrap();
+        assert_eq!(
+            status,
+            WorkspaceStatus {
+                staged: vec![
+                    StagedFile {
+                        file_path: FilePath::from("added.txt"),
+                        status: FileStatus::Added,
+                    },
+                    StagedFile {
+                        file_path: FilePath::from("deleted.txt"),
+                        status: FileStatus::Deleted,
+                    },
+                    StagedFile {
+                        file_path: FilePath::from("modified.txt"),
+                        status: FileStatus::Modified,
+                    },
+                ],
+                modified: vec![FilePath::from("modified.txt")],
+                deleted: Vec::new(),
+                untracked: Vec::new(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn deleted_file_is_not_reported_after_committed() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        fs.write_sync("hello.txt", b"hello");
+        init_owner_branch(fs.clone()).await;
+
+        fs.delete("hello.txt").await.unwrap();
+        Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        Commit::new(fs.clone()).execute(&branch, "delete").await.unwrap();
+
+        let status = Status::new(fs.clone()).execute(&branch).await.unwrap();
+        assert!(status.is_clean());
+    }
+
+    #[tokio::test]
+    async fn exclude_ignored_files() {
+        let fs = MemoryFileSystem::default();
+        init_owner_branch(fs.clone()).await;
+        WorkspaceIo::new(fs.clone())
+            .write_ignores(vec!["target".to_string()])
+            .await
+            .unwrap();
+        fs.write_sync("target/debug", b"debug");
+        fs.write_sync("hello.txt", b"hello");
+
+        let status = Status::new(fs.clone())
+            .execute(&BranchName::owner())
+            .await
+            .unwrap();
+        assert_eq!(status.untracked, vec![FilePath::from("hello.txt")]);
+    }
+}
@@ -0,0 +1,230 @@
+use crate::branch::BranchName;
+use crate::error;
+use crate::file_system::FileSystem;
+use crate::io::atomic::object::ObjIo;
+use crate::io::atomic::tag::TagIo;
+use crate::object::commit::CommitHash;
+use crate::object::tag::TagObj;
+use crate::object::{AsMeta, Obj, ObjHash};
+use crate::tag::TagName;
+use crate::time::since_epoch_secs;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TagEntry {
+    pub tag_name: TagName,
+    /// The tagged commit.
+    pub commit_hash: CommitHash,
+    /// `None` if it is a lightweight tag.
+    pub annotation: Option<TagObj>,
+}
+
+/// Creates, lists and deletes the tags that mark specific commits such as the reviewed version.
+///
+/// Tags cannot be moved once created; delete and create again to retarget them.
+#[derive(Debug, Clone)]
+pub struct Tag<Fs>
+where
+    Fs: FileSystem,
+{
+    tag: TagIo<Fs>,
+    object: ObjIo<Fs>,
+}
+
+impl<Fs> Tag<Fs>
+where
+    Fs: FileSystem + Clone,
+{
+    #[inline]
+    pub fn new(fs: Fs) -> Tag<Fs> {
+        Self {
+            tag: TagIo::new(fs.clone()),
+            object: ObjIo::new(fs),
+        }
+    }
+}
+
+impl<Fs> Tag<Fs>
+where
+    Fs: FileSystem,
+{
+    /// Creates the lightweight tag that refers to the commit directly.
+    pub async fn create(&self, tag_name: &TagName, commit_hash: &CommitHash) -> error::Result {
+        self.error_if_exists(tag_name).await?;
+        self.object.read_to_commit(commit_hash).await?;
+        self.tag.write(tag_name, &commit_hash.0).await
+    }
+
+    /// Creates the annotated tag and returns the hash of its [`TagObj`].
+    pub async fn create_annotated(
+        &self,
+        tag_name: &TagName,
+        commit_hash: &CommitHash,
+        tagger: &BranchName,
+        message: &str,
+    ) -> error::Result<ObjHash> {
+        self.error_if_exists(tag_name).await?;
+        self.object.read_to_commit(commit_hash).await?;
+        let tag_obj = TagObj {
+            tag_name: tag_name.clone(),
+            commit_hash: commit_hash.clone(),
+            tagger: tagger.clone(),
+            tagged_at: since_epoch_secs(),
+            message: message.to_string(),
+        };
+        let meta = tag_obj.as_meta()?;
+        self.object.write_obj(&tag_obj).await?;
+        self.tag.write(tag_name, &meta.hash).await?;
+        Ok(meta.hash)
+    }
+
+    pub async fn read(&self, tag_name: &TagName) -> error::Result<TagEntry> {
+        let hash = self.tag.try_read(tag_name).await?;
+        self.entry(tag_name.clone(), hash).await
+    }
+
+    /// Returns all tags sorted by name.
+    pub async fn list(&self) -> error::Result<Vec<TagEntry>> {
+        let mut tags = self.tag.read_all().await?;
+        tags.sort();
+        let mut entries = Vec::with_capacity(tags.len());
+        for (tag_name, hash) in tags {
+            entries.push(self.entry(tag_name, hash).await?);
+        }
+        Ok(entries)
+    }
+
+    /// Deletes the tag; the annotated tag object is left for [`Gc`](crate::operation::gc::Gc).
+    pub async fn delete(&self, tag_name: &TagName) -> error::Result {
+        self.tag.try_read(tag_name).await?;
+        self.tag.delete(tag_name).await
+    }
+
+    async fn entry(&self, tag_name: TagName, hash: ObjHash) -> error::Result<TagEntry> {
+        match self.object.try_read_obj(&hash).await? {
+            Obj::Tag(tag_obj) => {
+                Ok(TagEntry {
+                    tag_name,
+                    commit_hash: tag_obj.commit_hash.clone(),
+                    annotation: Some(tag_obj),
+                })
+            }
+            Obj::Commit(_) => {
+                Ok(TagEntry {
+                    tag_name,
+                    commit_hash: CommitHash(hash),
+                    annotation: None,
+                })
+            }
+            obj => {
+                Err(error::Error::InvalidObjType(
+                    "Tag or Commit".to_string(),
+                    obj.to_string(),
+                ))
+            }
+        }
+    }
+
+    async fn error_if_exists(&self, tag_name: &TagName) -> error::Result {
+        if self.tag.read(tag_name).await?.is_some() {
+            Err(error::Error::TagAlreadyExists(tag_name.clone()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::branch::BranchName;
+    use crate::error;
+    use crate::file_system::memory::MemoryFileSystem;
+    use crate::object::commit::CommitHash;
+    use crate::object::ObjHash;
+    use crate::operation::tag::Tag;
+    use crate::tag::TagName;
+    use crate::tests::init_owner_branch;
+
+    #[tokio::test]
+    async fn create_lightweight_and_annotated() {
+        let fs = MemoryFileSystem::default();
+        let head = init_owner_branch(fs.clone()).await;
+        let tag = Tag::new(fs.clone());
+        tag.create(&TagName::from("light"), &head).await.unwrap();
+        tag.create_annotated(&TagName::from("reviewed"), &head, &BranchName::owner(), "looks good")
+            .await
+            .unwrap();
+
+        let tags = tag.list().await.unwrap();
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags[0].tag_name, TagName::from("light"));
+        assert_eq!(tags[0].commit_hash, head);
+        assert!(tags[0].annotation.is_none());
+        assert_eq!(tags[1].tag_name, TagName::from("reviewed"));
+        assert_eq!(tags[1].commit_hash, head);
+        let annotation = tags[1].annotation.as_ref().unwrap();
+        assert_eq!(annotation.tagger, BranchName::owner());
+        assert_eq!(annotation.message, "looks good");
+    }
+
+    #[tokio::test]
+    async fn failed_if_tag_already_exists() {
+        let fs = MemoryFileSystem::default();
+        let head = init_owner_branch(fs.clone()).await;
+        let tag = Tag::new(fs.clone());
+        let tag_name = TagName::from("v1");
+        tag.create(&tag_name, &head).await.unwrap();
+        let result = tag
+            .create_annotated(&tag_name, &head, &BranchName::owner(), "")
+            .await;
+        assert!(matches!(result, Err(error::Error::TagAlreadyExists(_))));
+    }
+
+    #[tokio::test]
+    async fn failed_if_commit_not_exists() {
+        let fs = MemoryFileSystem::default();
+        init_owner_branch(fs.clone()).await;
+        let result = Tag::new(fs.clone())
+            .create(&TagName::from("v1"), &CommitHash(ObjHash::new(b"unknown")))
+            .await;
+        assert!(matches!(result, Err(error::Error::NotfoundObj(_))));
+    }
+
+    #[tokio::test]
+    async fn delete_tag() {
+        let fs = MemoryFileSystem::default();
+        let head = init_owner_branch(fs.clone()).await;
+        let tag = Tag::new(fs.clone());
+        let tag_name = TagName::from("v1");
+        tag.create(&tag_name, &head).await.unwrap();
+        tag.delete(&tag_name).await.unwrap();
+        assert!(tag.list().await.unwrap().is_empty());
+        assert!(matches!(
+            tag.delete(&tag_name).await,
+            Err(error::Error::NotfoundTag(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn list_tags_grouped_by_slash() {
+        let fs = MemoryFileSystem::default();
+        let head = init_owner_branch(fs.clone()).await;
+        let tag = Tag::new(fs.clone());
+        let tag_name = TagName::from("release/v1");
+        tag.create(&tag_name, &head).await.unwrap();
+
+        let tags = tag.list().await.unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].tag_name, tag_name);
+        assert_eq!(tags[0].commit_hash, head);
+    }
+
+    #[tokio::test]
+    async fn failed_if_tag_name_is_invalid() {
+        let fs = MemoryFileSystem::default();
+        let head = init_owner_branch(fs.clone()).await;
+        let result = Tag::new(fs.clone())
+            .create(&TagName::from("../heads/owner"), &head)
+            .await;
+        assert!(matches!(result, Err(error::Error::InvalidTagName(_))));
+    }
+}
@@ -3,6 +3,7 @@ use crate::error;
 use crate::file_system::FileSystem;
 use crate::io::atomic::head::HeadIo;
 use crate::io::atomic::object::ObjIo;
+use crate::io::atomic::sparse_checkout::SparseCheckoutIo;
 use crate::io::trace_tree::TraceTreeIo;
 use crate::io::workspace::WorkspaceIo;
 use crate::object::commit::CommitHash;
@@ -16,6 +17,7 @@ where
     trace_tree: TraceTreeIo<Fs>,
     object: ObjIo<Fs>,
     head: HeadIo<Fs>,
+    sparse_checkout: SparseCheckoutIo<Fs>,
     fs: Fs,
 }
 
@@ -29,6 +31,7 @@ where
             object: ObjIo::new(fs.clone()),
             head: HeadIo::new(fs.clone()),
             trace_tree: TraceTreeIo::new(fs.clone()),
+            sparse_checkout: SparseCheckoutIo::new(fs.clone()),
             fs,
         }
     }
@@ -39,14 +42,24 @@ where
     Fs: FileSystem,
 {
     /// Restore committed data into the workspace.
+    ///
+    /// The files are written with the modes in the trace tree,
+    /// and the files outside the sparse-checkout patterns are not written.
+    /// Nothing is written if any file is placed under a symlink.
     pub async fn execute(&self, branch_name: &BranchName) -> error::Result {
         self.fs.delete("workspace").await?;
         self.fs.create_dir("workspace").await?;
         let head = self.read_head(branch_name).await?;
         let trace_tree = self.trace_tree.read(&head).await?;
-        for (path, hash) in trace_tree.iter() {
+        trace_tree.check_symlink_parents()?;
+        let sparse = self.sparse_checkout.read().await?;
+        for (path, hash) in trace_tree.iter().filter(|(path, _)| sparse.contains(path)) {
             self.workspace
-                .unpack(path, &self.object.try_read_obj(hash).await?)
+                .unpack(
+                    path,
+                    &self.object.try_read_obj(hash).await?,
+                    trace_tree.mode(path),
+                )
                 .await?;
         }
         Ok(())
@@ -57,7 +70,7 @@ where
             Ok(head)
         } else {
             let head = self.head.try_read(&BranchName::owner()).await?;
-            self.head.write(branch_name, &head).await?;
+            self.head.update(branch_name, &head, "unzip").await?;
             Ok(head)
         }
     }
@@ -68,7 +81,13 @@ mod tests {
     use crate::branch::BranchName;
     use crate::error;
     use crate::file_system::memory::MemoryFileSystem;
-    use crate::file_system::FileSystem;
+    use crate::file_system::{FileMode, FilePath, FileSystem};
+    use crate::io::atomic::config::{ConfigIo, RepositoryConfig};
+    use crate::io::atomic::object::ObjIo;
+    use crate::io::trace_tree::TraceTreeIo;
+    use crate::object::file::FileObj;
+    use crate::object::tree::TreeObj;
+    use crate::object::AsMeta;
     use crate::operation::commit::Commit;
     use crate::operation::stage::Stage;
     use crate::operation::unzip::UnZip;
@@ -93,4 +112,103 @@ mod tests {
         assert_eq!(fs.try_read_file("hello").await?, b"hello");
         Ok(())
     }
+
+    #[tokio::test]
+    async fn keep_file_modes() -> error::Result {
+        let fs = MemoryFileSystem::default();
+        init_owner_branch(fs.clone()).await;
+        let branch = BranchName::owner();
+        fs.write_file_with_mode("run.sh", b"echo hi", FileMode::Executable)
+            .await?;
+        fs.write_file_with_mode("link", b"run.sh", FileMode::Symlink)
+            .await?;
+        Stage::new(fs.clone()).execute(&branch, ".").await?;
+        Commit::new(fs.clone()).execute(&branch, "commit").await?;
+        fs.delete("run.sh").await?;
+        fs.delete("link").await?;
+
+        UnZip::new(fs.clone()).execute(&branch).await?;
+        let stat = fs.stat("run.sh").await?.unwrap();
+        assert_eq!(stat.mode, FileMode::Executable);
+        assert_eq!(fs.stat("link").await?.unwrap().mode, FileMode::Symlink);
+        assert_eq!(fs.try_read_file("link").await?, b"run.sh");
+        Ok(())
+    }
+
+    async fn init_with_head_tree(fs: &MemoryFileSystem, entries: &[(&str, &[u8], FileMode)]) {
+        let head = init_owner_branch(fs.clone()).await;
+        let object = ObjIo::new(fs.clone());
+        let mut tree = TreeObj::default();
+        for (path, buf, mode) in entries {
+            let file = FileObj(buf.to_vec());
+            object.write_obj(&file).await.unwrap();
+            tree.insert_with_mode(FilePath::from(*path), file.as_meta().unwrap().hash, *mode);
+        }
+        TraceTreeIo::new(fs.clone())
+            .write(&tree, &head)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn failed_if_file_placed_under_symlink_in_tree() {
+        let fs = MemoryFileSystem::default();
+        init_with_head_tree(
+            &fs,
+            &[
+                ("dir", b"/anywhere", FileMode::Symlink),
+                ("dir/x", b"x", FileMode::Regular),
+            ],
+        )
+        .await;
+
+        let result = UnZip::new(fs.clone())
+            .execute(&BranchName::owner())
+            .await;
+        assert!(matches!(
+            result,
+            Err(error::Error::SymlinkInPath(path)) if path == FilePath::from("dir/x")
+        ));
+        assert!(fs.read_file("dir").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn failed_if_parent_is_symlink_on_disk() {
+        let fs = MemoryFileSystem::default();
+        init_with_head_tree(&fs, &[("dir/x", b"x", FileMode::Regular)]).await;
+        fs.write_file_with_mode("dir", b"/anywhere", FileMode::Symlink)
+            .await
+            .unwrap();
+
+        let result = UnZip::new(fs.clone())
+            .execute(&BranchName::owner())
+            .await;
+        assert!(matches!(result, Err(error::Error::SymlinkInPath(_))));
+    }
+
+    #[tokio::test]
+    async fn refuse_symlink_outside_workspace_unless_allowed() {
+        let fs = MemoryFileSystem::default();
+        init_with_head_tree(
+            &fs,
+            &[("link", b"../../etc/passwd", FileMode::Symlink)],
+        )
+        .await;
+        let unzip = UnZip::new(fs.clone());
+        assert!(matches!(
+            unzip.execute(&BranchName::owner()).await,
+            Err(error::Error::UnsafeSymlink(..))
+        ));
+        assert!(fs.read_file("link").await.unwrap().is_none());
+
+        ConfigIo::new(fs.clone())
+            .write(&RepositoryConfig {
+                allow_unsafe_symlinks: true,
+                ..RepositoryConfig::default()
+            })
+            .await
+            .unwrap();
+        unzip.execute(&BranchName::owner()).await.unwrap();
+        assert_eq!(fs.stat("link").await.unwrap().unwrap().mode, FileMode::Symlink);
+    }
 }
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use meltos_util::impl_string_new_type;
+
+use crate::branch::is_valid_ref_name;
+use crate::error;
+
+#[wasm_bindgen(getter_with_clone)]
+#[repr(transparent)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize, Ord, PartialOrd)]
+pub struct TagName(pub String);
+impl_string_new_type!(TagName);
+
+impl TagName {
+    /// Fails if the tag name cannot be stored under `.meltos/refs/tags`.
+    ///
+    /// The name may contain `/` to group tags, but must not be empty, start with `/`,
+    /// or contain empty, `.` or `..` segments, so that it never escapes the tags directory.
+    pub fn validate(&self) -> error::Result {
+        if is_valid_ref_name(&self.0) {
+            Ok(())
+        } else {
+            Err(error::Error::InvalidTagName(self.clone()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tag::TagName;
+
+    #[test]
+    fn valid_tag_names() {
+        for name in ["v1", "release/v1.0", "a..b"] {
+            assert!(TagName::from(name).validate().is_ok(), "{name}");
+        }
+    }
+
+    #[test]
+    fn invalid_tag_names() {
+        for name in ["", "/v1", "v1/", "release//v1", "..", "../v1", "release/../../v1", "./v1", "a\\b"] {
+            assert!(TagName::from(name).validate().is_err(), "{name}");
+        }
+    }
+}
@@ -1,4 +1,5 @@
 pub mod gz;
+pub mod zlib;
 
 pub trait CompressionBuf: Send + Sync {
     fn zip(&self, buf: &[u8]) -> std::io::Result<Vec<u8>>;
@@ -0,0 +1,62 @@
+use flate2::bufread::{ZlibDecoder, ZlibEncoder};
+use std::io::Read;
+
+use crate::compression::CompressionBuf;
+
+/// Compression used by git for loose objects and packfile entries.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash, Default)]
+pub struct Zlib;
+
+impl Zlib {
+    /// Inflates the zlib stream at the head of `buf`.
+    ///
+    /// Any bytes following the end of the stream are ignored, which is how entries are laid out in a git packfile.
+    pub fn unzip_head(&self, buf: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mut zlib = ZlibDecoder::new(buf);
+        let mut buffer = Vec::new();
+        zlib.read_to_end(&mut buffer)?;
+        Ok(buffer)
+    }
+}
+
+impl CompressionBuf for Zlib {
+    fn zip(&self, buf: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mut zlib = ZlibEncoder::new(buf, flate2::Compression::default());
+        let mut buffer = Vec::new();
+        zlib.read_to_end(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    #[inline(always)]
+    fn unzip(&self, buf: &[u8]) -> std::io::Result<Vec<u8>> {
+        self.unzip_head(buf)
+    }
+
+    fn file_extension() -> Option<&'static str> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compression::zlib::Zlib;
+    use crate::compression::CompressionBuf;
+
+    #[test]
+    fn ascii() {
+        let buff = b"hello world!";
+        let encode = Zlib.zip(buff).unwrap();
+        let decode = Zlib.unzip(&encode).unwrap();
+
+        assert_eq!(decode, buff);
+    }
+
+    #[test]
+    fn ignore_trailing_bytes() {
+        let mut encode = Zlib.zip(b"hello").unwrap();
+        encode.extend(Zlib.zip(b"world").unwrap());
+        let decode = Zlib.unzip_head(&encode).unwrap();
+
+        assert_eq!(decode, b"hello");
+    }
+}
@@ -5,7 +5,9 @@ use crate::commands::all::AllArgs;
 use meltos_client::config::SessionConfigs;
 use meltos_tvc::branch::BranchName;
 
+use crate::commands::branch::BranchArgs;
 use crate::commands::commit::CommitArgs;
+use crate::commands::export::ExportArgs;
 use crate::commands::fetch::FetchArgs;
 use crate::commands::join::JoinArgs;
 use crate::commands::leave::LeaveArgs;
@@ -14,11 +16,16 @@ use crate::commands::meta::MetaArgs;
 use crate::commands::open::OpenArgs;
 use crate::commands::push::PushArgs;
 use crate::commands::reply::ReplyArgs;
+use crate::commands::reset::ResetArgs;
+use crate::commands::sparse::SparseArgs;
 use crate::commands::speak::SpeakArgs;
 use crate::commands::stage::StageArgs;
+use crate::commands::stash::StashArgs;
 
 mod all;
+mod branch;
 mod commit;
+mod export;
 mod fetch;
 mod join;
 mod leave;
@@ -27,8 +34,11 @@ mod meta;
 mod open;
 mod push;
 mod reply;
+mod reset;
+mod sparse;
 mod speak;
 mod stage;
+mod stash;
 
 #[async_trait(?Send)]
 pub trait CommandExecutable {
@@ -50,6 +60,11 @@ pub enum Commands {
     Reply(ReplyArgs),
     All(AllArgs),
     Meta(MetaArgs),
+    Export(ExportArgs),
+    Branch(BranchArgs),
+    Stash(StashArgs),
+    Reset(ResetArgs),
+    Sparse(SparseArgs),
 }
 
 #[async_trait(?Send)]
@@ -68,6 +83,11 @@ impl CommandExecutable for Commands {
             Self::Reply(c) => c.execute().await,
             Self::All(c) => c.execute().await,
             Self::Meta(c) => c.execute().await,
+            Self::Export(c) => c.execute().await,
+            Self::Branch(c) => c.execute().await,
+            Self::Stash(c) => c.execute().await,
+            Self::Reset(c) => c.execute().await,
+            Self::Sparse(c) => c.execute().await,
         }
     }
 }
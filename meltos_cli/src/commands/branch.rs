@@ -0,0 +1,47 @@
+use async_trait::async_trait;
+use clap::Args;
+
+use meltos_client::tvc::TvcClient;
+use meltos_tvc::branch::BranchName;
+use meltos_tvc::file_system::std_fs::StdFileSystem;
+
+use crate::commands::CommandExecutable;
+
+/// List, delete or rename the local branches.
+#[derive(Debug, Args, Clone)]
+pub struct BranchArgs {
+    /// Delete the branch.
+    #[arg(short, long, conflicts_with = "rename")]
+    delete: Option<String>,
+
+    /// Delete the branch even if its local commits are not merged into other branches.
+    #[arg(long, requires = "delete")]
+    force: bool,
+
+    /// Rename the branch.
+    #[arg(short = 'm', long, num_args = 2, value_names = ["OLD", "NEW"])]
+    rename: Option<Vec<String>>,
+}
+
+#[async_trait(? Send)]
+impl CommandExecutable for BranchArgs {
+    async fn execute(self) -> meltos_client::error::Result {
+        let tvc = TvcClient::new(StdFileSystem);
+        if let Some(branch) = self.delete {
+            tvc.delete_branch(&BranchName(branch), self.force).await?;
+            return Ok(());
+        }
+        if let Some([old, new]) = self.rename.as_deref() {
+            tvc.rename_branch(&BranchName(old.clone()), &BranchName(new.clone()))
+                .await?;
+            return Ok(());
+        }
+
+        let mut branch_names = tvc.branch_names().await?;
+        branch_names.sort();
+        for branch_name in branch_names {
+            println!("{branch_name}");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,41 @@
+use std::io::Write;
+
+use async_trait::async_trait;
+use clap::Args;
+
+use meltos_client::tvc::TvcClient;
+use meltos_tvc::branch::BranchName;
+use meltos_tvc::file_system::std_fs::StdFileSystem;
+
+use crate::commands::{load_branch_name, load_configs, CommandExecutable};
+
+/// Write the history of the branch to stdout as a `git fast-import` stream.
+///
+/// e.g. `mci export | git fast-import`
+#[derive(Debug, Args, Clone)]
+pub struct ExportArgs {
+    /// The branch to export; the own branch if omitted.
+    branch: Option<String>,
+
+    /// Export the branch in the room's repository instead of the local one.
+    #[arg(long)]
+    room: bool,
+}
+
+#[async_trait(? Send)]
+impl CommandExecutable for ExportArgs {
+    async fn execute(self) -> meltos_client::error::Result {
+        let branch_name = match self.branch {
+            Some(branch) => BranchName(branch),
+            None => load_branch_name()?,
+        };
+        let tvc = TvcClient::new(StdFileSystem);
+        let stream = if self.room {
+            tvc.export_room_git(load_configs()?, &branch_name).await?
+        } else {
+            tvc.export_git(&branch_name).await?
+        };
+        std::io::stdout().write_all(&stream)?;
+        Ok(())
+    }
+}
@@ -13,12 +13,19 @@ pub struct JoinArgs {
 
     #[clap(short, long)]
     user_id: Option<String>,
+
+    /// Write only the files matching the gitignore-style pattern; can be repeated.
+    #[clap(long)]
+    sparse: Vec<String>,
 }
 
 #[async_trait(? Send)]
 impl CommandExecutable for JoinArgs {
     async fn execute(self) -> meltos_client::error::Result {
         let mut tvc = TvcClient::new(StdFileSystem);
+        if !self.sparse.is_empty() {
+            tvc.set_sparse_checkout(&self.sparse).await?;
+        }
         let configs = tvc
             .join_room(self.room_id, self.user_id.map(UserId))
             .await?;
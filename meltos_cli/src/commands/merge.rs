@@ -10,18 +10,39 @@ use crate::commands::{load_branch_name, CommandExecutable};
 
 #[derive(Debug, Args, Clone)]
 pub struct MergeArgs {
-    source_branch: String,
+    #[arg(required_unless_present_any = ["continue_merge", "abort"])]
+    source_branch: Option<String>,
+
+    /// Create the merge commit after the conflicts have been resolved.
+    #[arg(long = "continue", conflicts_with = "abort")]
+    continue_merge: bool,
+
+    /// Cancel the merge in progress.
+    #[arg(long)]
+    abort: bool,
 }
 
 #[async_trait(? Send)]
 impl CommandExecutable for MergeArgs {
     async fn execute(self) -> meltos_client::error::Result {
         let tvc = TvcClient::new(StdFileSystem);
+        if self.continue_merge {
+            let commit_hash = tvc.merge_continue().await?;
+            println!("merged commit = {commit_hash}");
+            return Ok(());
+        }
+        if self.abort {
+            tvc.merge_abort().await?;
+            println!("merge aborted");
+            return Ok(());
+        }
+
+        let source_branch = self.source_branch.unwrap_or_default();
         let status = tvc
             .merge(
                 load_branch_name()?,
                 HeadIo::new(StdFileSystem)
-                    .try_read(&BranchName(self.source_branch))
+                    .try_read(&BranchName(source_branch))
                     .await?,
             )
             .await?;
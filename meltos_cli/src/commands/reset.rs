@@ -0,0 +1,43 @@
+use async_trait::async_trait;
+use clap::Args;
+
+use meltos_client::tvc::TvcClient;
+use meltos_tvc::file_system::std_fs::StdFileSystem;
+use meltos_tvc::object::commit::CommitHash;
+use meltos_tvc::object::ObjHash;
+use meltos_tvc::operation::reset::ResetMode;
+
+use crate::commands::CommandExecutable;
+
+/// Move the head of the working branch to the commit.
+///
+/// By default the stage is rebuilt and the workspace is kept.
+#[derive(Debug, Args, Clone)]
+pub struct ResetArgs {
+    commit_hash: String,
+
+    /// Keep the stage; the changes of the dropped commits are staged.
+    #[arg(long, conflicts_with = "hard")]
+    soft: bool,
+
+    /// Restore the stage and the workspace to the commit.
+    #[arg(long)]
+    hard: bool,
+}
+
+#[async_trait(? Send)]
+impl CommandExecutable for ResetArgs {
+    async fn execute(self) -> meltos_client::error::Result {
+        let mode = if self.soft {
+            ResetMode::Soft
+        } else if self.hard {
+            ResetMode::Hard
+        } else {
+            ResetMode::Mixed
+        };
+        TvcClient::new(StdFileSystem)
+            .reset(&CommitHash(ObjHash(self.commit_hash)), mode)
+            .await?;
+        Ok(())
+    }
+}
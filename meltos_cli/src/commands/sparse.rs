@@ -0,0 +1,42 @@
+use async_trait::async_trait;
+use clap::{Args, Subcommand};
+
+use meltos_client::tvc::TvcClient;
+use meltos_tvc::file_system::std_fs::StdFileSystem;
+
+use crate::commands::CommandExecutable;
+
+/// Limit the files written into the workspace.
+#[derive(Debug, Args, Clone)]
+pub struct SparseArgs {
+    #[command(subcommand)]
+    command: SparseCommand,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+enum SparseCommand {
+    /// Replace the gitignore-style patterns.
+    Set {
+        patterns: Vec<String>,
+    },
+    List,
+    /// Write all files into the workspace again.
+    Disable,
+}
+
+#[async_trait(? Send)]
+impl CommandExecutable for SparseArgs {
+    async fn execute(self) -> meltos_client::error::Result {
+        let tvc = TvcClient::new(StdFileSystem);
+        match self.command {
+            SparseCommand::Set { patterns } => tvc.set_sparse_checkout(&patterns).await?,
+            SparseCommand::List => {
+                for pattern in tvc.sparse_checkout_patterns().await? {
+                    println!("{pattern}");
+                }
+            }
+            SparseCommand::Disable => tvc.disable_sparse_checkout().await?,
+        }
+        Ok(())
+    }
+}
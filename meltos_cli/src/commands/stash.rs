@@ -0,0 +1,69 @@
+use async_trait::async_trait;
+use clap::{Args, Subcommand};
+
+use meltos_client::tvc::TvcClient;
+use meltos_tvc::file_system::std_fs::StdFileSystem;
+
+use crate::commands::{load_branch_name, CommandExecutable};
+
+/// Stash the uncommitted changes, or restore them.
+#[derive(Debug, Args, Clone)]
+pub struct StashArgs {
+    #[command(subcommand)]
+    command: Option<StashCommand>,
+
+    /// The message of the stash.
+    #[arg(short, long)]
+    message: Option<String>,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+enum StashCommand {
+    /// Restore the stash and drop it if there are no conflicts.
+    Pop {
+        #[arg(default_value_t = 0)]
+        index: usize,
+    },
+    /// Restore the stash and keep it.
+    Apply {
+        #[arg(default_value_t = 0)]
+        index: usize,
+    },
+    List,
+    Drop {
+        #[arg(default_value_t = 0)]
+        index: usize,
+    },
+}
+
+#[async_trait(? Send)]
+impl CommandExecutable for StashArgs {
+    async fn execute(self) -> meltos_client::error::Result {
+        let tvc = TvcClient::new(StdFileSystem);
+        let conflicts = match self.command {
+            None => {
+                tvc.stash(&load_branch_name()?, self.message.as_deref())
+                    .await?;
+                return Ok(());
+            }
+            Some(StashCommand::Pop { index }) => tvc.stash_pop(&load_branch_name()?, index).await?,
+            Some(StashCommand::Apply { index }) => {
+                tvc.stash_apply(&load_branch_name()?, index).await?
+            }
+            Some(StashCommand::List) => {
+                for entry in tvc.stash_list().await? {
+                    println!("stash@{{{}}}: {}", entry.index, entry.message);
+                }
+                return Ok(());
+            }
+            Some(StashCommand::Drop { index }) => {
+                tvc.stash_drop(index).await?;
+                return Ok(());
+            }
+        };
+        for conflict in conflicts {
+            println!("conflicted: {conflict}");
+        }
+        Ok(())
+    }
+}
@@ -23,23 +23,6 @@ impl<D> AsSuccessResponse for D
     }
 }
 
-pub trait IntoHttpResult<T, E> {
-    fn into_http_result(self) -> HttpResult<T>;
-}
-
-
-impl<T> IntoHttpResult<T, meltos_backend::error::Error> for std::result::Result<T, meltos_backend::error::Error>
-{
-    #[inline(always)]
-    fn into_http_result(self) -> HttpResult<T> {
-        match self {
-            Ok(v) => Ok(v),
-            Err(e) => Err(e.into()),
-        }
-    }
-}
-
-
 #[cfg(test)]
 mod test_util {
     use axum::{async_trait, http, Router};
@@ -62,7 +45,8 @@ mod test_util {
     use meltos_backend::session::mock::MockSessionIo;
     use meltos_tvc::branch::BranchName;
     use meltos_tvc::file_system::memory::MemoryFileSystem;
-    use meltos_tvc::io::bundle::{Bundle, BundleIo};
+    use meltos_core::schema::wire::{self, FrameDecoder};
+    use meltos_tvc::io::bundle::{Bundle, BundleHashes, BundleIo, BundleRecord};
     use meltos_tvc::operation::init::Init;
     use meltos_tvc::operation::push::Pushable;
     use meltos_util::serde::SerializeJson;
@@ -97,6 +81,25 @@ mod test_util {
     impl<'a> Pushable<Response> for MockServerClient<'a> {
         type Error = std::io::Error;
 
+        async fn missing(&mut self, hashes: BundleHashes) -> std::io::Result<BundleHashes> {
+            let response = http_call(
+                self.app,
+                Request::builder()
+                    .header(
+                        header::SET_COOKIE,
+                        format!("session_id={}", self.session_id),
+                    )
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .method(http::method::Method::POST)
+                    .uri(format!("/room/{}/tvc/push/check", self.room_id))
+                    .body(Body::from(serde_json::to_string(&hashes).unwrap()))
+                    .unwrap(),
+            )
+                .await;
+
+            Ok(response.deserialize().await)
+        }
+
         async fn push(&mut self, bundle: Bundle) -> std::io::Result<Response> {
             let response = http_call(
                 self.app,
@@ -117,8 +120,36 @@ mod test_util {
         }
     }
 
+    /// ストリーム形式のレスポンスボディを全て読み込み、フレームを取り出せる状態にします。
+    pub async fn read_frames(response: Response) -> FrameDecoder {
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let mut decoder = FrameDecoder::default();
+        decoder.push(&bytes);
+        decoder
+    }
+
+    /// フレームからバンドルのレコードを全て取り出します。
+    pub fn decode_records(decoder: &mut FrameDecoder) -> Bundle {
+        let mut bundle = Bundle::default();
+        while let Some(record) = decoder.next_frame::<BundleRecord>().unwrap() {
+            bundle.push_record(record);
+        }
+        decoder.finish().unwrap();
+        bundle
+    }
+
+    /// バンドルをストリーム形式のボディにエンコードします。
+    pub fn encode_records(bundle: Bundle) -> Vec<u8> {
+        let mut buf = wire::MAGIC.to_vec();
+        for record in bundle.into_records() {
+            buf.extend(wire::encode_frame(&record).unwrap());
+        }
+        buf
+    }
+
     #[async_trait]
     pub trait ResponseConvertable {
+        #[allow(dead_code)]
         async fn into_json(self) -> String;
 
         async fn deserialize<D: DeserializeOwned>(self) -> D;
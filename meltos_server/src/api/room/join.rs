@@ -1,13 +1,16 @@
-use axum::Json;
-
 use meltos_core::channel::{ChannelMessage, MessageData};
 use meltos_core::schema::room::{Join, Joined};
+use meltos_tvc::io::bundle::Bundle;
 
-use crate::api::{AsSuccessResponse, HttpResult};
+use crate::api::HttpResult;
 use crate::middleware::room::SessionRoom;
+use crate::middleware::wire::{stream_response, WireBody, WireFormat};
 
 /// RoomIdに対応するRoomに参加します。
 ///
+/// `Accept`にストリーム形式が指定された場合、最初のフレームで空のバンドルを持つ[`Joined`]を返し、
+/// その後にバンドルのレコードを1件ずつ返します。
+///
 ///
 /// # Errors
 /// ## StatusCode: 400(BAD_REQUEST)
@@ -26,19 +29,28 @@ use crate::middleware::room::SessionRoom;
 ///
 /// - [`ReachedCapacity`](meltos_core::schema::error::ReachedCapacityBody) : ルームの定員に達した場合
 ///
-pub async fn join(SessionRoom(room): SessionRoom, Json(join): Json<Join>) -> HttpResult {
+pub async fn join(
+    SessionRoom(room): SessionRoom,
+    format: WireFormat,
+    WireBody(join): WireBody<Join>,
+) -> HttpResult {
     room.error_if_reached_capacity().await?;
 
     let (user_id, session_id) = room.session.register(join.user_id).await?;
     room.write_head(user_id.clone()).await?;
-    let bundle = room.create_bundle().await?;
     let discussions = room.discussions().await?;
-    let joined = Joined {
+    let mut joined = Joined {
         user_id: user_id.clone(),
         session_id,
-        bundle,
+        bundle: Bundle::default(),
         discussions,
     };
+    let response = if format == WireFormat::Stream {
+        stream_response(Some(&joined), room.create_bundle_stream().await?)
+    } else {
+        joined.bundle = room.create_bundle().await?;
+        format.response(&joined)
+    };
     room.send_all_users(ChannelMessage {
         message: MessageData::Joined {
             user_id: user_id.to_string(),
@@ -47,16 +59,19 @@ pub async fn join(SessionRoom(room): SessionRoom, Json(join): Json<Join>) -> Htt
     })
         .await;
 
-    Ok(joined.as_success_response())
+    Ok(response)
 }
 
 #[cfg(test)]
 mod tests {
-    use axum::http::StatusCode;
+    use axum::body::Body;
+    use axum::extract::Request;
+    use axum::http::{header, Method, StatusCode};
 
     use meltos_core::room::RoomId;
     use meltos_core::schema::error::{ErrorResponseBodyBase, ReachedCapacityBody};
-    use meltos_core::schema::room::{Joined, Opened};
+    use meltos_core::schema::room::{Join, Joined, Opened};
+    use meltos_core::schema::wire;
     use meltos_core::user::UserId;
     use meltos_backend::discussion::global::mock::MockGlobalDiscussionIo;
     use meltos_backend::session::mock::MockSessionIo;
@@ -67,8 +82,8 @@ mod tests {
     use meltos_tvc::operation::init::Init;
 
     use crate::api::test_util::{
-        http_call_with_deserialize, http_join, http_open_room, mock_app,
-        open_room_request_with_options, ResponseConvertable,
+        decode_records, http_call, http_call_with_deserialize, http_join, http_open_room,
+        mock_app, open_room_request_with_options, read_frames, ResponseConvertable,
     };
     use crate::app;
 
@@ -146,6 +161,7 @@ mod tests {
         let response = http_join(&mut app, &room_id, None).await;
         let meta = response.deserialize::<Joined>().await;
         assert_eq!(meta.bundle.branches.len(), 2);
+        assert!(!meta.bundle.objs.is_empty());
     }
 
     #[tokio::test]
@@ -196,4 +212,37 @@ mod tests {
         let user_head = std::fs::read(format!("resources/{}/.meltos/refs/heads/user1", opened.room_id)).unwrap();
         assert_eq!(owner_head, user_head);
     }
+
+    #[tokio::test]
+    async fn join_with_bundle_stream() {
+        let mut app = mock_app();
+        let fs = MemoryFileSystem::default();
+        fs.write_sync("some_text.txt", b"text file");
+        let opened = http_open_room(&mut app, fs.clone()).await;
+
+        let response = http_call(
+            &mut app,
+            Request::builder()
+                .uri(format!("/room/{}/join", opened.room_id))
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(header::ACCEPT, wire::STREAM_CONTENT_TYPE)
+                .method(Method::POST)
+                .body(Body::from(
+                    serde_json::to_string(&Join {
+                        user_id: Some(UserId::from("user1")),
+                    })
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let mut decoder = read_frames(response).await;
+        let joined = decoder.next_frame::<Joined>().unwrap().unwrap();
+        assert_eq!(joined.user_id, UserId::from("user1"));
+        assert!(joined.bundle.objs.is_empty());
+        let bundle = decode_records(&mut decoder);
+        assert_eq!(bundle.branches.len(), 2);
+        assert!(!bundle.objs.is_empty());
+    }
 }
@@ -1,26 +1,24 @@
 use std::fmt::Debug;
 
-use axum::body::Body;
 use axum::extract::State;
-use axum::Json;
-use axum::response::Response;
 
-use meltos_core::room::RoomId;
 use meltos_core::schema::room::Open;
 use meltos_core::schema::room::Opened;
-use meltos_core::user::{SessionId, UserId};
+use meltos_core::user::UserId;
 use meltos_backend::discussion::{DiscussionIo, NewDiscussIo};
 use meltos_backend::session::{NewSessionIo, SessionIo};
-use meltos_util::serde::SerializeJson;
 
 use crate::api::HttpResult;
 use crate::api::room::response_error_exceed_bundle_size;
 use crate::error;
+use crate::middleware::wire::{WireBody, WireFormat};
 use crate::room::{Room, Rooms};
 use crate::state::config::AppConfigs;
 
 /// 新規Roomを開きます。
 ///
+/// リクエストボディは`Content-Type`に応じてJSON形式またはバイナリ形式で受け付けます。
+///
 /// # Errors
 ///
 /// ## StatusCode: 413(PAYLOAD_TOO_LARGE)
@@ -35,7 +33,8 @@ use crate::state::config::AppConfigs;
 pub async fn open<Session, Discussion>(
     State(rooms): State<Rooms>,
     State(configs): State<AppConfigs>,
-    Json(param): Json<Open>,
+    format: WireFormat,
+    WireBody(param): WireBody<Open>,
 ) -> HttpResult
     where
         Discussion: DiscussionIo + NewDiscussIo + 'static,
@@ -70,7 +69,12 @@ pub async fn open<Session, Discussion>(
 
     rooms.insert_room(room, life_time).await;
 
-    Ok(response_success_create_room(room_id, user_id, session_id, capacity))
+    Ok(format.response(&Opened {
+        room_id,
+        user_id,
+        session_id,
+        capacity,
+    }))
 }
 
 
@@ -85,25 +89,6 @@ fn error_if_cannot_create_room() -> error::Result {
     }
 }
 
-fn response_success_create_room(
-    room_id: RoomId,
-    user_id: UserId,
-    session_id: SessionId,
-    user_limits: u64,
-) -> Response {
-    Response::builder()
-        .body(Body::from(
-            Opened {
-                room_id,
-                user_id,
-                session_id,
-                capacity: user_limits,
-            }
-                .as_json(),
-        ))
-        .unwrap()
-}
-
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
@@ -228,6 +213,7 @@ mod tests {
                 compressed_buf: CompressedBuf(buf),
             }],
             branches: Vec::with_capacity(0),
+            tags: Vec::with_capacity(0),
         }
     }
 }
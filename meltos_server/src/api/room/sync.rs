@@ -1,13 +1,18 @@
-use crate::api::{AsSuccessResponse, HttpResult};
+use crate::api::HttpResult;
 use crate::middleware::room::SessionRoom;
 use crate::middleware::session::user::SessionUser;
+use crate::middleware::wire::WireFormat;
 
 /// 現在のルームの状態を全て返します。
 ///
 /// リクエスト側とルームの状態を同期するために使用されます。
-pub async fn sync(SessionRoom(room): SessionRoom, SessionUser(_): SessionUser) -> HttpResult {
+pub async fn sync(
+    SessionRoom(room): SessionRoom,
+    SessionUser(_): SessionUser,
+    format: WireFormat,
+) -> HttpResult {
     let room_bundle = room.room_bundle().await?;
-    Ok(room_bundle.as_success_response())
+    Ok(format.response(&room_bundle))
 }
 
 #[cfg(test)]
@@ -96,7 +101,7 @@ mod tests {
 
         fs.write_sync("hello.txt", b"hello world!");
         stage.execute(&branch, ".").await.unwrap();
-        commit.execute(&branch, "commit text").await.unwrap();
+        let commit_hash = commit.execute(&branch, "commit text").await.unwrap();
         let response = push
             .execute(
                 branch.clone(),
@@ -106,14 +111,18 @@ mod tests {
             .unwrap();
         assert_eq!(response.status(), StatusCode::OK);
 
-        let mut room_bundle = http_sync(&mut app, &room_id, &session_id).await;
+        let room_bundle = http_sync(&mut app, &room_id, &session_id).await;
         assert_eq!(room_bundle.tvc.branches.len(), 1);
         assert_eq!(room_bundle.tvc.branches[0].branch_name, branch.clone());
 
         assert_eq!(room_bundle.tvc.traces.len(), 2);
-        room_bundle.tvc.traces.sort();
+        assert!(room_bundle
+            .tvc
+            .traces
+            .iter()
+            .any(|trace| trace.commit_hash == commit_hash));
         let hello_txt_hash = traces
-            .read(&room_bundle.tvc.traces[1].commit_hash)
+            .read(&commit_hash)
             .await
             .unwrap()
             .get(&FilePath::from_path("hello.txt"))
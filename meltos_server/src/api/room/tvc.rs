@@ -1,5 +1,11 @@
+mod export;
 mod fetch;
+mod fsck;
+mod gc;
 mod push;
 
-pub use fetch::fetch;
-pub use push::push;
+pub use export::export;
+pub use fetch::{fetch, fetch_since};
+pub use fsck::fsck;
+pub use gc::gc;
+pub use push::{check_push, push};
@@ -0,0 +1,105 @@
+use axum::body::Body;
+use axum::extract::Query;
+use axum::http::header;
+use axum::response::Response;
+use serde::Deserialize;
+
+use meltos_tvc::branch::BranchName;
+
+use crate::api::HttpResult;
+use crate::middleware::room::SessionRoom;
+use crate::middleware::session::user::SessionUser;
+
+#[derive(Deserialize, Debug)]
+pub struct Param {
+    branch: BranchName,
+}
+
+/// Room内のTvcリポジトリから指定したブランチの履歴を`git fast-import`形式で取得します。
+///
+/// ルームの有効期限が切れる前に、`git fast-import`でセッション中の履歴をGitリポジトリに取り込むために使用します。
+/// ブランチは`refs/heads/{branch}`として出力されます。
+///
+/// # Errors
+///
+/// ## StatusCode: 200(OK)
+///
+/// - `git fast-import`のストリーム
+///
+/// ## StatusCode: 401(UNAUTHORIZED)
+///
+/// - [`UserUnauthorized`](meltos_core::schema::error::ErrorResponseBodyBase) : 無効なセッションIDが指定された場合
+///
+/// ## StatusCode: 500(INTERNAL_SERVER_ERROR)
+///
+/// - [`FailedTvcBody`](meltos_core::schema::error::FailedTvcBody): ブランチが存在しない場合など、Tvc操作が失敗した場合
+///
+#[tracing::instrument]
+pub async fn export(
+    SessionRoom(room): SessionRoom,
+    SessionUser(_): SessionUser,
+    Query(param): Query<Param>,
+) -> HttpResult {
+    let stream = room.export_git(&param.branch).await?;
+    Ok(Response::builder()
+        .header(header::CONTENT_TYPE, "application/octet-stream")
+        .body(Body::from(stream))
+        .unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::{header, Request, StatusCode};
+    use http_body_util::BodyExt;
+
+    use meltos_core::room::RoomId;
+    use meltos_core::schema::room::{Joined, Opened};
+    use meltos_core::user::{SessionId, UserId};
+    use meltos_tvc::file_system::memory::MemoryFileSystem;
+
+    use crate::api::test_util::{http_call, http_join, http_open_room, mock_app, ResponseConvertable};
+
+    #[tokio::test]
+    async fn export_branch_by_member() {
+        let mut app = mock_app();
+        let Opened {
+            room_id,
+            ..
+        } = http_open_room(&mut app, MemoryFileSystem::default()).await;
+        let Joined {
+            session_id,
+            ..
+        } = http_join(&mut app, &room_id, Some(UserId::from("user1")))
+            .await
+            .deserialize()
+            .await;
+
+        let response = http_call(&mut app, export_request(&room_id, &session_id, "user1")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let stream = response.into_body().collect().await.unwrap().to_bytes();
+        let stream = String::from_utf8(stream.to_vec()).unwrap();
+        assert!(stream.starts_with("reset refs/heads/user1\ncommit refs/heads/user1\n"));
+    }
+
+    #[tokio::test]
+    async fn failed_if_branch_not_exists() {
+        let mut app = mock_app();
+        let Opened {
+            room_id,
+            session_id,
+            ..
+        } = http_open_room(&mut app, MemoryFileSystem::default()).await;
+
+        let response = http_call(&mut app, export_request(&room_id, &session_id, "unknown")).await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    fn export_request(room_id: &RoomId, session_id: &SessionId, branch: &str) -> Request<Body> {
+        Request::builder()
+            .uri(format!("/room/{room_id}/tvc/export?branch={branch}"))
+            .header(header::SET_COOKIE, format!("session_id={session_id}"))
+            .body(Body::empty())
+            .unwrap()
+    }
+}
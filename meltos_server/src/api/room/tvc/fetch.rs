@@ -1,9 +1,16 @@
-use crate::api::{AsSuccessResponse, HttpResult};
+use meltos_core::schema::room::Fetch;
+use meltos_tvc::object::commit::CommitHash;
+
+use crate::api::HttpResult;
 use crate::middleware::room::SessionRoom;
 use crate::middleware::session::user::SessionUser;
+use crate::middleware::wire::{stream_response, WireBody, WireFormat};
 
 /// Room内のTvcリポジトリをバンドル化して取得します。
 ///
+/// `Accept`に`application/x-meltos-bundle`が指定された場合はバイナリ形式で返します。
+/// `application/x-meltos-bundle-stream`が指定された場合は、オブジェクトを1件ずつ読み込みながらストリーム形式で返します。
+///
 /// StatusCode: 200(OK)
 ///
 /// - [`Bundle`](meltos_tvc::io::bundle::Bundle)
@@ -14,9 +21,50 @@ use crate::middleware::session::user::SessionUser;
 /// - [`FailedTvcBody`](meltos_core::schema::error::FailedTvcBody): Tvc操作が失敗した場合
 ///
 #[tracing::instrument]
-pub async fn fetch(SessionRoom(room): SessionRoom, SessionUser(_): SessionUser) -> HttpResult {
+pub async fn fetch(
+    SessionRoom(room): SessionRoom,
+    SessionUser(_): SessionUser,
+    format: WireFormat,
+) -> HttpResult {
+    if format == WireFormat::Stream {
+        let records = room.create_bundle_stream().await?;
+        return Ok(stream_response::<()>(None, records));
+    }
     let bundle = room.create_bundle().await?;
-    Ok(bundle.as_success_response())
+    Ok(format.response(&bundle))
+}
+
+/// Room内のTvcリポジトリのうち、クライアントが保持していないコミットとオブジェクトのみをバンドル化して取得します。
+///
+/// リクエストで送信されたコミットとその祖先、およびそれらのトレースに含まれるオブジェクトはバンドルに含まれません。
+///
+/// StatusCode: 200(OK)
+///
+/// - [`Bundle`](meltos_tvc::io::bundle::Bundle)
+///
+///
+/// StatusCode: 500(INTERNAL_SERVER_ERROR)
+///
+/// - [`FailedTvcBody`](meltos_core::schema::error::FailedTvcBody): Tvc操作が失敗した場合
+///
+#[tracing::instrument]
+pub async fn fetch_since(
+    SessionRoom(room): SessionRoom,
+    SessionUser(_): SessionUser,
+    format: WireFormat,
+    WireBody(fetch): WireBody<Fetch>,
+) -> HttpResult {
+    let known = fetch
+        .known
+        .into_iter()
+        .flat_map(|branch| branch.commits)
+        .collect::<Vec<CommitHash>>();
+    if format == WireFormat::Stream {
+        let records = room.create_bundle_stream_since(&known).await?;
+        return Ok(stream_response::<()>(None, records));
+    }
+    let bundle = room.create_bundle_since(&known).await?;
+    Ok(format.response(&bundle))
 }
 
 #[cfg(test)]
@@ -25,10 +73,17 @@ mod tests {
     use axum::extract::Request;
     use axum::http::StatusCode;
 
-    use meltos_core::schema::room::Opened;
+    use axum::http::{header, HeaderValue, Method};
+    use http_body_util::BodyExt;
+    use meltos_core::schema::room::{Fetch, Opened};
+    use meltos_core::schema::wire;
     use meltos_tvc::file_system::memory::MemoryFileSystem;
+    use meltos_tvc::io::bundle::Bundle;
 
-    use crate::api::test_util::{http_call, http_fetch, http_open_room, mock_app};
+    use crate::api::test_util::{
+        decode_records, fetch_request, http_call, http_fetch, http_open_room, mock_app,
+        read_frames, ResponseConvertable,
+    };
 
     #[tokio::test]
     async fn failed_if_not_logged_in() {
@@ -55,4 +110,86 @@ mod tests {
         let opened = http_open_room(&mut app, fs.clone()).await;
         let _bundle = http_fetch(&mut app, &opened.room_id, &opened.session_id).await;
     }
+
+    #[tokio::test]
+    async fn fetch_binary_bundle() {
+        let fs = MemoryFileSystem::default();
+        let mut app = mock_app();
+        let opened = http_open_room(&mut app, fs.clone()).await;
+        let mut request = fetch_request(&opened.room_id, &opened.session_id);
+        request.headers_mut().insert(
+            header::ACCEPT,
+            HeaderValue::from_static(wire::BINARY_CONTENT_TYPE),
+        );
+
+        let response = http_call(&mut app, request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            wire::BINARY_CONTENT_TYPE
+        );
+        let buf = response.into_body().collect().await.unwrap().to_bytes();
+        let bundle: Bundle = wire::decode(&buf).unwrap();
+        assert_eq!(
+            bundle,
+            http_fetch(&mut app, &opened.room_id, &opened.session_id).await
+        );
+    }
+
+    #[tokio::test]
+    async fn fetch_bundle_stream() {
+        let fs = MemoryFileSystem::default();
+        let mut app = mock_app();
+        fs.write_sync("hello.txt", b"hello");
+        let opened = http_open_room(&mut app, fs.clone()).await;
+        let mut request = fetch_request(&opened.room_id, &opened.session_id);
+        request.headers_mut().insert(
+            header::ACCEPT,
+            HeaderValue::from_static(wire::STREAM_CONTENT_TYPE),
+        );
+
+        let response = http_call(&mut app, request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            wire::STREAM_CONTENT_TYPE
+        );
+        let bundle = decode_records(&mut read_frames(response).await);
+        assert!(!bundle.objs.is_empty());
+        assert_eq!(
+            bundle,
+            http_fetch(&mut app, &opened.room_id, &opened.session_id).await
+        );
+    }
+
+    #[tokio::test]
+    async fn fetch_only_missing_objs() {
+        let fs = MemoryFileSystem::default();
+        let mut app = mock_app();
+        let opened = http_open_room(&mut app, fs.clone()).await;
+        let bundle = http_fetch(&mut app, &opened.room_id, &opened.session_id).await;
+        assert!(!bundle.objs.is_empty());
+
+        let response = http_call(
+            &mut app,
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("/room/{}/tvc/fetch", opened.room_id))
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(header::SET_COOKIE, format!("session_id={}", opened.session_id))
+                .body(Body::from(
+                    serde_json::to_string(&Fetch {
+                        known: bundle.branches.clone(),
+                    })
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let missing: Bundle = response.deserialize().await;
+        assert!(missing.objs.is_empty());
+        assert!(missing.traces.is_empty());
+        assert_eq!(missing.branches, bundle.branches);
+    }
 }
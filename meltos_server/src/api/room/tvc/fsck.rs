@@ -0,0 +1,88 @@
+use crate::api::{AsSuccessResponse, HttpResult};
+use crate::middleware::room::SessionRoom;
+use crate::middleware::session::owner::SessionOwner;
+
+/// Room内のTvcリポジトリの整合性を検査します。
+///
+/// 不審なプッシュの後などに、オブジェクトの破損や参照切れが無いかを確認するために使用します。
+///
+/// # Errors
+///
+/// ## StatusCode: 200(OK)
+///
+/// - [`FsckReport`](meltos_tvc::operation::fsck::FsckReport) : 検出された問題の一覧
+///
+/// ## StatusCode: 401(UNAUTHORIZED)
+///
+/// - [`UserUnauthorized`](meltos_core::schema::error::ErrorResponseBodyBase) : 無効なセッションIDが指定された場合
+///
+/// ## StatusCode: 403(FORBIDDEN)
+///
+/// - [`PermissionDenied`](meltos_core::schema::error::ErrorResponseBodyBase) : ルームオーナー以外からのリクエストの場合
+///
+/// ## StatusCode: 500(INTERNAL_SERVER_ERROR)
+///
+/// - [`FailedTvcBody`](meltos_core::schema::error::FailedTvcBody): Tvc操作が失敗した場合
+///
+#[tracing::instrument]
+pub async fn fsck(SessionRoom(room): SessionRoom, SessionOwner(_): SessionOwner) -> HttpResult {
+    let report = room.fsck().await?;
+    Ok(report.as_success_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::{header, Request, StatusCode};
+
+    use meltos_core::room::RoomId;
+    use meltos_core::schema::room::{Joined, Opened};
+    use meltos_core::user::{SessionId, UserId};
+    use meltos_tvc::file_system::memory::MemoryFileSystem;
+    use meltos_tvc::operation::fsck::FsckReport;
+
+    use crate::api::test_util::{http_call, http_join, http_open_room, mock_app, ResponseConvertable};
+
+    #[tokio::test]
+    async fn report_by_owner() {
+        let mut app = mock_app();
+        let Opened {
+            room_id,
+            session_id,
+            ..
+        } = http_open_room(&mut app, MemoryFileSystem::default()).await;
+
+        let response = http_call(&mut app, fsck_request(&room_id, &session_id)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let report: FsckReport = response.deserialize().await;
+        assert!(report.is_ok());
+        assert!(0 < report.objs);
+    }
+
+    #[tokio::test]
+    async fn failed_if_not_owner() {
+        let mut app = mock_app();
+        let Opened {
+            room_id,
+            ..
+        } = http_open_room(&mut app, MemoryFileSystem::default()).await;
+        let Joined {
+            session_id,
+            ..
+        } = http_join(&mut app, &room_id, Some(UserId::from("user1")))
+            .await
+            .deserialize()
+            .await;
+
+        let response = http_call(&mut app, fsck_request(&room_id, &session_id)).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    fn fsck_request(room_id: &RoomId, session_id: &SessionId) -> Request<Body> {
+        Request::builder()
+            .uri(format!("/room/{room_id}/tvc/fsck"))
+            .header(header::SET_COOKIE, format!("session_id={session_id}"))
+            .body(Body::empty())
+            .unwrap()
+    }
+}
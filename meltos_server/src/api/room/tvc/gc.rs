@@ -0,0 +1,97 @@
+use axum::Json;
+
+use meltos_core::schema::room::Gc;
+
+use crate::api::{AsSuccessResponse, HttpResult};
+use crate::middleware::room::SessionRoom;
+use crate::middleware::session::owner::SessionOwner;
+
+/// Room内のTvcリポジトリから、どのブランチからも到達できないオブジェクトを削除します。
+///
+/// # Errors
+///
+/// ## StatusCode: 200(OK)
+///
+/// - [`GcReport`](meltos_tvc::operation::gc::GcReport) : 削除された(`dry_run`の場合は削除対象の)オブジェクトの一覧
+///
+/// ## StatusCode: 401(UNAUTHORIZED)
+///
+/// - [`UserUnauthorized`](meltos_core::schema::error::ErrorResponseBodyBase) : 無効なセッションIDが指定された場合
+///
+/// ## StatusCode: 403(FORBIDDEN)
+///
+/// - [`PermissionDenied`](meltos_core::schema::error::ErrorResponseBodyBase) : ルームオーナー以外からのリクエストの場合
+///
+/// ## StatusCode: 500(INTERNAL_SERVER_ERROR)
+///
+/// - [`FailedTvcBody`](meltos_core::schema::error::FailedTvcBody): Tvc操作が失敗した場合
+///
+#[tracing::instrument]
+pub async fn gc(
+    SessionRoom(room): SessionRoom,
+    SessionOwner(_): SessionOwner,
+    Json(gc): Json<Gc>,
+) -> HttpResult {
+    let report = room.gc(gc.dry_run).await?;
+    Ok(report.as_success_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::{header, Method, Request, StatusCode};
+
+    use meltos_core::room::RoomId;
+    use meltos_core::schema::room::{Gc, Joined, Opened};
+    use meltos_core::user::{SessionId, UserId};
+    use meltos_tvc::file_system::memory::MemoryFileSystem;
+    use meltos_tvc::operation::gc::GcReport;
+
+    use crate::api::test_util::{http_call, http_join, http_open_room, mock_app, ResponseConvertable};
+
+    #[tokio::test]
+    async fn report_by_owner() {
+        let mut app = mock_app();
+        let Opened {
+            room_id,
+            session_id,
+            ..
+        } = http_open_room(&mut app, MemoryFileSystem::default()).await;
+
+        let response = http_call(&mut app, gc_request(&room_id, &session_id, true)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let report: GcReport = response.deserialize().await;
+        assert!(report.dry_run);
+        assert!(report.objs.is_empty());
+        assert_eq!(report.freed_bytes, 0);
+    }
+
+    #[tokio::test]
+    async fn failed_if_not_owner() {
+        let mut app = mock_app();
+        let Opened {
+            room_id,
+            ..
+        } = http_open_room(&mut app, MemoryFileSystem::default()).await;
+        let Joined {
+            session_id,
+            ..
+        } = http_join(&mut app, &room_id, Some(UserId::from("user1")))
+            .await
+            .deserialize()
+            .await;
+
+        let response = http_call(&mut app, gc_request(&room_id, &session_id, false)).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    fn gc_request(room_id: &RoomId, session_id: &SessionId, dry_run: bool) -> Request<Body> {
+        Request::builder()
+            .uri(format!("/room/{room_id}/tvc/gc"))
+            .method(Method::POST)
+            .header("Content-Type", "application/json")
+            .header(header::SET_COOKIE, format!("session_id={session_id}"))
+            .body(Body::from(serde_json::to_string(&Gc { dry_run }).unwrap()))
+            .unwrap()
+    }
+}
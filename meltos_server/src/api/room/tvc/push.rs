@@ -1,19 +1,31 @@
-use axum::body::Body;
+use axum::body::{Body, BodyDataStream};
 use axum::extract::State;
 use axum::http::{Response, StatusCode};
 use axum::Json;
+use futures::StreamExt;
 use serde_json::json;
 
 use meltos_core::channel::{ChannelMessage, MessageData};
-use meltos_tvc::io::bundle::Bundle;
+use meltos_core::schema::room::Pushed;
+use meltos_core::schema::wire::FrameDecoder;
+use meltos_tvc::io::bundle::{BundleHashes, BundleRecord};
 
-use crate::api::HttpResult;
+use crate::api::{AsSuccessResponse, HttpResult};
 use crate::api::room::response_error_exceed_bundle_size;
 use crate::middleware::room::SessionRoom;
 use crate::middleware::session::user::SessionUser;
+use crate::middleware::wire::{response_invalid_body, BundleBody};
+use crate::room::Room;
 use crate::state::config::AppConfigs;
 
-/// Room内のTvcリポジトリをバンドル化して取得します。
+/// 送信されたバンドルをRoom内のTvcリポジトリに保存します。
+///
+/// `Content-Type`に`application/x-meltos-bundle`が指定された場合、バンドルはバイナリ形式として扱われます。
+/// `application/x-meltos-bundle-stream`が指定された場合はレコードを受信しながら1件ずつ保存します。
+/// この場合、上限値を超えた時点でエラーになりますが、それまでに保存されたオブジェクトはGCで削除されるまで残ります。
+///
+/// 保存後、ルーム内のユーザーにはプッシュされたブランチとハッシュのみが[`Pushed`]として通知されます。
+/// ブランチのヘッドは、プッシュされたコミットが参照するオブジェクトが全て存在することを確認してから更新されます。
 ///
 /// # Errors
 ///
@@ -28,17 +40,100 @@ use crate::state::config::AppConfigs;
 ///
 /// ## StatusCode: 500(INTERNAL_SERVER_ERROR)
 ///
-/// - [`FailedTvcBody`](meltos_core::schema::error::FailedTvcBody): Tvc操作が失敗した場合
+/// - [`FailedTvcBody`](meltos_core::schema::error::FailedTvcBody): Tvc操作が失敗した場合、またはプッシュされたコミットが参照するオブジェクトが存在しない場合
 ///
 #[tracing::instrument]
 pub async fn push(
     State(configs): State<AppConfigs>,
     SessionRoom(room): SessionRoom,
     SessionUser(user_id): SessionUser,
-    Json(bundle): Json<Bundle>,
+    body: BundleBody,
 ) -> HttpResult {
-    let bundle_data_size = bundle.obj_data_size();
+    let pushed = match body {
+        BundleBody::Whole(bundle) => {
+            let repository_size = room.tvc_repository_size().await?;
+            error_if_exceed_limits(&configs, bundle.obj_data_size(), repository_size)?;
+            let pushed = Pushed::from(&bundle);
+            room.save_bundle(bundle).await?;
+            pushed
+        }
+        BundleBody::Stream(stream) => save_bundle_stream(&configs, &room, stream).await?,
+    };
+
+    room.send_all_users(ChannelMessage {
+        from: user_id,
+        message: MessageData::Pushed(pushed),
+    })
+        .await;
+    Ok(Response::default())
+}
+
+/// 送信されたオブジェクトとトレースのハッシュのうち、Room内のTvcリポジトリに存在しないものを取得します。
+///
+/// クライアントはプッシュ前にこのAPIを呼び出し、サーバーが保持していないオブジェクトのみを送信します。
+/// 存在すると報告されたオブジェクトがプッシュまでにGCで削除された場合、プッシュはエラーになります。
+///
+/// # Errors
+///
+/// ## StatusCode: 200(OK)
+///
+/// - [`BundleHashes`](meltos_tvc::io::bundle::BundleHashes) : 存在しないオブジェクトとトレースのハッシュ
+///
+/// ## StatusCode: 500(INTERNAL_SERVER_ERROR)
+///
+/// - [`FailedTvcBody`](meltos_core::schema::error::FailedTvcBody): Tvc操作が失敗した場合
+///
+#[tracing::instrument]
+pub async fn check_push(
+    SessionRoom(room): SessionRoom,
+    SessionUser(_): SessionUser,
+    Json(hashes): Json<BundleHashes>,
+) -> HttpResult {
+    let missing = room.missing(&hashes).await?;
+    Ok(missing.as_success_response())
+}
+
+/// ストリーム形式で送信されたレコードを1件ずつ保存します。
+///
+/// 保存中にGCでオブジェクトが削除されないよう、受信が終わるまでTvcリポジトリをロックします。
+/// チャンネルに通知するため、保存したレコードのハッシュを返します。
+async fn save_bundle_stream(
+    configs: &AppConfigs,
+    room: &Room,
+    mut stream: BodyDataStream,
+) -> HttpResult<Pushed> {
+    let _lock = room.lock_tvc().await;
+    let repository_size = room.tvc_repository_size().await?;
+    let mut decoder = FrameDecoder::default();
+    let mut pushed = Pushed::default();
+    let mut bundle_data_size = 0;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(response_invalid_body)?;
+        decoder.push(&chunk);
+        // 1件のレコードが上限値を超える場合、デコードする前に受信を打ち切ります。
+        error_if_exceed_limits(configs, bundle_data_size + decoder.buffered_len(), repository_size)?;
+        while let Some(record) = decoder
+            .next_frame::<BundleRecord>()
+            .map_err(response_invalid_body)?
+        {
+            if let BundleRecord::Obj(obj) = &record {
+                bundle_data_size += obj.compressed_buf.0.len();
+                error_if_exceed_limits(configs, bundle_data_size, repository_size)?;
+            }
+            pushed.push_record(&record);
+            room.save_record(record).await?;
+        }
+    }
+    decoder.finish().map_err(response_invalid_body)?;
+    Ok(pushed)
+}
 
+#[allow(clippy::result_large_err)]
+fn error_if_exceed_limits(
+    configs: &AppConfigs,
+    bundle_data_size: usize,
+    repository_size: usize,
+) -> HttpResult<()> {
     if configs.limit_bundle_size < bundle_data_size {
         return Err(response_error_exceed_bundle_size(
             bundle_data_size,
@@ -46,7 +141,6 @@ pub async fn push(
         ));
     }
 
-    let repository_size = room.tvc_repository_size().await?;
     let actual_size = bundle_data_size + repository_size;
     if configs.limit_tvc_repository_size < actual_size {
         return Err(response_error_exceed_tvc_repository_size(
@@ -54,14 +148,7 @@ pub async fn push(
             actual_size,
         ));
     }
-
-    room.save_bundle(bundle.clone()).await?;
-    room.send_all_users(ChannelMessage {
-        from: user_id,
-        message: MessageData::Pushed(bundle),
-    })
-        .await;
-    Ok(Response::default())
+    Ok(())
 }
 
 fn response_error_exceed_tvc_repository_size(
@@ -83,21 +170,30 @@ fn response_error_exceed_tvc_repository_size(
 
 #[cfg(test)]
 mod tests {
-    use axum::http::StatusCode;
+    use axum::body::Body;
+    use axum::extract::Request;
+    use axum::http::{header, Method, StatusCode};
     use axum::response::Response;
     use axum::Router;
 
     use meltos_core::room::RoomId;
     use meltos_core::schema::room::Opened;
+    use meltos_core::schema::wire;
     use meltos_core::user::SessionId;
     use meltos_tvc::branch::BranchName;
     use meltos_tvc::file_system::FileSystem;
     use meltos_tvc::file_system::memory::MemoryFileSystem;
+    use meltos_tvc::io::bundle::{Bundle, BundleIo};
+    use meltos_tvc::object::file::FileObj;
+    use meltos_tvc::object::{AsMeta, ObjHash};
     use meltos_tvc::operation;
+    use meltos_tvc::operation::push::Pushable;
     use meltos_tvc::operation::commit::Commit;
     use meltos_tvc::operation::stage;
 
-    use crate::api::test_util::{http_open_room, mock_app, MockServerClient};
+    use crate::api::test_util::{
+        encode_records, http_call, http_fetch, http_open_room, mock_app, MockServerClient,
+    };
 
     #[tokio::test]
     async fn success_send_bundle() {
@@ -186,7 +282,7 @@ mod tests {
         assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
 
-    async fn execute_tvc_operations<Fs: FileSystem + Clone>(
+    async fn execute_tvc_operations<Fs: FileSystem + Clone + 'static>(
         app: &mut Router,
         fs: &Fs,
         room_id: RoomId,
@@ -202,6 +298,149 @@ mod tests {
             .unwrap()
     }
 
+    #[tokio::test]
+    async fn push_binary_bundle() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        let mut app = mock_app();
+        let Opened {
+            room_id,
+            session_id,
+            ..
+        } = http_open_room(&mut app, fs.clone()).await;
+        fs.write_file("src/hello.txt", b"hello").await.unwrap();
+        stage::Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        Commit::new(fs.clone()).execute(&branch, "commit").await.unwrap();
+        let bundle = BundleIo::new(fs.clone()).create().await.unwrap();
+
+        let response = http_call(
+            &mut app,
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("/room/{room_id}/tvc/push"))
+                .header(header::SET_COOKIE, format!("session_id={session_id}"))
+                .header(header::CONTENT_TYPE, wire::BINARY_CONTENT_TYPE)
+                .body(Body::from(wire::encode(&bundle).unwrap()))
+                .unwrap(),
+        )
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let hello = FileObj(b"hello".to_vec()).as_meta().unwrap().hash;
+        let fetched = http_fetch(&mut app, &room_id, &session_id).await;
+        assert!(fetched.objs.iter().any(|obj| obj.hash == hello));
+    }
+
+    #[tokio::test]
+    async fn push_bundle_stream() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        let mut app = mock_app();
+        let Opened {
+            room_id,
+            session_id,
+            ..
+        } = http_open_room(&mut app, fs.clone()).await;
+        fs.write_file("src/hello.txt", b"hello").await.unwrap();
+        stage::Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        let head = Commit::new(fs.clone()).execute(&branch, "commit").await.unwrap();
+        let bundle = BundleIo::new(fs.clone()).create().await.unwrap();
+
+        let response = http_call(&mut app, push_stream_request(&room_id, &session_id, bundle)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let fetched = http_fetch(&mut app, &room_id, &session_id).await;
+        let hello = FileObj(b"hello".to_vec()).as_meta().unwrap().hash;
+        assert!(fetched.objs.iter().any(|obj| obj.hash == hello));
+        assert!(fetched
+            .branches
+            .iter()
+            .any(|b| b.branch_name == branch && b.commits == vec![head.clone()]));
+    }
+
+    #[tokio::test]
+    async fn failed_if_bundle_stream_exceed_bundle_size() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        let mut app = mock_app();
+        let Opened {
+            room_id,
+            session_id,
+            ..
+        } = http_open_room(&mut app, fs.clone()).await;
+        let head = http_fetch(&mut app, &room_id, &session_id).await.branches;
+        fs.write_sync("src/hello.txt", &dummy_large_buf());
+        stage::Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        Commit::new(fs.clone()).execute(&branch, "commit").await.unwrap();
+        let bundle = BundleIo::new(fs.clone()).create().await.unwrap();
+
+        let response = http_call(&mut app, push_stream_request(&room_id, &session_id, bundle)).await;
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(http_fetch(&mut app, &room_id, &session_id).await.branches, head);
+    }
+
+    #[tokio::test]
+    async fn failed_if_binary_bundle_is_broken() {
+        let mut app = mock_app();
+        let Opened {
+            room_id,
+            session_id,
+            ..
+        } = http_open_room(&mut app, MemoryFileSystem::default()).await;
+
+        let response = http_call(
+            &mut app,
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("/room/{room_id}/tvc/push"))
+                .header(header::SET_COOKIE, format!("session_id={session_id}"))
+                .header(header::CONTENT_TYPE, wire::BINARY_CONTENT_TYPE)
+                .body(Body::from(b"broken".to_vec()))
+                .unwrap(),
+        )
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn check_push_returns_missing_hashes() {
+        let fs = MemoryFileSystem::default();
+        let branch = BranchName::owner();
+        let mut app = mock_app();
+        let Opened {
+            room_id,
+            session_id,
+            ..
+        } = http_open_room(&mut app, fs.clone()).await;
+        fs.write_file("src/hello.txt", b"hello").await.unwrap();
+        stage::Stage::new(fs.clone()).execute(&branch, ".").await.unwrap();
+        Commit::new(fs.clone()).execute(&branch, "commit").await.unwrap();
+
+        let mut hashes = BundleIo::new(fs.clone()).create().await.unwrap().hashes();
+        let hello = FileObj(b"hello".to_vec()).as_meta().unwrap().hash;
+        let mut sender = MockServerClient::new(&mut app, room_id.clone(), session_id.clone());
+        let missing = sender.missing(hashes.clone()).await.unwrap();
+        assert!(missing.objs.contains(&hello));
+        assert_eq!(missing.traces.len(), 1);
+
+        operation::push::Push::new(fs.clone())
+            .execute(branch, &mut sender)
+            .await
+            .unwrap();
+        hashes.objs.push(ObjHash::new(b"unknown"));
+        let missing = sender.missing(hashes).await.unwrap();
+        assert_eq!(missing.objs, vec![ObjHash::new(b"unknown")]);
+        assert!(missing.traces.is_empty());
+    }
+
+    fn push_stream_request(room_id: &RoomId, session_id: &SessionId, bundle: Bundle) -> Request {
+        Request::builder()
+            .method(Method::POST)
+            .uri(format!("/room/{room_id}/tvc/push"))
+            .header(header::SET_COOKIE, format!("session_id={session_id}"))
+            .header(header::CONTENT_TYPE, wire::STREAM_CONTENT_TYPE)
+            .body(Body::from(encode_records(bundle)))
+            .unwrap()
+    }
+
     fn dummy_large_buf() -> Vec<u8> {
         // GZipで圧縮された際に1024bytesを超えるようにbuf作成
         vec![1; 1_000_000]
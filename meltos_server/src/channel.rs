@@ -1,6 +0,0 @@
-use axum::async_trait;
-
-#[async_trait]
-pub trait RoomSendable {
-    async fn send(&self);
-}
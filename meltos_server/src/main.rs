@@ -20,7 +20,6 @@ use crate::state::AppState;
 use crate::state::config::AppConfigs;
 
 mod api;
-mod channel;
 mod error;
 mod middleware;
 mod room;
@@ -96,8 +95,15 @@ fn room_operations_router() -> Router<AppState> {
 fn tvc_routes() -> Router<AppState> {
     Router::new()
         .route("/push", post(api::room::tvc::push))
+        .route("/push/check", post(api::room::tvc::check_push))
         .layer(DefaultBodyLimit::max(bundle_request_body_size()))
-        .route("/fetch", get(api::room::tvc::fetch))
+        .route(
+            "/fetch",
+            get(api::room::tvc::fetch).post(api::room::tvc::fetch_since),
+        )
+        .route("/gc", post(api::room::tvc::gc))
+        .route("/fsck", get(api::room::tvc::fsck))
+        .route("/export", get(api::room::tvc::export))
 }
 
 fn global_discussion_route() -> Router<AppState> {
@@ -112,6 +118,6 @@ fn global_discussion_route() -> Router<AppState> {
 fn bundle_request_body_size() -> usize {
     // Bundleの最大サイズは100MIBに設定したいですが、json形式でデータが送られて くる関係上
     // リクエストボディのデータサイズが大きくなることを考慮して4倍までは許容するように
-    // 今後修正する可能性あり
+    // バイナリ形式(`application/x-meltos-bundle`)で送られた場合はほぼバンドルのサイズと同じになります
     AppConfigs::default().limit_tvc_repository_size * 4
 }
\ No newline at end of file
@@ -1,3 +1,4 @@
 pub mod room;
 pub mod session;
+pub mod wire;
 
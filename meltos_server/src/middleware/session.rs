@@ -22,6 +22,7 @@ fn response_unauthorized() -> Response {
         .unwrap()
 }
 
+#[allow(clippy::result_large_err)]
 fn extract_session_id_from_cookie(parts: &mut Parts) -> Result<SessionId, Response> {
     let cookies = parts
         .headers
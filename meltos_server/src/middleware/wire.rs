@@ -0,0 +1,149 @@
+use std::convert::Infallible;
+
+use axum::async_trait;
+use axum::body::{Body, BodyDataStream, Bytes};
+use axum::extract::{FromRequest, FromRequestParts, Request};
+use axum::http::request::Parts;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use futures::{stream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use meltos_core::schema::wire;
+use meltos_tvc::io::bundle::{Bundle, BundleStream};
+
+use crate::api::AsSuccessResponse;
+
+/// `Content-Type`に応じてJSON形式またはバイナリ形式でデシリアライズされるリクエストボディ
+#[derive(Debug)]
+pub struct WireBody<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for WireBody<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if !is_media_type(req.headers(), header::CONTENT_TYPE, wire::is_binary) {
+            let Json(value) = Json::<T>::from_request(req, state)
+                .await
+                .map_err(IntoResponse::into_response)?;
+            return Ok(Self(value));
+        }
+
+        let buf = Bytes::from_request(req, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        let value = wire::decode(&buf).map_err(response_invalid_body)?;
+        Ok(Self(value))
+    }
+}
+
+/// プッシュされるバンドル
+///
+/// `Content-Type`がストリーム形式の場合はボディを読み込まずに返し、レコードは呼び出し側で1件ずつデコードします。
+#[derive(Debug)]
+pub enum BundleBody {
+    Whole(Bundle),
+    Stream(BodyDataStream),
+}
+
+#[async_trait]
+impl<S> FromRequest<S> for BundleBody
+where
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if is_media_type(req.headers(), header::CONTENT_TYPE, wire::is_stream) {
+            return Ok(Self::Stream(req.into_body().into_data_stream()));
+        }
+        let WireBody(bundle) = WireBody::from_request(req, state).await?;
+        Ok(Self::Whole(bundle))
+    }
+}
+
+/// `Accept`で指定されたレスポンスの形式
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum WireFormat {
+    Json,
+    Binary,
+    /// ストリーム形式に対応していないAPIでは[`WireFormat::Binary`]として扱われます。
+    Stream,
+}
+
+impl WireFormat {
+    pub fn response<T: Serialize>(&self, value: &T) -> Response {
+        match self {
+            Self::Json => value.as_success_response(),
+            Self::Binary | Self::Stream => match wire::encode(value) {
+                Ok(buf) => Response::builder()
+                    .header(header::CONTENT_TYPE, wire::BINARY_CONTENT_TYPE)
+                    .body(Body::from(buf))
+                    .unwrap(),
+                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for WireFormat
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        if is_media_type(&parts.headers, header::ACCEPT, wire::is_stream) {
+            Ok(Self::Stream)
+        } else if is_media_type(&parts.headers, header::ACCEPT, wire::is_binary) {
+            Ok(Self::Binary)
+        } else {
+            Ok(Self::Json)
+        }
+    }
+}
+
+/// バンドルのレコードをストリーム形式で返すレスポンスを作成します。
+///
+/// `head`が指定された場合は、レコードの前に最初のフレームとして送信されます。
+pub fn stream_response<H: Serialize>(head: Option<&H>, records: BundleStream) -> Response {
+    let mut first = wire::MAGIC.to_vec();
+    if let Some(head) = head {
+        match wire::encode_frame(head) {
+            Ok(frame) => first.extend(frame),
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+    let frames = records.map(|record| {
+        let record = record.map_err(std::io::Error::other)?;
+        wire::encode_frame(&record).map_err(std::io::Error::other)
+    });
+    let body = stream::once(async move { Ok(first) }).chain(frames);
+    Response::builder()
+        .header(header::CONTENT_TYPE, wire::STREAM_CONTENT_TYPE)
+        .body(Body::from_stream(body))
+        .unwrap()
+}
+
+pub fn response_invalid_body(e: impl std::fmt::Display) -> Response {
+    (
+        StatusCode::BAD_REQUEST,
+        format!("failed to decode binary body: {e}"),
+    )
+        .into_response()
+}
+
+fn is_media_type(headers: &HeaderMap, name: header::HeaderName, f: fn(&str) -> bool) -> bool {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(f)
+}
@@ -6,7 +6,8 @@ use std::time::Duration;
 
 use axum::response::Response;
 use serde::Serialize;
-use tokio::sync::Mutex;
+use futures::StreamExt;
+use tokio::sync::{Mutex, OwnedRwLockReadGuard, RwLock};
 
 use meltos_core::channel::{ChannelMessage, ChannelMessageSendable, MessageData};
 use meltos_core::discussion::DiscussionBundle;
@@ -20,7 +21,10 @@ use meltos_backend::sync::arc_mutex::ArcMutex;
 use meltos_backend::tvc::TvcBackendIo;
 use meltos_tvc::branch::BranchName;
 use meltos_tvc::file_system::std_fs::StdFileSystem;
-use meltos_tvc::io::bundle::Bundle;
+use meltos_tvc::io::bundle::{Bundle, BundleHashes, BundleRecord, BundleStream};
+use meltos_tvc::object::commit::CommitHash;
+use meltos_tvc::operation::fsck::FsckReport;
+use meltos_tvc::operation::gc::GcReport;
 use meltos_util::macros::Deref;
 
 use crate::api::HttpResult;
@@ -64,6 +68,7 @@ impl RoomMap {
         }
     }
 
+    #[allow(clippy::result_large_err)]
     #[inline(always)]
     pub fn room(&mut self, room_id: &RoomId) -> std::result::Result<Room, Response> {
         Ok(self.room_mut(room_id)?.clone())
@@ -83,6 +88,8 @@ pub struct Room {
     capacity: u64,
     discussion: Arc<dyn DiscussionIo>,
     channels: Arc<Mutex<Vec<Box<dyn ChannelMessageSendable<Error=error::Error>>>>>,
+    /// GCは排他的に、それ以外のTvcリポジトリの読み書きは共有して取得するロック
+    tvc_lock: Arc<RwLock<()>>,
 }
 
 impl Room {
@@ -103,6 +110,7 @@ impl Room {
             ),
             tvc: TvcBackendIo::new(room_id.clone(), StdFileSystem),
             channels: Arc::new(Mutex::new(Vec::new())),
+            tvc_lock: Arc::new(RwLock::new(())),
             session: Arc::new(
                 Session::new(room_id)
                     .map_err(|e| error::Error::FailedCreateSessionIo(e.to_string()))?,
@@ -164,8 +172,36 @@ impl Room {
         self.tvc.total_objs_size().await.map_err(crate::error::Error::Tvc)
     }
 
+    /// 読み書き中のオブジェクトがGCで削除されないよう、Tvcリポジトリを共有ロックします。
+    #[inline(always)]
+    pub async fn lock_tvc(&self) -> OwnedRwLockReadGuard<()> {
+        self.tvc_lock.clone().read_owned().await
+    }
+
+    #[inline(always)]
+    pub async fn gc(&self, dry_run: bool) -> error::Result<GcReport> {
+        let _lock = self.tvc_lock.write().await;
+        self.tvc.gc(dry_run).await.map_err(crate::error::Error::Tvc)
+    }
+
+    #[inline(always)]
+    pub async fn fsck(&self) -> error::Result<FsckReport> {
+        let _lock = self.lock_tvc().await;
+        self.tvc.fsck().await.map_err(crate::error::Error::Tvc)
+    }
+
+    #[inline(always)]
+    pub async fn export_git(&self, branch_name: &BranchName) -> error::Result<Vec<u8>> {
+        let _lock = self.lock_tvc().await;
+        self.tvc
+            .export_git(branch_name)
+            .await
+            .map_err(crate::error::Error::Tvc)
+    }
+
     #[inline(always)]
     pub async fn save_bundle(&self, bundle: Bundle) -> error::Result {
+        let _lock = self.lock_tvc().await;
         self.tvc.save(bundle).await?;
         Ok(())
     }
@@ -177,9 +213,57 @@ impl Room {
 
     #[inline(always)]
     pub async fn create_bundle(&self) -> error::Result<Bundle> {
+        let _lock = self.lock_tvc().await;
         self.tvc.bundle().await.map_err(crate::error::Error::Tvc)
     }
 
+    #[inline(always)]
+    pub async fn create_bundle_since(&self, known: &[CommitHash]) -> error::Result<Bundle> {
+        let _lock = self.lock_tvc().await;
+        self.tvc
+            .bundle_since(known)
+            .await
+            .map_err(crate::error::Error::Tvc)
+    }
+
+    /// オブジェクトはストリームの読み出し時に読み込まれるため、ストリームが破棄されるまでTvcリポジトリをロックします。
+    pub async fn create_bundle_stream(&self) -> error::Result<BundleStream> {
+        let lock = self.lock_tvc().await;
+        let stream = self
+            .tvc
+            .bundle_stream()
+            .await
+            .map_err(crate::error::Error::Tvc)?;
+        Ok(hold_lock(stream, lock))
+    }
+
+    /// [`Room::create_bundle_stream`]と同様に、ストリームが破棄されるまでTvcリポジトリをロックします。
+    pub async fn create_bundle_stream_since(&self, known: &[CommitHash]) -> error::Result<BundleStream> {
+        let lock = self.lock_tvc().await;
+        let stream = self
+            .tvc
+            .bundle_stream_since(known)
+            .await
+            .map_err(crate::error::Error::Tvc)?;
+        Ok(hold_lock(stream, lock))
+    }
+
+    #[inline(always)]
+    pub async fn save_record(&self, record: BundleRecord) -> error::Result {
+        self.tvc
+            .save_record(record)
+            .await
+            .map_err(crate::error::Error::Tvc)
+    }
+
+    #[inline(always)]
+    pub async fn missing(&self, hashes: &BundleHashes) -> error::Result<BundleHashes> {
+        self.tvc
+            .missing(hashes)
+            .await
+            .map_err(crate::error::Error::Tvc)
+    }
+
     #[inline(always)]
     pub async fn write_head(&self, user_id: UserId) -> error::Result {
         self.tvc.write_head(&BranchName(user_id.0)).await.map_err(crate::error::Error::Tvc)
@@ -223,6 +307,15 @@ impl Room {
     }
 }
 
+fn hold_lock(stream: BundleStream, lock: OwnedRwLockReadGuard<()>) -> BundleStream {
+    stream
+        .map(move |record| {
+            let _lock = &lock;
+            record
+        })
+        .boxed()
+}
+
 impl Debug for Room {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Room")
@@ -231,3 +324,50 @@ impl Debug for Room {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use meltos_backend::discussion::global::mock::MockGlobalDiscussionIo;
+    use meltos_backend::session::mock::MockSessionIo;
+    use meltos_core::user::UserId;
+
+    use crate::room::Room;
+
+    #[tokio::test]
+    async fn gc_waits_until_bundle_stream_dropped() {
+        let room = Room::open::<MockGlobalDiscussionIo, MockSessionIo>(UserId::from("owner"), 1).unwrap();
+        let stream = room.create_bundle_stream().await.unwrap();
+        assert!(tokio::time::timeout(Duration::from_millis(100), room.gc(true))
+            .await
+            .is_err());
+
+        drop(stream);
+        assert!(tokio::time::timeout(Duration::from_millis(100), room.gc(true))
+            .await
+            .is_ok());
+        room.delete_resource_dir();
+    }
+
+    #[tokio::test]
+    async fn reads_wait_until_gc_finished() {
+        let room = Room::open::<MockGlobalDiscussionIo, MockSessionIo>(UserId::from("owner"), 1).unwrap();
+        let gc_lock = room.tvc_lock.clone().write_owned().await;
+        assert!(tokio::time::timeout(Duration::from_millis(100), room.fsck())
+            .await
+            .is_err());
+        assert!(tokio::time::timeout(Duration::from_millis(100), room.create_bundle())
+            .await
+            .is_err());
+        assert!(tokio::time::timeout(Duration::from_millis(100), room.create_bundle_since(&[]))
+            .await
+            .is_err());
+
+        drop(gc_lock);
+        assert!(tokio::time::timeout(Duration::from_millis(100), room.fsck())
+            .await
+            .is_ok());
+        room.delete_resource_dir();
+    }
+}